@@ -0,0 +1,66 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::error::BlockOrMuteProfileError,
+    common::utils::permissions::is_caller_controller_or_global_admin,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Lists the creators muted by this profile, for the feed layer to filter out.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister,
+/// the controller or a global admin
+#[query]
+fn get_profiles_i_muted() -> Result<Vec<Principal>, BlockOrMuteProfileError> {
+    let current_caller = ic_cdk::caller();
+    let is_caller_privileged = is_caller_controller_or_global_admin().is_ok();
+
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_profiles_i_muted_impl(canister_data, current_caller, is_caller_privileged)
+    })
+}
+
+fn get_profiles_i_muted_impl(
+    canister_data: &CanisterData,
+    current_caller: Principal,
+    is_caller_privileged: bool,
+) -> Result<Vec<Principal>, BlockOrMuteProfileError> {
+    if !is_caller_privileged && canister_data.profile.principal_id != Some(current_caller) {
+        return Err(BlockOrMuteProfileError::Unauthorized);
+    }
+
+    Ok(canister_data.muted_principals.iter().copied().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_get_profiles_i_muted_impl() {
+        let mut canister_data = CanisterData::default();
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        canister_data
+            .muted_principals
+            .insert(get_mock_user_bob_principal_id());
+
+        assert_eq!(
+            get_profiles_i_muted_impl(&canister_data, get_mock_user_bob_principal_id(), false),
+            Err(BlockOrMuteProfileError::Unauthorized)
+        );
+        assert_eq!(
+            get_profiles_i_muted_impl(&canister_data, get_mock_user_alice_principal_id(), false),
+            Ok(vec![get_mock_user_bob_principal_id()])
+        );
+        assert_eq!(
+            get_profiles_i_muted_impl(&canister_data, get_mock_user_bob_principal_id(), true),
+            Ok(vec![get_mock_user_bob_principal_id()])
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod get_profiles_i_blocked;
+pub mod get_profiles_i_muted;
+pub mod update_profiles_i_block_toggle_list_with_specified_profile;
+pub mod update_profiles_i_mute_toggle_list_with_specified_profile;
@@ -0,0 +1,172 @@
+use candid::Principal;
+use ic_cdk::api::call;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::BlockOrMuteProfileError, follow::FollowEntryDetail,
+    },
+    constant::MAX_USERS_IN_BLOCK_OR_MUTE_LIST,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// Blocks the profile if it is not blocked yet, unblocks it otherwise.
+/// Returns whether the profile is blocked after the call.
+///
+/// Blocking drops the follow edges in both directions and keeps the blocked
+/// principal from following, betting on or commenting on this profile.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_profiles_i_block_toggle_list_with_specified_profile(
+    profile: FollowEntryDetail,
+) -> Result<bool, BlockOrMuteProfileError> {
+    let current_caller = ic_cdk::caller();
+
+    let is_blocked = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            canister_data,
+            current_caller,
+            &profile,
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    if is_blocked {
+        // * the blocked profile's canister drops its follow edges with this profile
+        let _ = call::notify(
+            profile.canister_id,
+            "remove_follow_edges_with_blocking_profile",
+            (FollowEntryDetail {
+                principal_id: current_caller,
+                canister_id: ic_cdk::id(),
+            },),
+        );
+    }
+
+    Ok(is_blocked)
+}
+
+fn update_profiles_i_block_toggle_list_with_specified_profile_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    profile: &FollowEntryDetail,
+) -> Result<bool, BlockOrMuteProfileError> {
+    if current_caller == Principal::anonymous() {
+        return Err(BlockOrMuteProfileError::Unauthenticated);
+    }
+
+    let my_principal_id = canister_data
+        .profile
+        .principal_id
+        .ok_or(BlockOrMuteProfileError::UserPrincipalNotSet)?;
+
+    if my_principal_id != current_caller {
+        return Err(BlockOrMuteProfileError::Unauthorized);
+    }
+
+    if profile.principal_id == my_principal_id {
+        return Err(BlockOrMuteProfileError::CannotBlockOrMuteSelf);
+    }
+
+    if canister_data
+        .blocked_principals
+        .remove(&profile.principal_id)
+    {
+        return Ok(false);
+    }
+
+    if canister_data.blocked_principals.len() as u64 >= MAX_USERS_IN_BLOCK_OR_MUTE_LIST {
+        return Err(BlockOrMuteProfileError::ListIsFull);
+    }
+
+    canister_data
+        .blocked_principals
+        .insert(profile.principal_id);
+
+    let follow_data = &mut canister_data.follow_data;
+    follow_data
+        .follower
+        .remove_all_with_principal_id(&profile.principal_id);
+    follow_data
+        .following
+        .remove_all_with_principal_id(&profile.principal_id);
+
+    canister_data
+        .ml_feed_cache
+        .retain(|item| item.creator_principal_id != Some(profile.principal_id));
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_update_profiles_i_block_toggle_list_with_specified_profile_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = FollowEntryDetail {
+            principal_id: get_mock_user_bob_principal_id(),
+            canister_id: get_mock_user_bob_canister_id(),
+        };
+
+        let result = update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            &bob,
+        );
+        assert_eq!(result, Err(BlockOrMuteProfileError::UserPrincipalNotSet));
+
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        canister_data.follow_data.follower.add(bob.clone());
+        canister_data.follow_data.following.add(bob.clone());
+
+        let result = update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_bob_principal_id(),
+            &bob,
+        );
+        assert_eq!(result, Err(BlockOrMuteProfileError::Unauthorized));
+
+        let result = update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            &FollowEntryDetail {
+                principal_id: get_mock_user_alice_principal_id(),
+                canister_id: get_mock_user_alice_canister_id(),
+            },
+        );
+        assert_eq!(result, Err(BlockOrMuteProfileError::CannotBlockOrMuteSelf));
+
+        let result = update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            &bob,
+        );
+        assert_eq!(result, Ok(true));
+        assert!(canister_data
+            .blocked_principals
+            .contains(&get_mock_user_bob_principal_id()));
+        assert!(canister_data.follow_data.follower.is_empty());
+        assert!(canister_data.follow_data.following.is_empty());
+
+        let result = update_profiles_i_block_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            &bob,
+        );
+        assert_eq!(result, Ok(false));
+        assert!(canister_data.blocked_principals.is_empty());
+    }
+}
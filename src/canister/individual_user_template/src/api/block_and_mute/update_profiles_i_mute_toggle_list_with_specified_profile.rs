@@ -0,0 +1,135 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::error::BlockOrMuteProfileError,
+    constant::MAX_USERS_IN_BLOCK_OR_MUTE_LIST,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// Mutes the creator if they are not muted yet, unmutes them otherwise.
+/// Returns whether the creator is muted after the call.
+///
+/// Muted creators are left out of the feed served from this canister,
+/// unlike blocking they can still interact with this profile.
+/// The feeds served by post_cache are shared by every user and are not filtered,
+/// clients have to drop the creators listed by `get_profiles_i_muted` from those.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_profiles_i_mute_toggle_list_with_specified_profile(
+    principal_id: Principal,
+) -> Result<bool, BlockOrMuteProfileError> {
+    let is_muted = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+            canister_data,
+            ic_cdk::caller(),
+            principal_id,
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    Ok(is_muted)
+}
+
+fn update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    principal_id: Principal,
+) -> Result<bool, BlockOrMuteProfileError> {
+    if current_caller == Principal::anonymous() {
+        return Err(BlockOrMuteProfileError::Unauthenticated);
+    }
+
+    let my_principal_id = canister_data
+        .profile
+        .principal_id
+        .ok_or(BlockOrMuteProfileError::UserPrincipalNotSet)?;
+
+    if my_principal_id != current_caller {
+        return Err(BlockOrMuteProfileError::Unauthorized);
+    }
+
+    if principal_id == my_principal_id {
+        return Err(BlockOrMuteProfileError::CannotBlockOrMuteSelf);
+    }
+
+    if canister_data.muted_principals.remove(&principal_id) {
+        return Ok(false);
+    }
+
+    if canister_data.muted_principals.len() as u64 >= MAX_USERS_IN_BLOCK_OR_MUTE_LIST {
+        return Err(BlockOrMuteProfileError::ListIsFull);
+    }
+
+    canister_data.muted_principals.insert(principal_id);
+    canister_data
+        .ml_feed_cache
+        .retain(|item| item.creator_principal_id != Some(principal_id));
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::ml_data::MLFeedCacheItem;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id, get_mock_user_charlie_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_update_profiles_i_mute_toggle_list_with_specified_profile_impl() {
+        let mut canister_data = CanisterData::default();
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        for (post_id, creator_principal_id) in [
+            (0, get_mock_user_bob_principal_id()),
+            (1, get_mock_user_charlie_principal_id()),
+        ] {
+            canister_data.ml_feed_cache.push(MLFeedCacheItem {
+                post_id,
+                canister_id: get_mock_user_bob_canister_id(),
+                video_id: "abcd#1234".into(),
+                creator_principal_id: Some(creator_principal_id),
+            });
+        }
+
+        let result = update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            Principal::anonymous(),
+            get_mock_user_bob_principal_id(),
+        );
+        assert_eq!(result, Err(BlockOrMuteProfileError::Unauthenticated));
+
+        let result = update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            get_mock_user_alice_principal_id(),
+        );
+        assert_eq!(result, Err(BlockOrMuteProfileError::CannotBlockOrMuteSelf));
+
+        let result = update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            get_mock_user_bob_principal_id(),
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(canister_data.ml_feed_cache.len(), 1);
+        assert_eq!(canister_data.ml_feed_cache[0].post_id, 1);
+
+        let result = update_profiles_i_mute_toggle_list_with_specified_profile_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            get_mock_user_bob_principal_id(),
+        );
+        assert_eq!(result, Ok(false));
+        assert!(canister_data.muted_principals.is_empty());
+    }
+}
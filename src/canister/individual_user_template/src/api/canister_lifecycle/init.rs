@@ -1,4 +1,16 @@
-use crate::{data_model::CanisterData, CANISTER_DATA};
+use crate::{
+    api::{
+        hot_or_not_bet::{
+            payout_notification_outbox::start_payout_notification_retry_timer,
+            reconcile_pending_bets::start_pending_bet_reconciliation_timer,
+            slot_tabulation_queue::start_slot_tabulation_queue_timer,
+        },
+        post::purge_deleted_posts::start_deleted_post_purge_timer,
+        prediction::settle_prediction::start_prediction_settlement_timer,
+    },
+    data_model::CanisterData,
+    CANISTER_DATA,
+};
 use ic_cdk_macros::init;
 use shared_utils::{
     canister_specific::individual_user_template::types::arg::IndividualUserTemplateInitArgs,
@@ -13,6 +25,11 @@ fn init(init_args: IndividualUserTemplateInitArgs) {
     });
 
     send_canister_metrics();
+    start_slot_tabulation_queue_timer();
+    start_deleted_post_purge_timer();
+    start_pending_bet_reconciliation_timer();
+    start_payout_notification_retry_timer();
+    start_prediction_settlement_timer();
 }
 
 fn init_impl(init_args: IndividualUserTemplateInitArgs, data: &mut CanisterData) {
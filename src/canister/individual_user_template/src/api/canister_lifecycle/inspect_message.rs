@@ -0,0 +1,18 @@
+use ic_cdk::api::call::accept_message;
+use ic_cdk_macros::inspect_message;
+
+use crate::CANISTER_DATA;
+
+/// Ingress update calls are dropped early while the canister is being migrated to another
+/// subnet, so nothing written by the user can get lost between the snapshot and the switch over.
+/// Calls from other canisters do not pass through here,
+/// they are rejected by the `is_not_frozen_for_migration` guard instead.
+#[inspect_message]
+fn inspect_message() {
+    let ingress_frozen =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.ingress_frozen_for_migration);
+
+    if !ingress_frozen {
+        accept_message();
+    }
+}
@@ -1,3 +1,4 @@
 pub mod init;
+pub mod inspect_message;
 pub mod post_upgrade;
 pub mod pre_upgrade;
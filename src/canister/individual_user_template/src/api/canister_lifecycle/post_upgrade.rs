@@ -9,7 +9,22 @@ use crate::data_model::memory;
 use shared_utils::canister_specific::individual_user_template::types::arg::IndividualUserTemplateInitArgs;
 
 use crate::{
-    api::hot_or_not_bet::reenqueue_timers_for_pending_bet_outcomes::reenqueue_timers_for_pending_bet_outcomes,
+    api::{
+        hot_or_not_bet::{
+            payout_notification_outbox::start_payout_notification_retry_timer,
+            reconcile_pending_bets::{
+                mark_pending_bets_orphaned_by_upgrade_for_reconciliation,
+                start_pending_bet_reconciliation_timer,
+            },
+            reenqueue_timers_for_pending_bet_outcomes::enqueue_slot_tabulation_jobs_for_pending_bet_outcomes,
+            slot_tabulation_queue::start_slot_tabulation_queue_timer,
+        },
+        post::purge_deleted_posts::start_deleted_post_purge_timer,
+        prediction::settle_prediction::{
+            enqueue_prediction_settlement_jobs_for_open_predictions,
+            start_prediction_settlement_timer,
+        },
+    },
     CANISTER_DATA,
 };
 
@@ -18,7 +33,14 @@ fn post_upgrade() {
     restore_data_from_stable_memory();
     save_upgrade_args_to_memory();
     migrate_excessive_tokens();
-    reenqueue_timers_for_pending_bet_outcomes();
+    enqueue_slot_tabulation_jobs_for_pending_bet_outcomes();
+    enqueue_prediction_settlement_jobs_for_open_predictions();
+    mark_pending_bets_orphaned_by_upgrade_for_reconciliation();
+    start_slot_tabulation_queue_timer();
+    start_deleted_post_purge_timer();
+    start_pending_bet_reconciliation_timer();
+    start_payout_notification_retry_timer();
+    start_prediction_settlement_timer();
 }
 
 fn restore_data_from_stable_memory() {
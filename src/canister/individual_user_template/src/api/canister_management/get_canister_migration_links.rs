@@ -0,0 +1,14 @@
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::individual_user_template::types::migration::CanisterMigrationLinks;
+
+use crate::CANISTER_DATA;
+
+/// # Access Control
+/// Anyone, other canisters use it to follow a profile that was migrated to another subnet
+#[query]
+fn get_canister_migration_links() -> CanisterMigrationLinks {
+    CANISTER_DATA.with_borrow(|canister_data| CanisterMigrationLinks {
+        migrated_to_canister_id: canister_data.migrated_to_canister_id,
+        former_canister_ids: canister_data.former_canister_ids.clone(),
+    })
+}
@@ -0,0 +1,46 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Points this canister at the user's canister on the target subnet once that one has taken over.
+/// This canister stays frozen from then on, and other canisters that still refer to it
+/// look up where the user went with `get_canister_migration_links`.
+///
+/// # Access Control
+/// Only the controller (user_index)
+#[update(guard = "is_caller_controller")]
+fn mark_canister_as_migrated(migrated_to_canister_id: Principal) {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        mark_canister_as_migrated_impl(canister_data, migrated_to_canister_id)
+    });
+}
+
+fn mark_canister_as_migrated_impl(
+    canister_data: &mut CanisterData,
+    migrated_to_canister_id: Principal,
+) {
+    canister_data.ingress_frozen_for_migration = true;
+    canister_data.migrated_to_canister_id = Some(migrated_to_canister_id);
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::get_mock_user_alice_canister_id;
+
+    use super::*;
+
+    #[test]
+    fn test_mark_canister_as_migrated_impl() {
+        let mut canister_data = CanisterData::default();
+
+        mark_canister_as_migrated_impl(&mut canister_data, get_mock_user_alice_canister_id());
+
+        assert!(canister_data.ingress_frozen_for_migration);
+        assert_eq!(
+            canister_data.migrated_to_canister_id,
+            Some(get_mock_user_alice_canister_id())
+        );
+    }
+}
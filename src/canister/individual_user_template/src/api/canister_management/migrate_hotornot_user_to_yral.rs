@@ -6,7 +6,9 @@ use shared_utils::canister_specific::individual_user_template::types::{
     migration::MigrationErrors, post::Post,
 };
 
-#[update]
+use crate::guard::migration::is_not_frozen_for_migration;
+
+#[update(guard = "is_not_frozen_for_migration")]
 pub async fn transfer_tokens_and_posts(
     to_account: Principal,
     to_account_canister_id: Principal,
@@ -18,7 +20,7 @@ pub async fn transfer_tokens_and_posts(
         .await
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 pub async fn receive_data_from_hotornot(
     from_account: Principal,
     amount: u64,
@@ -3,9 +3,13 @@ use ic_cdk_macros::query;
 
 use crate::CANISTER_DATA;
 
+pub mod get_canister_migration_links;
 pub mod get_last_access_time;
 pub mod get_session_type;
+pub mod mark_canister_as_migrated;
 pub mod migrate_hotornot_user_to_yral;
+pub mod record_former_canister_id;
+pub mod update_ingress_freeze_for_migration;
 pub mod update_last_access_time;
 pub mod update_profile_owner;
 pub mod update_session_type;
@@ -0,0 +1,53 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Remembers the canister this profile was migrated from,
+/// so that bet makers who still refer to it can be pointed here.
+///
+/// # Access Control
+/// Only the controller (user_index)
+#[update(guard = "is_caller_controller")]
+fn record_former_canister_id(former_canister_id: Principal) {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        record_former_canister_id_impl(canister_data, former_canister_id)
+    });
+}
+
+fn record_former_canister_id_impl(canister_data: &mut CanisterData, former_canister_id: Principal) {
+    if !canister_data
+        .former_canister_ids
+        .contains(&former_canister_id)
+    {
+        canister_data.former_canister_ids.push(former_canister_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_record_former_canister_id_impl() {
+        let mut canister_data = CanisterData::default();
+
+        record_former_canister_id_impl(&mut canister_data, get_mock_user_alice_canister_id());
+        record_former_canister_id_impl(&mut canister_data, get_mock_user_bob_canister_id());
+        // * a resumed migration records the same canister again
+        record_former_canister_id_impl(&mut canister_data, get_mock_user_bob_canister_id());
+
+        assert_eq!(
+            canister_data.former_canister_ids,
+            vec![
+                get_mock_user_alice_canister_id(),
+                get_mock_user_bob_canister_id()
+            ]
+        );
+    }
+}
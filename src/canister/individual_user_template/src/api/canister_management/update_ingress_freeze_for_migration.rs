@@ -0,0 +1,18 @@
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller;
+
+use crate::CANISTER_DATA;
+
+/// Freezes the canister before user_index snapshots it for a migration,
+/// and unfreezes it when the migration is rolled back.
+/// While frozen, updates from other canisters are rejected too, so that none of them
+/// land between the snapshot and the switch over and get lost.
+///
+/// # Access Control
+/// Only the controller (user_index)
+#[update(guard = "is_caller_controller")]
+fn update_ingress_freeze_for_migration(frozen: bool) {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data.ingress_frozen_for_migration = frozen;
+    });
+}
@@ -2,9 +2,9 @@ use ic_cdk::caller;
 use ic_cdk_macros::update;
 use shared_utils::common::utils::system_time::get_current_system_time_from_ic;
 
-use crate::CANISTER_DATA;
+use crate::{guard::migration::is_not_frozen_for_migration, CANISTER_DATA};
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn update_last_access_time() -> Result<String, String> {
     let profile_owner =
         CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id.unwrap());
@@ -23,7 +23,7 @@ fn update_last_access_time() -> Result<String, String> {
     })
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 pub fn update_last_canister_functionality_access_time() {
     CANISTER_DATA.with_borrow_mut(|canister_data| {
         canister_data.last_canister_functionality_access_time =
@@ -2,11 +2,11 @@ use candid::Principal;
 use ic_cdk::api::{self, is_controller};
 use ic_cdk_macros::update;
 
-use crate::CANISTER_DATA;
+use crate::{guard::migration::is_not_frozen_for_migration, CANISTER_DATA};
 
 use super::update_last_access_time::update_last_canister_functionality_access_time;
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 pub async fn update_profile_owner(user_id: Option<Principal>) -> Result<(), String> {
     if !is_controller(&api::caller()) {
         return Err("Unauthorised".into());
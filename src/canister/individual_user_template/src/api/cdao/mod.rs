@@ -31,9 +31,12 @@ use shared_utils::{
     constant::{NNS_LEDGER_CANISTER_ID, USER_SNS_CANISTER_INITIAL_CYCLES},
 };
 
-use crate::{util::cycles::request_cycles_from_subnet_orchestrator, CANISTER_DATA};
+use crate::{
+    guard::migration::is_not_frozen_for_migration,
+    util::cycles::request_cycles_from_subnet_orchestrator, CANISTER_DATA,
+};
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 pub async fn settle_neurons_fund_participation(
     request: SettleNeuronsFundParticipationRequest,
 ) -> SettleNeuronsFundParticipationResponse {
@@ -84,7 +87,7 @@ async fn deployed_cdao_canisters() -> Vec<DeployedCdaoCanisters> {
     CANISTER_DATA.with(|cdata| cdata.borrow().cdao_canisters.clone())
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn deploy_cdao_sns(
     init_payload: SnsInitPayload,
     swap_time: u64,
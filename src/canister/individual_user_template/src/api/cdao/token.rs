@@ -4,11 +4,11 @@ use ic_sns_root::pb::v1::{ListSnsCanistersRequest, ListSnsCanistersResponse};
 use icrc_ledger_types::icrc1::{account::Account, transfer::{Memo, TransferArg, TransferError}};
 use shared_utils::{canister_specific::individual_user_template::types::error::CdaoTokenError, pagination::{self, PaginationError}};
 
-use crate::CANISTER_DATA;
+use crate::{guard::migration::is_not_frozen_for_migration, CANISTER_DATA};
 
 /// Add a new token
 /// returns true if new token is added
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn add_token(root_canister: Principal) -> Result<bool, CdaoTokenError> {
     let token_added = CANISTER_DATA.with(|cdata| {
         let cdata = cdata.borrow();
@@ -40,7 +40,7 @@ async fn add_token(root_canister: Principal) -> Result<bool, CdaoTokenError> {
     return Ok(true);
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn transfer_token_to_user_canister(token_root: Principal, target_canister: Principal, memo: Option<Memo>, amount: Nat) -> Result<(), CdaoTokenError> {
     // * access control
     let current_caller = ic_cdk::caller();
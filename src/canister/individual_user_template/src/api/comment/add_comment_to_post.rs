@@ -0,0 +1,357 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::AddCommentToPostArg,
+        comment::{
+            is_comment_text_valid, Comment, CommentId, CommentRateLimitWindow, CommentStatus,
+            GlobalCommentId, MAX_COMMENTS_PER_AUTHOR_PER_WINDOW, MAX_COMMENTS_PER_POST,
+        },
+        error::CommentOnPostError,
+    },
+    common::{
+        types::{app_primitive_type::PostId, top_posts::post_score_index_item::PostStatus},
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        post::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+/// Adds a comment to a post of this profile, or a reply when `parent_comment_id` is set.
+/// Each post takes up to `MAX_COMMENTS_PER_POST` comments and each author can add
+/// up to `MAX_COMMENTS_PER_AUTHOR_PER_WINDOW` comments across the posts of this profile
+/// per rate limit window.
+///
+/// # Access Control
+/// Any authenticated caller
+#[update(guard = "is_not_frozen_for_migration")]
+fn add_comment_to_post(arg: AddCommentToPostArg) -> Result<CommentId, CommentOnPostError> {
+    let post_id = arg.post_id;
+
+    let comment_id = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        add_comment_to_post_impl(
+            canister_data,
+            ic_cdk::caller(),
+            arg,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+    update_scores_and_share_with_post_cache_if_difference_beyond_threshold(&post_id);
+
+    Ok(comment_id)
+}
+
+fn add_comment_to_post_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    arg: AddCommentToPostArg,
+    current_time: SystemTime,
+) -> Result<CommentId, CommentOnPostError> {
+    if current_caller == Principal::anonymous() {
+        return Err(CommentOnPostError::Unauthenticated);
+    }
+
+    if canister_data.blocked_principals.contains(&current_caller) {
+        return Err(CommentOnPostError::BlockedByPostCreator);
+    }
+
+    if !is_comment_text_valid(&arg.text) {
+        return Err(CommentOnPostError::InvalidCommentText);
+    }
+
+    let is_new_commenter = !has_visible_comment_on_post(canister_data, arg.post_id, current_caller);
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&arg.post_id)
+        .ok_or(CommentOnPostError::PostNotFound)?;
+
+    if matches!(
+        post.status,
+        PostStatus::BannedForExplicitness
+            | PostStatus::BannedDueToUserReporting
+            | PostStatus::Deleted
+    ) {
+        return Err(CommentOnPostError::PostNotAvailableForComments);
+    }
+
+    // * comments are never removed one by one, so ids of a post stay contiguous
+    let comment_id = canister_data
+        .comments_map
+        .range(GlobalCommentId(arg.post_id, 0)..GlobalCommentId(arg.post_id + 1, 0))
+        .count() as CommentId;
+
+    if comment_id >= MAX_COMMENTS_PER_POST {
+        return Err(CommentOnPostError::CommentLimitReachedForPost);
+    }
+
+    let parent_comment = arg
+        .parent_comment_id
+        .map(|parent_comment_id| {
+            canister_data
+                .comments_map
+                .get(&GlobalCommentId(arg.post_id, parent_comment_id))
+                .filter(|parent_comment| parent_comment.status == CommentStatus::Visible)
+                .ok_or(CommentOnPostError::ParentCommentNotFound)
+        })
+        .transpose()?;
+
+    record_comment_in_rate_limit_window(
+        &mut canister_data.comment_rate_limit_windows,
+        current_caller,
+        current_time,
+    )?;
+
+    if let Some(mut parent_comment) = parent_comment {
+        parent_comment.reply_count += 1;
+        canister_data.comments_map.insert(
+            GlobalCommentId(arg.post_id, parent_comment.id),
+            parent_comment,
+        );
+    }
+
+    canister_data.comments_map.insert(
+        GlobalCommentId(arg.post_id, comment_id),
+        Comment::new(
+            comment_id,
+            arg.parent_comment_id,
+            current_caller,
+            arg.text.trim().to_string(),
+            current_time,
+        ),
+    );
+    post.comment_count += 1;
+    if is_new_commenter {
+        post.commenter_count += 1;
+    }
+
+    Ok(comment_id)
+}
+
+/// Counts a comment against the author's current window, starting a new one if it ended.
+/// Windows of other authors that ended are dropped on the way.
+fn record_comment_in_rate_limit_window(
+    comment_rate_limit_windows: &mut BTreeMap<Principal, CommentRateLimitWindow>,
+    author_principal_id: Principal,
+    current_time: SystemTime,
+) -> Result<(), CommentOnPostError> {
+    comment_rate_limit_windows.retain(|_, window| !window.has_ended(&current_time));
+
+    let window = comment_rate_limit_windows
+        .entry(author_principal_id)
+        .or_insert_with(|| CommentRateLimitWindow::new(current_time));
+
+    if window.comment_count >= MAX_COMMENTS_PER_AUTHOR_PER_WINDOW {
+        return Err(CommentOnPostError::CommentRateLimitExceeded);
+    }
+
+    window.comment_count += 1;
+
+    Ok(())
+}
+
+pub(crate) fn has_visible_comment_on_post(
+    canister_data: &CanisterData,
+    post_id: PostId,
+    author_principal_id: Principal,
+) -> bool {
+    canister_data
+        .comments_map
+        .range(GlobalCommentId(post_id, 0)..GlobalCommentId(post_id + 1, 0))
+        .any(|(_, comment)| {
+            comment.author_principal_id == author_principal_id
+                && comment.status == CommentStatus::Visible
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        comment::COMMENT_RATE_LIMIT_WINDOW_IN_SECONDS,
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn comment_arg(parent_comment_id: Option<CommentId>, text: &str) -> AddCommentToPostArg {
+        AddCommentToPostArg {
+            post_id: 0,
+            parent_comment_id,
+            text: text.into(),
+        }
+    }
+
+    fn insert_post(canister_data: &mut CanisterData, created_at: SystemTime) {
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &created_at,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_add_comment_to_post_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+        let now = SystemTime::now();
+
+        assert_eq!(
+            add_comment_to_post_impl(
+                &mut canister_data,
+                Principal::anonymous(),
+                comment_arg(None, "Nice"),
+                now
+            ),
+            Err(CommentOnPostError::Unauthenticated)
+        );
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, "  "), now),
+            Err(CommentOnPostError::InvalidCommentText)
+        );
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, "Nice"), now),
+            Err(CommentOnPostError::PostNotFound)
+        );
+
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        insert_post(&mut canister_data, now);
+
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(Some(0), "Nice"), now),
+            Err(CommentOnPostError::ParentCommentNotFound)
+        );
+
+        canister_data.blocked_principals.insert(bob);
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, "Nice"), now),
+            Err(CommentOnPostError::BlockedByPostCreator)
+        );
+        canister_data.blocked_principals.remove(&bob);
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, " Nice "), now),
+            Ok(0)
+        );
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(Some(0), "Agreed"), now),
+            Ok(1)
+        );
+
+        let parent_comment = canister_data
+            .comments_map
+            .get(&GlobalCommentId(0, 0))
+            .unwrap();
+        assert_eq!(parent_comment.text, "Nice");
+        assert_eq!(parent_comment.reply_count, 1);
+        assert_eq!(
+            canister_data
+                .comments_map
+                .get(&GlobalCommentId(0, 1))
+                .unwrap()
+                .parent_comment_id,
+            Some(0)
+        );
+        assert_eq!(
+            canister_data
+                .all_created_posts
+                .get(&0)
+                .unwrap()
+                .comment_count,
+            2
+        );
+        // * bob's comment and reply count as a single commenter
+        assert_eq!(
+            canister_data
+                .all_created_posts
+                .get(&0)
+                .unwrap()
+                .commenter_count,
+            1
+        );
+
+        (2..MAX_COMMENTS_PER_AUTHOR_PER_WINDOW).for_each(|_| {
+            assert!(add_comment_to_post_impl(
+                &mut canister_data,
+                bob,
+                comment_arg(None, "Nice"),
+                now
+            )
+            .is_ok());
+        });
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, "Nice"), now),
+            Err(CommentOnPostError::CommentRateLimitExceeded)
+        );
+        let next_window_starts_at = now + Duration::from_secs(COMMENT_RATE_LIMIT_WINDOW_IN_SECONDS);
+        assert_eq!(
+            add_comment_to_post_impl(
+                &mut canister_data,
+                bob,
+                comment_arg(None, "Nice"),
+                next_window_starts_at
+            ),
+            Ok(MAX_COMMENTS_PER_AUTHOR_PER_WINDOW)
+        );
+
+        canister_data
+            .all_created_posts
+            .get_mut(&0)
+            .unwrap()
+            .update_status(PostStatus::Deleted);
+        assert_eq!(
+            add_comment_to_post_impl(&mut canister_data, bob, comment_arg(None, "Nice"), now),
+            Err(CommentOnPostError::PostNotAvailableForComments)
+        );
+    }
+
+    #[test]
+    fn test_add_comment_to_post_impl_caps_comments_per_post() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+        canister_data.profile.principal_id = Some(alice);
+        insert_post(&mut canister_data, now);
+
+        (0..MAX_COMMENTS_PER_POST).for_each(|comment_id| {
+            canister_data.comments_map.insert(
+                GlobalCommentId(0, comment_id),
+                Comment::new(comment_id, None, alice, "Nice".into(), now),
+            );
+        });
+
+        assert_eq!(
+            add_comment_to_post_impl(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                comment_arg(None, "Nice"),
+                now
+            ),
+            Err(CommentOnPostError::CommentLimitReachedForPost)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        comment::{CommentDetailsForFrontend, CommentId, CommentStatus, GlobalCommentId},
+        error::GetCommentsOfPostError,
+    },
+    common::types::app_primitive_type::PostId,
+    pagination::{self, PaginationError},
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Lists the top level comments of a post, or the replies to `parent_comment_id`, newest first.
+/// Hidden comments are only listed for the post creator. Deleted comments are only listed
+/// while they still have replies, so that threads stay reachable.
+#[query]
+fn get_comments_of_post_with_pagination_cursor(
+    post_id: PostId,
+    parent_comment_id: Option<CommentId>,
+    from_inclusive_index: u64,
+    limit: u64,
+) -> Result<Vec<CommentDetailsForFrontend>, GetCommentsOfPostError> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_comments_of_post_with_pagination_cursor_impl(
+            canister_data,
+            ic_cdk::caller(),
+            post_id,
+            parent_comment_id,
+            from_inclusive_index,
+            limit,
+        )
+    })
+}
+
+fn get_comments_of_post_with_pagination_cursor_impl(
+    canister_data: &CanisterData,
+    api_caller: Principal,
+    post_id: PostId,
+    parent_comment_id: Option<CommentId>,
+    from_inclusive_index: u64,
+    limit: u64,
+) -> Result<Vec<CommentDetailsForFrontend>, GetCommentsOfPostError> {
+    if !canister_data.all_created_posts.contains_key(&post_id) {
+        return Err(GetCommentsOfPostError::PostNotFound);
+    }
+
+    let caller_is_post_creator = canister_data.profile.principal_id == Some(api_caller);
+
+    let comments = canister_data
+        .comments_map
+        .range(GlobalCommentId(post_id, 0)..GlobalCommentId(post_id + 1, 0))
+        .map(|(_, comment)| comment)
+        .filter(|comment| comment.parent_comment_id == parent_comment_id)
+        .filter(|comment| match comment.status {
+            CommentStatus::Visible => true,
+            CommentStatus::HiddenByPostCreator => caller_is_post_creator,
+            CommentStatus::Deleted => comment.reply_count > 0,
+        })
+        .collect::<Vec<_>>();
+
+    let (from_inclusive_index, limit) = pagination::get_pagination_bounds_cursor(
+        from_inclusive_index,
+        limit,
+        comments.len() as u64,
+    )
+    .map_err(|e| match e {
+        PaginationError::InvalidBoundsPassed => GetCommentsOfPostError::InvalidBoundsPassed,
+        PaginationError::ReachedEndOfItemsList => GetCommentsOfPostError::ReachedEndOfItemsList,
+        PaginationError::ExceededMaxNumberOfItemsAllowedInOneRequest => {
+            GetCommentsOfPostError::ExceededMaxNumberOfItemsAllowedInOneRequest
+        }
+    })?;
+
+    Ok(comments
+        .iter()
+        .rev()
+        .skip(from_inclusive_index as usize)
+        .take(limit as usize)
+        .map(|comment| comment.get_comment_details_for_frontend(post_id, &api_caller))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        comment::Comment,
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_get_comments_of_post_with_pagination_cursor_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+        let now = SystemTime::now();
+
+        assert_eq!(
+            get_comments_of_post_with_pagination_cursor_impl(&canister_data, bob, 0, None, 0, 10),
+            Err(GetCommentsOfPostError::PostNotFound)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &now,
+            ),
+        );
+
+        assert_eq!(
+            get_comments_of_post_with_pagination_cursor_impl(&canister_data, bob, 0, None, 0, 10),
+            Err(GetCommentsOfPostError::ReachedEndOfItemsList)
+        );
+
+        // * 0 and 1 are top level, 2 replies to 0, 3 is hidden and 4 is deleted without replies
+        let mut comments = vec![
+            Comment::new(0, None, bob, "First".into(), now),
+            Comment::new(1, None, bob, "Second".into(), now),
+            Comment::new(2, Some(0), alice, "Reply".into(), now),
+            Comment::new(3, None, bob, "Hidden".into(), now),
+            Comment::new(4, None, bob, "Deleted".into(), now),
+        ];
+        comments[0].reply_count = 1;
+        comments[3].update_status(CommentStatus::HiddenByPostCreator);
+        comments[4].update_status(CommentStatus::Deleted);
+        comments.into_iter().for_each(|comment| {
+            canister_data
+                .comments_map
+                .insert(GlobalCommentId(0, comment.id), comment);
+        });
+
+        let comment_ids = |result: Result<Vec<CommentDetailsForFrontend>, _>| {
+            result
+                .unwrap()
+                .iter()
+                .map(|comment| comment.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            comment_ids(get_comments_of_post_with_pagination_cursor_impl(
+                &canister_data,
+                bob,
+                0,
+                None,
+                0,
+                10
+            )),
+            vec![1, 0]
+        );
+        assert_eq!(
+            comment_ids(get_comments_of_post_with_pagination_cursor_impl(
+                &canister_data,
+                alice,
+                0,
+                None,
+                0,
+                10
+            )),
+            vec![3, 1, 0]
+        );
+        assert_eq!(
+            comment_ids(get_comments_of_post_with_pagination_cursor_impl(
+                &canister_data,
+                bob,
+                0,
+                None,
+                1,
+                10
+            )),
+            vec![0]
+        );
+        assert_eq!(
+            comment_ids(get_comments_of_post_with_pagination_cursor_impl(
+                &canister_data,
+                bob,
+                0,
+                Some(0),
+                0,
+                10
+            )),
+            vec![2]
+        );
+    }
+}
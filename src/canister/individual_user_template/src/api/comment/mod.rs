@@ -0,0 +1,4 @@
+pub mod add_comment_to_post;
+pub mod get_comments_of_post_with_pagination_cursor;
+pub mod update_comment_status;
+pub mod update_comment_toggle_like_status_by_caller;
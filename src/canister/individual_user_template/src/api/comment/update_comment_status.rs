@@ -0,0 +1,273 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        comment::{CommentId, CommentStatus, GlobalCommentId},
+        error::CommentOnPostError,
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        comment::add_comment_to_post::has_visible_comment_on_post,
+        post::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+/// Hides, restores or deletes a comment. Deleting can not be undone.
+///
+/// # Access Control
+/// The post creator can set any status. The comment author can only delete their comment.
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_comment_status(
+    post_id: PostId,
+    comment_id: CommentId,
+    status: CommentStatus,
+) -> Result<(), CommentOnPostError> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_comment_status_impl(canister_data, ic_cdk::caller(), post_id, comment_id, status)
+    })?;
+
+    update_last_canister_functionality_access_time();
+    update_scores_and_share_with_post_cache_if_difference_beyond_threshold(&post_id);
+
+    Ok(())
+}
+
+fn update_comment_status_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    post_id: PostId,
+    comment_id: CommentId,
+    status: CommentStatus,
+) -> Result<(), CommentOnPostError> {
+    if current_caller == Principal::anonymous() {
+        return Err(CommentOnPostError::Unauthenticated);
+    }
+
+    let post_creator = canister_data
+        .profile
+        .principal_id
+        .ok_or(CommentOnPostError::UserPrincipalNotSet)?;
+
+    let global_comment_id = GlobalCommentId(post_id, comment_id);
+    let mut comment = canister_data
+        .comments_map
+        .get(&global_comment_id)
+        .ok_or(CommentOnPostError::CommentNotFound)?;
+
+    if comment.status == CommentStatus::Deleted {
+        return Err(CommentOnPostError::CommentAlreadyDeleted);
+    }
+
+    let caller_is_author_deleting =
+        current_caller == comment.author_principal_id && status == CommentStatus::Deleted;
+    if current_caller != post_creator && !caller_is_author_deleting {
+        return Err(CommentOnPostError::Unauthorized);
+    }
+
+    let was_visible = comment.status == CommentStatus::Visible;
+    let is_visible = status == CommentStatus::Visible;
+    let parent_comment_id = comment.parent_comment_id;
+    let author_principal_id = comment.author_principal_id;
+    let author_was_commenter =
+        has_visible_comment_on_post(canister_data, post_id, author_principal_id);
+
+    comment.update_status(status);
+    canister_data
+        .comments_map
+        .insert(global_comment_id, comment);
+
+    let author_is_commenter =
+        has_visible_comment_on_post(canister_data, post_id, author_principal_id);
+    if author_was_commenter != author_is_commenter {
+        if let Some(post) = canister_data.all_created_posts.get_mut(&post_id) {
+            post.commenter_count = if author_is_commenter {
+                post.commenter_count + 1
+            } else {
+                post.commenter_count.saturating_sub(1)
+            };
+        }
+    }
+
+    if was_visible != is_visible {
+        let adjust = |count: u64| {
+            if is_visible {
+                count + 1
+            } else {
+                count.saturating_sub(1)
+            }
+        };
+
+        if let Some(post) = canister_data.all_created_posts.get_mut(&post_id) {
+            post.comment_count = adjust(post.comment_count);
+        }
+
+        if let Some(parent_comment_id) = parent_comment_id {
+            let global_parent_comment_id = GlobalCommentId(post_id, parent_comment_id);
+            if let Some(mut parent_comment) =
+                canister_data.comments_map.get(&global_parent_comment_id)
+            {
+                parent_comment.reply_count = adjust(parent_comment.reply_count);
+                canister_data
+                    .comments_map
+                    .insert(global_parent_comment_id, parent_comment);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        comment::Comment,
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+        get_mock_user_charlie_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_update_comment_status_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+        let now = SystemTime::now();
+
+        canister_data.profile.principal_id = Some(alice);
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &now,
+        );
+        post.comment_count = 2;
+        post.commenter_count = 1;
+        canister_data.all_created_posts.insert(0, post);
+
+        let mut parent_comment = Comment::new(0, None, bob, "Nice".into(), now);
+        parent_comment.reply_count = 1;
+        canister_data
+            .comments_map
+            .insert(GlobalCommentId(0, 0), parent_comment);
+        canister_data.comments_map.insert(
+            GlobalCommentId(0, 1),
+            Comment::new(1, Some(0), bob, "Agreed".into(), now),
+        );
+
+        let counts = |canister_data: &CanisterData| {
+            (
+                canister_data
+                    .all_created_posts
+                    .get(&0)
+                    .unwrap()
+                    .comment_count,
+                canister_data
+                    .comments_map
+                    .get(&GlobalCommentId(0, 0))
+                    .unwrap()
+                    .reply_count,
+            )
+        };
+
+        assert_eq!(
+            update_comment_status_impl(
+                &mut canister_data,
+                get_mock_user_charlie_principal_id(),
+                0,
+                1,
+                CommentStatus::Deleted
+            ),
+            Err(CommentOnPostError::Unauthorized)
+        );
+        assert_eq!(
+            update_comment_status_impl(
+                &mut canister_data,
+                bob,
+                0,
+                1,
+                CommentStatus::HiddenByPostCreator
+            ),
+            Err(CommentOnPostError::Unauthorized)
+        );
+
+        // * the post creator hides and restores the reply
+        assert_eq!(
+            update_comment_status_impl(
+                &mut canister_data,
+                alice,
+                0,
+                1,
+                CommentStatus::HiddenByPostCreator
+            ),
+            Ok(())
+        );
+        assert_eq!(counts(&canister_data), (1, 0));
+        assert_eq!(
+            update_comment_status_impl(&mut canister_data, alice, 0, 1, CommentStatus::Visible),
+            Ok(())
+        );
+        assert_eq!(counts(&canister_data), (2, 1));
+
+        // * the author deletes their reply
+        assert_eq!(
+            update_comment_status_impl(&mut canister_data, bob, 0, 1, CommentStatus::Deleted),
+            Ok(())
+        );
+        assert_eq!(counts(&canister_data), (1, 0));
+        assert!(canister_data
+            .comments_map
+            .get(&GlobalCommentId(0, 1))
+            .unwrap()
+            .text
+            .is_empty());
+        assert_eq!(
+            update_comment_status_impl(&mut canister_data, alice, 0, 1, CommentStatus::Visible),
+            Err(CommentOnPostError::CommentAlreadyDeleted)
+        );
+
+        let commenter_count = |canister_data: &CanisterData| {
+            canister_data
+                .all_created_posts
+                .get(&0)
+                .unwrap()
+                .commenter_count
+        };
+
+        // * bob still has a visible comment after deleting the reply
+        assert_eq!(commenter_count(&canister_data), 1);
+        assert_eq!(
+            update_comment_status_impl(
+                &mut canister_data,
+                alice,
+                0,
+                0,
+                CommentStatus::HiddenByPostCreator
+            ),
+            Ok(())
+        );
+        assert_eq!(commenter_count(&canister_data), 0);
+        assert_eq!(
+            update_comment_status_impl(&mut canister_data, alice, 0, 0, CommentStatus::Visible),
+            Ok(())
+        );
+        assert_eq!(commenter_count(&canister_data), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        comment::{CommentId, CommentStatus, GlobalCommentId},
+        error::CommentOnPostError,
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// Returns true if the caller likes the comment after the toggle
+///
+/// # Access Control
+/// Any authenticated caller
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_comment_toggle_like_status_by_caller(
+    post_id: PostId,
+    comment_id: CommentId,
+) -> Result<bool, CommentOnPostError> {
+    update_last_canister_functionality_access_time();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_comment_toggle_like_status_by_caller_impl(
+            canister_data,
+            ic_cdk::caller(),
+            post_id,
+            comment_id,
+        )
+    })
+}
+
+fn update_comment_toggle_like_status_by_caller_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    post_id: PostId,
+    comment_id: CommentId,
+) -> Result<bool, CommentOnPostError> {
+    if current_caller == Principal::anonymous() {
+        return Err(CommentOnPostError::Unauthenticated);
+    }
+
+    if canister_data.blocked_principals.contains(&current_caller) {
+        return Err(CommentOnPostError::BlockedByPostCreator);
+    }
+
+    let global_comment_id = GlobalCommentId(post_id, comment_id);
+    let mut comment = canister_data
+        .comments_map
+        .get(&global_comment_id)
+        .filter(|comment| comment.status == CommentStatus::Visible)
+        .ok_or(CommentOnPostError::CommentNotFound)?;
+
+    let liked = comment.toggle_like_status(&current_caller);
+    canister_data
+        .comments_map
+        .insert(global_comment_id, comment);
+
+    Ok(liked)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::comment::Comment;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_update_comment_toggle_like_status_by_caller_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+
+        assert_eq!(
+            update_comment_toggle_like_status_by_caller_impl(&mut canister_data, bob, 0, 0),
+            Err(CommentOnPostError::CommentNotFound)
+        );
+
+        canister_data.comments_map.insert(
+            GlobalCommentId(0, 0),
+            Comment::new(
+                0,
+                None,
+                get_mock_user_alice_principal_id(),
+                "Nice".into(),
+                SystemTime::now(),
+            ),
+        );
+
+        assert_eq!(
+            update_comment_toggle_like_status_by_caller_impl(
+                &mut canister_data,
+                Principal::anonymous(),
+                0,
+                0
+            ),
+            Err(CommentOnPostError::Unauthenticated)
+        );
+        assert_eq!(
+            update_comment_toggle_like_status_by_caller_impl(&mut canister_data, bob, 0, 0),
+            Ok(true)
+        );
+        assert_eq!(
+            canister_data
+                .comments_map
+                .get(&GlobalCommentId(0, 0))
+                .unwrap()
+                .likes
+                .len(),
+            1
+        );
+        assert_eq!(
+            update_comment_toggle_like_status_by_caller_impl(&mut canister_data, bob, 0, 0),
+            Ok(false)
+        );
+    }
+}
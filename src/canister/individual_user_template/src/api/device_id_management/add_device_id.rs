@@ -2,11 +2,13 @@ use ic_cdk::{api, query};
 use ic_cdk_macros::update;
 use shared_utils::canister_specific::individual_user_template::types::device_id::DeviceIdentity;
 
-use crate::{data_model::CanisterData, CANISTER_DATA};
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
 
 /// #### Access Control
 /// Only the user whose profile details are stored in this canister can add the device identity.
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn add_device_id(identity_token: String) -> Result<bool, (String)> {
     // * access control
     let current_caller = ic_cdk::caller();
@@ -0,0 +1,85 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::individual_user_template::types::{
+    error::FollowAnotherUserProfileError, follow::FollowEntryDetail,
+};
+
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// # Access Control
+/// Only the canister of the profile being deleted can drop the follow edges pointing to it
+#[update(guard = "is_not_frozen_for_migration")]
+fn remove_follow_edges_with_deleted_profile(
+    deleted_profile: FollowEntryDetail,
+) -> Result<(), FollowAnotherUserProfileError> {
+    let calling_canister_principal = ic_cdk::caller();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        remove_follow_edges_with_deleted_profile_impl(
+            canister_data,
+            &calling_canister_principal,
+            &deleted_profile,
+        )
+    })
+}
+
+fn remove_follow_edges_with_deleted_profile_impl(
+    canister_data: &mut CanisterData,
+    calling_canister_principal: &Principal,
+    deleted_profile: &FollowEntryDetail,
+) -> Result<(), FollowAnotherUserProfileError> {
+    if *calling_canister_principal != deleted_profile.canister_id {
+        return Err(FollowAnotherUserProfileError::Unauthorized);
+    }
+
+    canister_data.follow_data.follower.remove(deleted_profile);
+    canister_data.follow_data.following.remove(deleted_profile);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_remove_follow_edges_with_deleted_profile_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = FollowEntryDetail {
+            principal_id: get_mock_user_alice_principal_id(),
+            canister_id: get_mock_user_alice_canister_id(),
+        };
+        let bob = FollowEntryDetail {
+            principal_id: get_mock_user_bob_principal_id(),
+            canister_id: get_mock_user_bob_canister_id(),
+        };
+        canister_data.follow_data.follower.add(alice.clone());
+        canister_data.follow_data.following.add(alice.clone());
+        canister_data.follow_data.follower.add(bob.clone());
+
+        let result = remove_follow_edges_with_deleted_profile_impl(
+            &mut canister_data,
+            &get_mock_user_bob_canister_id(),
+            &alice,
+        );
+        assert_eq!(result, Err(FollowAnotherUserProfileError::Unauthorized));
+        assert!(canister_data.follow_data.follower.contains(&alice));
+
+        let result = remove_follow_edges_with_deleted_profile_impl(
+            &mut canister_data,
+            &get_mock_user_alice_canister_id(),
+            &alice,
+        );
+        assert!(result.is_ok());
+        assert!(!canister_data.follow_data.follower.contains(&alice));
+        assert!(!canister_data.follow_data.following.contains(&alice));
+        assert!(canister_data.follow_data.follower.contains(&bob));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    data_model::CanisterData, CANISTER_DATA,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
 use candid::Principal;
@@ -15,7 +15,7 @@ pub const MAX_USERS_IN_FOLLOWER_FOLLOWING_LIST: u64 = 10_000;
 
 /// # Access Control
 /// Only the user whose profile details are stored in this canister can follow another user's profile.
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn update_profiles_i_follow_toggle_list_with_specified_profile(
     arg: FolloweeArg,
 ) -> Result<bool, FollowAnotherUserProfileError> {
@@ -6,7 +6,7 @@ use shared_utils::canister_specific::individual_user_template::types::{
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    data_model::CanisterData, CANISTER_DATA,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
 use super::update_profiles_i_follow_toggle_list_with_specified_profile::MAX_USERS_IN_FOLLOWER_FOLLOWING_LIST;
@@ -19,7 +19,7 @@ pub struct FollowerArg {
 
 /// # Access Control
 /// Only allow calls from canisters of this project
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn update_profiles_that_follow_me_toggle_list_with_specified_profile(
     arg: FollowerArg,
 ) -> Result<bool, FollowAnotherUserProfileError> {
@@ -46,6 +46,13 @@ fn update_profiles_that_follow_me_toggle_list_with_specified_profile_impl(
         return Err(FollowAnotherUserProfileError::Unauthorized);
     }
 
+    if canister_data
+        .blocked_principals
+        .contains(&arg.follower_principal_id)
+    {
+        return Err(FollowAnotherUserProfileError::UserITriedToFollowHasBlockedMe);
+    }
+
     if canister_data.follow_data.follower.len() as u64 > MAX_USERS_IN_FOLLOWER_FOLLOWING_LIST {
         return Err(FollowAnotherUserProfileError::UserITriedToFollowHasTheirFollowersListFull);
     }
@@ -1,121 +1,219 @@
+use std::time::SystemTime;
+
 use candid::Principal;
+use ic_cdk::api::{call::RejectionCode, management_canister::provisional::CanisterId};
 use ic_cdk_macros::update;
 use shared_utils::{
     canister_specific::individual_user_template::types::{
         arg::PlaceBetArg,
         error::BetOnCurrentlyViewingPostError,
-        hot_or_not::{BetOutcomeForBetMaker, BettingStatus, PlacedBetDetail},
+        hot_or_not::{
+            BetIdempotencyKey, BetOutcomeForBetMaker, BettingStatus, PendingBet, PlacedBetDetail,
+            RoomId, SlotId,
+        },
     },
     common::{
-        types::utility_token::token_event::{StakeEvent, TokenEvent},
+        types::{
+            app_primitive_type::PostId,
+            utility_token::token_event::{StakeEvent, TokenEvent},
+        },
         utils::system_time,
     },
 };
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    data_model::CanisterData, CANISTER_DATA,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
-#[update]
+/// The bet amount leaves the balance before the post creator's canister is called
+/// and the bet is kept as pending until that canister confirms or rejects it.
+/// If the call fails without saying whether the bet was taken, `reconcile_pending_bets`
+/// settles it later from what the post creator's canister has on record
+/// for the bet's idempotency key.
+#[update(guard = "is_not_frozen_for_migration")]
 async fn bet_on_currently_viewing_post(
     place_bet_arg: PlaceBetArg,
 ) -> Result<BettingStatus, BetOnCurrentlyViewingPostError> {
     let bet_maker_principal_id = ic_cdk::caller();
     let current_time = system_time::get_current_system_time_from_ic();
 
-    CANISTER_DATA.with(|canister_data_ref_cell| {
-        validate_incoming_bet(
-            &canister_data_ref_cell.borrow(),
-            &bet_maker_principal_id,
+    let idempotency_key = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        validate_incoming_bet(canister_data, &bet_maker_principal_id, &place_bet_arg)?;
+
+        Ok(prepare_bet_impl(
+            canister_data,
             &place_bet_arg,
-        )
+            current_time,
+        ))
     })?;
 
     update_last_canister_functionality_access_time();
-    update_token_balance_before_bet_happens(place_bet_arg.bet_amount);
-    
+
+    let pending_bet_key = (place_bet_arg.post_canister_id, place_bet_arg.post_id);
+
     let response = ic_cdk::call::<_, (Result<BettingStatus, BetOnCurrentlyViewingPostError>,)>(
         place_bet_arg.post_canister_id,
         "receive_bet_from_bet_makers_canister",
         (
             place_bet_arg.clone(),
-            CANISTER_DATA.with(|canister_data_ref_cell| {
-                canister_data_ref_cell
-                    .borrow()
-                    .profile
-                    .principal_id
-                    .unwrap()
-            }),
+            bet_maker_principal_id,
+            Some(idempotency_key),
         ),
     )
-    .await
-    .map_err(|_| {
-        update_token_balance_after_bet_placement_fails(place_bet_arg.bet_amount);
-        BetOnCurrentlyViewingPostError::PostCreatorCanisterCallFailed
-    })?
-    .0?;
+    .await;
 
     match response {
-        BettingStatus::BettingClosed => {
-            update_token_balance_after_bet_placement_fails(place_bet_arg.bet_amount);
-            return Err(BetOnCurrentlyViewingPostError::BettingClosed);
-        }
-        BettingStatus::BettingOpen {
-            ongoing_slot,
-            ongoing_room,
-            ..
-        } => {
-            CANISTER_DATA.with(|canister_data_ref_cell| {
-                let canister_data = &mut canister_data_ref_cell.borrow_mut();
-
-                let my_token_balance = &mut canister_data.my_token_balance;
-                my_token_balance.handle_token_event(TokenEvent::Stake {
-                    amount: place_bet_arg.bet_amount,
-                    details: StakeEvent::BetOnHotOrNotPost {
-                        post_canister_id: place_bet_arg.post_canister_id,
-                        post_id: place_bet_arg.post_id,
-                        bet_amount: place_bet_arg.bet_amount,
-                        bet_direction: place_bet_arg.bet_direction.clone(),
-                    },
-                    timestamp: current_time,
-                });
-
-                let all_hot_or_not_bets_placed = &mut canister_data.all_hot_or_not_bets_placed;
-                all_hot_or_not_bets_placed.insert(
-                    (place_bet_arg.post_canister_id, place_bet_arg.post_id),
-                    PlacedBetDetail {
-                        canister_id: place_bet_arg.post_canister_id,
-                        post_id: place_bet_arg.post_id,
-                        slot_id: ongoing_slot,
-                        room_id: ongoing_room,
-                        bet_direction: place_bet_arg.bet_direction,
-                        bet_placed_at: current_time,
-                        amount_bet: place_bet_arg.bet_amount,
-                        outcome_received: BetOutcomeForBetMaker::default(),
-                    },
+        Ok((Ok(
+            betting_status @ BettingStatus::BettingOpen {
+                ongoing_slot,
+                ongoing_room,
+                ..
+            },
+        ),)) => {
+            CANISTER_DATA.with_borrow_mut(|canister_data| {
+                commit_pending_bet_impl(
+                    canister_data,
+                    &pending_bet_key,
+                    ongoing_slot,
+                    ongoing_room,
                 );
             });
+
+            Ok(betting_status)
+        }
+        Ok((Ok(BettingStatus::BettingClosed),)) => {
+            CANISTER_DATA.with_borrow_mut(|canister_data| {
+                abort_pending_bet_impl(canister_data, &pending_bet_key);
+            });
+
+            Err(BetOnCurrentlyViewingPostError::BettingClosed)
+        }
+        Ok((Err(e),)) => {
+            CANISTER_DATA.with_borrow_mut(|canister_data| {
+                abort_pending_bet_impl(canister_data, &pending_bet_key);
+            });
+
+            Err(e)
+        }
+        Err((rejection_code, _)) => {
+            CANISTER_DATA.with_borrow_mut(|canister_data| {
+                if call_rejection_leaves_no_bet_behind(&rejection_code) {
+                    abort_pending_bet_impl(canister_data, &pending_bet_key);
+                } else {
+                    mark_pending_bet_for_reconciliation_impl(canister_data, &pending_bet_key);
+                }
+            });
+
+            Err(BetOnCurrentlyViewingPostError::PostCreatorCanisterCallFailed)
         }
     }
+}
+
+/// The post creator's canister trapped or was never reached, so it holds no bet.
+/// For any other rejection the bet may have been accepted, and the amount
+/// stays held until the bet is reconciled.
+pub fn call_rejection_leaves_no_bet_behind(rejection_code: &RejectionCode) -> bool {
+    matches!(
+        rejection_code,
+        RejectionCode::CanisterError
+            | RejectionCode::CanisterReject
+            | RejectionCode::DestinationInvalid
+    )
+}
 
-    Ok(response)
+/// Takes the bet amount out of the balance and records the bet as pending.
+/// Returns the idempotency key the bet is sent with.
+fn prepare_bet_impl(
+    canister_data: &mut CanisterData,
+    place_bet_arg: &PlaceBetArg,
+    current_time: SystemTime,
+) -> BetIdempotencyKey {
+    let idempotency_key = canister_data.next_bet_idempotency_key;
+    canister_data.next_bet_idempotency_key += 1;
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_pre_bet(place_bet_arg.bet_amount);
+
+    canister_data.pending_bets.insert(
+        (place_bet_arg.post_canister_id, place_bet_arg.post_id),
+        PendingBet {
+            post_canister_id: place_bet_arg.post_canister_id,
+            post_id: place_bet_arg.post_id,
+            amount: place_bet_arg.bet_amount,
+            bet_direction: place_bet_arg.bet_direction.clone(),
+            idempotency_key,
+            placed_at: current_time,
+            awaiting_reconciliation: false,
+        },
+    );
+
+    idempotency_key
 }
 
-// this #[update] is for local testing only see: src/lib/integration_tests/tests/upgrade/excessive_tokens_test.rs
-// #[update]
-pub fn update_token_balance_before_bet_happens(  bet_amount: u64) {
-    CANISTER_DATA.with_borrow_mut(|canister_data| {
-        canister_data.my_token_balance.adjust_balance_pre_bet(bet_amount);
-    });
+/// Moves a pending bet the post creator's canister accepted into the placed bets.
+/// Does nothing if the bet was already settled.
+pub fn commit_pending_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_bet_key: &(CanisterId, PostId),
+    slot_id: SlotId,
+    room_id: RoomId,
+) {
+    let Some(pending_bet) = canister_data.pending_bets.remove(pending_bet_key) else {
+        return;
+    };
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::Stake {
+            amount: pending_bet.amount,
+            details: StakeEvent::BetOnHotOrNotPost {
+                post_canister_id: pending_bet.post_canister_id,
+                post_id: pending_bet.post_id,
+                bet_amount: pending_bet.amount,
+                bet_direction: pending_bet.bet_direction.clone(),
+            },
+            timestamp: pending_bet.placed_at,
+        });
+
+    canister_data.all_hot_or_not_bets_placed.insert(
+        *pending_bet_key,
+        PlacedBetDetail {
+            canister_id: pending_bet.post_canister_id,
+            post_id: pending_bet.post_id,
+            slot_id,
+            room_id,
+            bet_direction: pending_bet.bet_direction,
+            bet_placed_at: pending_bet.placed_at,
+            amount_bet: pending_bet.amount,
+            outcome_received: BetOutcomeForBetMaker::default(),
+        },
+    );
 }
 
-fn update_token_balance_after_bet_placement_fails( bet_amount: u64) {
-    CANISTER_DATA.with_borrow_mut(|canister_data| {
-        canister_data.my_token_balance.adjust_balance_for_failed_bet_placement(bet_amount);
-    });
+/// Drops a pending bet the post creator's canister does not hold and returns its amount.
+/// Does nothing if the bet was already settled.
+pub fn abort_pending_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_bet_key: &(CanisterId, PostId),
+) {
+    if let Some(pending_bet) = canister_data.pending_bets.remove(pending_bet_key) {
+        canister_data
+            .my_token_balance
+            .adjust_balance_for_failed_bet_placement(pending_bet.amount);
+    }
 }
 
+/// Hands a pending bet whose outcome the call did not tell over to `reconcile_pending_bets`
+pub fn mark_pending_bet_for_reconciliation_impl(
+    canister_data: &mut CanisterData,
+    pending_bet_key: &(CanisterId, PostId),
+) {
+    if let Some(pending_bet) = canister_data.pending_bets.get_mut(pending_bet_key) {
+        pending_bet.awaiting_reconciliation = true;
+    }
+}
 
 fn validate_incoming_bet(
     canister_data: &CanisterData,
@@ -141,9 +239,11 @@ fn validate_incoming_bet(
         return Err(BetOnCurrentlyViewingPostError::InsufficientBalance);
     }
 
+    let placed_bet_key = (place_bet_arg.post_canister_id, place_bet_arg.post_id);
     if canister_data
         .all_hot_or_not_bets_placed
-        .contains_key(&(place_bet_arg.post_canister_id, place_bet_arg.post_id))
+        .contains_key(&placed_bet_key)
+        || canister_data.pending_bets.contains_key(&placed_bet_key)
     {
         return Err(BetOnCurrentlyViewingPostError::UserAlreadyParticipatedInThisPost);
     }
@@ -256,4 +356,65 @@ mod test {
             Err(BetOnCurrentlyViewingPostError::UserAlreadyParticipatedInThisPost)
         );
     }
+
+    #[test]
+    fn test_prepare_commit_and_abort_pending_bet_impl() {
+        let mut canister_data = CanisterData::default();
+        canister_data.my_token_balance.utility_token_balance = 1000;
+        let place_bet_arg = PlaceBetArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            bet_amount: 100,
+            bet_direction: BetDirection::Hot,
+        };
+        let pending_bet_key = (get_mock_user_alice_canister_id(), 0);
+
+        let idempotency_key =
+            prepare_bet_impl(&mut canister_data, &place_bet_arg, SystemTime::now());
+        assert_eq!(idempotency_key, 0);
+        assert_eq!(canister_data.next_bet_idempotency_key, 1);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        assert!(!canister_data.pending_bets[&pending_bet_key].awaiting_reconciliation);
+
+        // * a pending bet counts as participation until it is settled
+        canister_data.profile.principal_id = Some(get_mock_user_bob_principal_id());
+        assert_eq!(
+            validate_incoming_bet(
+                &canister_data,
+                &get_mock_user_bob_principal_id(),
+                &place_bet_arg
+            ),
+            Err(BetOnCurrentlyViewingPostError::UserAlreadyParticipatedInThisPost)
+        );
+
+        mark_pending_bet_for_reconciliation_impl(&mut canister_data, &pending_bet_key);
+        assert!(canister_data.pending_bets[&pending_bet_key].awaiting_reconciliation);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+
+        abort_pending_bet_impl(&mut canister_data, &pending_bet_key);
+        assert!(canister_data.pending_bets.is_empty());
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1000);
+
+        // * settling twice has no effect
+        abort_pending_bet_impl(&mut canister_data, &pending_bet_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1000);
+
+        let idempotency_key =
+            prepare_bet_impl(&mut canister_data, &place_bet_arg, SystemTime::now());
+        assert_eq!(idempotency_key, 1);
+
+        commit_pending_bet_impl(&mut canister_data, &pending_bet_key, 1, 2);
+        assert!(canister_data.pending_bets.is_empty());
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        let placed_bet_detail = canister_data
+            .all_hot_or_not_bets_placed
+            .get(&pending_bet_key)
+            .unwrap();
+        assert_eq!(placed_bet_detail.slot_id, 1);
+        assert_eq!(placed_bet_detail.room_id, 2);
+        assert_eq!(placed_bet_detail.amount_bet, 100);
+
+        abort_pending_bet_impl(&mut canister_data, &pending_bet_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+    }
 }
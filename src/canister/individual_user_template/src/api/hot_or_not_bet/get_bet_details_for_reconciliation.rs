@@ -0,0 +1,181 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::{
+        BetDetails, BetReconciliationDetails, GlobalBetId, GlobalRoomId, StablePrincipal,
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Returns what this canister has on record for the bet a user placed on one of its posts,
+/// or for the bet they last withdrew from it if none is placed.
+/// Bet makers' canisters use it to settle bets and withdrawals they never got a reply for.
+///
+/// # Access Control
+/// Any caller, bets on posts are public
+#[query]
+fn get_bet_details_for_reconciliation(
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+) -> Option<BetReconciliationDetails> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_bet_details_for_reconciliation_impl(canister_data, post_id, bet_maker_principal_id)
+    })
+}
+
+fn get_bet_details_for_reconciliation_impl(
+    canister_data: &CanisterData,
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+) -> Option<BetReconciliationDetails> {
+    let Some((global_bet_id, bet_details)) =
+        get_bet_placed_by_bet_maker_on_post(canister_data, post_id, bet_maker_principal_id)
+    else {
+        return canister_data
+            .withdrawn_bets
+            .get(&(post_id, bet_maker_principal_id))
+            .cloned();
+    };
+
+    let room_bet_outcome = canister_data
+        .room_details_map
+        .get(&global_bet_id.0)
+        .map(|room_details| room_details.bet_outcome)
+        .unwrap_or_default();
+
+    Some(BetReconciliationDetails {
+        slot_id: global_bet_id.0 .1,
+        room_id: global_bet_id.0 .2,
+        amount: bet_details.amount,
+        outcome: bet_details.get_outcome_for_bet_maker(&room_bet_outcome),
+        bet_direction: bet_details.bet_direction,
+        idempotency_key: bet_details.idempotency_key,
+        withdrawn_amount: None,
+    })
+}
+
+pub fn get_bet_placed_by_bet_maker_on_post(
+    canister_data: &CanisterData,
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+) -> Option<(GlobalBetId, BetDetails)> {
+    let bet_maker = StablePrincipal(bet_maker_principal_id);
+
+    if !canister_data
+        .post_principal_map
+        .contains_key(&(post_id, bet_maker.clone()))
+    {
+        return None;
+    }
+
+    let start_global_bet_id = GlobalBetId(GlobalRoomId(post_id, 0, 0), StablePrincipal::default());
+    let end_global_bet_id =
+        GlobalBetId(GlobalRoomId(post_id + 1, 0, 0), StablePrincipal::default());
+
+    canister_data
+        .bet_details_map
+        .range(start_global_bet_id..end_global_bet_id)
+        .find(|(global_bet_id, _)| global_bet_id.1 == bet_maker)
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::hot_or_not::{
+        BetDirection, BetOutcomeForBetMaker, BetPayout, RoomBetPossibleOutcomes, RoomDetailsV1,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_get_bet_details_for_reconciliation_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+
+        assert_eq!(
+            get_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            None
+        );
+
+        let global_bet_id = GlobalBetId(GlobalRoomId(0, 2, 3), StablePrincipal(bob));
+        canister_data.bet_details_map.insert(
+            global_bet_id.clone(),
+            BetDetails {
+                amount: 100,
+                bet_direction: BetDirection::Not,
+                payout: BetPayout::NotCalculatedYet,
+                bet_maker_canister_id: get_mock_user_bob_canister_id(),
+                bet_maker_informed_status: None,
+                idempotency_key: Some(7),
+                placed_at: None,
+            },
+        );
+        canister_data
+            .post_principal_map
+            .insert((0, StablePrincipal(bob)), ());
+        canister_data
+            .room_details_map
+            .insert(global_bet_id.0, RoomDetailsV1::default());
+
+        let expected = BetReconciliationDetails {
+            slot_id: 2,
+            room_id: 3,
+            amount: 100,
+            bet_direction: BetDirection::Not,
+            idempotency_key: Some(7),
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+            withdrawn_amount: None,
+        };
+        assert_eq!(
+            get_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            get_bet_details_for_reconciliation_impl(
+                &canister_data,
+                0,
+                get_mock_user_alice_principal_id()
+            ),
+            None
+        );
+
+        let mut bet_details = canister_data.bet_details_map.get(&global_bet_id).unwrap();
+        bet_details.payout = BetPayout::Calculated(180);
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id.clone(), bet_details);
+        canister_data.room_details_map.insert(
+            global_bet_id.0,
+            RoomDetailsV1 {
+                bet_outcome: RoomBetPossibleOutcomes::NotWon,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            get_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            Some(BetReconciliationDetails {
+                outcome: BetOutcomeForBetMaker::Won(180),
+                ..expected.clone()
+            })
+        );
+
+        // * once the bet is withdrawn, the withdrawal is on record instead
+        canister_data.bet_details_map.remove(&global_bet_id);
+        let withdrawn_bet = BetReconciliationDetails {
+            withdrawn_amount: Some(100),
+            ..expected
+        };
+        canister_data
+            .withdrawn_bets
+            .insert((0, bob), withdrawn_bet.clone());
+        assert_eq!(
+            get_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            Some(withdrawn_bet)
+        );
+    }
+}
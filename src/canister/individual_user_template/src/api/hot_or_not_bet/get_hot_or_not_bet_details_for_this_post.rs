@@ -82,6 +82,11 @@ mod test {
                 home_feed_score: FeedScore::default(),
                 hot_or_not_details: Some(HotOrNotDetails::default()),
                 slots_left_to_be_computed: Default::default(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
         );
 
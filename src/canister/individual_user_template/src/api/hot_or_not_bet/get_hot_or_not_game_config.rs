@@ -0,0 +1,10 @@
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig;
+
+use crate::CANISTER_DATA;
+
+/// Game config that new posts on this canister are created with
+#[query]
+fn get_hot_or_not_game_config() -> HotOrNotGameConfig {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.hot_or_not_game_config.clone())
+}
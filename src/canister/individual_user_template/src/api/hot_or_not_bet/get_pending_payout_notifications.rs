@@ -0,0 +1,34 @@
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::{
+        GlobalBetId, PendingPayoutNotification,
+    },
+    common::utils::permissions::is_caller_controller_or_global_admin,
+};
+
+use crate::CANISTER_DATA;
+
+const MAX_PAYOUT_NOTIFICATIONS_IN_ONE_REQUEST: u64 = 100;
+
+/// Lists the payout notifications that could not be delivered to bet makers yet,
+/// including the ones that are no longer retried.
+///
+/// # Access Control
+/// Only the controller or a global admin
+#[query(guard = "is_caller_controller_or_global_admin")]
+fn get_pending_payout_notifications(
+    from_inclusive_index: u64,
+    limit: u64,
+) -> Vec<(GlobalBetId, PendingPayoutNotification)> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .payout_notification_outbox
+            .iter()
+            .skip(from_inclusive_index as usize)
+            .take(limit.min(MAX_PAYOUT_NOTIFICATIONS_IN_ONE_REQUEST) as usize)
+            .map(|(global_bet_id, pending_payout_notification)| {
+                (global_bet_id.clone(), pending_payout_notification.clone())
+            })
+            .collect()
+    })
+}
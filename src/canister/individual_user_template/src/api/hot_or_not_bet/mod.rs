@@ -1,9 +1,20 @@
 pub mod bet_on_currently_viewing_hot_or_not_post;
 pub mod get_bet_details_for_a_user_on_a_post;
+pub mod get_bet_details_for_reconciliation;
 pub mod get_hot_or_not_bet_details_for_this_post;
 pub mod get_hot_or_not_bets_placed_by_this_profile_with_pagination;
+pub mod get_hot_or_not_game_config;
 pub mod get_individual_hot_or_not_bet_placed_by_this_profile;
+pub mod get_pending_payout_notifications;
+pub mod payout_notification_outbox;
 pub mod receive_bet_from_bet_makers_canister;
 pub mod receive_bet_winnings_when_distributed;
+pub mod receive_bet_withdrawal_from_bet_makers_canister;
+pub mod reconcile_pending_bets;
 pub mod reenqueue_timers_for_pending_bet_outcomes;
+pub mod requeue_payout_notification;
+pub mod slot_tabulation_queue;
 pub mod tabulate_hot_or_not_outcome_for_post_slot;
+pub mod update_hot_or_not_game_config;
+pub mod update_payout_notification_retry_horizon;
+pub mod withdraw_hot_or_not_bet;
@@ -0,0 +1,413 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+
+use candid::Principal;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::{
+        BetMakerInformedStatus, BetOutcomeForBetMaker, GlobalBetId, PendingPayoutNotification,
+    },
+    common::{types::app_primitive_type::PostId, utils::system_time},
+    constant::DEFAULT_PAYOUT_NOTIFICATION_RETRY_HORIZON_IN_SECONDS,
+};
+
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration_impl, CANISTER_DATA,
+};
+
+use super::tabulate_hot_or_not_outcome_for_post_slot::receive_bet_winnings_when_distributed;
+
+const PAYOUT_NOTIFICATION_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_PAYOUT_NOTIFICATIONS_RETRIED_PER_RUN: usize = 50;
+// * given up notifications stay around this long so they can still be requeued
+const GIVEN_UP_PAYOUT_NOTIFICATION_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Starts the recurring timer that retries the payout notifications which are due
+/// and drops the ones given up on past their retention.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+pub fn start_payout_notification_retry_timer() {
+    ic_cdk_timers::set_timer_interval(PAYOUT_NOTIFICATION_RETRY_INTERVAL, || {
+        if CANISTER_DATA
+            .with_borrow(is_not_frozen_for_migration_impl)
+            .is_err()
+        {
+            return;
+        }
+
+        let current_time = system_time::get_current_system_time_from_ic();
+
+        let due_payout_notifications = CANISTER_DATA.with_borrow_mut(|canister_data| {
+            drop_expired_payout_notifications(
+                &mut canister_data.payout_notification_outbox,
+                &current_time,
+            );
+            start_due_payout_notification_attempts(
+                &mut canister_data.payout_notification_outbox,
+                &current_time,
+            )
+        });
+
+        due_payout_notifications.into_iter().for_each(
+            |(global_bet_id, pending_payout_notification)| {
+                ic_cdk::spawn(receive_bet_winnings_when_distributed(
+                    global_bet_id,
+                    pending_payout_notification.bet_maker_canister_id,
+                    pending_payout_notification.post_id,
+                    pending_payout_notification.outcome,
+                ))
+            },
+        );
+    });
+}
+
+fn get_due_payout_notifications<K: Clone>(
+    outbox: &BTreeMap<K, PendingPayoutNotification>,
+    current_time: &SystemTime,
+) -> Vec<(K, PendingPayoutNotification)> {
+    outbox
+        .iter()
+        .filter(|(_, pending_payout_notification)| pending_payout_notification.is_due(current_time))
+        .take(MAX_PAYOUT_NOTIFICATIONS_RETRIED_PER_RUN)
+        .map(|(key, pending_payout_notification)| {
+            (key.clone(), pending_payout_notification.clone())
+        })
+        .collect()
+}
+
+/// Returns the due notifications, each rescheduled so that it is not sent again
+/// while its attempt is in flight
+pub fn start_due_payout_notification_attempts<K: Ord + Clone>(
+    outbox: &mut BTreeMap<K, PendingPayoutNotification>,
+    current_time: &SystemTime,
+) -> Vec<(K, PendingPayoutNotification)> {
+    let due_payout_notifications = get_due_payout_notifications(outbox, current_time);
+
+    due_payout_notifications.iter().for_each(|(key, _)| {
+        if let Some(pending_payout_notification) = outbox.get_mut(key) {
+            pending_payout_notification.record_attempt_started(*current_time);
+        }
+    });
+
+    due_payout_notifications
+}
+
+pub fn drop_expired_payout_notifications<K>(
+    outbox: &mut BTreeMap<K, PendingPayoutNotification>,
+    current_time: &SystemTime,
+) {
+    outbox.retain(|_, pending_payout_notification| {
+        !pending_payout_notification
+            .is_expired(current_time, GIVEN_UP_PAYOUT_NOTIFICATION_RETENTION)
+    });
+}
+
+pub fn get_payout_notification_retry_horizon(canister_data: &CanisterData) -> Duration {
+    Duration::from_secs(
+        canister_data
+            .configuration
+            .payout_notification_retry_horizon_in_seconds
+            .unwrap_or(DEFAULT_PAYOUT_NOTIFICATION_RETRY_HORIZON_IN_SECONDS),
+    )
+}
+
+/// Queues the notification of a first failed attempt,
+/// or reschedules the queued one if there already is one.
+/// The queued one is sent on to where the bet maker's canister was last found.
+pub fn record_failed_payout_notification<K: Ord>(
+    outbox: &mut BTreeMap<K, PendingPayoutNotification>,
+    key: K,
+    failed_payout_notification: PendingPayoutNotification,
+    retry_horizon: Duration,
+) {
+    match outbox.get_mut(&key) {
+        Some(pending_payout_notification) => {
+            pending_payout_notification.bet_maker_canister_id =
+                failed_payout_notification.bet_maker_canister_id;
+            pending_payout_notification.record_failed_attempt(
+                failed_payout_notification.last_error,
+                failed_payout_notification.first_failed_at,
+                retry_horizon,
+            )
+        }
+        None => {
+            outbox.insert(key, failed_payout_notification);
+        }
+    }
+}
+
+/// Marks the bet maker as informed and drops the notification from the outbox on success.
+/// On failure the notification is queued, or rescheduled if it already was.
+pub fn record_payout_notification_result_impl(
+    canister_data: &mut CanisterData,
+    global_bet_id: GlobalBetId,
+    bet_maker_canister_id: Principal,
+    post_id: PostId,
+    bet_outcome_for_bet_maker: BetOutcomeForBetMaker,
+    result: Result<(), String>,
+    current_time: SystemTime,
+) {
+    let bet_maker_informed_status = match result {
+        Ok(()) => {
+            canister_data
+                .payout_notification_outbox
+                .remove(&global_bet_id);
+
+            BetMakerInformedStatus::InformedSuccessfully
+        }
+        Err(error) => {
+            let retry_horizon = get_payout_notification_retry_horizon(canister_data);
+
+            record_failed_payout_notification(
+                &mut canister_data.payout_notification_outbox,
+                global_bet_id.clone(),
+                PendingPayoutNotification::new(
+                    bet_maker_canister_id,
+                    post_id,
+                    bet_outcome_for_bet_maker,
+                    error.clone(),
+                    current_time,
+                ),
+                retry_horizon,
+            );
+
+            BetMakerInformedStatus::Failed(error)
+        }
+    };
+
+    if let Some(mut bet_detail) = canister_data.bet_details_map.get(&global_bet_id) {
+        bet_detail.bet_maker_canister_id = bet_maker_canister_id;
+        bet_detail.bet_maker_informed_status = Some(bet_maker_informed_status);
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id, bet_detail);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::hot_or_not::{
+        BetDetails, BetDirection, BetPayout, GlobalRoomId, StablePrincipal,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn global_bet_id(post_id: PostId) -> GlobalBetId {
+        GlobalBetId(
+            GlobalRoomId(post_id, 1, 1),
+            StablePrincipal(get_mock_user_bob_principal_id()),
+        )
+    }
+
+    #[test]
+    fn test_record_payout_notification_result_impl() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        canister_data
+            .configuration
+            .payout_notification_retry_horizon_in_seconds = Some(60 * 60);
+        canister_data.bet_details_map.insert(
+            global_bet_id(0),
+            BetDetails {
+                amount: 100,
+                bet_direction: BetDirection::Hot,
+                payout: BetPayout::Calculated(180),
+                bet_maker_canister_id: get_mock_user_bob_canister_id(),
+                bet_maker_informed_status: None,
+                idempotency_key: None,
+                placed_at: None,
+            },
+        );
+
+        record_payout_notification_result_impl(
+            &mut canister_data,
+            global_bet_id(0),
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            Err("unreachable".into()),
+            current_time,
+        );
+        let pending_payout_notification = canister_data
+            .payout_notification_outbox
+            .get(&global_bet_id(0))
+            .unwrap();
+        assert_eq!(pending_payout_notification.attempts, 1);
+        assert_eq!(
+            pending_payout_notification.outcome,
+            BetOutcomeForBetMaker::Won(180)
+        );
+        assert_eq!(
+            canister_data
+                .bet_details_map
+                .get(&global_bet_id(0))
+                .unwrap()
+                .bet_maker_informed_status,
+            Some(BetMakerInformedStatus::Failed("unreachable".into()))
+        );
+
+        // * not due before its backoff has passed
+        assert!(get_due_payout_notifications(
+            &canister_data.payout_notification_outbox,
+            &current_time
+        )
+        .is_empty());
+        let retry_time = current_time + Duration::from_secs(60);
+        assert_eq!(
+            get_due_payout_notifications(&canister_data.payout_notification_outbox, &retry_time)
+                .len(),
+            1
+        );
+
+        // * the bet maker's canister was migrated and the retry followed it there
+        record_payout_notification_result_impl(
+            &mut canister_data,
+            global_bet_id(0),
+            get_mock_user_alice_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            Err("still unreachable".into()),
+            retry_time,
+        );
+        let pending_payout_notification = canister_data
+            .payout_notification_outbox
+            .get(&global_bet_id(0))
+            .unwrap();
+        assert_eq!(pending_payout_notification.attempts, 2);
+        assert_eq!(
+            pending_payout_notification.bet_maker_canister_id,
+            get_mock_user_alice_canister_id()
+        );
+
+        // * past the horizon the notification is kept but no longer retried
+        let past_horizon = current_time + Duration::from_secs(60 * 60);
+        record_payout_notification_result_impl(
+            &mut canister_data,
+            global_bet_id(0),
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            Err("still unreachable".into()),
+            past_horizon,
+        );
+        assert_eq!(
+            canister_data
+                .payout_notification_outbox
+                .get(&global_bet_id(0))
+                .unwrap()
+                .gave_up_at,
+            Some(past_horizon)
+        );
+        assert!(get_due_payout_notifications(
+            &canister_data.payout_notification_outbox,
+            &past_horizon
+        )
+        .is_empty());
+
+        record_payout_notification_result_impl(
+            &mut canister_data,
+            global_bet_id(0),
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            Ok(()),
+            past_horizon,
+        );
+        assert!(canister_data.payout_notification_outbox.is_empty());
+        assert_eq!(
+            canister_data
+                .bet_details_map
+                .get(&global_bet_id(0))
+                .unwrap()
+                .bet_maker_informed_status,
+            Some(BetMakerInformedStatus::InformedSuccessfully)
+        );
+    }
+
+    #[test]
+    fn test_an_attempt_in_flight_is_not_started_again() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        canister_data.payout_notification_outbox.insert(
+            global_bet_id(0),
+            PendingPayoutNotification::new(
+                get_mock_user_bob_canister_id(),
+                0,
+                BetOutcomeForBetMaker::Won(180),
+                "unreachable".into(),
+                current_time,
+            ),
+        );
+
+        let retry_time = current_time + Duration::from_secs(60);
+        assert_eq!(
+            start_due_payout_notification_attempts(
+                &mut canister_data.payout_notification_outbox,
+                &retry_time
+            )
+            .len(),
+            1
+        );
+        // * the attempt has not returned by the next timer run
+        let next_run = retry_time + PAYOUT_NOTIFICATION_RETRY_INTERVAL;
+        assert!(start_due_payout_notification_attempts(
+            &mut canister_data.payout_notification_outbox,
+            &next_run
+        )
+        .is_empty());
+        assert_eq!(
+            canister_data
+                .payout_notification_outbox
+                .get(&global_bet_id(0))
+                .unwrap()
+                .attempts,
+            1
+        );
+    }
+
+    #[test]
+    fn test_drop_expired_payout_notifications() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        for post_id in [0, 1] {
+            canister_data.payout_notification_outbox.insert(
+                global_bet_id(post_id),
+                PendingPayoutNotification::new(
+                    get_mock_user_bob_canister_id(),
+                    post_id,
+                    BetOutcomeForBetMaker::Won(180),
+                    "unreachable".into(),
+                    current_time,
+                ),
+            );
+        }
+        canister_data
+            .payout_notification_outbox
+            .get_mut(&global_bet_id(0))
+            .unwrap()
+            .record_failed_attempt("unreachable".into(), current_time, Duration::ZERO);
+
+        drop_expired_payout_notifications(
+            &mut canister_data.payout_notification_outbox,
+            &current_time,
+        );
+        assert_eq!(canister_data.payout_notification_outbox.len(), 2);
+
+        // * only the notification given up on expires
+        drop_expired_payout_notifications(
+            &mut canister_data.payout_notification_outbox,
+            &(current_time + GIVEN_UP_PAYOUT_NOTIFICATION_RETENTION),
+        );
+        assert_eq!(
+            canister_data
+                .payout_notification_outbox
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&global_bet_id(1)]
+        );
+    }
+}
@@ -7,7 +7,10 @@ use shared_utils::{
     canister_specific::individual_user_template::types::{
         arg::PlaceBetArg,
         error::BetOnCurrentlyViewingPostError,
-        hot_or_not::{BetDirection, BettingStatus},
+        hot_or_not::{
+            BetDirection, BetIdempotencyKey, BettingStatus, GlobalBetId, GlobalRoomId,
+            StablePrincipal,
+        },
     },
     common::utils::system_time,
 };
@@ -15,16 +18,21 @@ use shared_utils::{
 use crate::{
     api::{
         canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::get_bet_details_for_reconciliation::get_bet_placed_by_bet_maker_on_post,
         post::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold,
     },
     data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
     CANISTER_DATA,
 };
 
-#[update]
+/// A bet retried with the idempotency key of a bet already placed gets
+/// the status of that bet back instead of being placed again.
+#[update(guard = "is_not_frozen_for_migration")]
 fn receive_bet_from_bet_makers_canister(
     place_bet_arg: PlaceBetArg,
     bet_maker_principal_id: Principal,
+    idempotency_key: Option<BetIdempotencyKey>,
 ) -> Result<BettingStatus, BetOnCurrentlyViewingPostError> {
     let bet_maker_canister_id = ic_cdk::caller();
     update_last_canister_functionality_access_time();
@@ -35,6 +43,7 @@ fn receive_bet_from_bet_makers_canister(
             &bet_maker_principal_id,
             &bet_maker_canister_id,
             place_bet_arg.clone(),
+            idempotency_key,
             &system_time::get_current_system_time_from_ic(),
         )
     })?;
@@ -56,6 +65,7 @@ fn receive_bet_from_bet_makers_canister_impl(
     bet_maker_principal_id: &Principal,
     bet_maker_canister_id: &CanisterId,
     place_bet_arg: PlaceBetArg,
+    idempotency_key: Option<BetIdempotencyKey>,
     current_time: &SystemTime,
 ) -> Result<BettingStatus, BetOnCurrentlyViewingPostError> {
     let PlaceBetArg {
@@ -65,9 +75,43 @@ fn receive_bet_from_bet_makers_canister_impl(
         ..
     } = place_bet_arg;
 
-    let post = canister_data.all_created_posts.get_mut(&post_id).unwrap();
+    if let Some((global_bet_id, bet_details)) =
+        get_bet_placed_by_bet_maker_on_post(canister_data, post_id, *bet_maker_principal_id)
+    {
+        if idempotency_key.is_some() && bet_details.idempotency_key == idempotency_key {
+            let room_details = canister_data
+                .room_details_map
+                .get(&global_bet_id.0)
+                .unwrap_or_default();
+            let post = canister_data
+                .all_created_posts
+                .get(&post_id)
+                .ok_or(BetOnCurrentlyViewingPostError::PostNotFound)?;
 
-    post.place_hot_or_not_bet_v1(
+            return Ok(BettingStatus::BettingOpen {
+                started_at: post.created_at,
+                number_of_participants: (room_details.total_hot_bets + room_details.total_not_bets)
+                    as u8,
+                ongoing_slot: global_bet_id.0 .1,
+                ongoing_room: global_bet_id.0 .2,
+                has_this_user_participated_in_this_post: Some(true),
+            });
+        }
+    }
+
+    if canister_data
+        .blocked_principals
+        .contains(bet_maker_principal_id)
+    {
+        return Err(BetOnCurrentlyViewingPostError::BlockedByPostCreator);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or(BetOnCurrentlyViewingPostError::PostNotFound)?;
+
+    let betting_status = post.place_hot_or_not_bet_v1(
         bet_maker_principal_id,
         bet_maker_canister_id,
         bet_amount,
@@ -77,7 +121,27 @@ fn receive_bet_from_bet_makers_canister_impl(
         &mut canister_data.bet_details_map,
         &mut canister_data.post_principal_map,
         &mut canister_data.slot_details_map,
-    )
+    )?;
+
+    if let BettingStatus::BettingOpen {
+        ongoing_slot,
+        ongoing_room,
+        ..
+    } = betting_status
+    {
+        let global_bet_id = GlobalBetId(
+            GlobalRoomId(post_id, ongoing_slot, ongoing_room),
+            StablePrincipal(*bet_maker_principal_id),
+        );
+        if let Some(mut bet_details) = canister_data.bet_details_map.get(&global_bet_id) {
+            bet_details.idempotency_key = idempotency_key;
+            canister_data
+                .bet_details_map
+                .insert(global_bet_id, bet_details);
+        }
+    }
+
+    Ok(betting_status)
 }
 
 fn update_profile_stats_with_bet_placed(
@@ -102,6 +166,7 @@ mod test {
     };
     use test_utils::setup::test_constants::{
         get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
     };
 
     use super::*;
@@ -109,6 +174,22 @@ mod test {
     #[test]
     fn test_receive_bet_from_bet_makers_canister_impl() {
         let mut canister_data = CanisterData::default();
+
+        let result = receive_bet_from_bet_makers_canister_impl(
+            &mut canister_data,
+            &get_mock_user_alice_principal_id(),
+            &get_mock_user_alice_canister_id(),
+            PlaceBetArg {
+                post_canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                bet_amount: 100,
+                bet_direction: BetDirection::Hot,
+            },
+            Some(0),
+            &SystemTime::now(),
+        );
+        assert_eq!(result, Err(BetOnCurrentlyViewingPostError::PostNotFound));
+
         canister_data.all_created_posts.insert(
             0,
             Post::new(
@@ -134,6 +215,7 @@ mod test {
                 bet_amount: 100,
                 bet_direction: BetDirection::Hot,
             },
+            Some(0),
             &SystemTime::now(),
         );
 
@@ -168,5 +250,77 @@ mod test {
             bet_details.bet_maker_canister_id,
             get_mock_user_alice_canister_id()
         );
+        assert_eq!(bet_details.idempotency_key, Some(0));
+
+        // * a retry with the same key gets the placed bet back without placing it again
+        let retried_result = receive_bet_from_bet_makers_canister_impl(
+            &mut canister_data,
+            &get_mock_user_alice_principal_id(),
+            &get_mock_user_alice_canister_id(),
+            PlaceBetArg {
+                post_canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                bet_amount: 100,
+                bet_direction: BetDirection::Hot,
+            },
+            Some(0),
+            &SystemTime::now(),
+        );
+        assert_eq!(retried_result, result);
+        assert_eq!(
+            canister_data
+                .room_details_map
+                .get(&global_room_id)
+                .unwrap()
+                .room_bets_total_pot,
+            100
+        );
+
+        let result = receive_bet_from_bet_makers_canister_impl(
+            &mut canister_data,
+            &get_mock_user_alice_principal_id(),
+            &get_mock_user_alice_canister_id(),
+            PlaceBetArg {
+                post_canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                bet_amount: 100,
+                bet_direction: BetDirection::Hot,
+            },
+            Some(1),
+            &SystemTime::now(),
+        );
+        assert_eq!(
+            result,
+            Err(BetOnCurrentlyViewingPostError::UserAlreadyParticipatedInThisPost)
+        );
+
+        canister_data
+            .blocked_principals
+            .insert(get_mock_user_bob_principal_id());
+        let result = receive_bet_from_bet_makers_canister_impl(
+            &mut canister_data,
+            &get_mock_user_bob_principal_id(),
+            &get_mock_user_bob_canister_id(),
+            PlaceBetArg {
+                post_canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                bet_amount: 100,
+                bet_direction: BetDirection::Not,
+            },
+            None,
+            &SystemTime::now(),
+        );
+        assert_eq!(
+            result,
+            Err(BetOnCurrentlyViewingPostError::BlockedByPostCreator)
+        );
+        assert_eq!(
+            canister_data
+                .room_details_map
+                .get(&global_room_id)
+                .unwrap()
+                .total_not_bets,
+            0
+        );
     }
 }
@@ -1,3 +1,6 @@
+use std::time::SystemTime;
+
+use ic_cdk::api::management_canister::provisional::CanisterId;
 use ic_cdk_macros::update;
 
 use shared_utils::{
@@ -11,11 +14,27 @@ use shared_utils::{
     },
 };
 
-use crate::CANISTER_DATA;
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration,
+    util::canister_migration::move_bets_placed_with_former_canisters, CANISTER_DATA,
+};
 
-#[update]
-fn receive_bet_winnings_when_distributed(post_id: PostId, outcome: BetOutcomeForBetMaker) {
+/// Called by the post creator's canister once the slot the bet was placed in is tabulated.
+/// If the post creator's profile was migrated since, the bet is looked up under
+/// the canister it was placed with.
+#[update(guard = "is_not_frozen_for_migration")]
+async fn receive_bet_winnings_when_distributed(post_id: PostId, outcome: BetOutcomeForBetMaker) {
     let post_creator_canister_id = ic_cdk::caller();
+
+    let has_bet_on_record = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(post_creator_canister_id, post_id))
+    });
+    if !has_bet_on_record {
+        move_bets_placed_with_former_canisters(post_creator_canister_id).await;
+    }
+
     let current_time = system_time::get_current_system_time_from_ic();
 
     ic_cdk::println!(
@@ -24,63 +43,61 @@ fn receive_bet_winnings_when_distributed(post_id: PostId, outcome: BetOutcomeFor
         post_id
     );
 
-    if !CANISTER_DATA.with(|canister_data_ref_cell| {
-        canister_data_ref_cell
-            .borrow()
-            .all_hot_or_not_bets_placed
-            .contains_key(&(post_creator_canister_id, post_id))
-    }) {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        record_bet_outcome_impl(
+            canister_data,
+            post_creator_canister_id,
+            post_id,
+            outcome,
+            current_time,
+        );
+    });
+}
+
+/// Pays out the outcome of a placed bet, once.
+/// Outcomes for bets this canister has no record of are ignored.
+pub fn record_bet_outcome_impl(
+    canister_data: &mut CanisterData,
+    post_creator_canister_id: CanisterId,
+    post_id: PostId,
+    outcome: BetOutcomeForBetMaker,
+    current_time: SystemTime,
+) {
+    let Some(placed_bet_detail) = canister_data
+        .all_hot_or_not_bets_placed
+        .get_mut(&(post_creator_canister_id, post_id))
+    else {
         return;
-    }
+    };
 
-    if !CANISTER_DATA.with(|canister_data_ref_cell| {
-        canister_data_ref_cell
-            .borrow()
-            .all_hot_or_not_bets_placed
-            .get(&(post_creator_canister_id, post_id))
-            .unwrap()
-            .outcome_received
-            == BetOutcomeForBetMaker::AwaitingResult
-    }) {
+    if placed_bet_detail.outcome_received != BetOutcomeForBetMaker::AwaitingResult {
         return;
     }
 
-    CANISTER_DATA.with(|canister_data_ref_cell| {
-        let mut canister_data = canister_data_ref_cell.borrow_mut();
+    placed_bet_detail.outcome_received = outcome.clone();
+    let slot_id = placed_bet_detail.slot_id;
+    let room_id = placed_bet_detail.room_id;
+    let amount_bet = placed_bet_detail.amount_bet;
 
-        let all_hot_or_not_bets_placed = &mut canister_data.all_hot_or_not_bets_placed;
+    let winnings_amount = match outcome {
+        BetOutcomeForBetMaker::Draw(amount) => amount,
+        BetOutcomeForBetMaker::Won(amount) => amount,
+        _ => 0,
+    };
 
-        all_hot_or_not_bets_placed
-            .entry((post_creator_canister_id, post_id))
-            .and_modify(|placed_bet_detail| {
-                placed_bet_detail.outcome_received = outcome.clone();
-            });
-
-        let placed_bet_detail = all_hot_or_not_bets_placed
-            .get(&(post_creator_canister_id, post_id))
-            .cloned()
-            .unwrap();
-
-        let my_token_balance = &mut canister_data.my_token_balance;
-        my_token_balance.handle_token_event(TokenEvent::HotOrNotOutcomePayout {
-            amount: match outcome {
-                BetOutcomeForBetMaker::Draw(amount) => amount,
-                BetOutcomeForBetMaker::Won(amount) => amount,
-                _ => 0,
-            },
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::HotOrNotOutcomePayout {
+            amount: winnings_amount,
             details: HotOrNotOutcomePayoutEvent::WinningsEarnedFromBet {
                 post_canister_id: post_creator_canister_id,
                 post_id,
-                slot_id: placed_bet_detail.slot_id,
-                room_id: placed_bet_detail.room_id,
-                winnings_amount: match outcome {
-                    BetOutcomeForBetMaker::Draw(amount) => amount,
-                    BetOutcomeForBetMaker::Won(amount) => amount,
-                    _ => 0,
-                },
+                slot_id,
+                room_id,
+                winnings_amount,
                 event_outcome: outcome,
+                amount_bet,
             },
             timestamp: current_time,
         });
-    });
 }
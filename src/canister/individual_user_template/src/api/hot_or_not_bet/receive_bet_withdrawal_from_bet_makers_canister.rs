@@ -0,0 +1,220 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::WithdrawHotOrNotBetError,
+        hot_or_not::{
+            BetDirection, BetOutcomeForBetMaker, BetReconciliationDetails, BetWithdrawalKind,
+        },
+    },
+    common::{types::app_primitive_type::PostId, utils::system_time},
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::get_bet_details_for_reconciliation::get_bet_placed_by_bet_maker_on_post,
+        post::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+/// Takes a bet on one of this canister's posts out of its room and returns
+/// the amount the bet maker's canister has to credit back.
+/// The withdrawal is kept on record so that the bet maker's canister
+/// can settle it through `get_bet_details_for_reconciliation` if it never gets this reply.
+///
+/// # Access Control
+/// Only the bet maker's canister that placed the bet
+#[update(guard = "is_not_frozen_for_migration")]
+fn receive_bet_withdrawal_from_bet_makers_canister(
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+    withdrawal_kind: BetWithdrawalKind,
+) -> Result<u64, WithdrawHotOrNotBetError> {
+    let bet_maker_canister_id = ic_cdk::caller();
+    update_last_canister_functionality_access_time();
+
+    let amount_owed = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        receive_bet_withdrawal_from_bet_makers_canister_impl(
+            canister_data,
+            &bet_maker_principal_id,
+            &bet_maker_canister_id,
+            post_id,
+            withdrawal_kind,
+            &system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_scores_and_share_with_post_cache_if_difference_beyond_threshold(&post_id);
+
+    Ok(amount_owed)
+}
+
+fn receive_bet_withdrawal_from_bet_makers_canister_impl(
+    canister_data: &mut CanisterData,
+    bet_maker_principal_id: &Principal,
+    bet_maker_canister_id: &CanisterId,
+    post_id: PostId,
+    withdrawal_kind: BetWithdrawalKind,
+    current_time: &SystemTime,
+) -> Result<u64, WithdrawHotOrNotBetError> {
+    let (global_bet_id, _) =
+        get_bet_placed_by_bet_maker_on_post(canister_data, post_id, *bet_maker_principal_id)
+            .ok_or(WithdrawHotOrNotBetError::BetNotFound)?;
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or(WithdrawHotOrNotBetError::BetNotFound)?;
+
+    let (bet_details, amount_owed) = post.withdraw_hot_or_not_bet_v1(
+        bet_maker_principal_id,
+        bet_maker_canister_id,
+        withdrawal_kind,
+        current_time,
+        &mut canister_data.room_details_map,
+        &mut canister_data.bet_details_map,
+        &mut canister_data.post_principal_map,
+    )?;
+
+    let profile_stats = &mut canister_data.profile.profile_stats;
+    match bet_details.bet_direction {
+        BetDirection::Hot => {
+            profile_stats.hot_bets_received = profile_stats.hot_bets_received.saturating_sub(1);
+        }
+        BetDirection::Not => {
+            profile_stats.not_bets_received = profile_stats.not_bets_received.saturating_sub(1);
+        }
+    }
+
+    canister_data.withdrawn_bets.insert(
+        (post_id, *bet_maker_principal_id),
+        BetReconciliationDetails {
+            slot_id: global_bet_id.0 .1,
+            room_id: global_bet_id.0 .2,
+            amount: bet_details.amount,
+            bet_direction: bet_details.bet_direction,
+            idempotency_key: bet_details.idempotency_key,
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+            withdrawn_amount: Some(amount_owed),
+        },
+    );
+
+    Ok(amount_owed)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::game_config::HotOrNotGameConfig,
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_receive_bet_withdrawal_from_bet_makers_canister_impl() {
+        let mut canister_data = CanisterData::default();
+        let post_created_at = SystemTime::now();
+        let bob = get_mock_user_bob_principal_id();
+        let bob_canister_id = get_mock_user_bob_canister_id();
+
+        assert_eq!(
+            receive_bet_withdrawal_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &bob_canister_id,
+                0,
+                BetWithdrawalKind::Cancel,
+                &post_created_at,
+            ),
+            Err(WithdrawHotOrNotBetError::BetNotFound)
+        );
+
+        let mut post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            HotOrNotGameConfig {
+                bet_cancellation_window_in_seconds: 60,
+                ..Default::default()
+            },
+        );
+        post.place_hot_or_not_bet_v1(
+            &bob,
+            &bob_canister_id,
+            100,
+            &BetDirection::Not,
+            &post_created_at,
+            &mut canister_data.room_details_map,
+            &mut canister_data.bet_details_map,
+            &mut canister_data.post_principal_map,
+            &mut canister_data.slot_details_map,
+        )
+        .unwrap();
+        canister_data.all_created_posts.insert(0, post);
+        canister_data.profile.profile_stats.not_bets_received = 1;
+
+        // * cashing out is disabled by default
+        assert_eq!(
+            receive_bet_withdrawal_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &bob_canister_id,
+                0,
+                BetWithdrawalKind::CashOut,
+                &post_created_at,
+            ),
+            Err(WithdrawHotOrNotBetError::CashOutNotAvailable)
+        );
+        assert_eq!(
+            receive_bet_withdrawal_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &get_mock_user_alice_canister_id(),
+                0,
+                BetWithdrawalKind::Cancel,
+                &post_created_at,
+            ),
+            Err(WithdrawHotOrNotBetError::Unauthorized)
+        );
+        assert_eq!(
+            receive_bet_withdrawal_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &bob_canister_id,
+                0,
+                BetWithdrawalKind::Cancel,
+                &(post_created_at + Duration::from_secs(30)),
+            ),
+            Ok(100)
+        );
+        assert_eq!(canister_data.profile.profile_stats.not_bets_received, 0);
+        assert!(canister_data.bet_details_map.is_empty());
+        assert_eq!(
+            canister_data
+                .withdrawn_bets
+                .get(&(0, bob))
+                .and_then(|withdrawn_bet| withdrawn_bet.withdrawn_amount),
+            Some(100)
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use std::time::{Duration, SystemTime};
+
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::{
+        BetOutcomeForBetMaker, BetReconciliationDetails, PendingBet,
+    },
+    common::{types::app_primitive_type::PostId, utils::system_time},
+};
+
+use crate::{
+    api::{
+        post::reconcile_pending_post_tips::{
+            get_pending_post_tips_due_for_reconciliation, reconcile_pending_post_tip,
+        },
+        prediction::reconcile_pending_prediction_bets::{
+            get_pending_prediction_bets_due_for_reconciliation, reconcile_pending_prediction_bet,
+        },
+        token::reconcile_pending_token_transfers::{
+            get_pending_token_transfers_due_for_reconciliation, reconcile_pending_token_transfer,
+        },
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration_impl,
+    CANISTER_DATA,
+};
+
+use super::{
+    bet_on_currently_viewing_hot_or_not_post::{abort_pending_bet_impl, commit_pending_bet_impl},
+    receive_bet_winnings_when_distributed::record_bet_outcome_impl,
+    withdraw_hot_or_not_bet::{
+        get_pending_bet_withdrawals_due_for_reconciliation, reconcile_pending_bet_withdrawal,
+    },
+};
+
+const PENDING_BET_RECONCILIATION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Starts the recurring timer that settles the hot or not and prediction bets, the bet
+/// withdrawals, the token transfers and the tips whose call to the other user's canister failed
+/// without saying whether it went through.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+pub fn start_pending_bet_reconciliation_timer() {
+    ic_cdk_timers::set_timer_interval(PENDING_BET_RECONCILIATION_INTERVAL, || {
+        if CANISTER_DATA
+            .with_borrow(is_not_frozen_for_migration_impl)
+            .is_err()
+        {
+            return;
+        }
+
+        let (
+            pending_bets,
+            pending_bet_withdrawals,
+            pending_prediction_bets,
+            pending_token_transfers,
+            pending_post_tips,
+        ) = CANISTER_DATA.with_borrow(|canister_data| {
+            (
+                get_pending_bets_due_for_reconciliation(canister_data),
+                get_pending_bet_withdrawals_due_for_reconciliation(canister_data),
+                get_pending_prediction_bets_due_for_reconciliation(canister_data),
+                get_pending_token_transfers_due_for_reconciliation(canister_data),
+                get_pending_post_tips_due_for_reconciliation(canister_data),
+            )
+        });
+
+        pending_bets
+            .into_iter()
+            .for_each(|pending_bet| ic_cdk::spawn(reconcile_pending_bet(pending_bet)));
+        pending_bet_withdrawals
+            .into_iter()
+            .for_each(|pending_bet_withdrawal| {
+                ic_cdk::spawn(reconcile_pending_bet_withdrawal(pending_bet_withdrawal))
+            });
+        pending_prediction_bets
+            .into_iter()
+            .for_each(|pending_prediction_bet| {
+                ic_cdk::spawn(reconcile_pending_prediction_bet(pending_prediction_bet))
+            });
+        pending_token_transfers.into_iter().for_each(
+            |(idempotency_key, pending_token_transfer)| {
+                ic_cdk::spawn(reconcile_pending_token_transfer(
+                    idempotency_key,
+                    pending_token_transfer,
+                ))
+            },
+        );
+        pending_post_tips
+            .into_iter()
+            .for_each(|(idempotency_key, pending_post_tip)| {
+                ic_cdk::spawn(reconcile_pending_post_tip(
+                    idempotency_key,
+                    pending_post_tip,
+                ))
+            });
+    });
+}
+
+/// No call survives an upgrade, so every bet still pending afterwards lost its reply
+pub fn mark_pending_bets_orphaned_by_upgrade_for_reconciliation() {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data
+            .pending_bets
+            .values_mut()
+            .for_each(|pending_bet| pending_bet.awaiting_reconciliation = true);
+        canister_data
+            .pending_bet_withdrawals
+            .values_mut()
+            .for_each(|pending_bet_withdrawal| {
+                pending_bet_withdrawal.awaiting_reconciliation = true
+            });
+        canister_data
+            .pending_prediction_bets
+            .values_mut()
+            .for_each(|pending_prediction_bet| {
+                pending_prediction_bet.awaiting_reconciliation = true
+            });
+        canister_data
+            .pending_token_transfers
+            .values_mut()
+            .for_each(|pending_token_transfer| {
+                pending_token_transfer.awaiting_reconciliation = true
+            });
+        canister_data
+            .pending_post_tips
+            .values_mut()
+            .for_each(|pending_post_tip| pending_post_tip.awaiting_reconciliation = true);
+    });
+}
+
+fn get_pending_bets_due_for_reconciliation(canister_data: &CanisterData) -> Vec<PendingBet> {
+    canister_data
+        .pending_bets
+        .values()
+        .filter(|pending_bet| pending_bet.awaiting_reconciliation)
+        .cloned()
+        .collect()
+}
+
+async fn reconcile_pending_bet(pending_bet: PendingBet) {
+    let Some(bet_maker_principal_id) =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id)
+    else {
+        return;
+    };
+
+    // * on failure the bet stays pending and is retried on the next run
+    let Ok((bet_reconciliation_details,)) = ic_cdk::call::<_, (Option<BetReconciliationDetails>,)>(
+        pending_bet.post_canister_id,
+        "get_bet_details_for_reconciliation",
+        (pending_bet.post_id, bet_maker_principal_id),
+    )
+    .await
+    else {
+        return;
+    };
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        settle_pending_bet_impl(
+            canister_data,
+            &(pending_bet.post_canister_id, pending_bet.post_id),
+            bet_reconciliation_details,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+/// Commits the pending bet if the post creator's canister holds it,
+/// refunds it otherwise. An outcome the bet missed while it was
+/// pending is paid out right away.
+fn settle_pending_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_bet_key: &(CanisterId, PostId),
+    bet_reconciliation_details: Option<BetReconciliationDetails>,
+    current_time: SystemTime,
+) {
+    let Some(pending_bet) = canister_data.pending_bets.get(pending_bet_key) else {
+        return;
+    };
+
+    match bet_reconciliation_details {
+        // * canisters that predate idempotency keys hold no key,
+        // * and a user only ever has one bet on a post.
+        // * A withdrawn bet on record is an earlier bet on the same post.
+        Some(bet_reconciliation_details)
+            if bet_reconciliation_details.withdrawn_amount.is_none()
+                && bet_reconciliation_details
+                    .idempotency_key
+                    .map_or(true, |idempotency_key| {
+                        idempotency_key == pending_bet.idempotency_key
+                    }) =>
+        {
+            commit_pending_bet_impl(
+                canister_data,
+                pending_bet_key,
+                bet_reconciliation_details.slot_id,
+                bet_reconciliation_details.room_id,
+            );
+
+            if bet_reconciliation_details.outcome != BetOutcomeForBetMaker::AwaitingResult {
+                record_bet_outcome_impl(
+                    canister_data,
+                    pending_bet_key.0,
+                    pending_bet_key.1,
+                    bet_reconciliation_details.outcome,
+                    current_time,
+                );
+            }
+        }
+        _ => abort_pending_bet_impl(canister_data, pending_bet_key),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::hot_or_not::BetDirection;
+    use test_utils::setup::test_constants::get_mock_user_alice_canister_id;
+
+    use super::*;
+
+    fn pending_bet(post_id: PostId, idempotency_key: u64, placed_at: SystemTime) -> PendingBet {
+        PendingBet {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id,
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            idempotency_key,
+            placed_at,
+            awaiting_reconciliation: true,
+        }
+    }
+
+    #[test]
+    fn test_get_pending_bets_due_for_reconciliation() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+
+        // * however old, a bet whose call is still in flight is not reconciled
+        canister_data.pending_bets.insert(
+            (get_mock_user_alice_canister_id(), 0),
+            PendingBet {
+                awaiting_reconciliation: false,
+                ..pending_bet(0, 0, current_time - Duration::from_secs(60 * 60))
+            },
+        );
+        canister_data.pending_bets.insert(
+            (get_mock_user_alice_canister_id(), 1),
+            pending_bet(1, 1, current_time),
+        );
+
+        let due_pending_bets = get_pending_bets_due_for_reconciliation(&canister_data);
+        assert_eq!(due_pending_bets.len(), 1);
+        assert_eq!(due_pending_bets[0].post_id, 1);
+    }
+
+    #[test]
+    fn test_settle_pending_bet_impl() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        // * the amounts of the three pending bets below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 700;
+        for (post_id, idempotency_key) in [(0, 0), (1, 1), (2, 2)] {
+            canister_data.pending_bets.insert(
+                (get_mock_user_alice_canister_id(), post_id),
+                pending_bet(post_id, idempotency_key, current_time),
+            );
+        }
+        let reconciliation_details = BetReconciliationDetails {
+            slot_id: 1,
+            room_id: 1,
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            idempotency_key: Some(0),
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+            withdrawn_amount: None,
+        };
+
+        // * the post creator's canister holds the bet
+        settle_pending_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 0),
+            Some(reconciliation_details.clone()),
+            current_time,
+        );
+        assert!(canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 0)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 700);
+
+        // * the post creator's canister never got the bet
+        settle_pending_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 1),
+            None,
+            current_time,
+        );
+        assert!(!canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 1)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        // * the bet was accepted and its slot already settled while it was pending
+        settle_pending_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 2),
+            Some(BetReconciliationDetails {
+                idempotency_key: Some(2),
+                outcome: BetOutcomeForBetMaker::Won(180),
+                ..reconciliation_details
+            }),
+            current_time,
+        );
+        assert_eq!(
+            canister_data
+                .all_hot_or_not_bets_placed
+                .get(&(get_mock_user_alice_canister_id(), 2))
+                .unwrap()
+                .outcome_received,
+            BetOutcomeForBetMaker::Won(180)
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 980);
+        assert!(canister_data.pending_bets.is_empty());
+    }
+}
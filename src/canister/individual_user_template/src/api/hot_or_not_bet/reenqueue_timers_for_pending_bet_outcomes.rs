@@ -6,23 +6,24 @@ use std::{
 
 use ic_cdk_macros::update;
 use shared_utils::{
-    canister_specific::individual_user_template::types::hot_or_not::{
-        RoomBetPossibleOutcomes, DURATION_OF_EACH_SLOT_IN_SECONDS,
-    },
-    common::utils::permissions::is_caller_controller_or_global_admin,
-    common::utils::system_time,
+    canister_specific::individual_user_template::types::hot_or_not::RoomBetPossibleOutcomes,
+    common::utils::permissions::is_caller_controller_or_global_admin, common::utils::system_time,
 };
 
 use crate::{data_model::CanisterData, CANISTER_DATA};
 
-use super::tabulate_hot_or_not_outcome_for_post_slot::tabulate_hot_or_not_outcome_for_post_slot;
-
-pub fn reenqueue_timers_for_pending_bet_outcomes() {
-    let current_time = system_time::get_current_system_time_from_ic();
+use super::slot_tabulation_queue::{
+    enqueue_slot_tabulation_job, enqueue_slot_tabulation_jobs_for_post,
+};
 
-    CANISTER_DATA.with_borrow(|canister_data| {
+/// Enqueues every slot that is still waiting to be computed.
+/// Slots that already have a job queued are skipped, so running this more than once is harmless.
+pub fn enqueue_slot_tabulation_jobs_for_pending_bet_outcomes() {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
         let posts = get_posts_that_have_pending_outcomes(canister_data);
-        reenqueue_timers_for_these_posts(canister_data, posts, &current_time);
+        posts.into_iter().for_each(|post_id| {
+            enqueue_slot_tabulation_jobs_for_post(canister_data, post_id);
+        });
     })
 }
 
@@ -35,66 +36,24 @@ fn get_posts_that_have_pending_outcomes(canister_data: &CanisterData) -> Vec<u64
         .collect()
 }
 
-fn reenqueue_timers_for_these_posts(
-    canister_data: &CanisterData,
-    post_ids: Vec<u64>,
-    current_time: &SystemTime,
-) {
-    for post_id in post_ids {
-        let post = canister_data.all_created_posts.get(&post_id).unwrap();
-
-        post.slots_left_to_be_computed
-            .iter()
-            .for_each(|slot_number| {
-                let slot_id = *slot_number;
-                ic_cdk_timers::set_timer(
-                    post.created_at
-                        .checked_add(Duration::from_secs(
-                            (slot_id as u64) * DURATION_OF_EACH_SLOT_IN_SECONDS,
-                        ))
-                        .unwrap()
-                        .duration_since(*current_time)
-                        .unwrap_or_default(),
-                    move || {
-                        tabulate_hot_or_not_outcome_for_post_slot(post_id, slot_id);
-                    },
-                );
-            })
-    }
-}
-
 #[update(guard = "is_caller_controller_or_global_admin")]
 async fn once_reenqueue_timers_for_pending_bet_outcomes() -> Result<Vec<(u64, u8)>, String> {
     let current_time = system_time::get_current_system_time_from_ic();
 
-    let post_w_slot = CANISTER_DATA.with(|canister_data_ref_cell| {
-        let canister_data = canister_data_ref_cell.borrow_mut();
-
+    let post_w_slot = CANISTER_DATA.with_borrow_mut(|canister_data| {
         let posts_with_slots =
-            once_get_posts_that_have_pending_outcomes(&canister_data, &current_time);
+            once_get_posts_that_have_pending_outcomes(canister_data, &current_time);
 
-        // once_reenqueue_timers_for_these_posts(&mut canister_data, posts_with_slots, &current_time);
-        once_reenqueue_timers_for_these_posts(posts_with_slots.clone());
+        // * stuck slots are due right away, the queue timer picks them up on its next run
+        posts_with_slots.iter().for_each(|(post_id, slot_id)| {
+            enqueue_slot_tabulation_job(canister_data, *post_id, *slot_id, current_time);
+        });
         posts_with_slots
     });
 
     Ok(post_w_slot)
 }
 
-fn once_reenqueue_timers_for_these_posts(post_slot_ids: Vec<(u64, u8)>) {
-    for (post_id, slot_id) in post_slot_ids {
-        let slot_number = slot_id;
-
-        ic_cdk_timers::set_timer(
-            // random jitter
-            Duration::from_secs(300),
-            move || {
-                tabulate_hot_or_not_outcome_for_post_slot(post_id, slot_number);
-            },
-        );
-    }
-}
-
 fn once_get_posts_that_have_pending_outcomes(
     canister_data: &CanisterData,
     current_time: &SystemTime,
@@ -111,14 +70,22 @@ fn once_get_posts_that_have_pending_outcomes(
 
             let slot_id = global_room_id.1;
 
-            let post_created_time = canister_data
+            let post_created_time_and_slot_duration = canister_data
                 .all_created_posts
                 .get(&global_room_id.0)
-                .map(|post| post.created_at);
+                .map(|post| {
+                    (
+                        post.created_at,
+                        post.hot_or_not_game_config.duration_of_each_slot_in_seconds,
+                    )
+                });
 
-            if let Some(post_created_time) = post_created_time {
-                let slot_computation_time =
-                    post_created_time.checked_add(Duration::from_secs(slot_id as u64 * 65 * 60)); // 5 minutes more for buffer
+            if let Some((post_created_time, duration_of_each_slot_in_seconds)) =
+                post_created_time_and_slot_duration
+            {
+                let slot_computation_time = post_created_time.checked_add(Duration::from_secs(
+                    slot_id as u64 * (duration_of_each_slot_in_seconds + 5 * 60),
+                )); // 5 minutes more for buffer
 
                 let has_slot_passed = slot_computation_time.map(|slot_trigger_time| {
                     match slot_trigger_time.cmp(current_time) {
@@ -144,7 +111,7 @@ mod test {
 
     use shared_utils::{
         canister_specific::individual_user_template::types::{
-            hot_or_not::HotOrNotDetails,
+            hot_or_not::{HotOrNotDetails, DURATION_OF_EACH_SLOT_IN_SECONDS},
             post::{FeedScore, Post, PostViewStatistics},
         },
         common::types::top_posts::post_score_index_item::PostStatus,
@@ -171,6 +138,11 @@ mod test {
             home_feed_score: FeedScore::default(),
             hot_or_not_details: Some(HotOrNotDetails::default()),
             slots_left_to_be_computed: HashSet::new(),
+            tip_details: Default::default(),
+            hot_or_not_game_config: Default::default(),
+            revisions: Default::default(),
+            comment_count: 0,
+            commenter_count: 0,
         };
 
         canister_data
@@ -197,6 +169,11 @@ mod test {
             home_feed_score: FeedScore::default(),
             hot_or_not_details: Some(HotOrNotDetails::default()),
             slots_left_to_be_computed: (1..=48).collect(),
+            tip_details: Default::default(),
+            hot_or_not_game_config: Default::default(),
+            revisions: Default::default(),
+            comment_count: 0,
+            commenter_count: 0,
         };
 
         canister_data
@@ -224,6 +201,11 @@ mod test {
             home_feed_score: FeedScore::default(),
             hot_or_not_details: Some(HotOrNotDetails::default()),
             slots_left_to_be_computed: (10..=48).collect(),
+            tip_details: Default::default(),
+            hot_or_not_game_config: Default::default(),
+            revisions: Default::default(),
+            comment_count: 0,
+            commenter_count: 0,
         };
 
         canister_data
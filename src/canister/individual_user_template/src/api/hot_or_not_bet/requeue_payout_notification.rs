@@ -0,0 +1,100 @@
+use std::time::SystemTime;
+
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::GlobalBetId,
+    common::utils::{permissions::is_caller_controller_or_global_admin, system_time},
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Retries a payout notification on the next run of the retry timer,
+/// with a fresh retry horizon. Meant for notifications that were given up on,
+/// which stay in the outbox for 30 days.
+///
+/// # Access Control
+/// Only the controller or a global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn requeue_payout_notification(global_bet_id: GlobalBetId) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        requeue_payout_notification_impl(
+            canister_data,
+            &global_bet_id,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })
+}
+
+fn requeue_payout_notification_impl(
+    canister_data: &mut CanisterData,
+    global_bet_id: &GlobalBetId,
+    current_time: SystemTime,
+) -> Result<(), String> {
+    let pending_payout_notification = canister_data
+        .payout_notification_outbox
+        .get_mut(global_bet_id)
+        .ok_or("No pending payout notification for this bet")?;
+
+    pending_payout_notification.requeue(current_time);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::individual_user_template::types::hot_or_not::{
+        BetOutcomeForBetMaker, GlobalRoomId, PendingPayoutNotification, StablePrincipal,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_requeue_payout_notification_impl() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        let global_bet_id = GlobalBetId(
+            GlobalRoomId(0, 1, 1),
+            StablePrincipal(get_mock_user_bob_principal_id()),
+        );
+
+        assert!(
+            requeue_payout_notification_impl(&mut canister_data, &global_bet_id, current_time)
+                .is_err()
+        );
+
+        let mut pending_payout_notification = PendingPayoutNotification::new(
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            "unreachable".into(),
+            current_time,
+        );
+        pending_payout_notification.record_failed_attempt(
+            "unreachable".into(),
+            current_time,
+            Duration::ZERO,
+        );
+        canister_data
+            .payout_notification_outbox
+            .insert(global_bet_id.clone(), pending_payout_notification);
+
+        let requeue_time = current_time + Duration::from_secs(60);
+        assert!(
+            requeue_payout_notification_impl(&mut canister_data, &global_bet_id, requeue_time)
+                .is_ok()
+        );
+
+        let pending_payout_notification = canister_data
+            .payout_notification_outbox
+            .get(&global_bet_id)
+            .unwrap();
+        assert_eq!(pending_payout_notification.gave_up_at, None);
+        assert_eq!(pending_payout_notification.first_failed_at, requeue_time);
+        assert!(pending_payout_notification.is_due(&requeue_time));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::SlotTabulationJob,
+    common::{types::app_primitive_type::PostId, utils::system_time},
+    pagination::{self, PaginationError},
+};
+
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration_impl, CANISTER_DATA,
+};
+
+use super::tabulate_hot_or_not_outcome_for_post_slot::tabulate_hot_or_not_outcome_for_post_slot;
+
+const SLOT_TABULATION_QUEUE_DRAIN_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_SLOT_TABULATIONS_PER_DRAIN: usize = 20;
+
+/// Starts the single recurring timer that tabulates due slots.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+/// The jobs themselves live in stable memory and are never lost.
+pub fn start_slot_tabulation_queue_timer() {
+    ic_cdk_timers::set_timer_interval(
+        SLOT_TABULATION_QUEUE_DRAIN_INTERVAL,
+        drain_due_slot_tabulation_jobs,
+    );
+}
+
+fn drain_due_slot_tabulation_jobs() {
+    if CANISTER_DATA
+        .with_borrow(is_not_frozen_for_migration_impl)
+        .is_err()
+    {
+        return;
+    }
+
+    let current_time = system_time::get_current_system_time_from_ic();
+
+    let due_jobs = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        pop_due_slot_tabulation_jobs(canister_data, &current_time, MAX_SLOT_TABULATIONS_PER_DRAIN)
+    });
+
+    due_jobs.into_iter().for_each(|job| {
+        tabulate_hot_or_not_outcome_for_post_slot(job.post_id, job.slot_id);
+    });
+}
+
+/// Slots of the post that already have a job queued are skipped
+pub fn enqueue_slot_tabulation_jobs_for_post(canister_data: &mut CanisterData, post_id: PostId) {
+    let Some(post) = canister_data.all_created_posts.get(&post_id) else {
+        return;
+    };
+
+    let queued_slot_ids = get_queued_slot_ids_for_post(canister_data, post_id);
+
+    let duration_of_each_slot_in_seconds =
+        post.hot_or_not_game_config.duration_of_each_slot_in_seconds;
+
+    let jobs = post
+        .slots_left_to_be_computed
+        .iter()
+        .filter(|slot_id| !queued_slot_ids.contains(slot_id))
+        .map(|slot_id| SlotTabulationJob {
+            due_at: post.created_at
+                + Duration::from_secs(*slot_id as u64 * duration_of_each_slot_in_seconds),
+            post_id,
+            slot_id: *slot_id,
+        })
+        .collect::<Vec<_>>();
+
+    jobs.into_iter().for_each(|job| {
+        canister_data.slot_tabulation_queue.insert(job, ());
+    });
+}
+
+/// Does nothing if the slot already has a job queued, whatever it is due at
+pub fn enqueue_slot_tabulation_job(
+    canister_data: &mut CanisterData,
+    post_id: PostId,
+    slot_id: u8,
+    due_at: SystemTime,
+) {
+    if get_queued_slot_ids_for_post(canister_data, post_id).contains(&slot_id) {
+        return;
+    }
+
+    canister_data.slot_tabulation_queue.insert(
+        SlotTabulationJob {
+            due_at,
+            post_id,
+            slot_id,
+        },
+        (),
+    );
+}
+
+// * the queue is ordered by due time first, so finding the jobs of a post takes a full scan
+fn get_queued_slot_ids_for_post(canister_data: &CanisterData, post_id: PostId) -> HashSet<u8> {
+    canister_data
+        .slot_tabulation_queue
+        .iter()
+        .map(|(job, _)| job)
+        .filter(|job| job.post_id == post_id)
+        .map(|job| job.slot_id)
+        .collect()
+}
+
+pub fn remove_slot_tabulation_jobs_for_post(canister_data: &mut CanisterData, post_id: PostId) {
+    let jobs = canister_data
+        .slot_tabulation_queue
+        .iter()
+        .map(|(job, _)| job)
+        .filter(|job| job.post_id == post_id)
+        .collect::<Vec<_>>();
+
+    jobs.iter().for_each(|job| {
+        canister_data.slot_tabulation_queue.remove(job);
+    });
+}
+
+fn pop_due_slot_tabulation_jobs(
+    canister_data: &mut CanisterData,
+    current_time: &SystemTime,
+    limit: usize,
+) -> Vec<SlotTabulationJob> {
+    let due_jobs = canister_data
+        .slot_tabulation_queue
+        .iter()
+        .map(|(job, _)| job)
+        .take_while(|job| job.due_at <= *current_time)
+        .take(limit)
+        .collect::<Vec<_>>();
+
+    due_jobs.iter().for_each(|job| {
+        canister_data.slot_tabulation_queue.remove(job);
+    });
+
+    due_jobs
+}
+
+/// Slot tabulations that have not run yet, earliest due first
+#[query]
+fn get_pending_slot_tabulation_jobs(
+    from_inclusive_index: u64,
+    limit: u64,
+) -> Result<Vec<SlotTabulationJob>, PaginationError> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        let (from_inclusive_index, limit) = pagination::get_pagination_bounds_cursor(
+            from_inclusive_index,
+            limit,
+            canister_data.slot_tabulation_queue.len(),
+        )?;
+
+        Ok(canister_data
+            .slot_tabulation_queue
+            .iter()
+            .skip(from_inclusive_index as usize)
+            .take(limit as usize)
+            .map(|(job, _)| job)
+            .collect())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::game_config::HotOrNotGameConfig,
+        post::{Post, PostDetailsFromFrontend},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_enqueue_and_pop_due_slot_tabulation_jobs() {
+        let mut canister_data = CanisterData::default();
+        let post_created_at = SystemTime::now();
+
+        let post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            HotOrNotGameConfig {
+                maximum_number_of_slots: 3,
+                duration_of_each_slot_in_seconds: 60,
+                ..Default::default()
+            },
+        );
+        canister_data.all_created_posts.insert(0, post);
+
+        enqueue_slot_tabulation_jobs_for_post(&mut canister_data, 0);
+        // * enqueueing again does not duplicate jobs
+        enqueue_slot_tabulation_jobs_for_post(&mut canister_data, 0);
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 3);
+
+        let due_jobs =
+            pop_due_slot_tabulation_jobs(&mut canister_data, &post_created_at, usize::MAX);
+        assert!(due_jobs.is_empty());
+
+        let due_jobs = pop_due_slot_tabulation_jobs(
+            &mut canister_data,
+            &(post_created_at + Duration::from_secs(2 * 60)),
+            usize::MAX,
+        );
+        assert_eq!(
+            due_jobs.iter().map(|job| job.slot_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 1);
+
+        let due_jobs = pop_due_slot_tabulation_jobs(
+            &mut canister_data,
+            &(post_created_at + Duration::from_secs(10 * 60)),
+            usize::MAX,
+        );
+        assert_eq!(due_jobs.len(), 1);
+        assert_eq!(due_jobs[0].slot_id, 3);
+        assert!(canister_data.slot_tabulation_queue.is_empty());
+    }
+
+    #[test]
+    fn test_pop_due_slot_tabulation_jobs_respects_limit() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+
+        (1..=5).for_each(|slot_id| {
+            enqueue_slot_tabulation_job(
+                &mut canister_data,
+                0,
+                slot_id,
+                current_time - Duration::from_secs(slot_id as u64),
+            );
+        });
+
+        let due_jobs = pop_due_slot_tabulation_jobs(&mut canister_data, &current_time, 2);
+
+        // * the jobs that were due first come out first
+        assert_eq!(
+            due_jobs.iter().map(|job| job.slot_id).collect::<Vec<_>>(),
+            vec![5, 4]
+        );
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 3);
+    }
+
+    #[test]
+    fn test_a_slot_is_queued_once_whatever_its_due_time() {
+        let mut canister_data = CanisterData::default();
+        let post_created_at = SystemTime::now();
+
+        let post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            HotOrNotGameConfig {
+                maximum_number_of_slots: 3,
+                duration_of_each_slot_in_seconds: 60,
+                ..Default::default()
+            },
+        );
+        canister_data.all_created_posts.insert(0, post);
+
+        // * a stuck slot re-enqueued to run right away
+        enqueue_slot_tabulation_job(&mut canister_data, 0, 2, post_created_at);
+        enqueue_slot_tabulation_job(
+            &mut canister_data,
+            0,
+            2,
+            post_created_at + Duration::from_secs(1),
+        );
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 1);
+
+        enqueue_slot_tabulation_jobs_for_post(&mut canister_data, 0);
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 3);
+
+        let due_jobs = pop_due_slot_tabulation_jobs(
+            &mut canister_data,
+            &(post_created_at + Duration::from_secs(10 * 60)),
+            usize::MAX,
+        );
+        let mut slot_ids = due_jobs.iter().map(|job| job.slot_id).collect::<Vec<_>>();
+        slot_ids.sort();
+        assert_eq!(slot_ids, vec![1, 2, 3]);
+    }
+}
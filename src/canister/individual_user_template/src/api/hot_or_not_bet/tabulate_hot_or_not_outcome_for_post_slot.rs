@@ -1,13 +1,16 @@
 use candid::Principal;
 use shared_utils::{
     canister_specific::individual_user_template::types::hot_or_not::{
-        BetDirection, BetMakerInformedStatus, BetOutcomeForBetMaker, BetPayout, GlobalBetId,
-        GlobalRoomId, RoomBetPossibleOutcomes,
+        BetOutcomeForBetMaker, GlobalBetId, GlobalRoomId,
     },
     common::{types::known_principal::KnownPrincipalType, utils::system_time},
 };
 
-use crate::CANISTER_DATA;
+use crate::{
+    util::canister_migration::get_current_canister_id_of_migrated_canister, CANISTER_DATA,
+};
+
+use super::payout_notification_outbox::record_payout_notification_result_impl;
 
 pub fn tabulate_hot_or_not_outcome_for_post_slot(post_id: u64, slot_id: u8) {
     ic_cdk::println!("Computing outcome for post:{post_id} and slot:{slot_id} ");
@@ -76,30 +79,8 @@ pub fn inform_participants_of_outcome(post_id: u64, slot_id: u8) {
             });
 
             for (global_bet_id, bet) in bet_details.iter() {
-                let bet_outcome_for_bet_maker: BetOutcomeForBetMaker = match room_detail.bet_outcome
-                {
-                    RoomBetPossibleOutcomes::BetOngoing => BetOutcomeForBetMaker::AwaitingResult,
-                    RoomBetPossibleOutcomes::Draw => {
-                        BetOutcomeForBetMaker::Draw(match bet.payout {
-                            BetPayout::Calculated(amount) => amount,
-                            _ => 0,
-                        })
-                    }
-                    RoomBetPossibleOutcomes::HotWon => match bet.bet_direction {
-                        BetDirection::Hot => BetOutcomeForBetMaker::Won(match bet.payout {
-                            BetPayout::Calculated(amount) => amount,
-                            _ => 0,
-                        }),
-                        BetDirection::Not => BetOutcomeForBetMaker::Lost,
-                    },
-                    RoomBetPossibleOutcomes::NotWon => match bet.bet_direction {
-                        BetDirection::Hot => BetOutcomeForBetMaker::Lost,
-                        BetDirection::Not => BetOutcomeForBetMaker::Won(match bet.payout {
-                            BetPayout::Calculated(amount) => amount,
-                            _ => 0,
-                        }),
-                    },
-                };
+                let bet_outcome_for_bet_maker =
+                    bet.get_outcome_for_bet_maker(&room_detail.bet_outcome);
 
                 if bet_outcome_for_bet_maker == BetOutcomeForBetMaker::AwaitingResult {
                     continue;
@@ -115,7 +96,7 @@ pub fn inform_participants_of_outcome(post_id: u64, slot_id: u8) {
         });
 }
 
-async fn receive_bet_winnings_when_distributed(
+pub async fn receive_bet_winnings_when_distributed(
     global_bet_id: GlobalBetId,
     bet_maker_canister_id: Principal,
     post_id: u64,
@@ -126,31 +107,56 @@ async fn receive_bet_winnings_when_distributed(
         bet_maker_canister_id.to_string()
     );
 
-    let res = ic_cdk::call::<_, ()>(
+    let mut bet_maker_canister_id = bet_maker_canister_id;
+    let mut res =
+        inform_bet_maker_of_outcome(bet_maker_canister_id, post_id, &bet_outcome_for_bet_maker)
+            .await;
+
+    // * the bet maker's profile may have been migrated to another subnet since the bet
+    if res.is_err() {
+        if let Some(current_canister_id) =
+            get_current_canister_id_of_migrated_canister(bet_maker_canister_id).await
+        {
+            bet_maker_canister_id = current_canister_id;
+            res = inform_bet_maker_of_outcome(
+                bet_maker_canister_id,
+                post_id,
+                &bet_outcome_for_bet_maker,
+            )
+            .await;
+        }
+    }
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        record_payout_notification_result_impl(
+            canister_data,
+            global_bet_id,
+            bet_maker_canister_id,
+            post_id,
+            bet_outcome_for_bet_maker,
+            res,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+async fn inform_bet_maker_of_outcome(
+    bet_maker_canister_id: Principal,
+    post_id: u64,
+    bet_outcome_for_bet_maker: &BetOutcomeForBetMaker,
+) -> Result<(), String> {
+    ic_cdk::call::<_, ()>(
         bet_maker_canister_id,
         "receive_bet_winnings_when_distributed",
-        (post_id, bet_outcome_for_bet_maker),
+        (post_id, bet_outcome_for_bet_maker.clone()),
     )
-    .await;
-
-    let mut bet_maker_informed_status = Some(BetMakerInformedStatus::InformedSuccessfully);
-
-    if let Err(e) = res {
-        bet_maker_informed_status = Some(BetMakerInformedStatus::Failed(format!(
+    .await
+    .map_err(|e| {
+        format!(
             "Informing bet maker canister {} failed: {:?} {}",
             bet_maker_canister_id.to_string(),
             e.0,
             e.1
-        )));
-    }
-
-    CANISTER_DATA.with_borrow_mut(|canister_data| {
-        let bet_details_option = canister_data.bet_details_map.get(&global_bet_id);
-        bet_details_option.map(|mut bet_detail| {
-            bet_detail.bet_maker_informed_status = bet_maker_informed_status;
-            canister_data
-                .bet_details_map
-                .insert(global_bet_id, bet_detail);
-        });
-    });
+        )
+    })
 }
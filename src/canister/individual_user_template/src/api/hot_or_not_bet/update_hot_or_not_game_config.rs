@@ -0,0 +1,81 @@
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig,
+    common::utils::permissions::is_caller_controller,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// # Access Control
+/// Only the controller (user index) can update the game config.
+/// Posts that already exist keep the config they were created with.
+#[update(guard = "is_caller_controller")]
+fn update_hot_or_not_game_config(game_config: HotOrNotGameConfig) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_hot_or_not_game_config_impl(canister_data, game_config)
+    })
+}
+
+fn update_hot_or_not_game_config_impl(
+    canister_data: &mut CanisterData,
+    game_config: HotOrNotGameConfig,
+) -> Result<(), String> {
+    game_config.validate()?;
+
+    let current_game_config = &canister_data.hot_or_not_game_config;
+
+    // * rebroadcasts of the same version are a no-op
+    if *current_game_config == game_config {
+        return Ok(());
+    }
+
+    if game_config.version <= current_game_config.version {
+        return Err(format!(
+            "Game config version {} is not newer than the current version {}",
+            game_config.version, current_game_config.version
+        ));
+    }
+
+    canister_data.hot_or_not_game_config = game_config;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_hot_or_not_game_config_impl() {
+        let mut canister_data = CanisterData::default();
+
+        let new_config = HotOrNotGameConfig {
+            version: 1,
+            maximum_number_of_bets_per_room: 50,
+            ..Default::default()
+        };
+
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, new_config.clone()).is_ok());
+        assert_eq!(canister_data.hot_or_not_game_config, new_config);
+
+        // * same config again is accepted
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, new_config.clone()).is_ok());
+
+        // * stale version is rejected
+        let stale_config = HotOrNotGameConfig {
+            version: 1,
+            winnings_multiplier: 3,
+            ..Default::default()
+        };
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, stale_config).is_err());
+
+        // * invalid config is rejected
+        let invalid_config = HotOrNotGameConfig {
+            version: 2,
+            creator_commission_percentage: 100,
+            ..Default::default()
+        };
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, invalid_config).is_err());
+        assert_eq!(canister_data.hot_or_not_game_config, new_config);
+    }
+}
@@ -0,0 +1,52 @@
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller_or_global_admin;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Sets how long failed payout notifications are retried for before they are given up on.
+/// Applies to notifications already in the outbox too.
+///
+/// # Access Control
+/// Only the controller or a global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn update_payout_notification_retry_horizon(horizon_in_seconds: u64) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_payout_notification_retry_horizon_impl(canister_data, horizon_in_seconds)
+    })
+}
+
+fn update_payout_notification_retry_horizon_impl(
+    canister_data: &mut CanisterData,
+    horizon_in_seconds: u64,
+) -> Result<(), String> {
+    if horizon_in_seconds == 0 {
+        return Err("Retry horizon has to be greater than 0".into());
+    }
+
+    canister_data
+        .configuration
+        .payout_notification_retry_horizon_in_seconds = Some(horizon_in_seconds);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::api::hot_or_not_bet::payout_notification_outbox::get_payout_notification_retry_horizon;
+
+    use super::*;
+
+    #[test]
+    fn test_update_payout_notification_retry_horizon_impl() {
+        let mut canister_data = CanisterData::default();
+
+        assert!(update_payout_notification_retry_horizon_impl(&mut canister_data, 0).is_err());
+        assert!(update_payout_notification_retry_horizon_impl(&mut canister_data, 3600).is_ok());
+        assert_eq!(
+            get_payout_notification_retry_horizon(&canister_data),
+            Duration::from_secs(3600)
+        );
+    }
+}
@@ -0,0 +1,454 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::WithdrawHotOrNotBetError,
+        hot_or_not::{
+            BetOutcomeForBetMaker, BetReconciliationDetails, BetWithdrawalKind,
+            PendingBetWithdrawal,
+        },
+    },
+    common::{
+        types::{
+            app_primitive_type::PostId,
+            utility_token::token_event::{HotOrNotBetWithdrawalEvent, TokenEvent},
+        },
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+use super::bet_on_currently_viewing_hot_or_not_post::call_rejection_leaves_no_bet_behind;
+
+/// Cancels a bet for a full refund while the post's cancellation window is open,
+/// or cashes it out at a discount before its slot closes.
+/// Returns the amount credited back to the balance.
+/// The withdrawal is kept as pending until the post creator's canister replies.
+/// If the call fails without saying whether the bet was withdrawn, `reconcile_pending_bets`
+/// settles it later from what the post creator's canister has on record.
+///
+/// # Access Control
+/// Only the profile owner
+#[update(guard = "is_not_frozen_for_migration")]
+async fn withdraw_hot_or_not_bet(
+    post_canister_id: CanisterId,
+    post_id: PostId,
+    withdrawal_kind: BetWithdrawalKind,
+) -> Result<u64, WithdrawHotOrNotBetError> {
+    let bet_maker_principal_id = ic_cdk::caller();
+
+    let placed_bet_key = (post_canister_id, post_id);
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        validate_bet_withdrawal(canister_data, &bet_maker_principal_id, &placed_bet_key)?;
+
+        canister_data.pending_bet_withdrawals.insert(
+            placed_bet_key,
+            PendingBetWithdrawal {
+                post_canister_id,
+                post_id,
+                withdrawal_kind,
+                awaiting_reconciliation: false,
+            },
+        );
+
+        Ok(())
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    let response = ic_cdk::call::<_, (Result<u64, WithdrawHotOrNotBetError>,)>(
+        post_canister_id,
+        "receive_bet_withdrawal_from_bet_makers_canister",
+        (post_id, bet_maker_principal_id, withdrawal_kind),
+    )
+    .await;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| match response {
+        Ok((Ok(amount_owed),)) => {
+            canister_data
+                .pending_bet_withdrawals
+                .remove(&placed_bet_key);
+            record_bet_withdrawal_impl(
+                canister_data,
+                &placed_bet_key,
+                withdrawal_kind,
+                amount_owed,
+                system_time::get_current_system_time_from_ic(),
+            );
+
+            Ok(amount_owed)
+        }
+        Ok((Err(e),)) => {
+            canister_data
+                .pending_bet_withdrawals
+                .remove(&placed_bet_key);
+
+            Err(e)
+        }
+        Err((rejection_code, _)) => {
+            // * the same rejections that leave no bet behind leave the bet in place
+            if call_rejection_leaves_no_bet_behind(&rejection_code) {
+                canister_data
+                    .pending_bet_withdrawals
+                    .remove(&placed_bet_key);
+            } else if let Some(pending_bet_withdrawal) = canister_data
+                .pending_bet_withdrawals
+                .get_mut(&placed_bet_key)
+            {
+                pending_bet_withdrawal.awaiting_reconciliation = true;
+            }
+
+            Err(WithdrawHotOrNotBetError::PostCreatorCanisterCallFailed)
+        }
+    })
+}
+
+fn validate_bet_withdrawal(
+    canister_data: &CanisterData,
+    bet_maker_principal_id: &Principal,
+    placed_bet_key: &(CanisterId, PostId),
+) -> Result<(), WithdrawHotOrNotBetError> {
+    if *bet_maker_principal_id == Principal::anonymous() {
+        return Err(WithdrawHotOrNotBetError::UserNotLoggedIn);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(WithdrawHotOrNotBetError::UserPrincipalNotSet)?;
+
+    if *bet_maker_principal_id != profile_owner {
+        return Err(WithdrawHotOrNotBetError::Unauthorized);
+    }
+
+    canister_data
+        .all_hot_or_not_bets_placed
+        .get(placed_bet_key)
+        .filter(|placed_bet_detail| {
+            placed_bet_detail.outcome_received == BetOutcomeForBetMaker::AwaitingResult
+        })
+        .ok_or(WithdrawHotOrNotBetError::BetNotFound)?;
+
+    if canister_data
+        .pending_bet_withdrawals
+        .contains_key(placed_bet_key)
+    {
+        return Err(WithdrawHotOrNotBetError::WithdrawalInProgress);
+    }
+
+    Ok(())
+}
+
+/// Drops the withdrawn bet from the placed bets and credits what the post creator's canister
+/// returned for it. Does nothing if the bet was already settled.
+fn record_bet_withdrawal_impl(
+    canister_data: &mut CanisterData,
+    placed_bet_key: &(CanisterId, PostId),
+    withdrawal_kind: BetWithdrawalKind,
+    amount_owed: u64,
+    current_time: SystemTime,
+) {
+    let Some(placed_bet_detail) = canister_data
+        .all_hot_or_not_bets_placed
+        .remove(placed_bet_key)
+    else {
+        return;
+    };
+
+    let details = match withdrawal_kind {
+        BetWithdrawalKind::Cancel => HotOrNotBetWithdrawalEvent::BetCancelled {
+            post_canister_id: placed_bet_detail.canister_id,
+            post_id: placed_bet_detail.post_id,
+            slot_id: placed_bet_detail.slot_id,
+            room_id: placed_bet_detail.room_id,
+        },
+        BetWithdrawalKind::CashOut => HotOrNotBetWithdrawalEvent::BetCashedOut {
+            post_canister_id: placed_bet_detail.canister_id,
+            post_id: placed_bet_detail.post_id,
+            slot_id: placed_bet_detail.slot_id,
+            room_id: placed_bet_detail.room_id,
+            amount_bet: placed_bet_detail.amount_bet,
+        },
+    };
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::HotOrNotBetWithdrawal {
+            amount: amount_owed,
+            details,
+            timestamp: current_time,
+        });
+}
+
+pub fn get_pending_bet_withdrawals_due_for_reconciliation(
+    canister_data: &CanisterData,
+) -> Vec<PendingBetWithdrawal> {
+    canister_data
+        .pending_bet_withdrawals
+        .values()
+        .filter(|pending_bet_withdrawal| pending_bet_withdrawal.awaiting_reconciliation)
+        .cloned()
+        .collect()
+}
+
+pub async fn reconcile_pending_bet_withdrawal(pending_bet_withdrawal: PendingBetWithdrawal) {
+    let Some(bet_maker_principal_id) =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id)
+    else {
+        return;
+    };
+
+    // * on failure the withdrawal stays pending and is retried on the next run
+    let Ok((bet_reconciliation_details,)) = ic_cdk::call::<_, (Option<BetReconciliationDetails>,)>(
+        pending_bet_withdrawal.post_canister_id,
+        "get_bet_details_for_reconciliation",
+        (pending_bet_withdrawal.post_id, bet_maker_principal_id),
+    )
+    .await
+    else {
+        return;
+    };
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        settle_pending_bet_withdrawal_impl(
+            canister_data,
+            &(
+                pending_bet_withdrawal.post_canister_id,
+                pending_bet_withdrawal.post_id,
+            ),
+            bet_reconciliation_details,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+/// Credits the withdrawal if the post creator's canister has it on record.
+/// Otherwise the bet still stands and is settled with its slot.
+fn settle_pending_bet_withdrawal_impl(
+    canister_data: &mut CanisterData,
+    placed_bet_key: &(CanisterId, PostId),
+    bet_reconciliation_details: Option<BetReconciliationDetails>,
+    current_time: SystemTime,
+) {
+    let Some(pending_bet_withdrawal) = canister_data.pending_bet_withdrawals.remove(placed_bet_key)
+    else {
+        return;
+    };
+
+    if let Some(withdrawn_amount) = bet_reconciliation_details
+        .and_then(|bet_reconciliation_details| bet_reconciliation_details.withdrawn_amount)
+    {
+        record_bet_withdrawal_impl(
+            canister_data,
+            placed_bet_key,
+            pending_bet_withdrawal.withdrawal_kind,
+            withdrawn_amount,
+            current_time,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::hot_or_not::{
+        BetDirection, PlacedBetDetail,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn placed_bet_detail(post_id: PostId) -> PlacedBetDetail {
+        PlacedBetDetail {
+            canister_id: get_mock_user_alice_canister_id(),
+            post_id,
+            slot_id: 1,
+            room_id: 1,
+            amount_bet: 100,
+            bet_direction: BetDirection::Hot,
+            bet_placed_at: SystemTime::now(),
+            outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+        }
+    }
+
+    #[test]
+    fn test_validate_bet_withdrawal() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let placed_bet_key = (get_mock_user_alice_canister_id(), 0);
+
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &Principal::anonymous(), &placed_bet_key),
+            Err(WithdrawHotOrNotBetError::UserNotLoggedIn)
+        );
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &alice, &placed_bet_key),
+            Err(WithdrawHotOrNotBetError::UserPrincipalNotSet)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        assert_eq!(
+            validate_bet_withdrawal(
+                &canister_data,
+                &get_mock_user_bob_principal_id(),
+                &placed_bet_key
+            ),
+            Err(WithdrawHotOrNotBetError::Unauthorized)
+        );
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &alice, &placed_bet_key),
+            Err(WithdrawHotOrNotBetError::BetNotFound)
+        );
+
+        canister_data
+            .all_hot_or_not_bets_placed
+            .insert(placed_bet_key, placed_bet_detail(0));
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &alice, &placed_bet_key),
+            Ok(())
+        );
+
+        canister_data.pending_bet_withdrawals.insert(
+            placed_bet_key,
+            PendingBetWithdrawal {
+                post_canister_id: placed_bet_key.0,
+                post_id: placed_bet_key.1,
+                withdrawal_kind: BetWithdrawalKind::Cancel,
+                awaiting_reconciliation: true,
+            },
+        );
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &alice, &placed_bet_key),
+            Err(WithdrawHotOrNotBetError::WithdrawalInProgress)
+        );
+        canister_data.pending_bet_withdrawals.clear();
+
+        // * settled bets can not be withdrawn
+        canister_data
+            .all_hot_or_not_bets_placed
+            .get_mut(&placed_bet_key)
+            .unwrap()
+            .outcome_received = BetOutcomeForBetMaker::Lost;
+        assert_eq!(
+            validate_bet_withdrawal(&canister_data, &alice, &placed_bet_key),
+            Err(WithdrawHotOrNotBetError::BetNotFound)
+        );
+    }
+
+    #[test]
+    fn test_record_bet_withdrawal_impl() {
+        let mut canister_data = CanisterData::default();
+        // * the amounts of both bets below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 800;
+        for post_id in [0, 1] {
+            canister_data.all_hot_or_not_bets_placed.insert(
+                (get_mock_user_alice_canister_id(), post_id),
+                placed_bet_detail(post_id),
+            );
+        }
+
+        record_bet_withdrawal_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 0),
+            BetWithdrawalKind::Cancel,
+            100,
+            SystemTime::now(),
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+
+        record_bet_withdrawal_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 1),
+            BetWithdrawalKind::CashOut,
+            120,
+            SystemTime::now(),
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1020);
+        assert_eq!(canister_data.my_token_balance.lifetime_earnings, 20);
+        assert!(canister_data.all_hot_or_not_bets_placed.is_empty());
+
+        // * a bet that is no longer on record is not credited twice
+        record_bet_withdrawal_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 1),
+            BetWithdrawalKind::CashOut,
+            120,
+            SystemTime::now(),
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1020);
+    }
+
+    #[test]
+    fn test_settle_pending_bet_withdrawal_impl() {
+        let mut canister_data = CanisterData::default();
+        // * the amounts of both bets below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 800;
+        for post_id in [0, 1] {
+            let placed_bet_key = (get_mock_user_alice_canister_id(), post_id);
+            canister_data
+                .all_hot_or_not_bets_placed
+                .insert(placed_bet_key, placed_bet_detail(post_id));
+            canister_data.pending_bet_withdrawals.insert(
+                placed_bet_key,
+                PendingBetWithdrawal {
+                    post_canister_id: placed_bet_key.0,
+                    post_id,
+                    withdrawal_kind: BetWithdrawalKind::Cancel,
+                    awaiting_reconciliation: true,
+                },
+            );
+        }
+        assert_eq!(
+            get_pending_bet_withdrawals_due_for_reconciliation(&canister_data).len(),
+            2
+        );
+
+        let bet_reconciliation_details = BetReconciliationDetails {
+            slot_id: 1,
+            room_id: 1,
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            idempotency_key: Some(0),
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+            withdrawn_amount: None,
+        };
+
+        // * the post creator's canister still holds the bet
+        settle_pending_bet_withdrawal_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 0),
+            Some(bet_reconciliation_details.clone()),
+            SystemTime::now(),
+        );
+        assert!(canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 0)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        // * the post creator's canister withdrew the bet before the call failed
+        settle_pending_bet_withdrawal_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 1),
+            Some(BetReconciliationDetails {
+                withdrawn_amount: Some(100),
+                ..bet_reconciliation_details
+            }),
+            SystemTime::now(),
+        );
+        assert!(!canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 1)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        assert!(canister_data.pending_bet_withdrawals.is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+use ic_cdk::caller;
+use ic_cdk_macros::{query, update};
+use shared_utils::canister_specific::individual_user_template::types::kv_storage::{
+    BlobMetadata, NamespaceAccessLevel, NamespaceErrors,
+};
+
+use crate::{data_model::kv_storage::AppStorage, guard::migration::is_not_frozen_for_migration};
+
+/// Blobs are uploaded one chunk of up to 512 KiB at a time.
+/// `chunk_index` can overwrite an existing chunk or append right after the last one.
+#[update(guard = "is_not_frozen_for_migration")]
+fn write_blob_chunk(
+    namespace_id: u64,
+    key: String,
+    chunk_index: u32,
+    chunk: Vec<u8>,
+) -> Result<BlobMetadata, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
+    namespace.write_blob_chunk(key, chunk_index, chunk)
+}
+
+#[query]
+fn read_blob_chunk(
+    namespace_id: u64,
+    key: String,
+    chunk_index: u32,
+) -> Result<Option<Vec<u8>>, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
+    Ok(namespace.read_blob_chunk(key, chunk_index))
+}
+
+#[query]
+fn get_blob_metadata(
+    namespace_id: u64,
+    key: String,
+) -> Result<Option<BlobMetadata>, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
+    Ok(namespace.get_blob_metadata(key))
+}
+
+#[update(guard = "is_not_frozen_for_migration")]
+fn delete_blob(namespace_id: u64, key: String) -> Result<Option<BlobMetadata>, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
+    Ok(namespace.delete_blob(key))
+}
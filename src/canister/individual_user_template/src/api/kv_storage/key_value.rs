@@ -1,57 +1,110 @@
 use ic_cdk::caller;
 use ic_cdk_macros::{query, update};
-use shared_utils::canister_specific::individual_user_template::types::kv_storage::NamespaceErrors;
+use shared_utils::canister_specific::individual_user_template::types::kv_storage::{
+    KeyValueTransactionOperation, NamespaceAccessLevel, NamespaceErrors, NamespaceKeysPage,
+    VersionedValue,
+};
 use std::collections::BTreeMap;
 
-use crate::data_model::kv_storage::AppStorage;
+use crate::{data_model::kv_storage::AppStorage, guard::migration::is_not_frozen_for_migration};
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn delete_multiple_key_value_pairs(
     namespace_id: u64,
     keys: Vec<String>,
 ) -> Result<(), NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
     namespace.delete_multiple_keys(keys);
     Ok(())
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn write_multiple_key_value_pairs(
     namespace_id: u64,
     pairs: BTreeMap<String, String>,
 ) -> Result<(), NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
     namespace.write_multiple_key_value_pairs(pairs)
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn write_key_value_pair(
     namespace_id: u64,
     key: String,
     value: String,
 ) -> Result<Option<String>, NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
     let prev_value = namespace.write_key_value_pair(key, value)?;
     Ok(prev_value)
 }
 
+/// Keys are listed in order, at least 1 and at most 100 per call.
+/// Pass the returned `next_cursor` to get the next page.
 #[query]
-fn list_namespace_keys(namespace_id: u64) -> Result<Vec<String>, NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
-    Ok(namespace.list_keys())
+fn list_namespace_keys(
+    namespace_id: u64,
+    prefix: Option<String>,
+    cursor: Option<String>,
+    limit: u64,
+) -> Result<NamespaceKeysPage, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
+    namespace.list_keys(prefix, cursor, limit)
 }
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn delete_key_value_pair(
     namespace_id: u64,
     key: String,
 ) -> Result<Option<String>, NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
     Ok(namespace.delete_key_value_pair(key))
 }
 
 #[query]
 fn read_key_value_pair(namespace_id: u64, key: String) -> Result<Option<String>, NamespaceErrors> {
-    let namespace = AppStorage::get_a_namespace(caller(), namespace_id)?;
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
     Ok(namespace.read_key_value_pair(key))
 }
+
+#[query]
+fn read_versioned_key_value_pair(
+    namespace_id: u64,
+    key: String,
+) -> Result<Option<VersionedValue>, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
+    Ok(namespace.read_versioned_key_value_pair(key))
+}
+
+/// Writes the value only if the key is still at `expected_version`.
+/// Use version 0 to create a key that must not exist yet.
+/// Returns the new version.
+#[update(guard = "is_not_frozen_for_migration")]
+fn compare_and_swap(
+    namespace_id: u64,
+    key: String,
+    expected_version: u64,
+    value: String,
+) -> Result<u64, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
+    namespace.compare_and_swap(key, expected_version, value)
+}
+
+/// Applies every put and delete only if all their expected versions hold.
+/// Returns the version of each key afterwards, 0 for deleted keys.
+#[update(guard = "is_not_frozen_for_migration")]
+fn apply_key_value_transaction(
+    namespace_id: u64,
+    operations: Vec<KeyValueTransactionOperation>,
+) -> Result<Vec<u64>, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadWrite)?;
+    namespace.apply_transaction(operations)
+}
@@ -1,2 +1,3 @@
+pub mod blob;
 pub mod key_value;
 pub mod namespace;
@@ -1,17 +1,19 @@
 use std::borrow::Borrow;
 
+use candid::Principal;
 use ic_cdk::caller;
 use ic_cdk_macros::{query, update};
 use shared_utils::canister_specific::individual_user_template::types::kv_storage::{
-    NamespaceErrors, NamespaceForFrontend,
+    NamespaceAccessLevel, NamespaceErrors, NamespaceForFrontend, NamespaceUsage,
 };
 
 use crate::{
     data_model::kv_storage::{AppStorage, Namespace},
+    guard::migration::is_not_frozen_for_migration,
     CANISTER_DATA,
 };
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn create_a_namespace(title: String) -> Result<NamespaceForFrontend, NamespaceErrors> {
     AppStorage::create_a_namespace(caller(), title)
 }
@@ -20,3 +22,52 @@ fn create_a_namespace(title: String) -> Result<NamespaceForFrontend, NamespaceEr
 fn list_namespaces(start_index: usize, limit: usize) -> Vec<NamespaceForFrontend> {
     AppStorage::list_namespaces(start_index, limit)
 }
+
+/// # Access Control
+/// Only the profile owner or the namespace owner can grant access.
+/// Granting again replaces the previous access level, which is returned.
+#[update(guard = "is_not_frozen_for_migration")]
+fn grant_namespace_access(
+    namespace_id: u64,
+    grantee: Principal,
+    access_level: NamespaceAccessLevel,
+) -> Result<Option<NamespaceAccessLevel>, NamespaceErrors> {
+    AppStorage::grant_namespace_access(caller(), namespace_id, grantee, access_level)
+}
+
+/// # Access Control
+/// Only the profile owner or the namespace owner can revoke access.
+#[update(guard = "is_not_frozen_for_migration")]
+fn revoke_namespace_access(
+    namespace_id: u64,
+    grantee: Principal,
+) -> Result<Option<NamespaceAccessLevel>, NamespaceErrors> {
+    AppStorage::revoke_namespace_access(caller(), namespace_id, grantee)
+}
+
+/// # Access Control
+/// Only the profile owner or the namespace owner can list grants.
+#[query]
+fn list_namespace_access_grants(
+    namespace_id: u64,
+) -> Result<Vec<(Principal, NamespaceAccessLevel)>, NamespaceErrors> {
+    AppStorage::list_namespace_access_grants(caller(), namespace_id)
+}
+
+#[query]
+fn get_namespace_usage(namespace_id: u64) -> Result<NamespaceUsage, NamespaceErrors> {
+    let namespace =
+        AppStorage::get_a_namespace(caller(), namespace_id, NamespaceAccessLevel::ReadOnly)?;
+    Ok(namespace.get_usage())
+}
+
+/// # Access Control
+/// Only the profile owner can change a namespace's byte quota.
+/// Lowering it below the current usage only blocks further growth.
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_namespace_byte_quota(
+    namespace_id: u64,
+    byte_quota: u64,
+) -> Result<NamespaceUsage, NamespaceErrors> {
+    AppStorage::update_namespace_byte_quota(caller(), namespace_id, byte_quota)
+}
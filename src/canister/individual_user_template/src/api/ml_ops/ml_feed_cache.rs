@@ -25,6 +25,22 @@ fn update_ml_feed_cache_impl(
     ml_feed_cache_items: Vec<MLFeedCacheItem>,
     canister_data: &mut CanisterData,
 ) -> Result<String, String> {
+    // * creators muted or blocked by this profile are left out of the feed
+    let ml_feed_cache_items: Vec<MLFeedCacheItem> = ml_feed_cache_items
+        .into_iter()
+        .filter(|item| {
+            item.creator_principal_id
+                .map_or(true, |creator_principal_id| {
+                    !canister_data
+                        .muted_principals
+                        .contains(&creator_principal_id)
+                        && !canister_data
+                            .blocked_principals
+                            .contains(&creator_principal_id)
+                })
+        })
+        .collect();
+
     // insert ml_feed_cache_items into canister_data.ml_feed_cache at the start of Vec
     canister_data
         .ml_feed_cache
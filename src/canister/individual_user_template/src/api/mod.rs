@@ -14,3 +14,6 @@ pub mod token;
 pub mod well_known_principal;
 pub mod cdao;
 pub mod device_id_management;
+pub mod comment;
+pub mod block_and_mute;
+pub mod prediction;
@@ -1,4 +1,4 @@
-use std::time::{Duration, SystemTime};
+use std::time::SystemTime;
 
 use ic_cdk_macros::update;
 use shared_utils::{
@@ -9,9 +9,10 @@ use shared_utils::{
 use crate::{
     api::{
         canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-        hot_or_not_bet::tabulate_hot_or_not_outcome_for_post_slot::tabulate_hot_or_not_outcome_for_post_slot,
+        hot_or_not_bet::slot_tabulation_queue::enqueue_slot_tabulation_jobs_for_post,
     },
     data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
     util::cycles::{
         recieve_cycles_from_subnet_orchestrator, request_cycles_from_subnet_orchestrator,
     },
@@ -22,7 +23,7 @@ use super::update_scores_and_share_with_post_cache_if_difference_beyond_threshol
 
 /// #### Access Control
 /// Only the user whose profile details are stored in this canister can create a post.
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn add_post_v2(post_details: PostDetailsFromFrontend) -> Result<u64, String> {
     // * access control
     let current_caller = ic_cdk::caller();
@@ -38,26 +39,20 @@ fn add_post_v2(post_details: PostDetailsFromFrontend) -> Result<u64, String> {
     update_last_canister_functionality_access_time();
 
     let response = CANISTER_DATA.with(|canister_data_ref_cell| {
-        add_post_to_memory(
-            &mut canister_data_ref_cell.borrow_mut(),
+        let mut canister_data = canister_data_ref_cell.borrow_mut();
+        let post_id = add_post_to_memory(
+            &mut canister_data,
             &post_details,
             &system_time::get_current_system_time_from_ic(),
-        )
+        );
+        enqueue_slot_tabulation_jobs_for_post(&mut canister_data, post_id);
+        post_id
     });
 
     let post_id = response;
 
     update_scores_and_share_with_post_cache_if_difference_beyond_threshold(&post_id);
 
-    (1..=48).for_each(|slot_number: u8| {
-        ic_cdk_timers::set_timer(
-            Duration::from_secs(slot_number as u64 * 60 * 60),
-            move || {
-                tabulate_hot_or_not_outcome_for_post_slot(post_id, slot_number);
-            },
-        );
-    });
-
     ic_cdk::spawn(async {
         let _res = recieve_cycles_from_subnet_orchestrator().await;
     }); // 100B additional cycles for computing
@@ -70,10 +65,12 @@ pub fn add_post_to_memory(
     post_details: &PostDetailsFromFrontend,
     current_system_time: &SystemTime,
 ) -> u64 {
-    let new_post = Post::new(
+    // * the post keeps the game config it was created with
+    let new_post = Post::new_with_game_config(
         canister_data.all_created_posts.len() as u64,
         post_details,
         current_system_time,
+        canister_data.hot_or_not_game_config.clone(),
     );
     let new_post_id = new_post.id;
     canister_data
@@ -1,30 +1,337 @@
-use shared_utils::common::types::known_principal::KnownPrincipalType;
-
-use crate::CANISTER_DATA;
-
-// For feed_filter_upgrade_test
-// #[ic_cdk::update]
-// #[candid::candid_method(update)]
-// fn delete_post_temp(id: u64) {
-//     CANISTER_DATA.with(|canister_data_ref_cell| {
-//         let api_caller = ic_cdk::caller();
-
-//         let global_super_admin_principal_id = CANISTER_DATA.with(|canister_data_ref_cell| {
-//             canister_data_ref_cell
-//                 .borrow()
-//                 .known_principal_ids
-//                 .get(&KnownPrincipalType::UserIdGlobalSuperAdmin)
-//                 .cloned()
-//                 .unwrap()
-//         });
-
-//         if api_caller != global_super_admin_principal_id {
-//             return;
-//         }
-
-//         canister_data_ref_cell
-//             .borrow_mut()
-//             .all_created_posts
-//             .remove(&id);
-//     });
-// }
+use std::time::{Duration, SystemTime};
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::DeletePostError, hot_or_not::SlotId, prediction::PredictionStatus,
+    },
+    common::{
+        types::{app_primitive_type::PostId, top_posts::post_score_index_item::PostStatus},
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::{
+            slot_tabulation_queue::remove_slot_tabulation_jobs_for_post,
+            tabulate_hot_or_not_outcome_for_post_slot::inform_participants_of_outcome,
+        },
+        prediction::settle_prediction::settle_prediction_impl,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+use super::send_update_post_cache::send_update_post_cache;
+
+/// Marks the post as deleted and takes it out of every post_cache feed.
+/// Slots that already ended are tabulated right away, bets in slots that are still running
+/// are refunded in full, as are the bets on an open prediction.
+/// The heavy fields are dropped later by the purge job.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn delete_post(post_id: PostId) -> Result<(), DeletePostError> {
+    let current_caller = ic_cdk::caller();
+
+    let settled_slot_ids = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let current_time = system_time::get_current_system_time_from_ic();
+        delete_post_impl(
+            canister_data,
+            current_caller,
+            ic_cdk::id(),
+            post_id,
+            current_time,
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    send_update_post_cache(&post_id);
+
+    settled_slot_ids
+        .into_iter()
+        .for_each(|slot_id| inform_participants_of_outcome(post_id, slot_id));
+
+    Ok(())
+}
+
+/// Returns the slots whose bets were settled
+fn delete_post_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    this_canister_id: Principal,
+    post_id: PostId,
+    current_time: SystemTime,
+) -> Result<Vec<SlotId>, DeletePostError> {
+    if current_caller == Principal::anonymous() {
+        return Err(DeletePostError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(DeletePostError::UserPrincipalNotSet)?;
+
+    if current_caller != profile_owner {
+        return Err(DeletePostError::Unauthorized);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or(DeletePostError::PostNotFound)?;
+
+    if post.status == PostStatus::Deleted {
+        return Err(DeletePostError::PostAlreadyDeleted);
+    }
+
+    post.update_status(PostStatus::Deleted);
+
+    Ok(close_bets_on_deleted_post(
+        canister_data,
+        this_canister_id,
+        post_id,
+        current_time,
+    ))
+}
+
+/// Tabulates the slots of a deleted post that already ended, refunds the bets in slots
+/// that are still running and refunds the bets on its prediction if it is still open.
+/// The bet makers of the prediction are informed from the prediction settlement timer.
+/// Returns the slots whose bets were settled
+pub(crate) fn close_bets_on_deleted_post(
+    canister_data: &mut CanisterData,
+    this_canister_id: Principal,
+    post_id: PostId,
+    current_time: SystemTime,
+) -> Vec<SlotId> {
+    let Some(post) = canister_data.all_created_posts.get_mut(&post_id) else {
+        return vec![];
+    };
+
+    let duration_of_each_slot_in_seconds =
+        post.hot_or_not_game_config.duration_of_each_slot_in_seconds;
+    let mut slot_ids = std::mem::take(&mut post.slots_left_to_be_computed)
+        .into_iter()
+        .collect::<Vec<_>>();
+    slot_ids.sort();
+
+    slot_ids.iter().for_each(|slot_id| {
+        let slot_ends_at = post.created_at
+            + Duration::from_secs(*slot_id as u64 * duration_of_each_slot_in_seconds);
+
+        if slot_ends_at <= current_time {
+            post.tabulate_hot_or_not_outcome_for_slot_v1(
+                &this_canister_id,
+                slot_id,
+                &mut canister_data.my_token_balance,
+                &current_time,
+                &mut canister_data.room_details_map,
+                &mut canister_data.bet_details_map,
+            );
+        } else {
+            post.refund_hot_or_not_bets_for_slot(
+                slot_id,
+                &mut canister_data.room_details_map,
+                &mut canister_data.bet_details_map,
+            );
+        }
+    });
+
+    remove_slot_tabulation_jobs_for_post(canister_data, post_id);
+    canister_data.posts_pending_purge.insert(post_id);
+
+    settle_prediction_impl(
+        canister_data,
+        this_canister_id,
+        post_id,
+        PredictionStatus::Refunded,
+        current_time,
+    );
+
+    slot_ids
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{
+            game_config::HotOrNotGameConfig, BetDirection, BetPayout, GlobalBetId, GlobalRoomId,
+            RoomBetPossibleOutcomes, StablePrincipal,
+        },
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+        get_mock_user_charlie_canister_id, get_mock_user_charlie_principal_id,
+    };
+
+    use crate::api::hot_or_not_bet::slot_tabulation_queue::enqueue_slot_tabulation_jobs_for_post;
+
+    use super::*;
+
+    fn place_bet(
+        canister_data: &mut CanisterData,
+        bet_maker_principal_id: Principal,
+        bet_maker_canister_id: Principal,
+        bet_direction: BetDirection,
+        placed_at: SystemTime,
+    ) {
+        let post = canister_data.all_created_posts.get_mut(&0).unwrap();
+        let result = post.place_hot_or_not_bet_v1(
+            &bet_maker_principal_id,
+            &bet_maker_canister_id,
+            100,
+            &bet_direction,
+            &placed_at,
+            &mut canister_data.room_details_map,
+            &mut canister_data.bet_details_map,
+            &mut canister_data.post_principal_map,
+            &mut canister_data.slot_details_map,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_delete_post_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let post_created_at = SystemTime::now();
+
+        assert_eq!(
+            delete_post_impl(
+                &mut canister_data,
+                Principal::anonymous(),
+                get_mock_user_alice_canister_id(),
+                0,
+                post_created_at
+            ),
+            Err(DeletePostError::Unauthenticated)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        assert_eq!(
+            delete_post_impl(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                get_mock_user_alice_canister_id(),
+                0,
+                post_created_at
+            ),
+            Err(DeletePostError::Unauthorized)
+        );
+        assert_eq!(
+            delete_post_impl(
+                &mut canister_data,
+                alice,
+                get_mock_user_alice_canister_id(),
+                0,
+                post_created_at
+            ),
+            Err(DeletePostError::PostNotFound)
+        );
+
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new_with_game_config(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &post_created_at,
+                HotOrNotGameConfig {
+                    maximum_number_of_slots: 3,
+                    duration_of_each_slot_in_seconds: 60,
+                    ..Default::default()
+                },
+            ),
+        );
+        enqueue_slot_tabulation_jobs_for_post(&mut canister_data, 0);
+
+        // * bob bets in the first slot, charlie in the second
+        place_bet(
+            &mut canister_data,
+            get_mock_user_bob_principal_id(),
+            get_mock_user_bob_canister_id(),
+            BetDirection::Hot,
+            post_created_at,
+        );
+        place_bet(
+            &mut canister_data,
+            get_mock_user_charlie_principal_id(),
+            get_mock_user_charlie_canister_id(),
+            BetDirection::Not,
+            post_created_at + Duration::from_secs(61),
+        );
+
+        let deleted_at = post_created_at + Duration::from_secs(90);
+        let settled_slot_ids = delete_post_impl(
+            &mut canister_data,
+            alice,
+            get_mock_user_alice_canister_id(),
+            0,
+            deleted_at,
+        )
+        .unwrap();
+        assert_eq!(settled_slot_ids, vec![1, 2, 3]);
+
+        let post = canister_data.all_created_posts.get(&0).unwrap();
+        assert_eq!(post.status, PostStatus::Deleted);
+        assert!(post.slots_left_to_be_computed.is_empty());
+        assert_eq!(canister_data.slot_tabulation_queue.len(), 0);
+        assert!(canister_data.posts_pending_purge.contains(&0));
+
+        // * the slot that ended is settled as usual
+        assert_eq!(
+            canister_data
+                .room_details_map
+                .get(&GlobalRoomId(0, 1, 1))
+                .unwrap()
+                .bet_outcome,
+            RoomBetPossibleOutcomes::HotWon
+        );
+
+        // * the running slot is refunded in full
+        assert_eq!(
+            canister_data
+                .room_details_map
+                .get(&GlobalRoomId(0, 2, 1))
+                .unwrap()
+                .bet_outcome,
+            RoomBetPossibleOutcomes::Draw
+        );
+        assert_eq!(
+            canister_data
+                .bet_details_map
+                .get(&GlobalBetId(
+                    GlobalRoomId(0, 2, 1),
+                    StablePrincipal(get_mock_user_charlie_principal_id())
+                ))
+                .unwrap()
+                .payout,
+            BetPayout::Calculated(100)
+        );
+
+        assert_eq!(
+            delete_post_impl(
+                &mut canister_data,
+                alice,
+                get_mock_user_alice_canister_id(),
+                0,
+                deleted_at
+            ),
+            Err(DeletePostError::PostAlreadyDeleted)
+        );
+    }
+}
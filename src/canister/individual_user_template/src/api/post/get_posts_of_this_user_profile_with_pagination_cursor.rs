@@ -128,6 +128,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
             Post {
                 id: 2,
@@ -143,6 +148,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
             Post {
                 id: 3,
@@ -158,6 +168,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
             Post {
                 id: 4,
@@ -173,6 +188,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
             Post {
                 id: 5,
@@ -188,6 +208,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
             Post {
                 id: 6,
@@ -203,6 +228,11 @@ mod test {
                 hot_or_not_details: None,
                 is_nsfw: false,
                 slots_left_to_be_computed: (1..=48).collect(),
+                tip_details: Default::default(),
+                hot_or_not_game_config: Default::default(),
+                revisions: Default::default(),
+                comment_count: 0,
+                commenter_count: 0,
             },
         ];
 
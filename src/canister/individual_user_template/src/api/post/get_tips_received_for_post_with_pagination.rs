@@ -0,0 +1,49 @@
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::GetTipsForPostError, post::PostTip,
+    },
+    common::types::app_primitive_type::PostId,
+    pagination::{self, PaginationError},
+};
+
+use crate::CANISTER_DATA;
+
+/// Only the most recent tips of a post are kept, see `MAX_TIPS_KEPT_PER_POST`
+#[query]
+fn get_tips_received_for_post_with_pagination(
+    post_id: PostId,
+    from_inclusive_index: u64,
+    to_exclusive_index: u64,
+) -> Result<Vec<PostTip>, GetTipsForPostError> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        let tip_history = &canister_data
+            .all_created_posts
+            .get(&post_id)
+            .ok_or(GetTipsForPostError::PostNotFound)?
+            .tip_details
+            .tip_history;
+
+        let (from_inclusive_index, to_exclusive_index) = pagination::get_pagination_bounds(
+            from_inclusive_index,
+            to_exclusive_index,
+            tip_history.len() as u64,
+        )
+        .map_err(|e| match e {
+            PaginationError::InvalidBoundsPassed => GetTipsForPostError::InvalidBoundsPassed,
+            PaginationError::ReachedEndOfItemsList => GetTipsForPostError::ReachedEndOfItemsList,
+            PaginationError::ExceededMaxNumberOfItemsAllowedInOneRequest => {
+                GetTipsForPostError::ExceededMaxNumberOfItemsAllowedInOneRequest
+            }
+        })?;
+
+        // * most recent tips first
+        Ok(tip_history
+            .iter()
+            .rev()
+            .skip(from_inclusive_index as usize)
+            .take((to_exclusive_index - from_inclusive_index) as usize)
+            .cloned()
+            .collect())
+    })
+}
@@ -4,10 +4,20 @@ pub mod get_entire_individual_post_detail_by_id;
 pub mod get_individual_post_details_by_id;
 pub mod get_posts_of_this_user_profile_with_pagination;
 pub mod get_posts_of_this_user_profile_with_pagination_cursor;
+pub mod get_tips_received_for_post_with_pagination;
+pub mod purge_deleted_posts;
+pub mod receive_post_report_from_reporters_canister;
+pub mod receive_tip_from_tippers_canister;
+pub mod reconcile_pending_post_tips;
+pub mod report_post;
+pub mod review_post_reports;
 pub mod send_update_post_cache;
+pub mod tip_post_creator;
 pub mod update_post_add_view_details;
 pub mod update_post_as_ready_to_view;
+pub mod update_post_details;
 pub mod update_post_increment_share_count;
+pub mod update_post_report_threshold;
 pub mod update_post_status;
 pub mod update_post_toggle_like_status_by_caller;
 pub mod update_scores_and_share_with_post_cache_if_difference_beyond_threshold;
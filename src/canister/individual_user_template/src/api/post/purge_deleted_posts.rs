@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        comment::GlobalCommentId,
+        hot_or_not::{BetPayout, GlobalBetId, GlobalRoomId, StablePrincipal},
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration_impl, CANISTER_DATA,
+};
+
+const DELETED_POST_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const MAX_POSTS_PURGED_PER_RUN: usize = 50;
+
+/// Starts the recurring timer that drops the heavy fields and the comments of deleted posts.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+pub fn start_deleted_post_purge_timer() {
+    ic_cdk_timers::set_timer_interval(DELETED_POST_PURGE_INTERVAL, || {
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            if is_not_frozen_for_migration_impl(canister_data).is_err() {
+                return;
+            }
+
+            purge_deleted_posts_impl(canister_data, MAX_POSTS_PURGED_PER_RUN);
+        });
+    });
+}
+
+/// Returns the ids of the posts that were purged
+fn purge_deleted_posts_impl(canister_data: &mut CanisterData, limit: usize) -> Vec<PostId> {
+    let purgeable_post_ids = canister_data
+        .posts_pending_purge
+        .iter()
+        .copied()
+        .filter(|post_id| are_bets_on_post_final(canister_data, *post_id))
+        .take(limit)
+        .collect::<Vec<_>>();
+
+    purgeable_post_ids.iter().for_each(|post_id| {
+        if let Some(post) = canister_data.all_created_posts.get_mut(post_id) {
+            post.purge_heavy_fields();
+        }
+
+        let comment_ids = canister_data
+            .comments_map
+            .range(GlobalCommentId(*post_id, 0)..GlobalCommentId(*post_id + 1, 0))
+            .map(|(global_comment_id, _)| global_comment_id)
+            .collect::<Vec<_>>();
+        comment_ids.iter().for_each(|global_comment_id| {
+            canister_data.comments_map.remove(global_comment_id);
+        });
+
+        canister_data.posts_pending_purge.remove(post_id);
+    });
+
+    purgeable_post_ids
+}
+
+/// Bets are final once every slot is tabulated, every payout is calculated
+/// and every bet maker has been told about the outcome
+pub(crate) fn are_bets_on_post_final(canister_data: &CanisterData, post_id: PostId) -> bool {
+    let all_slots_computed = canister_data
+        .all_created_posts
+        .get(&post_id)
+        .map_or(true, |post| post.slots_left_to_be_computed.is_empty());
+
+    let start_global_bet_id = GlobalBetId(GlobalRoomId(post_id, 0, 0), StablePrincipal::default());
+    let end_global_bet_id =
+        GlobalBetId(GlobalRoomId(post_id + 1, 0, 0), StablePrincipal::default());
+
+    all_slots_computed
+        && canister_data
+            .bet_details_map
+            .range(start_global_bet_id..end_global_bet_id)
+            .all(|(_, bet_details)| {
+                bet_details.payout != BetPayout::NotCalculatedYet
+                    && bet_details.bet_maker_informed_status.is_some()
+            })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::{
+        canister_specific::individual_user_template::types::{
+            comment::Comment,
+            hot_or_not::{BetDetails, BetDirection, BetMakerInformedStatus},
+            post::{Post, PostDetailsFromFrontend},
+        },
+        common::types::top_posts::post_score_index_item::PostStatus,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_purge_deleted_posts_impl() {
+        let mut canister_data = CanisterData::default();
+
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &SystemTime::now(),
+        );
+        post.update_status(PostStatus::Deleted);
+        post.slots_left_to_be_computed.clear();
+        canister_data.all_created_posts.insert(0, post);
+        canister_data.posts_pending_purge.insert(0);
+        canister_data.comments_map.insert(
+            GlobalCommentId(0, 0),
+            Comment::new(
+                0,
+                None,
+                get_mock_user_bob_principal_id(),
+                "Nice".into(),
+                SystemTime::now(),
+            ),
+        );
+
+        let global_bet_id = GlobalBetId(
+            GlobalRoomId(0, 1, 1),
+            StablePrincipal(get_mock_user_bob_principal_id()),
+        );
+        let mut bet_details = BetDetails {
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            payout: BetPayout::Calculated(100),
+            bet_maker_canister_id: get_mock_user_bob_canister_id(),
+            bet_maker_informed_status: None,
+            idempotency_key: None,
+            placed_at: None,
+        };
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id.clone(), bet_details.clone());
+
+        // * the bet maker has not been informed yet
+        assert!(purge_deleted_posts_impl(&mut canister_data, 10).is_empty());
+        assert!(canister_data.posts_pending_purge.contains(&0));
+
+        bet_details.bet_maker_informed_status = Some(BetMakerInformedStatus::InformedSuccessfully);
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id, bet_details);
+
+        assert_eq!(purge_deleted_posts_impl(&mut canister_data, 10), vec![0]);
+        assert!(canister_data.posts_pending_purge.is_empty());
+
+        let post = canister_data.all_created_posts.get(&0).unwrap();
+        assert_eq!(post.status, PostStatus::Deleted);
+        assert!(post.description.is_empty());
+        assert!(post.video_uid.is_empty());
+        assert!(post.hashtags.is_empty());
+        assert_eq!(canister_data.comments_map.len(), 0);
+    }
+}
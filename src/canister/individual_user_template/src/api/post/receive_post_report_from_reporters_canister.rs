@@ -0,0 +1,272 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::call;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::ReportPostError,
+        report::{PostReport, PostReportReason, PostReportSummary},
+    },
+    common::{
+        types::{
+            app_primitive_type::PostId, known_principal::KnownPrincipalType,
+            top_posts::post_score_index_item::PostStatus,
+        },
+        utils::system_time,
+    },
+    constant::DEFAULT_POST_REPORT_THRESHOLD,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration,
+    util::user_index::get_user_canister_id_from_user_principal_id, CANISTER_DATA,
+};
+
+use super::send_update_post_cache::send_update_post_cache;
+
+/// Records a report relayed by the reporter's own canister, one per reporter.
+/// Once enough distinct reporters are pending review, the post is banned and taken out of
+/// post_cache, and the reports are forwarded to user_index for admins to review.
+/// An admin can lift the ban from there.
+/// Returns the number of reports pending review.
+///
+/// # Access Control
+/// Only the individual canister that user_index has on record for `reporter_principal_id`
+#[update(guard = "is_not_frozen_for_migration")]
+async fn receive_post_report_from_reporters_canister(
+    post_id: PostId,
+    reason: PostReportReason,
+    reporter_principal_id: Principal,
+) -> Result<u64, ReportPostError> {
+    let reporter_canister_id = ic_cdk::caller();
+
+    let registered_reporter_canister_id =
+        get_user_canister_id_from_user_principal_id(reporter_principal_id)
+            .await
+            .map_err(|_| ReportPostError::UserIndexCrossCanisterCallFailed)?;
+
+    if registered_reporter_canister_id != Some(reporter_canister_id) {
+        return Err(ReportPostError::ReporterCanisterDoesNotMatch);
+    }
+
+    let (summary, banned_now) = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        receive_post_report_from_reporters_canister_impl(
+            canister_data,
+            reporter_principal_id,
+            ic_cdk::id(),
+            post_id,
+            reason,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    if banned_now {
+        send_update_post_cache(&post_id);
+    }
+
+    let user_index_canister_id = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .known_principal_ids
+            .get(&KnownPrincipalType::CanisterIdUserIndex)
+            .copied()
+    });
+    if let (true, Some(user_index_canister_id)) = (summary.banned, user_index_canister_id) {
+        let _ = call::notify(
+            user_index_canister_id,
+            "receive_post_report_summary",
+            (summary.clone(),),
+        );
+    }
+
+    Ok(summary.pending_report_count)
+}
+
+/// Returns the summary to send to user_index and whether this report banned the post
+fn receive_post_report_from_reporters_canister_impl(
+    canister_data: &mut CanisterData,
+    reporter_principal_id: Principal,
+    this_canister_id: Principal,
+    post_id: PostId,
+    reason: PostReportReason,
+    current_time: SystemTime,
+) -> Result<(PostReportSummary, bool), ReportPostError> {
+    if reporter_principal_id == Principal::anonymous() {
+        return Err(ReportPostError::Unauthenticated);
+    }
+
+    if !reason.is_valid() {
+        return Err(ReportPostError::InvalidReason);
+    }
+
+    let post_creator = canister_data
+        .profile
+        .principal_id
+        .ok_or(ReportPostError::UserPrincipalNotSet)?;
+
+    if reporter_principal_id == post_creator {
+        return Err(ReportPostError::CannotReportOwnPost);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or(ReportPostError::PostNotFound)?;
+
+    if matches!(
+        post.status,
+        PostStatus::BannedForExplicitness | PostStatus::Deleted
+    ) {
+        return Err(ReportPostError::PostNotAvailableForReporting);
+    }
+
+    let post_reports = canister_data.post_reports.entry(post_id).or_default();
+
+    if post_reports.has_reported(&reporter_principal_id) {
+        return Err(ReportPostError::AlreadyReported);
+    }
+
+    post_reports.reports.push(PostReport {
+        reporter_principal_id,
+        reason,
+        reported_at: current_time,
+    });
+
+    let threshold = canister_data
+        .configuration
+        .post_report_threshold
+        .unwrap_or(DEFAULT_POST_REPORT_THRESHOLD);
+
+    let banned_now = post.status != PostStatus::BannedDueToUserReporting
+        && post_reports.pending_report_count() >= threshold;
+    if banned_now {
+        post.update_status(PostStatus::BannedDueToUserReporting);
+    }
+
+    Ok((
+        PostReportSummary {
+            post_creator_canister_id: this_canister_id,
+            post_id,
+            pending_report_count: post_reports.pending_report_count(),
+            pending_reports: post_reports.pending_reports().to_vec(),
+            banned: post.status == PostStatus::BannedDueToUserReporting,
+        },
+        banned_now,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::post::{
+        Post, PostDetailsFromFrontend,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_principal_id, get_mock_user_charlie_principal_id,
+        get_mock_user_dan_principal_id,
+    };
+
+    use super::*;
+
+    fn report(
+        canister_data: &mut CanisterData,
+        reporter: Principal,
+    ) -> Result<(PostReportSummary, bool), ReportPostError> {
+        receive_post_report_from_reporters_canister_impl(
+            canister_data,
+            reporter,
+            get_mock_user_alice_canister_id(),
+            0,
+            PostReportReason::Spam,
+            SystemTime::now(),
+        )
+    }
+
+    #[test]
+    fn test_receive_post_report_from_reporters_canister_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+
+        assert_eq!(
+            report(&mut canister_data, Principal::anonymous()),
+            Err(ReportPostError::Unauthenticated)
+        );
+        assert_eq!(
+            report(&mut canister_data, bob),
+            Err(ReportPostError::UserPrincipalNotSet)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        canister_data.configuration.post_report_threshold = Some(2);
+        assert_eq!(
+            report(&mut canister_data, bob),
+            Err(ReportPostError::PostNotFound)
+        );
+
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &SystemTime::now(),
+            ),
+        );
+
+        assert_eq!(
+            report(&mut canister_data, alice),
+            Err(ReportPostError::CannotReportOwnPost)
+        );
+        assert_eq!(
+            receive_post_report_from_reporters_canister_impl(
+                &mut canister_data,
+                bob,
+                get_mock_user_alice_canister_id(),
+                0,
+                PostReportReason::Other("".into()),
+                SystemTime::now(),
+            ),
+            Err(ReportPostError::InvalidReason)
+        );
+
+        let (summary, banned_now) = report(&mut canister_data, bob).unwrap();
+        assert_eq!(summary.pending_report_count, 1);
+        assert!(!banned_now);
+        assert!(!summary.banned);
+        assert_eq!(
+            report(&mut canister_data, bob),
+            Err(ReportPostError::AlreadyReported)
+        );
+
+        let (summary, banned_now) =
+            report(&mut canister_data, get_mock_user_charlie_principal_id()).unwrap();
+        assert_eq!(summary.pending_report_count, 2);
+        assert_eq!(summary.pending_reports.len(), 2);
+        assert_eq!(
+            summary.post_creator_canister_id,
+            get_mock_user_alice_canister_id()
+        );
+        assert!(banned_now);
+        assert!(summary.banned);
+        assert_eq!(
+            canister_data.all_created_posts.get(&0).unwrap().status,
+            PostStatus::BannedDueToUserReporting
+        );
+
+        // * a banned post keeps collecting reports for review without being banned again
+        let (summary, banned_now) =
+            report(&mut canister_data, get_mock_user_dan_principal_id()).unwrap();
+        assert_eq!(summary.pending_report_count, 3);
+        assert!(!banned_now);
+        assert!(summary.banned);
+    }
+}
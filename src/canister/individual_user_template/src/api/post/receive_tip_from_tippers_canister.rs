@@ -0,0 +1,281 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::TipPostError, post::PostTip, token::TokenTransferIdempotencyKey,
+    },
+    common::{
+        types::{
+            app_primitive_type::PostId,
+            top_posts::post_score_index_item::PostStatus,
+            utility_token::token_event::{PostTipEvent, TokenEvent},
+        },
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        token::receive_utility_tokens_from_user::record_received_token_transfer_impl,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    util::user_index::get_user_canister_id_from_user_principal_id,
+    CANISTER_DATA,
+};
+
+use super::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold;
+
+/// Credits a tip once per idempotency key. A tip already credited
+/// is acknowledged again without being credited twice.
+///
+/// # Access Control
+/// Only the individual canister that user_index has on record for `tipper_principal_id`
+#[update(guard = "is_not_frozen_for_migration")]
+async fn receive_tip_from_tippers_canister(
+    post_id: PostId,
+    amount: u64,
+    tipper_principal_id: Principal,
+    idempotency_key: Option<TokenTransferIdempotencyKey>,
+) -> Result<Principal, TipPostError> {
+    let tipper_canister_id = ic_cdk::caller();
+
+    if amount == 0 {
+        return Err(TipPostError::InvalidAmount);
+    }
+
+    let registered_tipper_canister_id =
+        get_user_canister_id_from_user_principal_id(tipper_principal_id)
+            .await
+            .map_err(|_| TipPostError::UserIndexCrossCanisterCallFailed)?;
+
+    if registered_tipper_canister_id != Some(tipper_canister_id) {
+        return Err(TipPostError::TipperCanisterDoesNotMatch);
+    }
+
+    update_last_canister_functionality_access_time();
+
+    let post_creator_principal_id = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        receive_tip_from_tippers_canister_impl(
+            canister_data,
+            ic_cdk::id(),
+            post_id,
+            idempotency_key,
+            PostTip {
+                tipper_principal_id,
+                tipper_canister_id,
+                amount,
+                tipped_at: system_time::get_current_system_time_from_ic(),
+            },
+        )
+    })?;
+
+    update_scores_and_share_with_post_cache_if_difference_beyond_threshold(&post_id);
+
+    Ok(post_creator_principal_id)
+}
+
+fn receive_tip_from_tippers_canister_impl(
+    canister_data: &mut CanisterData,
+    my_canister_id: Principal,
+    post_id: PostId,
+    idempotency_key: Option<TokenTransferIdempotencyKey>,
+    tip: PostTip,
+) -> Result<Principal, TipPostError> {
+    let post_creator_principal_id = canister_data
+        .profile
+        .principal_id
+        .ok_or(TipPostError::UserPrincipalNotSet)?;
+
+    let is_already_received = idempotency_key.is_some_and(|idempotency_key| {
+        canister_data
+            .received_token_transfer_keys
+            .get(&tip.tipper_principal_id)
+            .is_some_and(|received_keys| received_keys.contains(&idempotency_key))
+    });
+    if is_already_received {
+        return Ok(post_creator_principal_id);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or(TipPostError::PostNotFound)?;
+
+    if matches!(
+        post.status,
+        PostStatus::BannedForExplicitness
+            | PostStatus::BannedDueToUserReporting
+            | PostStatus::Deleted
+    ) {
+        return Err(TipPostError::PostNotAvailableForTips);
+    }
+
+    let amount = tip.amount;
+    let from_account = tip.tipper_principal_id;
+    let timestamp = tip.tipped_at;
+
+    post.add_tip(tip);
+
+    if let Some(idempotency_key) = idempotency_key {
+        record_received_token_transfer_impl(canister_data, from_account, idempotency_key);
+    }
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::PostTip {
+            amount,
+            details: PostTipEvent::TipReceived {
+                post_canister_id: my_canister_id,
+                post_id,
+                from_account,
+            },
+            timestamp,
+        });
+
+    Ok(post_creator_principal_id)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::post::{
+        Post, PostDetailsFromFrontend,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_receive_tip_from_tippers_canister_impl() {
+        let mut canister_data = CanisterData::default();
+        let tip = PostTip {
+            tipper_principal_id: get_mock_user_bob_principal_id(),
+            tipper_canister_id: get_mock_user_bob_canister_id(),
+            amount: 100,
+            tipped_at: SystemTime::now(),
+        };
+
+        let result = receive_tip_from_tippers_canister_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            None,
+            tip.clone(),
+        );
+        assert_eq!(result, Err(TipPostError::UserPrincipalNotSet));
+
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+
+        let result = receive_tip_from_tippers_canister_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            None,
+            tip.clone(),
+        );
+        assert_eq!(result, Err(TipPostError::PostNotFound));
+
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &SystemTime::now(),
+            ),
+        );
+
+        let result = receive_tip_from_tippers_canister_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            None,
+            tip.clone(),
+        );
+        assert_eq!(result, Ok(get_mock_user_alice_principal_id()));
+
+        let post = canister_data.all_created_posts.get(&0).unwrap();
+        assert_eq!(post.tip_details.total_tip_amount, 100);
+        assert_eq!(post.tip_details.tip_history, vec![tip.clone()]);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 100);
+        assert_eq!(canister_data.my_token_balance.lifetime_earnings, 100);
+
+        canister_data
+            .all_created_posts
+            .get_mut(&0)
+            .unwrap()
+            .update_status(PostStatus::Deleted);
+
+        let result = receive_tip_from_tippers_canister_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            None,
+            tip,
+        );
+        assert_eq!(result, Err(TipPostError::PostNotAvailableForTips));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 100);
+    }
+
+    #[test]
+    fn test_receive_tip_from_tippers_canister_impl_credits_a_tip_once() {
+        let mut canister_data = CanisterData::default();
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &SystemTime::now(),
+            ),
+        );
+        let tip = PostTip {
+            tipper_principal_id: get_mock_user_bob_principal_id(),
+            tipper_canister_id: get_mock_user_bob_canister_id(),
+            amount: 100,
+            tipped_at: SystemTime::now(),
+        };
+
+        for _ in 0..2 {
+            let result = receive_tip_from_tippers_canister_impl(
+                &mut canister_data,
+                get_mock_user_alice_canister_id(),
+                0,
+                Some(7),
+                tip.clone(),
+            );
+            assert_eq!(result, Ok(get_mock_user_alice_principal_id()));
+        }
+
+        let post = canister_data.all_created_posts.get(&0).unwrap();
+        assert_eq!(post.tip_details.total_tip_amount, 100);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 100);
+
+        let result = receive_tip_from_tippers_canister_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            Some(8),
+            tip,
+        );
+        assert_eq!(result, Ok(get_mock_user_alice_principal_id()));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 200);
+    }
+}
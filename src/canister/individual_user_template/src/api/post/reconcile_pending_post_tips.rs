@@ -0,0 +1,137 @@
+use candid::Principal;
+use shared_utils::canister_specific::individual_user_template::types::token::{
+    PendingPostTip, TokenTransferIdempotencyKey,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+use super::tip_post_creator::{abort_pending_post_tip_impl, commit_pending_post_tip_impl};
+
+pub fn get_pending_post_tips_due_for_reconciliation(
+    canister_data: &CanisterData,
+) -> Vec<(TokenTransferIdempotencyKey, PendingPostTip)> {
+    canister_data
+        .pending_post_tips
+        .iter()
+        .filter(|(_, pending_post_tip)| pending_post_tip.awaiting_reconciliation)
+        .map(|(idempotency_key, pending_post_tip)| (*idempotency_key, pending_post_tip.clone()))
+        .collect()
+}
+
+pub async fn reconcile_pending_post_tip(
+    idempotency_key: TokenTransferIdempotencyKey,
+    pending_post_tip: PendingPostTip,
+) {
+    let Some(my_principal_id) =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id)
+    else {
+        return;
+    };
+
+    // * on failure the tip stays pending and is retried on the next run
+    let Ok((post_creator_principal_id,)) = ic_cdk::call::<_, (Option<Principal>,)>(
+        pending_post_tip.post_canister_id,
+        "get_token_transfer_for_reconciliation",
+        (my_principal_id, idempotency_key),
+    )
+    .await
+    else {
+        return;
+    };
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        settle_pending_post_tip_impl(canister_data, idempotency_key, post_creator_principal_id);
+    });
+}
+
+/// Records the tip if the post creator's canister credited it, refunds it otherwise
+fn settle_pending_post_tip_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+    post_creator_principal_id: Option<Principal>,
+) {
+    match post_creator_principal_id {
+        Some(post_creator_principal_id) => {
+            commit_pending_post_tip_impl(canister_data, idempotency_key, post_creator_principal_id)
+        }
+        None => abort_pending_post_tip_impl(canister_data, idempotency_key),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::common::types::utility_token::token_event::{PostTipEvent, TokenEvent};
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use super::*;
+
+    fn pending_post_tip(awaiting_reconciliation: bool) -> PendingPostTip {
+        PendingPostTip {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            amount: 100,
+            initiated_at: SystemTime::now(),
+            awaiting_reconciliation,
+        }
+    }
+
+    #[test]
+    fn test_settle_pending_post_tip_impl() {
+        let mut canister_data = CanisterData::default();
+        // * the amounts of the three pending tips below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 700;
+        canister_data
+            .pending_post_tips
+            .insert(0, pending_post_tip(true));
+        canister_data
+            .pending_post_tips
+            .insert(1, pending_post_tip(true));
+        // * a tip whose call is still in flight is not reconciled
+        canister_data
+            .pending_post_tips
+            .insert(2, pending_post_tip(false));
+
+        let due_pending_post_tips = get_pending_post_tips_due_for_reconciliation(&canister_data);
+        assert_eq!(
+            due_pending_post_tips
+                .iter()
+                .map(|(idempotency_key, _)| *idempotency_key)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        settle_pending_post_tip_impl(
+            &mut canister_data,
+            0,
+            Some(get_mock_user_alice_principal_id()),
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 700);
+        assert!(matches!(
+            canister_data
+                .my_token_balance
+                .utility_token_transaction_history
+                .values()
+                .next(),
+            Some(TokenEvent::PostTip {
+                details: PostTipEvent::TipSent { .. },
+                ..
+            })
+        ));
+
+        settle_pending_post_tip_impl(&mut canister_data, 1, None);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        assert_eq!(
+            canister_data
+                .pending_post_tips
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+}
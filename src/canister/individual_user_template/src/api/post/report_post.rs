@@ -0,0 +1,145 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::individual_user_template::types::{
+    arg::ReportPostArg, error::ReportPostError,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// Relays a report from the owner of this canister to the canister of the post creator,
+/// which only accepts reports from canisters that user_index has on record.
+/// Returns the number of reports pending review for the post.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister can report posts.
+#[update(guard = "is_not_frozen_for_migration")]
+async fn report_post(arg: ReportPostArg) -> Result<u64, ReportPostError> {
+    let current_caller = ic_cdk::caller();
+    let my_canister_id = ic_cdk::id();
+
+    let my_principal_id = CANISTER_DATA.with_borrow(|canister_data| {
+        validate_outgoing_report(canister_data, &current_caller, &my_canister_id, &arg)
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    let (response,) = ic_cdk::call::<_, (Result<u64, ReportPostError>,)>(
+        arg.post_canister_id,
+        "receive_post_report_from_reporters_canister",
+        (arg.post_id, arg.reason, my_principal_id),
+    )
+    .await
+    .map_err(|_| ReportPostError::PostCreatorCanisterCallFailed)?;
+
+    response
+}
+
+fn validate_outgoing_report(
+    canister_data: &CanisterData,
+    current_caller: &Principal,
+    my_canister_id: &Principal,
+    arg: &ReportPostArg,
+) -> Result<Principal, ReportPostError> {
+    if *current_caller == Principal::anonymous() {
+        return Err(ReportPostError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(ReportPostError::UserPrincipalNotSet)?;
+
+    if *current_caller != profile_owner {
+        return Err(ReportPostError::Unauthorized);
+    }
+
+    if !arg.reason.is_valid() {
+        return Err(ReportPostError::InvalidReason);
+    }
+
+    if arg.post_canister_id == *my_canister_id {
+        return Err(ReportPostError::CannotReportOwnPost);
+    }
+
+    Ok(profile_owner)
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::report::PostReportReason;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_validate_outgoing_report() {
+        let mut canister_data = CanisterData::default();
+        let my_canister_id = get_mock_user_bob_canister_id();
+        let arg = ReportPostArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            reason: PostReportReason::Spam,
+        };
+
+        assert_eq!(
+            validate_outgoing_report(
+                &canister_data,
+                &Principal::anonymous(),
+                &my_canister_id,
+                &arg
+            ),
+            Err(ReportPostError::Unauthenticated)
+        );
+
+        canister_data.profile.principal_id = Some(get_mock_user_bob_principal_id());
+
+        assert_eq!(
+            validate_outgoing_report(
+                &canister_data,
+                &get_mock_user_alice_principal_id(),
+                &my_canister_id,
+                &arg
+            ),
+            Err(ReportPostError::Unauthorized)
+        );
+        assert_eq!(
+            validate_outgoing_report(
+                &canister_data,
+                &get_mock_user_bob_principal_id(),
+                &my_canister_id,
+                &ReportPostArg {
+                    reason: PostReportReason::Other("".into()),
+                    ..arg.clone()
+                }
+            ),
+            Err(ReportPostError::InvalidReason)
+        );
+        assert_eq!(
+            validate_outgoing_report(
+                &canister_data,
+                &get_mock_user_bob_principal_id(),
+                &my_canister_id,
+                &ReportPostArg {
+                    post_canister_id: my_canister_id,
+                    ..arg.clone()
+                }
+            ),
+            Err(ReportPostError::CannotReportOwnPost)
+        );
+        assert_eq!(
+            validate_outgoing_report(
+                &canister_data,
+                &get_mock_user_bob_principal_id(),
+                &my_canister_id,
+                &arg
+            ),
+            Ok(get_mock_user_bob_principal_id())
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::report::PostReportReviewDecision,
+    common::{
+        types::{app_primitive_type::PostId, top_posts::post_score_index_item::PostStatus},
+        utils::permissions::is_caller_controller,
+    },
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+use super::send_update_post_cache::send_update_post_cache;
+
+/// Applies an admin decision on the reports pending review for a post.
+///
+/// # Access Control
+/// Only the controller (user index), which relays the decision of a global admin
+#[update(guard = "is_caller_controller")]
+fn review_post_reports(post_id: PostId, decision: PostReportReviewDecision) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        review_post_reports_impl(canister_data, post_id, decision)
+    })?;
+
+    send_update_post_cache(&post_id);
+
+    Ok(())
+}
+
+fn review_post_reports_impl(
+    canister_data: &mut CanisterData,
+    post_id: PostId,
+    decision: PostReportReviewDecision,
+) -> Result<(), String> {
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&post_id)
+        .ok_or("Post not found")?;
+
+    match decision {
+        PostReportReviewDecision::Ban => {
+            if post.status != PostStatus::Deleted {
+                post.update_status(PostStatus::BannedDueToUserReporting);
+            }
+        }
+        PostReportReviewDecision::Dismiss => {
+            if post.status == PostStatus::BannedDueToUserReporting {
+                post.update_status(PostStatus::ReadyToView);
+            }
+        }
+    }
+
+    if let Some(post_reports) = canister_data.post_reports.get_mut(&post_id) {
+        post_reports.reports_dismissed = post_reports.reports.len() as u64;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use candid::Principal;
+    use shared_utils::canister_specific::individual_user_template::types::{
+        post::{Post, PostDetailsFromFrontend},
+        report::{PostReport, PostReportReason, PostReports},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_review_post_reports_impl() {
+        let mut canister_data = CanisterData::default();
+
+        assert!(
+            review_post_reports_impl(&mut canister_data, 0, PostReportReviewDecision::Ban).is_err()
+        );
+
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &SystemTime::now(),
+        );
+        post.update_status(PostStatus::BannedDueToUserReporting);
+        canister_data.all_created_posts.insert(0, post);
+        canister_data.post_reports.insert(
+            0,
+            PostReports {
+                reports: vec![PostReport {
+                    reporter_principal_id: Principal::self_authenticating((0u64).to_ne_bytes()),
+                    reason: PostReportReason::Spam,
+                    reported_at: SystemTime::now(),
+                }],
+                reports_dismissed: 0,
+            },
+        );
+
+        assert!(
+            review_post_reports_impl(&mut canister_data, 0, PostReportReviewDecision::Dismiss)
+                .is_ok()
+        );
+        assert_eq!(
+            canister_data.all_created_posts.get(&0).unwrap().status,
+            PostStatus::ReadyToView
+        );
+        assert_eq!(
+            canister_data
+                .post_reports
+                .get(&0)
+                .unwrap()
+                .pending_report_count(),
+            0
+        );
+
+        assert!(
+            review_post_reports_impl(&mut canister_data, 0, PostReportReviewDecision::Ban).is_ok()
+        );
+        assert_eq!(
+            canister_data.all_created_posts.get(&0).unwrap().status,
+            PostStatus::BannedDueToUserReporting
+        );
+    }
+}
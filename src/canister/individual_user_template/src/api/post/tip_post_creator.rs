@@ -0,0 +1,314 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::TipPostArg,
+        error::TipPostError,
+        token::{PendingPostTip, TokenTransferIdempotencyKey},
+    },
+    common::{
+        types::utility_token::token_event::{PostTipEvent, TokenEvent},
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::bet_on_currently_viewing_hot_or_not_post::call_rejection_leaves_no_bet_behind,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+/// The tip leaves the balance before the post creator's canister is called
+/// and is kept as pending until that canister confirms or rejects it.
+/// If the call fails without saying whether the tip was credited,
+/// `reconcile_pending_post_tips` settles it later from what the post creator's
+/// canister has on record for the tip's idempotency key.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister can tip from their balance.
+#[update(guard = "is_not_frozen_for_migration")]
+async fn tip_post_creator(arg: TipPostArg) -> Result<(), TipPostError> {
+    let current_caller = ic_cdk::caller();
+    let my_canister_id = ic_cdk::id();
+
+    let (my_principal_id, idempotency_key) = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let my_principal_id =
+            validate_incoming_tip(canister_data, &current_caller, &my_canister_id, &arg)?;
+
+        Ok((
+            my_principal_id,
+            prepare_post_tip_impl(
+                canister_data,
+                &arg,
+                system_time::get_current_system_time_from_ic(),
+            ),
+        ))
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    let response = ic_cdk::call::<_, (Result<Principal, TipPostError>,)>(
+        arg.post_canister_id,
+        "receive_tip_from_tippers_canister",
+        (
+            arg.post_id,
+            arg.amount,
+            my_principal_id,
+            Some(idempotency_key),
+        ),
+    )
+    .await;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| match response {
+        Ok((Ok(post_creator_principal_id),)) => {
+            commit_pending_post_tip_impl(canister_data, idempotency_key, post_creator_principal_id);
+
+            Ok(())
+        }
+        Ok((Err(e),)) => {
+            abort_pending_post_tip_impl(canister_data, idempotency_key);
+
+            Err(e)
+        }
+        Err((rejection_code, _)) => {
+            // * the same rejections that leave no bet behind leave no tip behind
+            if call_rejection_leaves_no_bet_behind(&rejection_code) {
+                abort_pending_post_tip_impl(canister_data, idempotency_key);
+            } else if let Some(pending_post_tip) =
+                canister_data.pending_post_tips.get_mut(&idempotency_key)
+            {
+                pending_post_tip.awaiting_reconciliation = true;
+            }
+
+            Err(TipPostError::PostCreatorCanisterCallFailed)
+        }
+    })
+}
+
+/// Takes the tip out of the balance and records it as pending.
+/// Returns the idempotency key the tip is sent with.
+fn prepare_post_tip_impl(
+    canister_data: &mut CanisterData,
+    arg: &TipPostArg,
+    current_time: SystemTime,
+) -> TokenTransferIdempotencyKey {
+    let idempotency_key = canister_data.next_token_transfer_idempotency_key;
+    canister_data.next_token_transfer_idempotency_key += 1;
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_pre_transfer(arg.amount);
+
+    canister_data.pending_post_tips.insert(
+        idempotency_key,
+        PendingPostTip {
+            post_canister_id: arg.post_canister_id,
+            post_id: arg.post_id,
+            amount: arg.amount,
+            initiated_at: current_time,
+            awaiting_reconciliation: false,
+        },
+    );
+
+    idempotency_key
+}
+
+/// Records a pending tip the post creator's canister credited.
+/// Does nothing if the tip was already settled.
+pub fn commit_pending_post_tip_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+    post_creator_principal_id: Principal,
+) {
+    let Some(pending_post_tip) = canister_data.pending_post_tips.remove(&idempotency_key) else {
+        return;
+    };
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::PostTip {
+            amount: pending_post_tip.amount,
+            details: PostTipEvent::TipSent {
+                post_canister_id: pending_post_tip.post_canister_id,
+                post_id: pending_post_tip.post_id,
+                to_account: post_creator_principal_id,
+            },
+            timestamp: pending_post_tip.initiated_at,
+        });
+}
+
+/// Drops a pending tip the post creator's canister did not credit and refunds its amount.
+/// Does nothing if the tip was already settled.
+pub fn abort_pending_post_tip_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+) {
+    let Some(pending_post_tip) = canister_data.pending_post_tips.remove(&idempotency_key) else {
+        return;
+    };
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_for_failed_transfer(pending_post_tip.amount);
+}
+
+fn validate_incoming_tip(
+    canister_data: &CanisterData,
+    current_caller: &Principal,
+    my_canister_id: &Principal,
+    arg: &TipPostArg,
+) -> Result<Principal, TipPostError> {
+    if *current_caller == Principal::anonymous() {
+        return Err(TipPostError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(TipPostError::UserPrincipalNotSet)?;
+
+    if *current_caller != profile_owner {
+        return Err(TipPostError::Unauthorized);
+    }
+
+    if arg.amount == 0 {
+        return Err(TipPostError::InvalidAmount);
+    }
+
+    if arg.post_canister_id == *my_canister_id {
+        return Err(TipPostError::CannotTipOwnPost);
+    }
+
+    if canister_data.my_token_balance.get_utility_token_balance() < arg.amount {
+        return Err(TipPostError::InsufficientBalance);
+    }
+
+    Ok(profile_owner)
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_validate_incoming_tip() {
+        let mut canister_data = CanisterData::default();
+        let my_canister_id = get_mock_user_bob_canister_id();
+        let arg = TipPostArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            amount: 100,
+        };
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &Principal::anonymous(),
+            &my_canister_id,
+            &arg,
+        );
+        assert_eq!(result, Err(TipPostError::Unauthenticated));
+
+        canister_data.profile.principal_id = Some(get_mock_user_bob_principal_id());
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &get_mock_user_alice_principal_id(),
+            &my_canister_id,
+            &arg,
+        );
+        assert_eq!(result, Err(TipPostError::Unauthorized));
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &get_mock_user_bob_principal_id(),
+            &my_canister_id,
+            &TipPostArg {
+                post_canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                amount: 0,
+            },
+        );
+        assert_eq!(result, Err(TipPostError::InvalidAmount));
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &get_mock_user_bob_principal_id(),
+            &my_canister_id,
+            &TipPostArg {
+                post_canister_id: my_canister_id,
+                post_id: 0,
+                amount: 100,
+            },
+        );
+        assert_eq!(result, Err(TipPostError::CannotTipOwnPost));
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &get_mock_user_bob_principal_id(),
+            &my_canister_id,
+            &arg,
+        );
+        assert_eq!(result, Err(TipPostError::InsufficientBalance));
+
+        canister_data.my_token_balance.utility_token_balance = 1000;
+
+        let result = validate_incoming_tip(
+            &canister_data,
+            &get_mock_user_bob_principal_id(),
+            &my_canister_id,
+            &arg,
+        );
+        assert_eq!(result, Ok(get_mock_user_bob_principal_id()));
+    }
+
+    #[test]
+    fn test_pending_post_tip_is_committed_or_aborted_once() {
+        let mut canister_data = CanisterData::default();
+        canister_data.my_token_balance.utility_token_balance = 1000;
+        let arg = TipPostArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            amount: 100,
+        };
+
+        let first_key = prepare_post_tip_impl(&mut canister_data, &arg, SystemTime::now());
+        let second_key = prepare_post_tip_impl(&mut canister_data, &arg, SystemTime::now());
+        assert_ne!(first_key, second_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        commit_pending_post_tip_impl(
+            &mut canister_data,
+            first_key,
+            get_mock_user_alice_principal_id(),
+        );
+        commit_pending_post_tip_impl(
+            &mut canister_data,
+            first_key,
+            get_mock_user_alice_principal_id(),
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+        assert_eq!(
+            canister_data
+                .my_token_balance
+                .utility_token_transaction_history
+                .len(),
+            1
+        );
+
+        abort_pending_post_tip_impl(&mut canister_data, second_key);
+        abort_pending_post_tip_impl(&mut canister_data, second_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        assert!(canister_data.pending_post_tips.is_empty());
+    }
+}
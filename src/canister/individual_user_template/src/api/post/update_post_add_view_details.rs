@@ -3,12 +3,12 @@ use shared_utils::canister_specific::individual_user_template::types::post::Post
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
 use super::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold;
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn update_post_add_view_details(id: u64, details: PostViewDetailsFromFrontend) {
     update_last_canister_functionality_access_time();
 
@@ -0,0 +1,206 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::{query, update};
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::UpdatePostDetailsArg, error::UpdatePostDetailsError, post::PostRevision,
+    },
+    common::{
+        types::{app_primitive_type::PostId, top_posts::post_score_index_item::PostStatus},
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+use super::send_update_post_cache::send_update_post_cache;
+
+/// Replaces the description, hashtags and nsfw flag of a post.
+/// The previous details are kept and returned by `get_post_revisions`.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn update_post_details(arg: UpdatePostDetailsArg) -> Result<(), UpdatePostDetailsError> {
+    let current_caller = ic_cdk::caller();
+    let post_id = arg.post_id;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_post_details_impl(
+            canister_data,
+            current_caller,
+            arg,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    // * post_cache keeps its own copy of the nsfw flag
+    send_update_post_cache(&post_id);
+
+    Ok(())
+}
+
+/// Earlier versions of the post's details, oldest first
+#[query]
+fn get_post_revisions(post_id: PostId) -> Option<Vec<PostRevision>> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .all_created_posts
+            .get(&post_id)
+            .map(|post| post.revisions.clone())
+    })
+}
+
+fn update_post_details_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    arg: UpdatePostDetailsArg,
+    current_time: SystemTime,
+) -> Result<(), UpdatePostDetailsError> {
+    if current_caller == Principal::anonymous() {
+        return Err(UpdatePostDetailsError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(UpdatePostDetailsError::UserPrincipalNotSet)?;
+
+    if current_caller != profile_owner {
+        return Err(UpdatePostDetailsError::Unauthorized);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get_mut(&arg.post_id)
+        .ok_or(UpdatePostDetailsError::PostNotFound)?;
+
+    if matches!(
+        post.status,
+        PostStatus::BannedForExplicitness
+            | PostStatus::BannedDueToUserReporting
+            | PostStatus::Deleted
+    ) {
+        return Err(UpdatePostDetailsError::PostNotEditable);
+    }
+
+    if !post.update_details(arg.description, arg.hashtags, arg.is_nsfw, current_time) {
+        return Err(UpdatePostDetailsError::NothingToUpdate);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::post::{
+        Post, PostDetailsFromFrontend,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn update_post_details_arg(description: &str) -> UpdatePostDetailsArg {
+        UpdatePostDetailsArg {
+            post_id: 0,
+            description: description.into(),
+            hashtags: vec!["doggo".into()],
+            is_nsfw: false,
+        }
+    }
+
+    #[test]
+    fn test_update_post_details_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let current_time = SystemTime::now();
+
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                Principal::anonymous(),
+                update_post_details_arg("Doggos"),
+                current_time
+            ),
+            Err(UpdatePostDetailsError::Unauthenticated)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                update_post_details_arg("Doggos"),
+                current_time
+            ),
+            Err(UpdatePostDetailsError::Unauthorized)
+        );
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                alice,
+                update_post_details_arg("Doggos"),
+                current_time
+            ),
+            Err(UpdatePostDetailsError::PostNotFound)
+        );
+
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Dogos".into(),
+                    hashtags: vec!["doggo".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &current_time,
+            ),
+        );
+
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                alice,
+                update_post_details_arg("Dogos"),
+                current_time
+            ),
+            Err(UpdatePostDetailsError::NothingToUpdate)
+        );
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                alice,
+                update_post_details_arg("Doggos"),
+                current_time
+            ),
+            Ok(())
+        );
+
+        let post = canister_data.all_created_posts.get_mut(&0).unwrap();
+        assert_eq!(post.description, "Doggos");
+        assert_eq!(post.revisions.len(), 1);
+        assert_eq!(post.revisions[0].description, "Dogos");
+
+        post.update_status(PostStatus::Deleted);
+        assert_eq!(
+            update_post_details_impl(
+                &mut canister_data,
+                alice,
+                update_post_details_arg("Doggos and puppers"),
+                current_time
+            ),
+            Err(UpdatePostDetailsError::PostNotEditable)
+        );
+    }
+}
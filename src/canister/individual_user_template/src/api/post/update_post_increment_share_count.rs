@@ -2,12 +2,12 @@ use ic_cdk_macros::update;
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
 use super::update_scores_and_share_with_post_cache_if_difference_beyond_threshold::update_scores_and_share_with_post_cache_if_difference_beyond_threshold;
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn update_post_increment_share_count(id: u64) -> u64 {
     update_last_canister_functionality_access_time();
 
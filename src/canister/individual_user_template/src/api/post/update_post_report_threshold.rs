@@ -0,0 +1,43 @@
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// # Access Control
+/// Only the controller (user index) can update the threshold.
+/// Reports that are already pending are counted against the new threshold on the next report.
+#[update(guard = "is_caller_controller")]
+fn update_post_report_threshold(threshold: u64) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_post_report_threshold_impl(canister_data, threshold)
+    })
+}
+
+fn update_post_report_threshold_impl(
+    canister_data: &mut CanisterData,
+    threshold: u64,
+) -> Result<(), String> {
+    if threshold == 0 {
+        return Err("Post report threshold must be at least 1".into());
+    }
+
+    canister_data.configuration.post_report_threshold = Some(threshold);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_post_report_threshold_impl() {
+        let mut canister_data = CanisterData::default();
+
+        assert!(update_post_report_threshold_impl(&mut canister_data, 0).is_err());
+        assert_eq!(canister_data.configuration.post_report_threshold, None);
+
+        assert!(update_post_report_threshold_impl(&mut canister_data, 3).is_ok());
+        assert_eq!(canister_data.configuration.post_report_threshold, Some(3));
+    }
+}
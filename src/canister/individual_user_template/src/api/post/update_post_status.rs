@@ -5,12 +5,12 @@ use shared_utils::common::types::{
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
 use super::send_update_post_cache::send_update_post_cache;
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn update_post_status(id: u64, status: PostStatus) {
     let api_caller = ic_cdk::caller();
 
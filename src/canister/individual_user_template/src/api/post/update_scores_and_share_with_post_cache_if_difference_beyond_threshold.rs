@@ -6,7 +6,7 @@ use shared_utils::{
     common::{
         types::{
             known_principal::KnownPrincipalType,
-            top_posts::post_score_index_item::PostScoreIndexItemV1,
+            top_posts::post_score_index_item::{PostScoreIndexItemV1, PostStatus},
         },
         utils::system_time,
     },
@@ -18,10 +18,10 @@ use shared_utils::{
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    data_model::CanisterData, CANISTER_DATA,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn check_and_update_scores_and_share_with_post_cache_if_difference_beyond_threshold(
     post_ids: Vec<u64>,
 ) {
@@ -96,6 +96,11 @@ fn update_home_feed_and_hot_or_not_feed_score_and_get_post_index_item_to_send(
         return (None, None);
     }
 
+    // * deleted posts must not be re-added to post_cache
+    if all_posts.get(&post_id).unwrap().status == PostStatus::Deleted {
+        return (None, None);
+    }
+
     let mut home_feed_index_score_item: Option<PostScoreIndexItemV1> = None;
     let mut hot_or_not_index_score_item: Option<PostScoreIndexItemV1> = None;
 
@@ -226,5 +231,21 @@ mod test {
             get_mock_user_alice_canister_id(),
         );
         assert_eq!(response, (None, None));
+
+        canister_data
+            .all_created_posts
+            .get_mut(&0)
+            .unwrap()
+            .update_status(PostStatus::Deleted);
+
+        let response = update_home_feed_and_hot_or_not_feed_score_and_get_post_index_item_to_send(
+            &mut canister_data,
+            0,
+            post_creation_time
+                .checked_add(Duration::from_secs(60 * 60 * 24))
+                .unwrap(),
+            get_mock_user_alice_canister_id(),
+        );
+        assert_eq!(response, (None, None));
     }
 }
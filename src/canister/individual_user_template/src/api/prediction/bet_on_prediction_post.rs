@@ -0,0 +1,395 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::PlacePredictionBetArg,
+        error::BetOnPredictionError,
+        hot_or_not::{BetIdempotencyKey, BetOutcomeForBetMaker},
+        prediction::{PendingPredictionBet, PlacedPredictionBetDetail},
+    },
+    common::{
+        types::{
+            app_primitive_type::PostId,
+            utility_token::token_event::{StakeEvent, TokenEvent},
+        },
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::bet_on_currently_viewing_hot_or_not_post::call_rejection_leaves_no_bet_behind,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    CANISTER_DATA,
+};
+
+/// Follows the same steps as `bet_on_currently_viewing_post`. The bet amount leaves
+/// the balance before the post creator's canister is called and the bet is kept as pending
+/// until that canister confirms or rejects it. If the call fails without saying whether
+/// the bet was taken, `reconcile_pending_bets` settles it later.
+///
+/// # Access Control
+/// Only the profile owner
+#[update(guard = "is_not_frozen_for_migration")]
+async fn bet_on_prediction_post(
+    place_prediction_bet_arg: PlacePredictionBetArg,
+) -> Result<(), BetOnPredictionError> {
+    let bet_maker_principal_id = ic_cdk::caller();
+    let my_canister_id = ic_cdk::id();
+    let current_time = system_time::get_current_system_time_from_ic();
+
+    let idempotency_key = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        validate_prediction_bet(
+            canister_data,
+            &bet_maker_principal_id,
+            &my_canister_id,
+            &place_prediction_bet_arg,
+        )?;
+
+        Ok(prepare_prediction_bet_impl(
+            canister_data,
+            &place_prediction_bet_arg,
+            current_time,
+        ))
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    let pending_prediction_bet_key = (
+        place_prediction_bet_arg.post_canister_id,
+        place_prediction_bet_arg.post_id,
+    );
+
+    let response = ic_cdk::call::<_, (Result<(), BetOnPredictionError>,)>(
+        place_prediction_bet_arg.post_canister_id,
+        "receive_prediction_bet_from_bet_makers_canister",
+        (
+            place_prediction_bet_arg.clone(),
+            bet_maker_principal_id,
+            Some(idempotency_key),
+        ),
+    )
+    .await;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| match response {
+        Ok((Ok(()),)) => {
+            commit_pending_prediction_bet_impl(canister_data, &pending_prediction_bet_key);
+
+            Ok(())
+        }
+        Ok((Err(e),)) => {
+            abort_pending_prediction_bet_impl(canister_data, &pending_prediction_bet_key);
+
+            Err(e)
+        }
+        Err((rejection_code, _)) => {
+            if call_rejection_leaves_no_bet_behind(&rejection_code) {
+                abort_pending_prediction_bet_impl(canister_data, &pending_prediction_bet_key);
+            } else {
+                mark_pending_prediction_bet_for_reconciliation_impl(
+                    canister_data,
+                    &pending_prediction_bet_key,
+                );
+            }
+
+            Err(BetOnPredictionError::PostCreatorCanisterCallFailed)
+        }
+    })
+}
+
+fn validate_prediction_bet(
+    canister_data: &CanisterData,
+    bet_maker_principal_id: &Principal,
+    my_canister_id: &CanisterId,
+    place_prediction_bet_arg: &PlacePredictionBetArg,
+) -> Result<(), BetOnPredictionError> {
+    if *bet_maker_principal_id == Principal::anonymous() {
+        return Err(BetOnPredictionError::UserNotLoggedIn);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(BetOnPredictionError::UserPrincipalNotSet)?;
+
+    if *bet_maker_principal_id != profile_owner {
+        return Err(BetOnPredictionError::Unauthorized);
+    }
+
+    if place_prediction_bet_arg.post_canister_id == *my_canister_id {
+        return Err(BetOnPredictionError::CreatorCannotBetOnOwnPrediction);
+    }
+
+    if canister_data.my_token_balance.get_utility_token_balance()
+        < place_prediction_bet_arg.bet_amount
+    {
+        return Err(BetOnPredictionError::InsufficientBalance);
+    }
+
+    let placed_prediction_bet_key = (
+        place_prediction_bet_arg.post_canister_id,
+        place_prediction_bet_arg.post_id,
+    );
+    if canister_data
+        .all_prediction_bets_placed
+        .contains_key(&placed_prediction_bet_key)
+        || canister_data
+            .pending_prediction_bets
+            .contains_key(&placed_prediction_bet_key)
+    {
+        return Err(BetOnPredictionError::UserAlreadyParticipatedInThisPrediction);
+    }
+
+    Ok(())
+}
+
+/// Takes the bet amount out of the balance and records the bet as pending.
+/// Returns the idempotency key the bet is sent with.
+fn prepare_prediction_bet_impl(
+    canister_data: &mut CanisterData,
+    place_prediction_bet_arg: &PlacePredictionBetArg,
+    current_time: SystemTime,
+) -> BetIdempotencyKey {
+    let idempotency_key = canister_data.next_bet_idempotency_key;
+    canister_data.next_bet_idempotency_key += 1;
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_pre_bet(place_prediction_bet_arg.bet_amount);
+
+    canister_data.pending_prediction_bets.insert(
+        (
+            place_prediction_bet_arg.post_canister_id,
+            place_prediction_bet_arg.post_id,
+        ),
+        PendingPredictionBet {
+            post_canister_id: place_prediction_bet_arg.post_canister_id,
+            post_id: place_prediction_bet_arg.post_id,
+            outcome_id: place_prediction_bet_arg.outcome_id,
+            amount: place_prediction_bet_arg.bet_amount,
+            idempotency_key,
+            placed_at: current_time,
+            awaiting_reconciliation: false,
+        },
+    );
+
+    idempotency_key
+}
+
+/// Moves a pending prediction bet the post creator's canister accepted into the placed bets.
+/// Does nothing if the bet was already settled.
+pub fn commit_pending_prediction_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_prediction_bet_key: &(CanisterId, PostId),
+) {
+    let Some(pending_prediction_bet) = canister_data
+        .pending_prediction_bets
+        .remove(pending_prediction_bet_key)
+    else {
+        return;
+    };
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::Stake {
+            amount: pending_prediction_bet.amount,
+            details: StakeEvent::BetOnPredictionPost {
+                post_canister_id: pending_prediction_bet.post_canister_id,
+                post_id: pending_prediction_bet.post_id,
+                bet_amount: pending_prediction_bet.amount,
+                outcome_id: pending_prediction_bet.outcome_id,
+            },
+            timestamp: pending_prediction_bet.placed_at,
+        });
+
+    canister_data.all_prediction_bets_placed.insert(
+        *pending_prediction_bet_key,
+        PlacedPredictionBetDetail {
+            canister_id: pending_prediction_bet.post_canister_id,
+            post_id: pending_prediction_bet.post_id,
+            outcome_id: pending_prediction_bet.outcome_id,
+            amount_bet: pending_prediction_bet.amount,
+            bet_placed_at: pending_prediction_bet.placed_at,
+            outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+        },
+    );
+}
+
+/// Drops a pending prediction bet the post creator's canister does not hold
+/// and returns its amount. Does nothing if the bet was already settled.
+pub fn abort_pending_prediction_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_prediction_bet_key: &(CanisterId, PostId),
+) {
+    if let Some(pending_prediction_bet) = canister_data
+        .pending_prediction_bets
+        .remove(pending_prediction_bet_key)
+    {
+        canister_data
+            .my_token_balance
+            .adjust_balance_for_failed_bet_placement(pending_prediction_bet.amount);
+    }
+}
+
+/// Hands a pending prediction bet whose outcome the call did not tell
+/// over to `reconcile_pending_bets`
+fn mark_pending_prediction_bet_for_reconciliation_impl(
+    canister_data: &mut CanisterData,
+    pending_prediction_bet_key: &(CanisterId, PostId),
+) {
+    if let Some(pending_prediction_bet) = canister_data
+        .pending_prediction_bets
+        .get_mut(pending_prediction_bet_key)
+    {
+        pending_prediction_bet.awaiting_reconciliation = true;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn place_prediction_bet_arg() -> PlacePredictionBetArg {
+        PlacePredictionBetArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            outcome_id: 1,
+            bet_amount: 100,
+        }
+    }
+
+    #[test]
+    fn test_validate_prediction_bet() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+        let bob_canister_id = get_mock_user_bob_canister_id();
+        let arg = place_prediction_bet_arg();
+
+        assert_eq!(
+            validate_prediction_bet(
+                &canister_data,
+                &Principal::anonymous(),
+                &bob_canister_id,
+                &arg
+            ),
+            Err(BetOnPredictionError::UserNotLoggedIn)
+        );
+        assert_eq!(
+            validate_prediction_bet(&canister_data, &bob, &bob_canister_id, &arg),
+            Err(BetOnPredictionError::UserPrincipalNotSet)
+        );
+
+        canister_data.profile.principal_id = Some(bob);
+        assert_eq!(
+            validate_prediction_bet(
+                &canister_data,
+                &get_mock_user_alice_principal_id(),
+                &bob_canister_id,
+                &arg
+            ),
+            Err(BetOnPredictionError::Unauthorized)
+        );
+        assert_eq!(
+            validate_prediction_bet(&canister_data, &bob, &bob_canister_id, &arg),
+            Err(BetOnPredictionError::InsufficientBalance)
+        );
+
+        canister_data.my_token_balance.utility_token_balance = 1000;
+        assert_eq!(
+            validate_prediction_bet(&canister_data, &bob, &bob_canister_id, &arg),
+            Ok(())
+        );
+
+        // * a creator can not bet on a prediction of their own post
+        assert_eq!(
+            validate_prediction_bet(
+                &canister_data,
+                &bob,
+                &get_mock_user_alice_canister_id(),
+                &arg
+            ),
+            Err(BetOnPredictionError::CreatorCannotBetOnOwnPrediction)
+        );
+
+        // * a pending bet counts as participation until it is settled
+        prepare_prediction_bet_impl(&mut canister_data, &arg, SystemTime::now());
+        assert_eq!(
+            validate_prediction_bet(&canister_data, &bob, &bob_canister_id, &arg),
+            Err(BetOnPredictionError::UserAlreadyParticipatedInThisPrediction)
+        );
+
+        commit_pending_prediction_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 0),
+        );
+        assert_eq!(
+            validate_prediction_bet(&canister_data, &bob, &bob_canister_id, &arg),
+            Err(BetOnPredictionError::UserAlreadyParticipatedInThisPrediction)
+        );
+    }
+
+    #[test]
+    fn test_prepare_commit_and_abort_pending_prediction_bet_impl() {
+        let mut canister_data = CanisterData::default();
+        canister_data.my_token_balance.utility_token_balance = 1000;
+        let arg = place_prediction_bet_arg();
+        let pending_prediction_bet_key = (get_mock_user_alice_canister_id(), 0);
+
+        let idempotency_key =
+            prepare_prediction_bet_impl(&mut canister_data, &arg, SystemTime::now());
+        assert_eq!(idempotency_key, 0);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        assert!(
+            !canister_data.pending_prediction_bets[&pending_prediction_bet_key]
+                .awaiting_reconciliation
+        );
+
+        mark_pending_prediction_bet_for_reconciliation_impl(
+            &mut canister_data,
+            &pending_prediction_bet_key,
+        );
+        assert!(
+            canister_data.pending_prediction_bets[&pending_prediction_bet_key]
+                .awaiting_reconciliation
+        );
+
+        abort_pending_prediction_bet_impl(&mut canister_data, &pending_prediction_bet_key);
+        assert!(canister_data.pending_prediction_bets.is_empty());
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1000);
+
+        // * the idempotency keys are shared with hot or not bets
+        canister_data.next_bet_idempotency_key = 5;
+        let idempotency_key =
+            prepare_prediction_bet_impl(&mut canister_data, &arg, SystemTime::now());
+        assert_eq!(idempotency_key, 5);
+
+        commit_pending_prediction_bet_impl(&mut canister_data, &pending_prediction_bet_key);
+        assert!(canister_data.pending_prediction_bets.is_empty());
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        let placed_prediction_bet_detail = canister_data
+            .all_prediction_bets_placed
+            .get(&pending_prediction_bet_key)
+            .unwrap();
+        assert_eq!(placed_prediction_bet_detail.outcome_id, 1);
+        assert_eq!(placed_prediction_bet_detail.amount_bet, 100);
+        assert_eq!(
+            placed_prediction_bet_detail.outcome_received,
+            BetOutcomeForBetMaker::AwaitingResult
+        );
+
+        // * settling twice has no effect
+        abort_pending_prediction_bet_impl(&mut canister_data, &pending_prediction_bet_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+    }
+}
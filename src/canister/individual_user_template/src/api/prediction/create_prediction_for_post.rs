@@ -0,0 +1,234 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::CreatePredictionArg, error::CreatePredictionError, prediction::PredictionDetails,
+    },
+    common::{types::top_posts::post_score_index_item::PostStatus, utils::system_time},
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+use super::settle_prediction::enqueue_prediction_settlement_job;
+
+/// Attaches a poll or prediction with 2 to 6 named outcomes to a post of this profile.
+/// The post's hot or not game is left as it is.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn create_prediction_for_post(arg: CreatePredictionArg) -> Result<(), CreatePredictionError> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        create_prediction_for_post_impl(
+            canister_data,
+            ic_cdk::caller(),
+            arg,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    Ok(())
+}
+
+fn create_prediction_for_post_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    arg: CreatePredictionArg,
+    current_time: SystemTime,
+) -> Result<(), CreatePredictionError> {
+    if current_caller == Principal::anonymous() {
+        return Err(CreatePredictionError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(CreatePredictionError::UserPrincipalNotSet)?;
+
+    if current_caller != profile_owner {
+        return Err(CreatePredictionError::Unauthorized);
+    }
+
+    let post = canister_data
+        .all_created_posts
+        .get(&arg.post_id)
+        .ok_or(CreatePredictionError::PostNotFound)?;
+
+    if matches!(
+        post.status,
+        PostStatus::BannedForExplicitness
+            | PostStatus::BannedDueToUserReporting
+            | PostStatus::Deleted
+    ) {
+        return Err(CreatePredictionError::PostNotAvailableForPredictions);
+    }
+
+    if canister_data
+        .prediction_details_map
+        .contains_key(&arg.post_id)
+    {
+        return Err(CreatePredictionError::PredictionAlreadyExists);
+    }
+
+    let prediction_details = PredictionDetails::new(
+        arg.outcomes,
+        arg.resolution_mode,
+        arg.duration_in_seconds,
+        post.hot_or_not_game_config.creator_commission_percentage,
+        current_time,
+    )?;
+
+    canister_data
+        .prediction_details_map
+        .insert(arg.post_id, prediction_details);
+    enqueue_prediction_settlement_job(canister_data, arg.post_id);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::{
+        post::{Post, PostDetailsFromFrontend},
+        prediction::{PredictionResolutionMode, PredictionSettlementJob, PredictionStatus},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn prediction_arg(outcomes: &[&str]) -> CreatePredictionArg {
+        CreatePredictionArg {
+            post_id: 0,
+            outcomes: outcomes.iter().map(|outcome| outcome.to_string()).collect(),
+            resolution_mode: PredictionResolutionMode::DecidedByVoteShare,
+            duration_in_seconds: 60 * 60,
+        }
+    }
+
+    #[test]
+    fn test_create_prediction_for_post_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                Principal::anonymous(),
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::Unauthenticated)
+        );
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::UserPrincipalNotSet)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::Unauthorized)
+        );
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::PostNotFound)
+        );
+
+        canister_data.all_created_posts.insert(
+            0,
+            Post::new(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Who wins the final?".into(),
+                    hashtags: vec![],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &now,
+            ),
+        );
+
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Yes"]),
+                now
+            ),
+            Err(CreatePredictionError::InvalidNumberOfOutcomes)
+        );
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Home", "Away", "Draw"]),
+                now
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::PredictionAlreadyExists)
+        );
+
+        let prediction_details = canister_data.prediction_details_map.get(&0).unwrap();
+        assert_eq!(prediction_details.outcomes.len(), 3);
+        assert_eq!(prediction_details.status, PredictionStatus::Open);
+        assert_eq!(prediction_details.creator_commission_percentage, 10);
+        // * a prediction decided by vote share is settled when it closes
+        assert_eq!(
+            canister_data
+                .prediction_settlement_queue
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![PredictionSettlementJob {
+                due_at: prediction_details.closes_at,
+                post_id: 0,
+            }]
+        );
+
+        canister_data.all_created_posts.get_mut(&0).unwrap().status = PostStatus::Deleted;
+        canister_data.prediction_details_map.remove(&0);
+        assert_eq!(
+            create_prediction_for_post_impl(
+                &mut canister_data,
+                alice,
+                prediction_arg(&["Yes", "No"]),
+                now
+            ),
+            Err(CreatePredictionError::PostNotAvailableForPredictions)
+        );
+    }
+}
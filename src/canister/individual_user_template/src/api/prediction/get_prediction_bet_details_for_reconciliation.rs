@@ -0,0 +1,147 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        hot_or_not::StablePrincipal,
+        prediction::{GlobalPredictionBetId, PredictionBetReconciliationDetails},
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Returns what this canister has on record for the bet a user placed on the prediction
+/// of one of its posts. Bet makers' canisters use it to settle bets they never got a reply for.
+///
+/// # Access Control
+/// Any caller, bets on predictions are public
+#[query]
+fn get_prediction_bet_details_for_reconciliation(
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+) -> Option<PredictionBetReconciliationDetails> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_prediction_bet_details_for_reconciliation_impl(
+            canister_data,
+            post_id,
+            bet_maker_principal_id,
+        )
+    })
+}
+
+fn get_prediction_bet_details_for_reconciliation_impl(
+    canister_data: &CanisterData,
+    post_id: PostId,
+    bet_maker_principal_id: Principal,
+) -> Option<PredictionBetReconciliationDetails> {
+    let prediction_details = canister_data.prediction_details_map.get(&post_id)?;
+    let prediction_bet_details =
+        canister_data
+            .prediction_bet_details_map
+            .get(&GlobalPredictionBetId(
+                post_id,
+                StablePrincipal(bet_maker_principal_id),
+            ))?;
+
+    Some(PredictionBetReconciliationDetails {
+        outcome_id: prediction_bet_details.outcome_id,
+        amount: prediction_bet_details.amount,
+        idempotency_key: prediction_bet_details.idempotency_key,
+        outcome: prediction_bet_details.get_outcome_for_bet_maker(&prediction_details.status),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{BetOutcomeForBetMaker, BetPayout},
+        prediction::{
+            PredictionBetDetails, PredictionDetails, PredictionResolutionMode, PredictionStatus,
+        },
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_get_prediction_bet_details_for_reconciliation_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+        let now = SystemTime::now();
+
+        assert_eq!(
+            get_prediction_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            None
+        );
+
+        let mut prediction_details = PredictionDetails::new(
+            vec!["Yes".into(), "No".into()],
+            PredictionResolutionMode::DecidedByCreator,
+            60,
+            10,
+            now,
+        )
+        .unwrap();
+        prediction_details.record_bet(1, 100);
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details.clone());
+        let global_prediction_bet_id = GlobalPredictionBetId(0, StablePrincipal(bob));
+        canister_data.prediction_bet_details_map.insert(
+            global_prediction_bet_id.clone(),
+            PredictionBetDetails {
+                amount: 100,
+                outcome_id: 1,
+                payout: BetPayout::NotCalculatedYet,
+                bet_maker_canister_id: get_mock_user_bob_canister_id(),
+                placed_at: now,
+                bet_maker_informed_status: None,
+                idempotency_key: Some(7),
+            },
+        );
+
+        let expected = PredictionBetReconciliationDetails {
+            outcome_id: 1,
+            amount: 100,
+            idempotency_key: Some(7),
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+        };
+        assert_eq!(
+            get_prediction_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            get_prediction_bet_details_for_reconciliation_impl(
+                &canister_data,
+                0,
+                get_mock_user_alice_principal_id()
+            ),
+            None
+        );
+
+        let mut prediction_bet_details = canister_data
+            .prediction_bet_details_map
+            .get(&global_prediction_bet_id)
+            .unwrap();
+        prediction_bet_details.payout = BetPayout::Calculated(100);
+        canister_data
+            .prediction_bet_details_map
+            .insert(global_prediction_bet_id, prediction_bet_details);
+        prediction_details.status = PredictionStatus::Resolved(1);
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details);
+        assert_eq!(
+            get_prediction_bet_details_for_reconciliation_impl(&canister_data, 0, bob),
+            Some(PredictionBetReconciliationDetails {
+                outcome: BetOutcomeForBetMaker::Won(100),
+                ..expected
+            })
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::prediction::PlacedPredictionBetDetail,
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::CANISTER_DATA;
+
+#[query]
+fn get_prediction_bet_placed_by_this_profile(
+    canister_id: CanisterId,
+    post_id: PostId,
+) -> Option<PlacedPredictionBetDetail> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .all_prediction_bets_placed
+            .get(&(canister_id, post_id))
+            .cloned()
+    })
+}
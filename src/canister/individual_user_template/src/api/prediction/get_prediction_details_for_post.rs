@@ -0,0 +1,16 @@
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::prediction::PredictionDetails,
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::CANISTER_DATA;
+
+/// Outcomes, pots and status of the prediction attached to a post of this profile
+///
+/// # Access Control
+/// Any caller, predictions on posts are public
+#[query]
+fn get_prediction_details_for_post(post_id: PostId) -> Option<PredictionDetails> {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.prediction_details_map.get(&post_id))
+}
@@ -0,0 +1,10 @@
+pub mod bet_on_prediction_post;
+pub mod create_prediction_for_post;
+pub mod get_prediction_bet_details_for_reconciliation;
+pub mod get_prediction_bet_placed_by_this_profile;
+pub mod get_prediction_details_for_post;
+pub mod receive_prediction_bet_from_bet_makers_canister;
+pub mod receive_prediction_winnings_when_distributed;
+pub mod reconcile_pending_prediction_bets;
+pub mod resolve_prediction;
+pub mod settle_prediction;
@@ -0,0 +1,272 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::PlacePredictionBetArg,
+        error::BetOnPredictionError,
+        hot_or_not::{BetIdempotencyKey, BetPayout, StablePrincipal},
+        prediction::{GlobalPredictionBetId, PredictionBetDetails},
+    },
+    common::utils::system_time,
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+/// Records a bet on the prediction of a post of this profile.
+/// Called by the bet maker's canister, which already took the amount out of its balance.
+/// A bet retried with the idempotency key of the bet already placed is accepted again
+/// instead of being placed twice.
+#[update(guard = "is_not_frozen_for_migration")]
+fn receive_prediction_bet_from_bet_makers_canister(
+    place_prediction_bet_arg: PlacePredictionBetArg,
+    bet_maker_principal_id: Principal,
+    idempotency_key: Option<BetIdempotencyKey>,
+) -> Result<(), BetOnPredictionError> {
+    let bet_maker_canister_id = ic_cdk::caller();
+    let my_canister_id = ic_cdk::id();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        receive_prediction_bet_from_bet_makers_canister_impl(
+            canister_data,
+            &bet_maker_principal_id,
+            &bet_maker_canister_id,
+            &my_canister_id,
+            &place_prediction_bet_arg,
+            idempotency_key,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    Ok(())
+}
+
+fn receive_prediction_bet_from_bet_makers_canister_impl(
+    canister_data: &mut CanisterData,
+    bet_maker_principal_id: &Principal,
+    bet_maker_canister_id: &CanisterId,
+    my_canister_id: &CanisterId,
+    place_prediction_bet_arg: &PlacePredictionBetArg,
+    idempotency_key: Option<BetIdempotencyKey>,
+    current_time: SystemTime,
+) -> Result<(), BetOnPredictionError> {
+    if *bet_maker_principal_id == Principal::anonymous() {
+        return Err(BetOnPredictionError::UserNotLoggedIn);
+    }
+
+    if Some(*bet_maker_principal_id) == canister_data.profile.principal_id
+        || bet_maker_canister_id == my_canister_id
+    {
+        return Err(BetOnPredictionError::CreatorCannotBetOnOwnPrediction);
+    }
+
+    if canister_data
+        .blocked_principals
+        .contains(bet_maker_principal_id)
+    {
+        return Err(BetOnPredictionError::BlockedByPostCreator);
+    }
+
+    let post_id = place_prediction_bet_arg.post_id;
+    let outcome_id = place_prediction_bet_arg.outcome_id;
+    let global_prediction_bet_id =
+        GlobalPredictionBetId(post_id, StablePrincipal(*bet_maker_principal_id));
+
+    if let Some(prediction_bet_details) = canister_data
+        .prediction_bet_details_map
+        .get(&global_prediction_bet_id)
+    {
+        if idempotency_key.is_some() && prediction_bet_details.idempotency_key == idempotency_key {
+            return Ok(());
+        }
+
+        return Err(BetOnPredictionError::UserAlreadyParticipatedInThisPrediction);
+    }
+
+    let mut prediction_details = canister_data
+        .prediction_details_map
+        .get(&post_id)
+        .ok_or(BetOnPredictionError::PredictionNotFound)?;
+
+    if !prediction_details.is_open_for_bets(&current_time) {
+        return Err(BetOnPredictionError::BettingClosed);
+    }
+
+    if !prediction_details.is_valid_outcome(outcome_id) {
+        return Err(BetOnPredictionError::InvalidOutcome);
+    }
+
+    if prediction_details.is_full() {
+        return Err(BetOnPredictionError::PredictionFull);
+    }
+
+    prediction_details.record_bet(outcome_id, place_prediction_bet_arg.bet_amount);
+    canister_data
+        .prediction_details_map
+        .insert(post_id, prediction_details);
+    canister_data.prediction_bet_details_map.insert(
+        global_prediction_bet_id,
+        PredictionBetDetails {
+            amount: place_prediction_bet_arg.bet_amount,
+            outcome_id,
+            payout: BetPayout::NotCalculatedYet,
+            bet_maker_canister_id: *bet_maker_canister_id,
+            placed_at: current_time,
+            bet_maker_informed_status: None,
+            idempotency_key,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::individual_user_template::types::prediction::{
+        PredictionDetails, PredictionResolutionMode, MAXIMUM_NUMBER_OF_BETS_PER_PREDICTION,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+        get_mock_user_charlie_principal_id,
+    };
+
+    use super::*;
+
+    fn bet_arg(outcome_id: u8) -> PlacePredictionBetArg {
+        PlacePredictionBetArg {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            outcome_id,
+            bet_amount: 100,
+        }
+    }
+
+    #[test]
+    fn test_receive_prediction_bet_from_bet_makers_canister_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+        let bob_canister_id = get_mock_user_bob_canister_id();
+        let now = SystemTime::now();
+        let receive = |canister_data: &mut CanisterData, outcome_id, current_time| {
+            receive_prediction_bet_from_bet_makers_canister_impl(
+                canister_data,
+                &bob,
+                &bob_canister_id,
+                &get_mock_user_alice_canister_id(),
+                &bet_arg(outcome_id),
+                Some(7),
+                current_time,
+            )
+        };
+
+        assert_eq!(
+            receive(&mut canister_data, 0, now),
+            Err(BetOnPredictionError::PredictionNotFound)
+        );
+
+        canister_data.prediction_details_map.insert(
+            0,
+            PredictionDetails::new(
+                vec!["Home".into(), "Away".into(), "Draw".into()],
+                PredictionResolutionMode::DecidedByVoteShare,
+                60,
+                10,
+                now,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            receive(&mut canister_data, 3, now),
+            Err(BetOnPredictionError::InvalidOutcome)
+        );
+        assert_eq!(
+            receive(&mut canister_data, 2, now + Duration::from_secs(60)),
+            Err(BetOnPredictionError::BettingClosed)
+        );
+
+        canister_data.blocked_principals.insert(bob);
+        assert_eq!(
+            receive(&mut canister_data, 2, now),
+            Err(BetOnPredictionError::BlockedByPostCreator)
+        );
+        canister_data.blocked_principals.remove(&bob);
+
+        // * the creator can not bet on their own prediction, from any canister
+        canister_data.profile.principal_id = Some(bob);
+        assert_eq!(
+            receive(&mut canister_data, 2, now),
+            Err(BetOnPredictionError::CreatorCannotBetOnOwnPrediction)
+        );
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+        assert_eq!(
+            receive_prediction_bet_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &get_mock_user_alice_canister_id(),
+                &get_mock_user_alice_canister_id(),
+                &bet_arg(2),
+                Some(7),
+                now,
+            ),
+            Err(BetOnPredictionError::CreatorCannotBetOnOwnPrediction)
+        );
+
+        assert_eq!(receive(&mut canister_data, 2, now), Ok(()));
+        // * a retry of the same bet is accepted without placing it again
+        assert_eq!(receive(&mut canister_data, 2, now), Ok(()));
+        assert_eq!(
+            receive_prediction_bet_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &bob,
+                &bob_canister_id,
+                &get_mock_user_alice_canister_id(),
+                &bet_arg(1),
+                Some(8),
+                now,
+            ),
+            Err(BetOnPredictionError::UserAlreadyParticipatedInThisPrediction)
+        );
+
+        let prediction_details = canister_data.prediction_details_map.get(&0).unwrap();
+        assert_eq!(prediction_details.total_amount_per_outcome, vec![0, 0, 100]);
+        assert_eq!(prediction_details.total_bets_per_outcome, vec![0, 0, 1]);
+        let prediction_bet_details = canister_data
+            .prediction_bet_details_map
+            .get(&GlobalPredictionBetId(0, StablePrincipal(bob)))
+            .unwrap();
+        assert_eq!(prediction_bet_details.outcome_id, 2);
+        assert_eq!(
+            prediction_bet_details.bet_maker_canister_id,
+            bob_canister_id
+        );
+
+        let mut prediction_details = canister_data.prediction_details_map.get(&0).unwrap();
+        prediction_details.total_bets_per_outcome[0] = MAXIMUM_NUMBER_OF_BETS_PER_PREDICTION - 1;
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details);
+        assert_eq!(
+            receive_prediction_bet_from_bet_makers_canister_impl(
+                &mut canister_data,
+                &get_mock_user_charlie_principal_id(),
+                &bob_canister_id,
+                &get_mock_user_alice_canister_id(),
+                &bet_arg(1),
+                None,
+                now,
+            ),
+            Err(BetOnPredictionError::PredictionFull)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::SystemTime;
+
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::BetOutcomeForBetMaker,
+    common::{
+        types::{
+            app_primitive_type::PostId,
+            utility_token::token_event::{PredictionOutcomePayoutEvent, TokenEvent},
+        },
+        utils::system_time,
+    },
+};
+
+use crate::{
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration,
+    util::canister_migration::move_bets_placed_with_former_canisters, CANISTER_DATA,
+};
+
+/// Called by the post creator's canister once the prediction the bet was placed on is settled.
+/// If the post creator's profile was migrated since, the bet is looked up under
+/// the canister it was placed with.
+#[update(guard = "is_not_frozen_for_migration")]
+async fn receive_prediction_winnings_when_distributed(
+    post_id: PostId,
+    outcome: BetOutcomeForBetMaker,
+) {
+    let post_creator_canister_id = ic_cdk::caller();
+
+    let has_bet_on_record = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .all_prediction_bets_placed
+            .contains_key(&(post_creator_canister_id, post_id))
+    });
+    if !has_bet_on_record {
+        move_bets_placed_with_former_canisters(post_creator_canister_id).await;
+    }
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        record_prediction_outcome_impl(
+            canister_data,
+            post_creator_canister_id,
+            post_id,
+            outcome,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+/// Pays out the outcome of a placed prediction bet, once.
+/// Outcomes for bets this canister has no record of are ignored.
+pub fn record_prediction_outcome_impl(
+    canister_data: &mut CanisterData,
+    post_creator_canister_id: CanisterId,
+    post_id: PostId,
+    outcome: BetOutcomeForBetMaker,
+    current_time: SystemTime,
+) {
+    let Some(placed_prediction_bet_detail) = canister_data
+        .all_prediction_bets_placed
+        .get_mut(&(post_creator_canister_id, post_id))
+    else {
+        return;
+    };
+
+    if placed_prediction_bet_detail.outcome_received != BetOutcomeForBetMaker::AwaitingResult {
+        return;
+    }
+
+    placed_prediction_bet_detail.outcome_received = outcome.clone();
+    let amount_bet = placed_prediction_bet_detail.amount_bet;
+
+    let winnings_amount = match outcome {
+        BetOutcomeForBetMaker::Won(amount) | BetOutcomeForBetMaker::Draw(amount) => amount,
+        _ => 0,
+    };
+
+    canister_data
+        .my_token_balance
+        .handle_token_event(TokenEvent::PredictionOutcomePayout {
+            amount: winnings_amount,
+            details: PredictionOutcomePayoutEvent::WinningsEarnedFromPrediction {
+                post_canister_id: post_creator_canister_id,
+                post_id,
+                event_outcome: outcome,
+                amount_bet,
+                winnings_amount,
+            },
+            timestamp: current_time,
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::prediction::PlacedPredictionBetDetail;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_record_prediction_outcome_impl() {
+        let mut canister_data = CanisterData::default();
+        let now = SystemTime::now();
+        canister_data.all_prediction_bets_placed.insert(
+            (get_mock_user_alice_canister_id(), 0),
+            PlacedPredictionBetDetail {
+                canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                outcome_id: 1,
+                amount_bet: 100,
+                bet_placed_at: now,
+                outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+            },
+        );
+
+        // * only the canister the bet was placed with can settle it
+        record_prediction_outcome_impl(
+            &mut canister_data,
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(235),
+            now,
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 0);
+
+        record_prediction_outcome_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(235),
+            now,
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 235);
+        assert_eq!(canister_data.my_token_balance.lifetime_earnings, 135);
+        assert_eq!(
+            canister_data
+                .all_prediction_bets_placed
+                .get(&(get_mock_user_alice_canister_id(), 0))
+                .unwrap()
+                .outcome_received,
+            BetOutcomeForBetMaker::Won(235)
+        );
+
+        record_prediction_outcome_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(235),
+            now,
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 235);
+    }
+}
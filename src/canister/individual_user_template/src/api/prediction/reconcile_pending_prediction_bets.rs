@@ -0,0 +1,229 @@
+use std::time::SystemTime;
+
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        hot_or_not::BetOutcomeForBetMaker,
+        prediction::{PendingPredictionBet, PredictionBetReconciliationDetails},
+    },
+    common::{types::app_primitive_type::PostId, utils::system_time},
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+use super::{
+    bet_on_prediction_post::{
+        abort_pending_prediction_bet_impl, commit_pending_prediction_bet_impl,
+    },
+    receive_prediction_winnings_when_distributed::record_prediction_outcome_impl,
+};
+
+pub fn get_pending_prediction_bets_due_for_reconciliation(
+    canister_data: &CanisterData,
+) -> Vec<PendingPredictionBet> {
+    canister_data
+        .pending_prediction_bets
+        .values()
+        .filter(|pending_prediction_bet| pending_prediction_bet.awaiting_reconciliation)
+        .cloned()
+        .collect()
+}
+
+pub async fn reconcile_pending_prediction_bet(pending_prediction_bet: PendingPredictionBet) {
+    let Some(bet_maker_principal_id) =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id)
+    else {
+        return;
+    };
+
+    // * on failure the bet stays pending and is retried on the next run
+    let Ok((prediction_bet_reconciliation_details,)) =
+        ic_cdk::call::<_, (Option<PredictionBetReconciliationDetails>,)>(
+            pending_prediction_bet.post_canister_id,
+            "get_prediction_bet_details_for_reconciliation",
+            (pending_prediction_bet.post_id, bet_maker_principal_id),
+        )
+        .await
+    else {
+        return;
+    };
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        settle_pending_prediction_bet_impl(
+            canister_data,
+            &(
+                pending_prediction_bet.post_canister_id,
+                pending_prediction_bet.post_id,
+            ),
+            prediction_bet_reconciliation_details,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+/// Commits the pending prediction bet if the post creator's canister holds it,
+/// refunds it otherwise. An outcome the bet missed while it was
+/// pending is paid out right away.
+fn settle_pending_prediction_bet_impl(
+    canister_data: &mut CanisterData,
+    pending_prediction_bet_key: &(CanisterId, PostId),
+    prediction_bet_reconciliation_details: Option<PredictionBetReconciliationDetails>,
+    current_time: SystemTime,
+) {
+    let Some(pending_prediction_bet) = canister_data
+        .pending_prediction_bets
+        .get(pending_prediction_bet_key)
+    else {
+        return;
+    };
+
+    match prediction_bet_reconciliation_details {
+        // * canisters that predate idempotency keys hold no key,
+        // * and a user only ever has one bet on a prediction
+        Some(prediction_bet_reconciliation_details)
+            if prediction_bet_reconciliation_details
+                .idempotency_key
+                .map_or(true, |idempotency_key| {
+                    idempotency_key == pending_prediction_bet.idempotency_key
+                }) =>
+        {
+            commit_pending_prediction_bet_impl(canister_data, pending_prediction_bet_key);
+
+            if prediction_bet_reconciliation_details.outcome
+                != BetOutcomeForBetMaker::AwaitingResult
+            {
+                record_prediction_outcome_impl(
+                    canister_data,
+                    pending_prediction_bet_key.0,
+                    pending_prediction_bet_key.1,
+                    prediction_bet_reconciliation_details.outcome,
+                    current_time,
+                );
+            }
+        }
+        _ => abort_pending_prediction_bet_impl(canister_data, pending_prediction_bet_key),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::get_mock_user_alice_canister_id;
+
+    use super::*;
+
+    fn pending_prediction_bet(post_id: PostId, idempotency_key: u64) -> PendingPredictionBet {
+        PendingPredictionBet {
+            post_canister_id: get_mock_user_alice_canister_id(),
+            post_id,
+            outcome_id: 1,
+            amount: 100,
+            idempotency_key,
+            placed_at: SystemTime::now(),
+            awaiting_reconciliation: true,
+        }
+    }
+
+    #[test]
+    fn test_get_pending_prediction_bets_due_for_reconciliation() {
+        let mut canister_data = CanisterData::default();
+
+        // * a bet whose call is still in flight is not reconciled
+        canister_data.pending_prediction_bets.insert(
+            (get_mock_user_alice_canister_id(), 0),
+            PendingPredictionBet {
+                awaiting_reconciliation: false,
+                ..pending_prediction_bet(0, 0)
+            },
+        );
+        canister_data.pending_prediction_bets.insert(
+            (get_mock_user_alice_canister_id(), 1),
+            pending_prediction_bet(1, 1),
+        );
+
+        let due_pending_prediction_bets =
+            get_pending_prediction_bets_due_for_reconciliation(&canister_data);
+        assert_eq!(due_pending_prediction_bets.len(), 1);
+        assert_eq!(due_pending_prediction_bets[0].post_id, 1);
+    }
+
+    #[test]
+    fn test_settle_pending_prediction_bet_impl() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        // * the amounts of the four pending bets below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 600;
+        for (post_id, idempotency_key) in [(0, 0), (1, 1), (2, 2), (3, 3)] {
+            canister_data.pending_prediction_bets.insert(
+                (get_mock_user_alice_canister_id(), post_id),
+                pending_prediction_bet(post_id, idempotency_key),
+            );
+        }
+        let reconciliation_details = PredictionBetReconciliationDetails {
+            outcome_id: 1,
+            amount: 100,
+            idempotency_key: Some(0),
+            outcome: BetOutcomeForBetMaker::AwaitingResult,
+        };
+
+        // * the post creator's canister holds the bet
+        settle_pending_prediction_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 0),
+            Some(reconciliation_details.clone()),
+            current_time,
+        );
+        assert!(canister_data
+            .all_prediction_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 0)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 600);
+
+        // * the post creator's canister never got the bet
+        settle_pending_prediction_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 1),
+            None,
+            current_time,
+        );
+        assert!(!canister_data
+            .all_prediction_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 1)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 700);
+
+        // * the post creator's canister holds a bet under another idempotency key
+        settle_pending_prediction_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 2),
+            Some(PredictionBetReconciliationDetails {
+                idempotency_key: Some(0),
+                ..reconciliation_details.clone()
+            }),
+            current_time,
+        );
+        assert!(!canister_data
+            .all_prediction_bets_placed
+            .contains_key(&(get_mock_user_alice_canister_id(), 2)));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        // * the bet was accepted and its prediction already settled while it was pending
+        settle_pending_prediction_bet_impl(
+            &mut canister_data,
+            &(get_mock_user_alice_canister_id(), 3),
+            Some(PredictionBetReconciliationDetails {
+                idempotency_key: Some(3),
+                outcome: BetOutcomeForBetMaker::Won(235),
+                ..reconciliation_details
+            }),
+            current_time,
+        );
+        assert_eq!(
+            canister_data
+                .all_prediction_bets_placed
+                .get(&(get_mock_user_alice_canister_id(), 3))
+                .unwrap()
+                .outcome_received,
+            BetOutcomeForBetMaker::Won(235)
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 1035);
+        assert!(canister_data.pending_prediction_bets.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::ResolvePredictionError,
+        prediction::{PredictionOutcomeId, PredictionResolutionMode, PredictionStatus},
+    },
+    common::{types::app_primitive_type::PostId, utils::system_time},
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
+};
+
+use super::settle_prediction::settle_prediction_impl;
+
+/// Picks the winning outcome of a prediction decided by the creator once betting has closed
+/// and pays out every bet on it. The bet makers are informed from the prediction settlement timer.
+/// Once the resolution deadline has passed the prediction can only be refunded.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister
+#[update(guard = "is_not_frozen_for_migration")]
+fn resolve_prediction(
+    post_id: PostId,
+    winning_outcome_id: PredictionOutcomeId,
+) -> Result<(), ResolvePredictionError> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        resolve_prediction_impl(
+            canister_data,
+            ic_cdk::caller(),
+            ic_cdk::id(),
+            post_id,
+            winning_outcome_id,
+            system_time::get_current_system_time_from_ic(),
+        )
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    Ok(())
+}
+
+fn resolve_prediction_impl(
+    canister_data: &mut CanisterData,
+    current_caller: Principal,
+    this_canister_id: CanisterId,
+    post_id: PostId,
+    winning_outcome_id: PredictionOutcomeId,
+    current_time: SystemTime,
+) -> Result<(), ResolvePredictionError> {
+    if current_caller == Principal::anonymous() {
+        return Err(ResolvePredictionError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(ResolvePredictionError::UserPrincipalNotSet)?;
+
+    if current_caller != profile_owner {
+        return Err(ResolvePredictionError::Unauthorized);
+    }
+
+    let prediction_details = canister_data
+        .prediction_details_map
+        .get(&post_id)
+        .ok_or(ResolvePredictionError::PredictionNotFound)?;
+
+    if prediction_details.resolution_mode != PredictionResolutionMode::DecidedByCreator {
+        return Err(ResolvePredictionError::NotDecidedByCreator);
+    }
+
+    if prediction_details.status != PredictionStatus::Open {
+        return Err(ResolvePredictionError::PredictionAlreadySettled);
+    }
+
+    if current_time < prediction_details.closes_at {
+        return Err(ResolvePredictionError::PredictionStillOpen);
+    }
+
+    if current_time >= prediction_details.resolution_deadline() {
+        return Err(ResolvePredictionError::ResolutionDeadlinePassed);
+    }
+
+    if !prediction_details.is_valid_outcome(winning_outcome_id) {
+        return Err(ResolvePredictionError::InvalidOutcome);
+    }
+
+    settle_prediction_impl(
+        canister_data,
+        this_canister_id,
+        post_id,
+        PredictionStatus::Resolved(winning_outcome_id),
+        current_time,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::individual_user_template::types::prediction::PredictionDetails;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_prediction_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+        let closes_at = now + Duration::from_secs(60);
+        let resolve = |canister_data: &mut CanisterData, caller, outcome_id, current_time| {
+            resolve_prediction_impl(
+                canister_data,
+                caller,
+                get_mock_user_alice_canister_id(),
+                0,
+                outcome_id,
+                current_time,
+            )
+        };
+
+        assert_eq!(
+            resolve(&mut canister_data, Principal::anonymous(), 0, closes_at),
+            Err(ResolvePredictionError::Unauthenticated)
+        );
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, closes_at),
+            Err(ResolvePredictionError::UserPrincipalNotSet)
+        );
+
+        canister_data.profile.principal_id = Some(alice);
+        assert_eq!(
+            resolve(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                0,
+                closes_at
+            ),
+            Err(ResolvePredictionError::Unauthorized)
+        );
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, closes_at),
+            Err(ResolvePredictionError::PredictionNotFound)
+        );
+
+        let mut prediction_details = PredictionDetails::new(
+            vec!["Yes".into(), "No".into()],
+            PredictionResolutionMode::DecidedByVoteShare,
+            60,
+            10,
+            now,
+        )
+        .unwrap();
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details.clone());
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, closes_at),
+            Err(ResolvePredictionError::NotDecidedByCreator)
+        );
+
+        prediction_details.resolution_mode = PredictionResolutionMode::DecidedByCreator;
+        let resolution_deadline = prediction_details.resolution_deadline();
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details);
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, now),
+            Err(ResolvePredictionError::PredictionStillOpen)
+        );
+        // * past the deadline the settlement timer refunds the prediction instead
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, resolution_deadline),
+            Err(ResolvePredictionError::ResolutionDeadlinePassed)
+        );
+        assert_eq!(
+            resolve(&mut canister_data, alice, 2, closes_at),
+            Err(ResolvePredictionError::InvalidOutcome)
+        );
+        assert_eq!(resolve(&mut canister_data, alice, 1, closes_at), Ok(()));
+        assert_eq!(
+            canister_data.prediction_details_map.get(&0).unwrap().status,
+            PredictionStatus::Resolved(1)
+        );
+        assert_eq!(
+            resolve(&mut canister_data, alice, 0, closes_at),
+            Err(ResolvePredictionError::PredictionAlreadySettled)
+        );
+    }
+}
@@ -0,0 +1,698 @@
+use std::time::{Duration, SystemTime};
+
+use ic_cdk::api::management_canister::provisional::CanisterId;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        hot_or_not::{BetMakerInformedStatus, BetOutcomeForBetMaker, PendingPayoutNotification},
+        prediction::{
+            GlobalPredictionBetId, PredictionResolutionMode, PredictionSettlementJob,
+            PredictionStatus,
+        },
+    },
+    common::{
+        types::{
+            app_primitive_type::PostId,
+            utility_token::token_event::{PredictionOutcomePayoutEvent, TokenEvent},
+        },
+        utils::system_time,
+    },
+};
+
+use crate::{
+    api::hot_or_not_bet::payout_notification_outbox::{
+        drop_expired_payout_notifications, get_payout_notification_retry_horizon,
+        record_failed_payout_notification, start_due_payout_notification_attempts,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration_impl,
+    util::canister_migration::get_current_canister_id_of_migrated_canister,
+    CANISTER_DATA,
+};
+
+const PREDICTION_SETTLEMENT_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_PREDICTIONS_SETTLED_PER_RUN: usize = 5;
+
+/// Starts the recurring timer that settles predictions once they close
+/// and sends the outcome notifications that are due, a batch at a time.
+/// Predictions decided by vote share are settled at close, predictions decided by the creator
+/// are refunded if the creator has not picked an outcome by the resolution deadline.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+pub fn start_prediction_settlement_timer() {
+    ic_cdk_timers::set_timer_interval(PREDICTION_SETTLEMENT_INTERVAL, || {
+        if CANISTER_DATA
+            .with_borrow(is_not_frozen_for_migration_impl)
+            .is_err()
+        {
+            return;
+        }
+
+        let current_time = system_time::get_current_system_time_from_ic();
+
+        let due_payout_notifications = CANISTER_DATA.with_borrow_mut(|canister_data| {
+            settle_closed_predictions_impl(
+                canister_data,
+                ic_cdk::id(),
+                current_time,
+                MAX_PREDICTIONS_SETTLED_PER_RUN,
+            );
+
+            drop_expired_payout_notifications(
+                &mut canister_data.prediction_payout_notification_outbox,
+                &current_time,
+            );
+            start_due_payout_notification_attempts(
+                &mut canister_data.prediction_payout_notification_outbox,
+                &current_time,
+            )
+        });
+
+        due_payout_notifications.into_iter().for_each(
+            |(global_prediction_bet_id, pending_payout_notification)| {
+                ic_cdk::spawn(receive_prediction_winnings_when_distributed(
+                    global_prediction_bet_id,
+                    pending_payout_notification.bet_maker_canister_id,
+                    pending_payout_notification.post_id,
+                    pending_payout_notification.outcome,
+                ))
+            },
+        );
+    });
+}
+
+/// Queues the prediction on the post to be settled when it is due.
+/// Queueing the same prediction again is harmless, its job is always the same.
+pub fn enqueue_prediction_settlement_job(canister_data: &mut CanisterData, post_id: PostId) {
+    let Some(prediction_details) = canister_data.prediction_details_map.get(&post_id) else {
+        return;
+    };
+
+    canister_data
+        .prediction_settlement_queue
+        .insert(PredictionSettlementJob {
+            due_at: prediction_details.settlement_due_at(),
+            post_id,
+        });
+}
+
+/// Queues every prediction that is still open.
+/// Covers predictions created before the queue existed, so it has to run on `post_upgrade`.
+pub fn enqueue_prediction_settlement_jobs_for_open_predictions() {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let open_predictions = canister_data
+            .prediction_details_map
+            .iter()
+            .filter(|(_, prediction_details)| prediction_details.status == PredictionStatus::Open)
+            .map(|(post_id, _)| post_id)
+            .collect::<Vec<_>>();
+
+        open_predictions.into_iter().for_each(|post_id| {
+            enqueue_prediction_settlement_job(canister_data, post_id);
+        });
+    })
+}
+
+fn pop_due_prediction_settlement_jobs(
+    canister_data: &mut CanisterData,
+    current_time: &SystemTime,
+    limit: usize,
+) -> Vec<PredictionSettlementJob> {
+    let due_jobs = canister_data
+        .prediction_settlement_queue
+        .iter()
+        .take_while(|job| job.due_at <= *current_time)
+        .take(limit)
+        .copied()
+        .collect::<Vec<_>>();
+
+    due_jobs.iter().for_each(|job| {
+        canister_data.prediction_settlement_queue.remove(job);
+    });
+
+    due_jobs
+}
+
+/// Returns the posts whose predictions were settled.
+/// Jobs of predictions that were resolved or refunded in the meantime are dropped.
+fn settle_closed_predictions_impl(
+    canister_data: &mut CanisterData,
+    this_canister_id: CanisterId,
+    current_time: SystemTime,
+    limit: usize,
+) -> Vec<PostId> {
+    let due_jobs = pop_due_prediction_settlement_jobs(canister_data, &current_time, limit);
+
+    due_jobs
+        .into_iter()
+        .filter_map(|job| {
+            let prediction_details = canister_data.prediction_details_map.get(&job.post_id)?;
+
+            let status = match prediction_details.resolution_mode {
+                PredictionResolutionMode::DecidedByVoteShare => {
+                    prediction_details.get_status_decided_by_vote_share()
+                }
+                PredictionResolutionMode::DecidedByCreator => PredictionStatus::Refunded,
+            };
+
+            settle_prediction_impl(
+                canister_data,
+                this_canister_id,
+                job.post_id,
+                status,
+                current_time,
+            )
+            .then_some(job.post_id)
+        })
+        .collect()
+}
+
+/// Closes an open prediction with the given status, pays the creator's commission,
+/// records the payout of every bet and queues the notification of each bet maker
+/// for the settlement timer to send. Returns false if the prediction was not open.
+pub fn settle_prediction_impl(
+    canister_data: &mut CanisterData,
+    this_canister_id: CanisterId,
+    post_id: PostId,
+    status: PredictionStatus,
+    current_time: SystemTime,
+) -> bool {
+    let Some(mut prediction_details) = canister_data.prediction_details_map.get(&post_id) else {
+        return false;
+    };
+
+    if prediction_details.status != PredictionStatus::Open {
+        return false;
+    }
+
+    prediction_details.status = status;
+
+    let bets = canister_data
+        .prediction_bet_details_map
+        .range(GlobalPredictionBetId::range_for_post(post_id))
+        .collect::<Vec<_>>();
+    let payouts = prediction_details.get_payouts(
+        &bets
+            .iter()
+            .map(|(_, prediction_bet_details)| {
+                (
+                    prediction_bet_details.amount,
+                    prediction_bet_details.outcome_id,
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let creator_commission = prediction_details.get_creator_commission(&payouts);
+    if creator_commission > 0 {
+        canister_data
+            .my_token_balance
+            .handle_token_event(TokenEvent::PredictionOutcomePayout {
+                amount: creator_commission,
+                details: PredictionOutcomePayoutEvent::CommissionFromPrediction {
+                    post_canister_id: this_canister_id,
+                    post_id,
+                    pot_total_amount: prediction_details.total_pot(),
+                },
+                timestamp: current_time,
+            });
+    }
+
+    bets.into_iter().zip(payouts).for_each(
+        |((global_prediction_bet_id, mut prediction_bet_details), payout)| {
+            prediction_bet_details.payout = payout;
+
+            let outcome = prediction_bet_details.get_outcome_for_bet_maker(&status);
+            if outcome != BetOutcomeForBetMaker::AwaitingResult {
+                canister_data.prediction_payout_notification_outbox.insert(
+                    global_prediction_bet_id.clone(),
+                    PendingPayoutNotification::queued(
+                        prediction_bet_details.bet_maker_canister_id,
+                        post_id,
+                        outcome,
+                        current_time,
+                    ),
+                );
+            }
+
+            canister_data
+                .prediction_bet_details_map
+                .insert(global_prediction_bet_id, prediction_bet_details);
+        },
+    );
+    canister_data
+        .prediction_details_map
+        .insert(post_id, prediction_details);
+
+    true
+}
+
+async fn receive_prediction_winnings_when_distributed(
+    global_prediction_bet_id: GlobalPredictionBetId,
+    bet_maker_canister_id: CanisterId,
+    post_id: PostId,
+    outcome: BetOutcomeForBetMaker,
+) {
+    let mut bet_maker_canister_id = bet_maker_canister_id;
+    let mut result =
+        inform_prediction_bet_maker_of_outcome(bet_maker_canister_id, post_id, &outcome).await;
+
+    // * the bet maker's profile may have been migrated to another subnet since the bet
+    if result.is_err() {
+        if let Some(current_canister_id) =
+            get_current_canister_id_of_migrated_canister(bet_maker_canister_id).await
+        {
+            bet_maker_canister_id = current_canister_id;
+            result =
+                inform_prediction_bet_maker_of_outcome(bet_maker_canister_id, post_id, &outcome)
+                    .await;
+        }
+    }
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        record_prediction_payout_notification_result_impl(
+            canister_data,
+            global_prediction_bet_id,
+            bet_maker_canister_id,
+            post_id,
+            outcome,
+            result,
+            system_time::get_current_system_time_from_ic(),
+        );
+    });
+}
+
+async fn inform_prediction_bet_maker_of_outcome(
+    bet_maker_canister_id: CanisterId,
+    post_id: PostId,
+    outcome: &BetOutcomeForBetMaker,
+) -> Result<(), String> {
+    ic_cdk::call::<_, ()>(
+        bet_maker_canister_id,
+        "receive_prediction_winnings_when_distributed",
+        (post_id, outcome.clone()),
+    )
+    .await
+    .map_err(|e| {
+        format!(
+            "Informing bet maker canister {} failed: {:?} {}",
+            bet_maker_canister_id.to_string(),
+            e.0,
+            e.1
+        )
+    })
+}
+
+/// Marks the bet maker as informed and drops the notification from the outbox on success.
+/// On failure the notification is rescheduled for the settlement timer to retry.
+fn record_prediction_payout_notification_result_impl(
+    canister_data: &mut CanisterData,
+    global_prediction_bet_id: GlobalPredictionBetId,
+    bet_maker_canister_id: CanisterId,
+    post_id: PostId,
+    outcome: BetOutcomeForBetMaker,
+    result: Result<(), String>,
+    current_time: SystemTime,
+) {
+    let bet_maker_informed_status = match result {
+        Ok(()) => {
+            canister_data
+                .prediction_payout_notification_outbox
+                .remove(&global_prediction_bet_id);
+
+            BetMakerInformedStatus::InformedSuccessfully
+        }
+        Err(error) => {
+            let retry_horizon = get_payout_notification_retry_horizon(canister_data);
+
+            record_failed_payout_notification(
+                &mut canister_data.prediction_payout_notification_outbox,
+                global_prediction_bet_id.clone(),
+                PendingPayoutNotification::new(
+                    bet_maker_canister_id,
+                    post_id,
+                    outcome,
+                    error.clone(),
+                    current_time,
+                ),
+                retry_horizon,
+            );
+
+            BetMakerInformedStatus::Failed(error)
+        }
+    };
+
+    if let Some(mut prediction_bet_details) = canister_data
+        .prediction_bet_details_map
+        .get(&global_prediction_bet_id)
+    {
+        prediction_bet_details.bet_maker_canister_id = bet_maker_canister_id;
+        prediction_bet_details.bet_maker_informed_status = Some(bet_maker_informed_status);
+        canister_data
+            .prediction_bet_details_map
+            .insert(global_prediction_bet_id, prediction_bet_details);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use candid::Principal;
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{BetPayout, StablePrincipal},
+        prediction::{
+            PredictionBetDetails, PredictionDetails, PREDICTION_RESOLUTION_DEADLINE_IN_SECONDS,
+        },
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+    };
+
+    use super::*;
+
+    /// Bets 100, 300, 200 and 400 on outcomes 0, 0, 1 and 2 of the prediction on post 0
+    fn setup_prediction(
+        canister_data: &mut CanisterData,
+        resolution_mode: PredictionResolutionMode,
+        created_at: SystemTime,
+    ) {
+        let mut prediction_details = PredictionDetails::new(
+            vec!["Home".into(), "Away".into(), "Draw".into()],
+            resolution_mode,
+            60,
+            10,
+            created_at,
+        )
+        .unwrap();
+
+        [(1u8, 100, 0), (2, 300, 0), (3, 200, 1), (4, 400, 2)]
+            .into_iter()
+            .for_each(|(i, amount, outcome_id)| {
+                prediction_details.record_bet(outcome_id, amount);
+                canister_data.prediction_bet_details_map.insert(
+                    GlobalPredictionBetId(0, StablePrincipal(Principal::from_slice(&[i]))),
+                    PredictionBetDetails {
+                        amount,
+                        outcome_id,
+                        payout: BetPayout::NotCalculatedYet,
+                        bet_maker_canister_id: get_mock_user_bob_canister_id(),
+                        placed_at: created_at,
+                        bet_maker_informed_status: None,
+                        idempotency_key: None,
+                    },
+                );
+            });
+
+        canister_data
+            .prediction_details_map
+            .insert(0, prediction_details);
+        enqueue_prediction_settlement_job(canister_data, 0);
+    }
+
+    fn payouts_of_post(canister_data: &CanisterData, post_id: PostId) -> Vec<BetPayout> {
+        canister_data
+            .prediction_bet_details_map
+            .range(GlobalPredictionBetId::range_for_post(post_id))
+            .map(|(_, prediction_bet_details)| prediction_bet_details.payout)
+            .collect()
+    }
+
+    #[test]
+    fn test_settle_prediction_impl() {
+        let mut canister_data = CanisterData::default();
+        let now = SystemTime::now();
+        setup_prediction(
+            &mut canister_data,
+            PredictionResolutionMode::DecidedByCreator,
+            now,
+        );
+
+        assert!(!settle_prediction_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            1,
+            PredictionStatus::Resolved(0),
+            now
+        ));
+        assert!(settle_prediction_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            PredictionStatus::Resolved(0),
+            now
+        ));
+
+        assert_eq!(
+            payouts_of_post(&canister_data, 0),
+            vec![
+                BetPayout::Calculated(235),
+                BetPayout::Calculated(705),
+                BetPayout::Calculated(0),
+                BetPayout::Calculated(0),
+            ]
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 60);
+        assert_eq!(
+            canister_data.prediction_details_map.get(&0).unwrap().status,
+            PredictionStatus::Resolved(0)
+        );
+
+        // * every bet maker is queued to be told, the timer sends them a batch at a time
+        let queued_outcomes = canister_data
+            .prediction_payout_notification_outbox
+            .values()
+            .map(|pending_payout_notification| pending_payout_notification.outcome.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            queued_outcomes,
+            vec![
+                BetOutcomeForBetMaker::Won(235),
+                BetOutcomeForBetMaker::Won(705),
+                BetOutcomeForBetMaker::Lost,
+                BetOutcomeForBetMaker::Lost,
+            ]
+        );
+        assert_eq!(
+            start_due_payout_notification_attempts(
+                &mut canister_data.prediction_payout_notification_outbox,
+                &now
+            )
+            .len(),
+            4
+        );
+
+        // * a settled prediction is never paid out twice
+        assert!(!settle_prediction_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            PredictionStatus::Refunded,
+            now
+        ));
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 60);
+    }
+
+    #[test]
+    fn test_settle_closed_predictions_decided_by_vote_share() {
+        let now = SystemTime::now();
+        let closes_at = now + Duration::from_secs(60);
+        let mut canister_data = CanisterData::default();
+        setup_prediction(
+            &mut canister_data,
+            PredictionResolutionMode::DecidedByVoteShare,
+            now,
+        );
+
+        assert!(settle_closed_predictions_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            now,
+            10
+        )
+        .is_empty());
+        assert_eq!(
+            settle_closed_predictions_impl(
+                &mut canister_data,
+                get_mock_user_alice_canister_id(),
+                closes_at,
+                10
+            ),
+            vec![0]
+        );
+        // * outcome 0 has the most bets
+        assert_eq!(
+            canister_data.prediction_details_map.get(&0).unwrap().status,
+            PredictionStatus::Resolved(0)
+        );
+        assert!(canister_data.prediction_settlement_queue.is_empty());
+    }
+
+    #[test]
+    fn test_settle_closed_predictions_drops_jobs_of_settled_predictions() {
+        let now = SystemTime::now();
+        let mut canister_data = CanisterData::default();
+        setup_prediction(
+            &mut canister_data,
+            PredictionResolutionMode::DecidedByCreator,
+            now,
+        );
+        // * queueing again does not duplicate the job
+        enqueue_prediction_settlement_job(&mut canister_data, 0);
+        assert_eq!(canister_data.prediction_settlement_queue.len(), 1);
+
+        // * the creator picks an outcome before the resolution deadline
+        assert!(settle_prediction_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            0,
+            PredictionStatus::Resolved(1),
+            now
+        ));
+
+        let resolution_deadline = canister_data
+            .prediction_details_map
+            .get(&0)
+            .unwrap()
+            .resolution_deadline();
+        assert!(settle_closed_predictions_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            resolution_deadline,
+            10
+        )
+        .is_empty());
+        assert!(canister_data.prediction_settlement_queue.is_empty());
+        assert_eq!(
+            canister_data.prediction_details_map.get(&0).unwrap().status,
+            PredictionStatus::Resolved(1)
+        );
+    }
+
+    #[test]
+    fn test_settle_closed_predictions_decided_by_creator_past_the_deadline() {
+        let now = SystemTime::now();
+        let closes_at = now + Duration::from_secs(60);
+        let mut canister_data = CanisterData::default();
+        setup_prediction(
+            &mut canister_data,
+            PredictionResolutionMode::DecidedByCreator,
+            now,
+        );
+
+        assert!(settle_closed_predictions_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            closes_at,
+            10
+        )
+        .is_empty());
+
+        let resolution_deadline =
+            closes_at + Duration::from_secs(PREDICTION_RESOLUTION_DEADLINE_IN_SECONDS);
+        assert_eq!(
+            settle_closed_predictions_impl(
+                &mut canister_data,
+                get_mock_user_alice_canister_id(),
+                resolution_deadline,
+                10
+            ),
+            vec![0]
+        );
+        assert_eq!(
+            payouts_of_post(&canister_data, 0),
+            vec![
+                BetPayout::Calculated(100),
+                BetPayout::Calculated(300),
+                BetPayout::Calculated(200),
+                BetPayout::Calculated(400),
+            ]
+        );
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 0);
+    }
+
+    #[test]
+    fn test_record_prediction_payout_notification_result_impl() {
+        let mut canister_data = CanisterData::default();
+        let now = SystemTime::now();
+        setup_prediction(
+            &mut canister_data,
+            PredictionResolutionMode::DecidedByCreator,
+            now,
+        );
+        let global_prediction_bet_id =
+            GlobalPredictionBetId(0, StablePrincipal(Principal::from_slice(&[1])));
+        let record = |canister_data: &mut CanisterData, result, current_time| {
+            record_prediction_payout_notification_result_impl(
+                canister_data,
+                global_prediction_bet_id.clone(),
+                get_mock_user_bob_canister_id(),
+                0,
+                BetOutcomeForBetMaker::Won(235),
+                result,
+                current_time,
+            )
+        };
+        let informed_status = |canister_data: &CanisterData| {
+            canister_data
+                .prediction_bet_details_map
+                .get(&global_prediction_bet_id)
+                .unwrap()
+                .bet_maker_informed_status
+        };
+
+        record(&mut canister_data, Err("unreachable".into()), now);
+        assert_eq!(
+            informed_status(&canister_data),
+            Some(BetMakerInformedStatus::Failed("unreachable".into()))
+        );
+        assert_eq!(
+            canister_data
+                .prediction_payout_notification_outbox
+                .get(&global_prediction_bet_id)
+                .unwrap()
+                .outcome,
+            BetOutcomeForBetMaker::Won(235)
+        );
+
+        // * picked up by the timer once its backoff has passed, and only once
+        let retry_time = now + Duration::from_secs(60);
+        assert!(start_due_payout_notification_attempts(
+            &mut canister_data.prediction_payout_notification_outbox,
+            &now
+        )
+        .is_empty());
+        assert_eq!(
+            start_due_payout_notification_attempts(
+                &mut canister_data.prediction_payout_notification_outbox,
+                &retry_time
+            )
+            .len(),
+            1
+        );
+        assert!(start_due_payout_notification_attempts(
+            &mut canister_data.prediction_payout_notification_outbox,
+            &retry_time
+        )
+        .is_empty());
+
+        record(
+            &mut canister_data,
+            Err("still unreachable".into()),
+            retry_time,
+        );
+        assert_eq!(
+            canister_data
+                .prediction_payout_notification_outbox
+                .get(&global_prediction_bet_id)
+                .unwrap()
+                .attempts,
+            2
+        );
+
+        record(&mut canister_data, Ok(()), retry_time);
+        assert!(canister_data
+            .prediction_payout_notification_outbox
+            .is_empty());
+        assert_eq!(
+            informed_status(&canister_data),
+            Some(BetMakerInformedStatus::InformedSuccessfully)
+        );
+    }
+}
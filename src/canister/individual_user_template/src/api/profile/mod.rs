@@ -1,5 +1,6 @@
 pub mod get_profile_details;
 pub mod get_profile_details_v2;
+pub mod prepare_profile_for_deletion;
 pub mod update_profile_display_details;
 pub mod update_profile_set_unique_username_once;
 pub mod update_referrer_details;
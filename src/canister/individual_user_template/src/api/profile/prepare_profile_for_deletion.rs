@@ -0,0 +1,301 @@
+use std::{collections::BTreeSet, time::SystemTime};
+
+use candid::Principal;
+use ic_cdk::api::call;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        follow::{FollowData, FollowEntryDetail},
+        hot_or_not::SlotId,
+        prediction::PredictionStatus,
+    },
+    common::{
+        types::{app_primitive_type::PostId, top_posts::post_score_index_item::PostStatus},
+        utils::{permissions::is_caller_controller, system_time},
+    },
+};
+
+use crate::{
+    api::{
+        hot_or_not_bet::tabulate_hot_or_not_outcome_for_post_slot::inform_participants_of_outcome,
+        post::{
+            delete_post::close_bets_on_deleted_post, purge_deleted_posts::are_bets_on_post_final,
+            send_update_post_cache::send_update_post_cache,
+        },
+    },
+    data_model::CanisterData,
+    CANISTER_DATA,
+};
+
+/// Tombstones every post of this profile in post_cache, settles or refunds the bets on them
+/// and asks the canisters of followers and followees to drop their follow edges with this profile.
+/// Fails until every bet maker has been told about the outcome of their bets,
+/// the subnet orchestrator retries on its next deletion run and only wipes the canister
+/// once this succeeds.
+///
+/// # Access Control
+/// Only the subnet orchestrator, once the profile owner's deletion grace period has ended
+#[update(guard = "is_caller_controller")]
+fn prepare_profile_for_deletion() -> Result<(), String> {
+    let current_time = system_time::get_current_system_time_from_ic();
+
+    let (tombstoned_posts, follow_peer_canister_ids, profile_principal_id) = CANISTER_DATA
+        .with_borrow_mut(|canister_data| {
+            (
+                tombstone_all_posts(canister_data, ic_cdk::id(), current_time),
+                take_follow_peer_canister_ids(canister_data),
+                canister_data.profile.principal_id,
+            )
+        });
+
+    tombstoned_posts
+        .into_iter()
+        .for_each(|(post_id, settled_slot_ids)| {
+            send_update_post_cache(&post_id);
+            settled_slot_ids
+                .into_iter()
+                .for_each(|slot_id| inform_participants_of_outcome(post_id, slot_id));
+        });
+
+    if let Some(principal_id) = profile_principal_id {
+        let deleted_profile = FollowEntryDetail {
+            principal_id,
+            canister_id: ic_cdk::id(),
+        };
+        follow_peer_canister_ids
+            .into_iter()
+            .for_each(|canister_id| {
+                let _ = call::notify(
+                    canister_id,
+                    "remove_follow_edges_with_deleted_profile",
+                    (deleted_profile.clone(),),
+                );
+            });
+    }
+
+    if !CANISTER_DATA.with_borrow(are_all_bets_settled) {
+        return Err("Bets on the posts of this profile are still being settled".into());
+    }
+
+    Ok(())
+}
+
+/// Returns the posts that were tombstoned along with their slots whose bets were settled
+fn tombstone_all_posts(
+    canister_data: &mut CanisterData,
+    this_canister_id: Principal,
+    current_time: SystemTime,
+) -> Vec<(PostId, Vec<SlotId>)> {
+    let post_ids = canister_data
+        .all_created_posts
+        .values_mut()
+        .filter(|post| post.status != PostStatus::Deleted)
+        .map(|post| {
+            post.update_status(PostStatus::Deleted);
+            post.id
+        })
+        .collect::<Vec<_>>();
+
+    post_ids
+        .into_iter()
+        .map(|post_id| {
+            let settled_slot_ids =
+                close_bets_on_deleted_post(canister_data, this_canister_id, post_id, current_time);
+            (post_id, settled_slot_ids)
+        })
+        .collect()
+}
+
+/// Bets are settled once every post's bets are final, every prediction is closed
+/// and no payout notification is still being retried.
+/// Notifications that were given up on only go out on request, so they are not waited for.
+fn are_all_bets_settled(canister_data: &CanisterData) -> bool {
+    canister_data
+        .all_created_posts
+        .keys()
+        .all(|post_id| are_bets_on_post_final(canister_data, *post_id))
+        && canister_data
+            .payout_notification_outbox
+            .values()
+            .all(|pending_payout_notification| pending_payout_notification.gave_up_at.is_some())
+        && canister_data
+            .prediction_details_map
+            .iter()
+            .all(|(_, prediction_details)| prediction_details.status != PredictionStatus::Open)
+        && canister_data
+            .prediction_bet_details_map
+            .iter()
+            .all(|(_, prediction_bet_details)| {
+                prediction_bet_details.bet_maker_informed_status.is_some()
+            })
+        && canister_data
+            .prediction_payout_notification_outbox
+            .values()
+            .all(|pending_payout_notification| pending_payout_notification.gave_up_at.is_some())
+}
+
+fn take_follow_peer_canister_ids(canister_data: &mut CanisterData) -> BTreeSet<Principal> {
+    let follow_data = std::mem::take(&mut canister_data.follow_data);
+    let FollowData {
+        follower,
+        following,
+    } = follow_data;
+
+    follower
+        .members
+        .into_keys()
+        .chain(following.members.into_keys())
+        .map(|follow_entry_detail| follow_entry_detail.canister_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{
+            BetDetails, BetDirection, BetMakerInformedStatus, BetOutcomeForBetMaker, BetPayout,
+            GlobalBetId, GlobalRoomId, PendingPayoutNotification, StablePrincipal,
+        },
+        post::{Post, PostDetailsFromFrontend},
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn post(id: PostId) -> Post {
+        Post::new(
+            id,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &SystemTime::now(),
+        )
+    }
+
+    #[test]
+    fn test_tombstone_all_posts() {
+        let mut canister_data = CanisterData::default();
+        canister_data.all_created_posts.insert(0, post(0));
+        canister_data.all_created_posts.insert(1, post(1));
+        let mut deleted_post = post(2);
+        deleted_post.update_status(PostStatus::Deleted);
+        canister_data.all_created_posts.insert(2, deleted_post);
+
+        let tombstoned_posts = tombstone_all_posts(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            SystemTime::now(),
+        );
+
+        assert_eq!(
+            tombstoned_posts
+                .iter()
+                .map(|(post_id, _)| *post_id)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert!(canister_data
+            .all_created_posts
+            .values()
+            .all(|post| post.status == PostStatus::Deleted));
+        // * the bets on the tombstoned posts are settled or refunded
+        assert!(canister_data
+            .all_created_posts
+            .values()
+            .all(|post| post.slots_left_to_be_computed.is_empty()));
+        assert!(canister_data.posts_pending_purge.contains(&0));
+        assert!(canister_data.posts_pending_purge.contains(&1));
+    }
+
+    #[test]
+    fn test_are_all_bets_settled() {
+        let mut canister_data = CanisterData::default();
+        let mut deleted_post = post(0);
+        deleted_post.update_status(PostStatus::Deleted);
+        deleted_post.slots_left_to_be_computed.clear();
+        canister_data.all_created_posts.insert(0, deleted_post);
+
+        let global_bet_id = GlobalBetId(
+            GlobalRoomId(0, 1, 1),
+            StablePrincipal(get_mock_user_bob_principal_id()),
+        );
+        let mut bet_details = BetDetails {
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            payout: BetPayout::Calculated(100),
+            bet_maker_canister_id: get_mock_user_bob_canister_id(),
+            bet_maker_informed_status: None,
+            idempotency_key: None,
+            placed_at: None,
+        };
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id.clone(), bet_details.clone());
+
+        // * the bet maker has not been informed yet
+        assert!(!are_all_bets_settled(&canister_data));
+
+        bet_details.bet_maker_informed_status =
+            Some(BetMakerInformedStatus::Failed("Canister is stopped".into()));
+        canister_data
+            .bet_details_map
+            .insert(global_bet_id.clone(), bet_details);
+        let mut pending_payout_notification = PendingPayoutNotification::new(
+            get_mock_user_bob_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Draw(100),
+            "Canister is stopped".into(),
+            SystemTime::now(),
+        );
+        canister_data
+            .payout_notification_outbox
+            .insert(global_bet_id.clone(), pending_payout_notification.clone());
+
+        // * the failed notification is still being retried
+        assert!(!are_all_bets_settled(&canister_data));
+
+        pending_payout_notification.gave_up_at = Some(SystemTime::now());
+        canister_data
+            .payout_notification_outbox
+            .insert(global_bet_id, pending_payout_notification);
+
+        assert!(are_all_bets_settled(&canister_data));
+    }
+
+    #[test]
+    fn test_take_follow_peer_canister_ids() {
+        let mut canister_data = CanisterData::default();
+        let alice = FollowEntryDetail {
+            principal_id: get_mock_user_alice_principal_id(),
+            canister_id: get_mock_user_alice_canister_id(),
+        };
+        let bob = FollowEntryDetail {
+            principal_id: get_mock_user_bob_principal_id(),
+            canister_id: get_mock_user_bob_canister_id(),
+        };
+        canister_data.follow_data.follower.add(alice.clone());
+        canister_data.follow_data.following.add(alice);
+        canister_data.follow_data.following.add(bob);
+
+        let follow_peer_canister_ids = take_follow_peer_canister_ids(&mut canister_data);
+
+        assert_eq!(
+            follow_peer_canister_ids,
+            BTreeSet::from([
+                get_mock_user_alice_canister_id(),
+                get_mock_user_bob_canister_id()
+            ])
+        );
+        assert!(canister_data.follow_data.follower.is_empty());
+        assert!(canister_data.follow_data.following.is_empty());
+    }
+}
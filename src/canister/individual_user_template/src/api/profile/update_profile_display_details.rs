@@ -1,6 +1,6 @@
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 use candid::CandidType;
 use ic_cdk_macros::update;
@@ -15,7 +15,7 @@ pub enum UpdateProfileDetailsError {
 
 /// # Access Control
 /// Only the user whose profile details are stored in this canister can update their details.
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn update_profile_display_details(
     user_profile_details: UserProfileUpdateDetailsFromFrontend,
 ) -> Result<UserProfileDetailsForFrontend, UpdateProfileDetailsError> {
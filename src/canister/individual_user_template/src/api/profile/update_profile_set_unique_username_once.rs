@@ -1,20 +1,23 @@
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 use ic_cdk::api::call;
 use ic_cdk_macros::update;
 use shared_utils::{
-    common::types::known_principal::KnownPrincipalType,
+    common::{types::known_principal::KnownPrincipalType, utils::username::validate_username},
     types::canister_specific::{
         individual_user_template::error_types::UpdateProfileSetUniqueUsernameError,
         user_index::error_types::SetUniqueUsernameError,
     },
 };
 
+/// Despite the name, the username can be changed again once the user index's rename cooldown
+/// has passed. The previous username stays reserved for this user for a while.
+///
 /// # Access Control
 /// Only the user whose profile details are stored in this canister can update their details.
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn update_profile_set_unique_username_once(
     new_unique_username: String,
 ) -> Result<(), UpdateProfileSetUniqueUsernameError> {
@@ -28,6 +31,9 @@ async fn update_profile_set_unique_username_once(
         return Err(UpdateProfileSetUniqueUsernameError::NotAuthorized);
     }
 
+    validate_username(&new_unique_username)
+        .map_err(UpdateProfileSetUniqueUsernameError::InvalidUsername)?;
+
     update_last_canister_functionality_access_time();
 
     let user_index_canister_principal_id = CANISTER_DATA.with(|canister_data_ref_cell| {
@@ -66,5 +72,11 @@ async fn update_profile_set_unique_username_once(
         Err(SetUniqueUsernameError::UserCanisterEntryDoesNotExist) => {
             Err(UpdateProfileSetUniqueUsernameError::UserCanisterEntryDoesNotExist)
         }
+        Err(SetUniqueUsernameError::InvalidUsername(e)) => {
+            Err(UpdateProfileSetUniqueUsernameError::InvalidUsername(e))
+        }
+        Err(SetUniqueUsernameError::UsernameChangeOnCooldown) => {
+            Err(UpdateProfileSetUniqueUsernameError::UsernameChangeOnCooldown)
+        }
     }
 }
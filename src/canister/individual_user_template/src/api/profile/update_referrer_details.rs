@@ -7,10 +7,10 @@ use shared_utils::canister_specific::individual_user_template::types::{
 
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 async fn update_referrer_details(referrer: UserCanisterDetails) -> Result<String, String> {
     let profile_owner =
         CANISTER_DATA.with_borrow_mut(|canister_data| canister_data.profile.principal_id);
@@ -0,0 +1,660 @@
+use std::{collections::BTreeMap, io::Cursor, iter};
+
+use candid::Principal;
+use ic_stable_structures::Storable;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        comment::{Comment, GlobalCommentId},
+        hot_or_not::{
+            BetDetails, GlobalBetId, GlobalRoomId, RoomDetailsV1, SlotDetailsV1, SlotId,
+            SlotTabulationJob, StablePrincipal,
+        },
+        ml_data::{SuccessHistoryItemV1, WatchHistoryItem},
+        prediction::{GlobalPredictionBetId, PredictionBetDetails, PredictionDetails},
+        snapshot::{
+            SnapshotChunkInfo, SnapshotError, SnapshotManifest, SnapshotRestoreProgress,
+            SnapshotSaveProgress,
+        },
+    },
+    common::types::app_primitive_type::PostId,
+};
+
+use crate::data_model::{
+    kv_storage::{AppStorageSnapshotEntry, AppStorageSnapshotSection},
+    stable_entries_after, CanisterData,
+};
+
+use super::{CanisterDataForSnapshot, SNAPSHOT_SCHEMA_VERSION};
+
+pub const SNAPSHOT_CHUNK_SIZE_IN_BYTES: usize = 1024 * 1024;
+
+/// A binary snapshot is a sequence of CBOR encoded records.
+/// The heap state comes first, then one record per entry of every stable structure.
+/// A record never spans two chunks, so every chunk can be restored on its own.
+#[derive(Serialize, Deserialize)]
+pub enum SnapshotRecord {
+    HeapState(Box<CanisterDataForSnapshot>),
+    RoomDetails(GlobalRoomId, RoomDetailsV1),
+    BetDetails(GlobalBetId, BetDetails),
+    PostPrincipal(PostId, StablePrincipal),
+    SlotDetails(PostId, SlotId, SlotDetailsV1),
+    WatchHistory(WatchHistoryItem),
+    SuccessHistory(SuccessHistoryItemV1),
+    TokenRoot(Principal),
+    SlotTabulationJob(SlotTabulationJob),
+    AppStorage(AppStorageSnapshotEntry),
+    Comment(GlobalCommentId, Comment),
+    PredictionDetails(PostId, PredictionDetails),
+    PredictionBetDetails(GlobalPredictionBetId, PredictionBetDetails),
+}
+
+/// The parts of a snapshot in the order they are saved.
+/// The heap state comes first, then every stable structure in key order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum SnapshotSection {
+    #[default]
+    HeapState,
+    RoomDetails,
+    BetDetails,
+    PostPrincipal,
+    SlotDetails,
+    WatchHistory,
+    SuccessHistory,
+    TokenRoots,
+    SlotTabulationQueue,
+    AppStorage(AppStorageSnapshotSection),
+    Comments,
+    PredictionDetails,
+    PredictionBetDetails,
+}
+
+impl SnapshotSection {
+    fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::HeapState,
+            Self::RoomDetails,
+            Self::BetDetails,
+            Self::PostPrincipal,
+            Self::SlotDetails,
+            Self::WatchHistory,
+            Self::SuccessHistory,
+            Self::TokenRoots,
+            Self::SlotTabulationQueue,
+        ]
+        .into_iter()
+        .chain(AppStorageSnapshotSection::ALL.map(Self::AppStorage))
+        .chain([
+            Self::Comments,
+            Self::PredictionDetails,
+            Self::PredictionBetDetails,
+        ])
+    }
+}
+
+/// Where saving continues on the next call: the section being saved
+/// and the stored key of the last record saved from it
+#[derive(Clone, Default)]
+struct SnapshotSaveCursor {
+    section: SnapshotSection,
+    last_key: Option<Vec<u8>>,
+}
+
+/// A snapshot that is being saved across calls
+#[derive(Default)]
+struct SnapshotBeingSaved {
+    cursor: SnapshotSaveCursor,
+    chunk_infos: Vec<SnapshotChunkInfo>,
+}
+
+#[derive(Default)]
+pub struct SnapshotData {
+    pub manifest: Option<SnapshotManifest>,
+    pub chunks: BTreeMap<u64, Vec<u8>>,
+    pub chunks_restored: u64,
+    snapshot_being_saved: Option<SnapshotBeingSaved>,
+}
+
+impl SnapshotRecord {
+    /// Where saving continues once this record is saved
+    fn cursor_after(&self) -> SnapshotSaveCursor {
+        let (section, last_key) = match self {
+            SnapshotRecord::HeapState(_) => (SnapshotSection::HeapState, vec![]),
+            SnapshotRecord::RoomDetails(room_id, _) => (
+                SnapshotSection::RoomDetails,
+                room_id.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::BetDetails(bet_id, _) => {
+                (SnapshotSection::BetDetails, bet_id.to_bytes().into_owned())
+            }
+            SnapshotRecord::PostPrincipal(post_id, principal) => (
+                SnapshotSection::PostPrincipal,
+                (*post_id, principal.clone()).to_bytes().into_owned(),
+            ),
+            SnapshotRecord::SlotDetails(post_id, slot_id, _) => (
+                SnapshotSection::SlotDetails,
+                (*post_id, *slot_id).to_bytes().into_owned(),
+            ),
+            SnapshotRecord::WatchHistory(item) => {
+                (SnapshotSection::WatchHistory, item.to_bytes().into_owned())
+            }
+            SnapshotRecord::SuccessHistory(item) => (
+                SnapshotSection::SuccessHistory,
+                item.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::TokenRoot(token_root) => (
+                SnapshotSection::TokenRoots,
+                token_root.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::SlotTabulationJob(job) => (
+                SnapshotSection::SlotTabulationQueue,
+                job.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::AppStorage(entry) => (
+                SnapshotSection::AppStorage(entry.section()),
+                entry.key_bytes(),
+            ),
+            SnapshotRecord::Comment(global_comment_id, _) => (
+                SnapshotSection::Comments,
+                global_comment_id.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::PredictionDetails(post_id, _) => (
+                SnapshotSection::PredictionDetails,
+                post_id.to_bytes().into_owned(),
+            ),
+            SnapshotRecord::PredictionBetDetails(global_prediction_bet_id, _) => (
+                SnapshotSection::PredictionBetDetails,
+                global_prediction_bet_id.to_bytes().into_owned(),
+            ),
+        };
+
+        SnapshotSaveCursor {
+            section,
+            last_key: Some(last_key),
+        }
+    }
+}
+
+/// Records of one section, starting after `last_key` when it is set
+fn section_records<'a>(
+    canister_data: &'a CanisterData,
+    section: SnapshotSection,
+    last_key: Option<&[u8]>,
+) -> Box<dyn Iterator<Item = SnapshotRecord> + 'a> {
+    match section {
+        SnapshotSection::HeapState => match last_key {
+            Some(_) => Box::new(iter::empty()),
+            None => Box::new(iter::once(SnapshotRecord::HeapState(Box::new(
+                CanisterDataForSnapshot::from_heap_state(canister_data),
+            )))),
+        },
+        SnapshotSection::RoomDetails => Box::new(
+            stable_entries_after(&canister_data.room_details_map, last_key)
+                .map(|(room_id, room_details)| SnapshotRecord::RoomDetails(room_id, room_details)),
+        ),
+        SnapshotSection::BetDetails => Box::new(
+            stable_entries_after(&canister_data.bet_details_map, last_key)
+                .map(|(bet_id, bet_details)| SnapshotRecord::BetDetails(bet_id, bet_details)),
+        ),
+        SnapshotSection::PostPrincipal => Box::new(
+            stable_entries_after(&canister_data.post_principal_map, last_key)
+                .map(|((post_id, principal), _)| SnapshotRecord::PostPrincipal(post_id, principal)),
+        ),
+        SnapshotSection::SlotDetails => Box::new(
+            stable_entries_after(&canister_data.slot_details_map, last_key).map(
+                |((post_id, slot_id), slot_details)| {
+                    SnapshotRecord::SlotDetails(post_id, slot_id, slot_details)
+                },
+            ),
+        ),
+        SnapshotSection::WatchHistory => Box::new(
+            stable_entries_after(&canister_data.watch_history, last_key)
+                .map(|(item, _)| SnapshotRecord::WatchHistory(item)),
+        ),
+        SnapshotSection::SuccessHistory => Box::new(
+            stable_entries_after(&canister_data.success_history, last_key)
+                .map(|(item, _)| SnapshotRecord::SuccessHistory(item)),
+        ),
+        SnapshotSection::TokenRoots => Box::new(
+            stable_entries_after(&canister_data.token_roots, last_key)
+                .map(|(token_root, _)| SnapshotRecord::TokenRoot(token_root)),
+        ),
+        SnapshotSection::SlotTabulationQueue => Box::new(
+            stable_entries_after(&canister_data.slot_tabulation_queue, last_key)
+                .map(|(job, _)| SnapshotRecord::SlotTabulationJob(job)),
+        ),
+        SnapshotSection::AppStorage(app_storage_section) => Box::new(
+            canister_data
+                .app_storage
+                .snapshot_entries_after(app_storage_section, last_key)
+                .map(SnapshotRecord::AppStorage),
+        ),
+        SnapshotSection::Comments => Box::new(
+            stable_entries_after(&canister_data.comments_map, last_key).map(
+                |(global_comment_id, comment)| SnapshotRecord::Comment(global_comment_id, comment),
+            ),
+        ),
+        SnapshotSection::PredictionDetails => Box::new(
+            stable_entries_after(&canister_data.prediction_details_map, last_key).map(
+                |(post_id, prediction_details)| {
+                    SnapshotRecord::PredictionDetails(post_id, prediction_details)
+                },
+            ),
+        ),
+        SnapshotSection::PredictionBetDetails => Box::new(
+            stable_entries_after(&canister_data.prediction_bet_details_map, last_key).map(
+                |(global_prediction_bet_id, prediction_bet_details)| {
+                    SnapshotRecord::PredictionBetDetails(
+                        global_prediction_bet_id,
+                        prediction_bet_details,
+                    )
+                },
+            ),
+        ),
+    }
+}
+
+/// Records still to be saved, read from the cursor on
+fn snapshot_records_from(
+    canister_data: &CanisterData,
+    cursor: SnapshotSaveCursor,
+) -> impl Iterator<Item = SnapshotRecord> + '_ {
+    SnapshotSection::all()
+        .filter(move |section| *section >= cursor.section)
+        .flat_map(move |section| {
+            let last_key = if section == cursor.section {
+                cursor.last_key.as_deref()
+            } else {
+                None
+            };
+            section_records(canister_data, section, last_key)
+        })
+}
+
+fn apply_snapshot_record(
+    canister_data: &mut CanisterData,
+    record: SnapshotRecord,
+) -> Result<(), SnapshotError> {
+    match record {
+        SnapshotRecord::HeapState(heap_state) => {
+            if heap_state.schema_version > SNAPSHOT_SCHEMA_VERSION {
+                return Err(SnapshotError::UnsupportedSchemaVersion);
+            }
+            // * the heap state comes first, so nothing held before the restore survives it
+            canister_data.clear_stable_structures();
+            *canister_data = CanisterData::from(*heap_state);
+        }
+        SnapshotRecord::RoomDetails(room_id, room_details) => {
+            canister_data.room_details_map.insert(room_id, room_details);
+        }
+        SnapshotRecord::BetDetails(bet_id, bet_details) => {
+            canister_data.bet_details_map.insert(bet_id, bet_details);
+        }
+        SnapshotRecord::PostPrincipal(post_id, principal) => {
+            canister_data
+                .post_principal_map
+                .insert((post_id, principal), ());
+        }
+        SnapshotRecord::SlotDetails(post_id, slot_id, slot_details) => {
+            canister_data
+                .slot_details_map
+                .insert((post_id, slot_id), slot_details);
+        }
+        SnapshotRecord::WatchHistory(item) => {
+            canister_data.watch_history.insert(item, ());
+        }
+        SnapshotRecord::SuccessHistory(item) => {
+            canister_data.success_history.insert(item, ());
+        }
+        SnapshotRecord::TokenRoot(token_root) => {
+            canister_data.token_roots.insert(token_root, ());
+        }
+        SnapshotRecord::SlotTabulationJob(job) => {
+            canister_data.slot_tabulation_queue.insert(job, ());
+        }
+        SnapshotRecord::AppStorage(entry) => {
+            canister_data.app_storage.restore_snapshot_entry(entry);
+        }
+        SnapshotRecord::Comment(global_comment_id, comment) => {
+            canister_data
+                .comments_map
+                .insert(global_comment_id, comment);
+        }
+        SnapshotRecord::PredictionDetails(post_id, prediction_details) => {
+            canister_data
+                .prediction_details_map
+                .insert(post_id, prediction_details);
+        }
+        SnapshotRecord::PredictionBetDetails(global_prediction_bet_id, prediction_bet_details) => {
+            canister_data
+                .prediction_bet_details_map
+                .insert(global_prediction_bet_id, prediction_bet_details);
+        }
+    }
+
+    Ok(())
+}
+
+fn checksum(bytes: &[u8]) -> Vec<u8> {
+    Sha256::digest(bytes).to_vec()
+}
+
+fn verify_chunk(chunk_info: &SnapshotChunkInfo, chunk: &[u8]) -> Result<(), SnapshotError> {
+    if chunk.len() as u64 != chunk_info.length || checksum(chunk) != chunk_info.checksum {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+
+    Ok(())
+}
+
+fn save_chunk(
+    chunks: &mut BTreeMap<u64, Vec<u8>>,
+    chunk_infos: &mut Vec<SnapshotChunkInfo>,
+    chunk: Vec<u8>,
+) {
+    chunk_infos.push(SnapshotChunkInfo {
+        length: chunk.len() as u64,
+        checksum: checksum(&chunk),
+    });
+    chunks.insert(chunks.len() as u64, chunk);
+}
+
+impl SnapshotData {
+    /// Saves at most `max_chunks` more chunks, continuing where the previous call stopped.
+    /// A new snapshot is started, dropping whatever was held before, when none is being saved.
+    pub fn save_next_chunks(
+        &mut self,
+        canister_data: &CanisterData,
+        max_chunks: u64,
+    ) -> SnapshotSaveProgress {
+        self.save_next_chunks_with_chunk_size(
+            canister_data,
+            max_chunks,
+            SNAPSHOT_CHUNK_SIZE_IN_BYTES,
+        )
+    }
+
+    fn save_next_chunks_with_chunk_size(
+        &mut self,
+        canister_data: &CanisterData,
+        max_chunks: u64,
+        chunk_size: usize,
+    ) -> SnapshotSaveProgress {
+        if self.snapshot_being_saved.is_none() {
+            *self = Self::default();
+        }
+        let snapshot_being_saved = self
+            .snapshot_being_saved
+            .get_or_insert_with(SnapshotBeingSaved::default);
+
+        let mut records =
+            snapshot_records_from(canister_data, snapshot_being_saved.cursor.clone()).peekable();
+        let mut current_chunk = vec![];
+        let mut chunks_saved_in_this_call = 0;
+
+        while let Some(record) = records.peek() {
+            let mut record_bytes = vec![];
+            ciborium::ser::into_writer(record, &mut record_bytes).unwrap();
+
+            if !current_chunk.is_empty() && current_chunk.len() + record_bytes.len() > chunk_size {
+                save_chunk(
+                    &mut self.chunks,
+                    &mut snapshot_being_saved.chunk_infos,
+                    std::mem::take(&mut current_chunk),
+                );
+                chunks_saved_in_this_call += 1;
+
+                // * the record that did not fit is saved first thing on the next call
+                if chunks_saved_in_this_call >= max_chunks.max(1) {
+                    return SnapshotSaveProgress {
+                        chunks_saved: self.chunks.len() as u64,
+                        manifest: None,
+                    };
+                }
+            }
+
+            current_chunk.extend(record_bytes);
+            snapshot_being_saved.cursor = record.cursor_after();
+            records.next();
+        }
+
+        // * the heap state record is always there, so the snapshot is never empty
+        if !current_chunk.is_empty() {
+            save_chunk(
+                &mut self.chunks,
+                &mut snapshot_being_saved.chunk_infos,
+                current_chunk,
+            );
+        }
+
+        let chunk_infos = self
+            .snapshot_being_saved
+            .take()
+            .map(|snapshot_being_saved| snapshot_being_saved.chunk_infos)
+            .unwrap_or_default();
+        let manifest = SnapshotManifest {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            total_length: chunk_infos.iter().map(|chunk_info| chunk_info.length).sum(),
+            chunks: chunk_infos,
+        };
+        self.manifest = Some(manifest.clone());
+
+        SnapshotSaveProgress {
+            chunks_saved: self.chunks.len() as u64,
+            manifest: Some(manifest),
+        }
+    }
+
+    /// Starts receiving a snapshot taken elsewhere, dropping whatever was held before
+    pub fn from_manifest(manifest: SnapshotManifest) -> Result<Self, SnapshotError> {
+        if manifest.schema_version > SNAPSHOT_SCHEMA_VERSION {
+            return Err(SnapshotError::UnsupportedSchemaVersion);
+        }
+
+        Ok(Self {
+            manifest: Some(manifest),
+            ..Default::default()
+        })
+    }
+
+    fn chunk_info(&self, chunk_index: u64) -> Result<&SnapshotChunkInfo, SnapshotError> {
+        self.manifest
+            .as_ref()
+            .ok_or(SnapshotError::NoSnapshot)?
+            .chunks
+            .get(chunk_index as usize)
+            .ok_or(SnapshotError::ChunkIndexOutOfRange)
+    }
+
+    pub fn get_verified_chunk(&self, chunk_index: u64) -> Result<&Vec<u8>, SnapshotError> {
+        let chunk_info = self.chunk_info(chunk_index)?;
+        let chunk = self
+            .chunks
+            .get(&chunk_index)
+            .ok_or(SnapshotError::MissingChunks)?;
+        verify_chunk(chunk_info, chunk)?;
+
+        Ok(chunk)
+    }
+
+    pub fn receive_chunk(&mut self, chunk_index: u64, chunk: Vec<u8>) -> Result<(), SnapshotError> {
+        verify_chunk(self.chunk_info(chunk_index)?, &chunk)?;
+        self.chunks.insert(chunk_index, chunk);
+
+        Ok(())
+    }
+
+    /// Restores at most `max_chunks` chunks, continuing where the previous call stopped.
+    /// Only starts once every chunk has been received.
+    pub fn restore_next_chunks(
+        &mut self,
+        canister_data: &mut CanisterData,
+        max_chunks: u64,
+    ) -> Result<SnapshotRestoreProgress, SnapshotError> {
+        let total_chunks = self
+            .manifest
+            .as_ref()
+            .ok_or(SnapshotError::NoSnapshot)?
+            .chunks
+            .len() as u64;
+        if self.chunks.len() as u64 != total_chunks {
+            return Err(SnapshotError::MissingChunks);
+        }
+
+        let last_chunk_index = total_chunks.min(self.chunks_restored.saturating_add(max_chunks));
+        for chunk_index in self.chunks_restored..last_chunk_index {
+            let chunk = self.get_verified_chunk(chunk_index)?;
+
+            let mut reader = Cursor::new(chunk.as_slice());
+            while (reader.position() as usize) < chunk.len() {
+                let record: SnapshotRecord = ciborium::de::from_reader(&mut reader)
+                    .map_err(|e| SnapshotError::MalformedChunk(format!("{e:?}")))?;
+                apply_snapshot_record(canister_data, record)?;
+            }
+
+            self.chunks_restored = chunk_index + 1;
+        }
+
+        Ok(SnapshotRestoreProgress {
+            chunks_restored: self.chunks_restored,
+            total_chunks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{BetDirection, BetPayout},
+        kv_storage::NamespaceAccessLevel,
+    };
+    use test_utils::setup::test_constants::get_mock_user_alice_principal_id;
+
+    use crate::{
+        data_model::{kv_storage::AppStorage, memory},
+        CANISTER_DATA,
+    };
+
+    use super::*;
+
+    fn snapshot_json(canister_data: &CanisterData) -> String {
+        serde_json::to_string(&CanisterDataForSnapshot::from(canister_data)).unwrap()
+    }
+
+    #[test]
+    fn test_chunked_snapshot_restores_incrementally() {
+        let alice = get_mock_user_alice_principal_id();
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data.profile.principal_id = Some(alice);
+            (0..20).for_each(|room_id| {
+                let global_room_id = GlobalRoomId(1, 1, room_id);
+                canister_data
+                    .room_details_map
+                    .insert(global_room_id, RoomDetailsV1::default());
+                canister_data.bet_details_map.insert(
+                    GlobalBetId(global_room_id, StablePrincipal(alice)),
+                    BetDetails {
+                        amount: 10,
+                        bet_direction: BetDirection::Not,
+                        payout: BetPayout::NotCalculatedYet,
+                        bet_maker_canister_id: alice,
+                        bet_maker_informed_status: None,
+                        idempotency_key: None,
+                        placed_at: None,
+                    },
+                );
+            });
+            canister_data.token_roots.insert(alice, ());
+        });
+        let namespace = AppStorage::create_a_namespace(alice, "app".into()).unwrap();
+        AppStorage::get_a_namespace(alice, namespace.id, NamespaceAccessLevel::ReadWrite)
+            .unwrap()
+            .write_key_value_pair("key".into(), "value".into())
+            .unwrap();
+
+        let mut source = SnapshotData::default();
+        let (manifest, save_calls, expected_json) = CANISTER_DATA.with_borrow(|canister_data| {
+            let mut save_calls = 0;
+            loop {
+                let progress = source.save_next_chunks_with_chunk_size(canister_data, 2, 256);
+                save_calls += 1;
+                if let Some(manifest) = progress.manifest {
+                    break (manifest, save_calls, snapshot_json(canister_data));
+                }
+                assert_eq!(progress.chunks_saved, save_calls * 2);
+            }
+        });
+        assert!(manifest.chunks.len() > 2);
+        assert_eq!(save_calls, manifest.chunks.len().div_ceil(2) as u64);
+        assert_eq!(source.manifest, Some(manifest.clone()));
+
+        // * restore onto empty stable memory so nothing survives except through the snapshot
+        memory::init_memory_manager();
+        CANISTER_DATA.with_borrow_mut(|canister_data| *canister_data = CanisterData::default());
+
+        let mut target = SnapshotData::from_manifest(manifest.clone()).unwrap();
+        let mut corrupted_chunk = source.get_verified_chunk(0).unwrap().clone();
+        corrupted_chunk[0] ^= 1;
+        assert_eq!(
+            target.receive_chunk(0, corrupted_chunk),
+            Err(SnapshotError::ChecksumMismatch)
+        );
+        assert_eq!(
+            target.receive_chunk(manifest.chunks.len() as u64, vec![]),
+            Err(SnapshotError::ChunkIndexOutOfRange)
+        );
+
+        // * entries held before the restore must not survive it
+        let mut restored_canister_data = CanisterData::default();
+        restored_canister_data
+            .room_details_map
+            .insert(GlobalRoomId(9, 9, 9), RoomDetailsV1::default());
+        assert_eq!(
+            target.restore_next_chunks(&mut restored_canister_data, 1),
+            Err(SnapshotError::MissingChunks)
+        );
+
+        // * chunks can arrive in any order
+        (0..manifest.chunks.len() as u64)
+            .rev()
+            .for_each(|chunk_index| {
+                target
+                    .receive_chunk(
+                        chunk_index,
+                        source.get_verified_chunk(chunk_index).unwrap().clone(),
+                    )
+                    .unwrap();
+            });
+
+        let mut calls = 0;
+        loop {
+            let progress = target
+                .restore_next_chunks(&mut restored_canister_data, 2)
+                .unwrap();
+            calls += 1;
+            if progress.is_complete() {
+                break;
+            }
+        }
+        assert_eq!(calls, manifest.chunks.len().div_ceil(2));
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            *canister_data = restored_canister_data;
+            assert_eq!(snapshot_json(canister_data), expected_json);
+        });
+    }
+
+    #[test]
+    fn test_newer_snapshot_schema_is_rejected() {
+        let manifest = SnapshotManifest {
+            schema_version: SNAPSHOT_SCHEMA_VERSION + 1,
+            total_length: 0,
+            chunks: vec![],
+        };
+
+        assert!(matches!(
+            SnapshotData::from_manifest(manifest),
+            Err(SnapshotError::UnsupportedSchemaVersion)
+        ));
+    }
+}
@@ -1,79 +1,60 @@
-use crate::{
-    data_model::memory::{self, MEMORY_MANAGER},
-    CANISTER_DATA, SNAPSHOT_DATA,
-};
-use candid::Principal;
-use ic_cdk::api::stable;
+use crate::{CANISTER_DATA, SNAPSHOT_DATA};
 use ic_cdk_macros::{query, update};
-use ic_stable_structures::{memory_manager::MemoryId, writer::Writer, Memory};
-use shared_utils::common::utils::permissions::is_reclaim_canister_id;
-use shared_utils::constant::RECLAIM_CANISTER_PRINCIPAL_ID;
-
-use super::CanisterDataForSnapshot;
-
-#[update(guard = "is_reclaim_canister_id")]
-fn save_snapshot_json() -> u32 {
-    let mut state_bytes = vec![];
-
-    CANISTER_DATA.with(|canister_data_ref_cell| {
-        let canister_data_snapshot =
-            CanisterDataForSnapshot::from(&*canister_data_ref_cell.borrow());
-
-        let serde_str = serde_json::to_string(&canister_data_snapshot).unwrap();
-        state_bytes = serde_str.as_bytes().to_vec();
-    });
-
-    let len = state_bytes.len() as u32;
-
-    SNAPSHOT_DATA.with(|snapshot_data_ref_cell| {
-        *snapshot_data_ref_cell.borrow_mut() = state_bytes;
-    });
+use shared_utils::canister_specific::individual_user_template::types::snapshot::{
+    SnapshotError, SnapshotManifest, SnapshotRestoreProgress, SnapshotSaveProgress,
+};
+use shared_utils::common::utils::permissions::is_caller_controller_or_reclaim_canister_id;
+
+use super::chunked_snapshot::SnapshotData;
+
+/// Saves at most `max_chunks` chunks per call.
+/// Keep calling until the returned progress carries the manifest.
+/// A new snapshot is started when none is being saved, so keep the canister frozen meanwhile.
+#[update(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn save_snapshot(max_chunks: u64) -> SnapshotSaveProgress {
+    SNAPSHOT_DATA.with_borrow_mut(|snapshot| {
+        CANISTER_DATA
+            .with_borrow(|canister_data| snapshot.save_next_chunks(canister_data, max_chunks))
+    })
+}
 
-    len
+#[query(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn get_snapshot_manifest() -> Option<SnapshotManifest> {
+    SNAPSHOT_DATA.with_borrow(|snapshot| snapshot.manifest.clone())
 }
 
-#[query(guard = "is_reclaim_canister_id")]
-fn download_snapshot(offset: u64, length: u64) -> Vec<u8> {
-    let state_bytes = SNAPSHOT_DATA.with(|snapshot_data_ref_cell| {
-        let snapshot = snapshot_data_ref_cell.borrow();
+/// Chunks are checked against the manifest before they are returned
+#[query(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn download_snapshot(chunk_index: u64) -> Result<Vec<u8>, SnapshotError> {
+    SNAPSHOT_DATA.with_borrow(|snapshot| snapshot.get_verified_chunk(chunk_index).cloned())
+}
 
-        snapshot[offset as usize..(offset + length) as usize].to_vec()
-    });
+/// Has to be called before any chunk of a snapshot taken elsewhere is uploaded
+#[update(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn receive_snapshot_manifest(manifest: SnapshotManifest) -> Result<(), SnapshotError> {
+    let snapshot_data = SnapshotData::from_manifest(manifest)?;
+    SNAPSHOT_DATA.with_borrow_mut(|snapshot| *snapshot = snapshot_data);
 
-    state_bytes
+    Ok(())
 }
 
-#[update(guard = "is_reclaim_canister_id")]
-fn receive_and_save_snaphot(offset: u64, state_bytes: Vec<u8>) {
-    SNAPSHOT_DATA.with(|snapshot_data_ref_cell| {
-        let mut snapshot = snapshot_data_ref_cell.borrow_mut();
-        // grow snapshot if needed
-        if snapshot.len() < (offset + state_bytes.len() as u64) as usize {
-            snapshot.resize((offset + state_bytes.len() as u64) as usize, 0);
-        }
-        snapshot.splice(
-            offset as usize..(offset + state_bytes.len() as u64) as usize,
-            state_bytes,
-        );
-    });
+#[update(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn receive_and_save_snaphot(chunk_index: u64, chunk: Vec<u8>) -> Result<(), SnapshotError> {
+    SNAPSHOT_DATA.with_borrow_mut(|snapshot| snapshot.receive_chunk(chunk_index, chunk))
 }
 
-#[update(guard = "is_reclaim_canister_id")]
-fn load_snapshot(length: u64) {
-    let state_bytes =
-        SNAPSHOT_DATA.with(|snapshot_data_ref_cell| snapshot_data_ref_cell.borrow().clone());
-
-    let canister_data_snapshot: CanisterDataForSnapshot =
-        serde_json::from_str(std::str::from_utf8(&state_bytes).unwrap()).unwrap();
-
-    CANISTER_DATA.with(|canister_data_ref_cell| {
-        *canister_data_ref_cell.borrow_mut() = canister_data_snapshot.into();
-    });
+/// Restores at most `max_chunks` chunks per call.
+/// Keep calling until the returned progress is complete.
+#[update(guard = "is_caller_controller_or_reclaim_canister_id")]
+fn load_snapshot(max_chunks: u64) -> Result<SnapshotRestoreProgress, SnapshotError> {
+    SNAPSHOT_DATA.with_borrow_mut(|snapshot| {
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            snapshot.restore_next_chunks(canister_data, max_chunks)
+        })
+    })
 }
 
-#[update(guard = "is_reclaim_canister_id")]
+#[update(guard = "is_caller_controller_or_reclaim_canister_id")]
 fn clear_snapshot() {
-    SNAPSHOT_DATA.with(|snapshot_data_ref_cell| {
-        *snapshot_data_ref_cell.borrow_mut() = vec![];
-    });
+    SNAPSHOT_DATA.with_borrow_mut(|snapshot| *snapshot = SnapshotData::default());
 }
@@ -10,18 +10,29 @@ use serde::Serialize;
 use serde_json_any_key::*;
 use shared_utils::{
     canister_specific::individual_user_template::types::{
+        cdao::DeployedCdaoCanisters,
+        comment::{Comment, CommentRateLimitWindow, GlobalCommentId},
         configuration::IndividualUserConfiguration,
+        device_id::DeviceIdentity,
         follow::{FollowData, FollowEntryDetail, FollowEntryId, FollowList},
         hot_or_not::{
-            AggregateStats, BetDetails, BetMaker, BetMakerPrincipal, GlobalBetId, GlobalRoomId,
-            HotOrNotDetails, PlacedBetDetail, RoomDetailsV1, RoomId, SlotDetailsV1, SlotId,
-            StablePrincipal,
+            game_config::HotOrNotGameConfig, AggregateStats, BetDetails, BetIdempotencyKey,
+            BetMaker, BetMakerPrincipal, BetReconciliationDetails, GlobalBetId, GlobalRoomId,
+            HotOrNotDetails, PendingBet, PendingBetWithdrawal, PendingPayoutNotification,
+            PlacedBetDetail, RoomDetailsV1, RoomId, SlotDetails, SlotDetailsV1, SlotId,
+            SlotTabulationJob, StablePrincipal,
         },
         migration::MigrationInfo,
-        post::{FeedScore, Post, PostViewStatistics},
+        ml_data::{MLFeedCacheItem, SuccessHistoryItemV1, WatchHistoryItem},
+        post::{FeedScore, Post, PostRevision, PostTipDetails, PostViewStatistics},
+        prediction::{
+            GlobalPredictionBetId, PendingPredictionBet, PlacedPredictionBetDetail,
+            PredictionBetDetails, PredictionDetails, PredictionSettlementJob,
+        },
         profile::UserProfile,
+        report::PostReports,
         session::SessionType,
-        token::TokenBalance,
+        token::{PendingPostTip, PendingTokenTransfer, TokenBalance, TokenTransferIdempotencyKey},
     },
     common::types::{
         app_primitive_type::PostId,
@@ -36,16 +47,30 @@ use shared_utils::{
     },
 };
 
-use crate::data_model::_default_room_details;
+use crate::data_model::{
+    kv_storage::{AppStorage, AppStorageForSnapshot},
+    _default_comments_map, _default_prediction_bet_details_map, _default_prediction_details_map,
+    _default_room_details, _default_slot_tabulation_queue, _default_success_history_v1,
+    _default_token_list, _default_watch_history,
+};
 use crate::data_model::{
     CanisterData, _default_bet_details, _default_post_principal_map, _default_slot_details_map,
 };
 
+/// Bumped whenever the snapshot format changes.
+/// Snapshots taken before versioning deserialize as version 0.
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+pub mod chunked_snapshot;
 pub mod get_snapshot;
 pub mod serde_json_snapshot_test;
 
+/// Mirrors every field of `CanisterData` except `ingress_frozen_for_migration`
+/// and `migrated_to_canister_id`, a restored canister always starts unfrozen and in place.
 #[derive(Deserialize, Serialize)]
 pub struct CanisterDataForSnapshot {
+    #[serde(default)]
+    pub schema_version: u32,
     // Key is Post ID
     pub all_created_posts: BTreeMap<u64, PostForSnapshot>,
     #[serde(with = "any_key_map")]
@@ -63,6 +88,14 @@ pub struct CanisterDataForSnapshot {
     #[serde(with = "any_key_map")]
     pub known_principal_ids: KnownPrincipalMap,
     pub my_token_balance: TokenBalanceForSnapshot,
+    #[serde(default, with = "any_key_map")]
+    pub pending_token_transfers: BTreeMap<TokenTransferIdempotencyKey, PendingTokenTransfer>,
+    #[serde(default, with = "any_key_map")]
+    pub pending_post_tips: BTreeMap<TokenTransferIdempotencyKey, PendingPostTip>,
+    #[serde(default)]
+    pub next_token_transfer_idempotency_key: TokenTransferIdempotencyKey,
+    #[serde(default)]
+    pub received_token_transfer_keys: BTreeMap<Principal, BTreeSet<TokenTransferIdempotencyKey>>,
     pub posts_index_sorted_by_home_feed_score: PostScoreIndexForSnapshot,
     pub posts_index_sorted_by_hot_or_not_feed_score: PostScoreIndexForSnapshot,
     pub principals_i_follow: BTreeSet<Principal>,
@@ -73,6 +106,61 @@ pub struct CanisterDataForSnapshot {
     pub last_access_time: Option<SystemTime>,
     pub last_canister_functionality_access_time: Option<SystemTime>,
     pub migration_info: MigrationInfo,
+    #[serde(default)]
+    pub app_storage: AppStorageForSnapshot,
+    #[serde(default)]
+    pub watch_history: Vec<WatchHistoryItem>,
+    #[serde(default)]
+    pub success_history: Vec<SuccessHistoryItemV1>,
+    #[serde(default)]
+    pub device_identities: Vec<DeviceIdentity>,
+    #[serde(default)]
+    pub ml_feed_cache: Vec<MLFeedCacheItem>,
+    #[serde(default)]
+    pub cdao_canisters: Vec<DeployedCdaoCanisters>,
+    #[serde(default)]
+    pub token_roots: Vec<Principal>,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    #[serde(default)]
+    pub slot_tabulation_queue: Vec<SlotTabulationJob>,
+    #[serde(default)]
+    pub posts_pending_purge: BTreeSet<PostId>,
+    #[serde(default)]
+    pub comments: Vec<(GlobalCommentId, Comment)>,
+    #[serde(default)]
+    pub comment_rate_limit_windows: BTreeMap<Principal, CommentRateLimitWindow>,
+    #[serde(default)]
+    pub post_reports: BTreeMap<PostId, PostReports>,
+    #[serde(default)]
+    pub blocked_principals: BTreeSet<Principal>,
+    #[serde(default)]
+    pub muted_principals: BTreeSet<Principal>,
+    #[serde(default, with = "any_key_map")]
+    pub pending_bets: BTreeMap<(CanisterId, PostId), PendingBet>,
+    #[serde(default, with = "any_key_map")]
+    pub pending_bet_withdrawals: BTreeMap<(CanisterId, PostId), PendingBetWithdrawal>,
+    #[serde(default)]
+    pub next_bet_idempotency_key: BetIdempotencyKey,
+    #[serde(default, with = "any_key_map")]
+    pub payout_notification_outbox: BTreeMap<GlobalBetId, PendingPayoutNotification>,
+    #[serde(default, with = "any_key_map")]
+    pub withdrawn_bets: BTreeMap<(PostId, Principal), BetReconciliationDetails>,
+    #[serde(default)]
+    pub predictions: Vec<(PostId, PredictionDetails)>,
+    #[serde(default)]
+    pub prediction_bets: Vec<(GlobalPredictionBetId, PredictionBetDetails)>,
+    #[serde(default, with = "any_key_map")]
+    pub all_prediction_bets_placed: BTreeMap<(CanisterId, PostId), PlacedPredictionBetDetail>,
+    #[serde(default, with = "any_key_map")]
+    pub pending_prediction_bets: BTreeMap<(CanisterId, PostId), PendingPredictionBet>,
+    #[serde(default, with = "any_key_map")]
+    pub prediction_payout_notification_outbox:
+        BTreeMap<GlobalPredictionBetId, PendingPayoutNotification>,
+    #[serde(default)]
+    pub prediction_settlement_queue: BTreeSet<PredictionSettlementJob>,
+    #[serde(default)]
+    pub former_canister_ids: Vec<Principal>,
 }
 
 #[derive(CandidType, Clone, Deserialize, Debug, Serialize)]
@@ -91,12 +179,24 @@ pub struct PostForSnapshot {
     #[serde(default)]
     pub is_nsfw: bool,
     pub slots_left_to_be_computed: HashSet<SlotId>,
+    #[serde(default)]
+    pub tip_details: PostTipDetails,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    #[serde(default)]
+    pub revisions: Vec<PostRevision>,
+    #[serde(default)]
+    pub comment_count: u64,
+    #[serde(default)]
+    pub commenter_count: u64,
 }
 
 #[derive(CandidType, Clone, Deserialize, Debug, Serialize, Default)]
 pub struct HotOrNotDetailsForSnapshot {
     pub hot_or_not_feed_score: FeedScore,
     pub aggregate_stats: AggregateStats,
+    #[serde(default)]
+    pub slot_history: BTreeMap<SlotId, SlotDetails>,
 }
 
 #[derive(Default, Clone, Deserialize, CandidType, Debug, Serialize)]
@@ -131,6 +231,44 @@ pub struct PostScoreIndexForSnapshot {
 
 impl From<&CanisterData> for CanisterDataForSnapshot {
     fn from(canister_data: &CanisterData) -> Self {
+        Self {
+            room_details_map: canister_data.room_details_map.iter().collect(),
+            bet_details_map: canister_data.bet_details_map.iter().collect(),
+            post_principal_map: canister_data.post_principal_map.iter().collect(),
+            slot_details_map: canister_data.slot_details_map.iter().collect(),
+            app_storage: AppStorageForSnapshot::from(&canister_data.app_storage),
+            watch_history: canister_data
+                .watch_history
+                .iter()
+                .map(|(item, _)| item)
+                .collect(),
+            success_history: canister_data
+                .success_history
+                .iter()
+                .map(|(item, _)| item)
+                .collect(),
+            token_roots: canister_data
+                .token_roots
+                .iter()
+                .map(|(token_root, _)| token_root)
+                .collect(),
+            slot_tabulation_queue: canister_data
+                .slot_tabulation_queue
+                .iter()
+                .map(|(job, _)| job)
+                .collect(),
+            comments: canister_data.comments_map.iter().collect(),
+            predictions: canister_data.prediction_details_map.iter().collect(),
+            prediction_bets: canister_data.prediction_bet_details_map.iter().collect(),
+            ..Self::from_heap_state(canister_data)
+        }
+    }
+}
+
+impl CanisterDataForSnapshot {
+    /// Leaves out the entries of stable structures,
+    /// the binary snapshot streams those as separate records
+    pub fn from_heap_state(canister_data: &CanisterData) -> Self {
         let mut all_created_posts: BTreeMap<u64, PostForSnapshot> = BTreeMap::new();
         canister_data.all_created_posts.iter().for_each(|(k, v)| {
             let hot_or_not_details = v.hot_or_not_details.clone();
@@ -138,6 +276,7 @@ impl From<&CanisterData> for CanisterDataForSnapshot {
                 hot_or_not_details.map(|hot_or_not_details| HotOrNotDetailsForSnapshot {
                     hot_or_not_feed_score: hot_or_not_details.hot_or_not_feed_score,
                     aggregate_stats: hot_or_not_details.aggregate_stats.clone(),
+                    slot_history: hot_or_not_details.slot_history.clone(),
                 });
 
             let post_details = PostForSnapshot {
@@ -154,31 +293,16 @@ impl From<&CanisterData> for CanisterDataForSnapshot {
                 hot_or_not_details: hot_or_not_details_snapshot,
                 is_nsfw: v.is_nsfw,
                 slots_left_to_be_computed: v.slots_left_to_be_computed.clone(),
+                tip_details: v.tip_details.clone(),
+                hot_or_not_game_config: v.hot_or_not_game_config.clone(),
+                revisions: v.revisions.clone(),
+                comment_count: v.comment_count,
+                commenter_count: v.commenter_count,
             };
 
             all_created_posts.insert(k.clone(), post_details);
         });
 
-        let mut room_details_map: BTreeMap<GlobalRoomId, RoomDetailsV1> = BTreeMap::new();
-        canister_data.room_details_map.iter().for_each(|(k, v)| {
-            room_details_map.insert(k, v.clone());
-        });
-
-        let mut bet_details_map: BTreeMap<GlobalBetId, BetDetails> = BTreeMap::new();
-        canister_data.bet_details_map.iter().for_each(|(k, v)| {
-            bet_details_map.insert(k, v.clone());
-        });
-
-        let mut post_principal_map: BTreeMap<(PostId, StablePrincipal), ()> = BTreeMap::new();
-        canister_data.post_principal_map.iter().for_each(|(k, v)| {
-            post_principal_map.insert(k, v.clone());
-        });
-
-        let mut slot_details_map: BTreeMap<(PostId, SlotId), SlotDetailsV1> = BTreeMap::new();
-        canister_data.slot_details_map.iter().for_each(|(k, v)| {
-            slot_details_map.insert(k, v.clone());
-        });
-
         let my_token_balance = TokenBalanceForSnapshot {
             utility_token_balance: canister_data.my_token_balance.utility_token_balance,
             utility_token_transaction_history: canister_data
@@ -222,16 +346,21 @@ impl From<&CanisterData> for CanisterDataForSnapshot {
         };
 
         Self {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
             all_created_posts,
-            room_details_map,
-            bet_details_map,
-            post_principal_map,
-            slot_details_map,
+            room_details_map: BTreeMap::new(),
+            bet_details_map: BTreeMap::new(),
+            post_principal_map: BTreeMap::new(),
+            slot_details_map: BTreeMap::new(),
             all_hot_or_not_bets_placed: canister_data.all_hot_or_not_bets_placed.clone(),
             configuration: canister_data.configuration.clone(),
             follow_data,
             known_principal_ids: canister_data.known_principal_ids.clone(),
             my_token_balance,
+            pending_token_transfers: canister_data.pending_token_transfers.clone(),
+            pending_post_tips: canister_data.pending_post_tips.clone(),
+            next_token_transfer_idempotency_key: canister_data.next_token_transfer_idempotency_key,
+            received_token_transfer_keys: canister_data.received_token_transfer_keys.clone(),
             posts_index_sorted_by_home_feed_score,
             posts_index_sorted_by_hot_or_not_feed_score,
             principals_i_follow: canister_data.principals_i_follow.clone(),
@@ -243,6 +372,35 @@ impl From<&CanisterData> for CanisterDataForSnapshot {
             last_canister_functionality_access_time: canister_data
                 .last_canister_functionality_access_time,
             migration_info: canister_data.migration_info,
+            app_storage: AppStorageForSnapshot::from_heap_state(&canister_data.app_storage),
+            watch_history: vec![],
+            success_history: vec![],
+            device_identities: canister_data.device_identities.clone(),
+            ml_feed_cache: canister_data.ml_feed_cache.clone(),
+            cdao_canisters: canister_data.cdao_canisters.clone(),
+            token_roots: vec![],
+            hot_or_not_game_config: canister_data.hot_or_not_game_config.clone(),
+            slot_tabulation_queue: vec![],
+            posts_pending_purge: canister_data.posts_pending_purge.clone(),
+            comments: vec![],
+            comment_rate_limit_windows: canister_data.comment_rate_limit_windows.clone(),
+            post_reports: canister_data.post_reports.clone(),
+            blocked_principals: canister_data.blocked_principals.clone(),
+            muted_principals: canister_data.muted_principals.clone(),
+            pending_bets: canister_data.pending_bets.clone(),
+            pending_bet_withdrawals: canister_data.pending_bet_withdrawals.clone(),
+            next_bet_idempotency_key: canister_data.next_bet_idempotency_key,
+            payout_notification_outbox: canister_data.payout_notification_outbox.clone(),
+            withdrawn_bets: canister_data.withdrawn_bets.clone(),
+            predictions: vec![],
+            prediction_bets: vec![],
+            all_prediction_bets_placed: canister_data.all_prediction_bets_placed.clone(),
+            pending_prediction_bets: canister_data.pending_prediction_bets.clone(),
+            prediction_payout_notification_outbox: canister_data
+                .prediction_payout_notification_outbox
+                .clone(),
+            prediction_settlement_queue: canister_data.prediction_settlement_queue.clone(),
+            former_canister_ids: canister_data.former_canister_ids.clone(),
         }
     }
 }
@@ -256,7 +414,7 @@ impl From<CanisterDataForSnapshot> for CanisterData {
                 hot_or_not_details_snapshot.map(|hot_or_not_details_snapshot| HotOrNotDetails {
                     hot_or_not_feed_score: hot_or_not_details_snapshot.hot_or_not_feed_score,
                     aggregate_stats: hot_or_not_details_snapshot.aggregate_stats.clone(),
-                    slot_history: BTreeMap::new(),
+                    slot_history: hot_or_not_details_snapshot.slot_history.clone(),
                 });
 
             let post_details = Post {
@@ -273,6 +431,11 @@ impl From<CanisterDataForSnapshot> for CanisterData {
                 hot_or_not_details: hot_or_not_details,
                 is_nsfw: v.is_nsfw,
                 slots_left_to_be_computed: v.slots_left_to_be_computed.clone(),
+                tip_details: v.tip_details.clone(),
+                hot_or_not_game_config: v.hot_or_not_game_config.clone(),
+                revisions: v.revisions.clone(),
+                comment_count: v.comment_count,
+                commenter_count: v.commenter_count,
             };
 
             all_created_posts.insert(k.clone(), post_details);
@@ -329,6 +492,52 @@ impl From<CanisterDataForSnapshot> for CanisterData {
                 .clone(),
         };
 
+        let mut watch_history = _default_watch_history();
+        canister_data.watch_history.into_iter().for_each(|item| {
+            watch_history.insert(item, ());
+        });
+
+        let mut success_history = _default_success_history_v1();
+        canister_data.success_history.into_iter().for_each(|item| {
+            success_history.insert(item, ());
+        });
+
+        let mut token_roots = _default_token_list();
+        canister_data.token_roots.into_iter().for_each(|token_root| {
+            token_roots.insert(token_root, ());
+        });
+
+        let mut slot_tabulation_queue = _default_slot_tabulation_queue();
+        canister_data
+            .slot_tabulation_queue
+            .into_iter()
+            .for_each(|job| {
+                slot_tabulation_queue.insert(job, ());
+            });
+
+        let mut comments_map = _default_comments_map();
+        canister_data
+            .comments
+            .into_iter()
+            .for_each(|(global_comment_id, comment)| {
+                comments_map.insert(global_comment_id, comment);
+            });
+
+        let mut prediction_details_map = _default_prediction_details_map();
+        canister_data
+            .predictions
+            .into_iter()
+            .for_each(|(post_id, prediction_details)| {
+                prediction_details_map.insert(post_id, prediction_details);
+            });
+
+        let mut prediction_bet_details_map = _default_prediction_bet_details_map();
+        canister_data.prediction_bets.into_iter().for_each(
+            |(global_prediction_bet_id, prediction_bet_details)| {
+                prediction_bet_details_map.insert(global_prediction_bet_id, prediction_bet_details);
+            },
+        );
+
         let posts_index_sorted_by_hot_or_not_feed_score = PostScoreIndex {
             items_sorted_by_score: canister_data
                 .posts_index_sorted_by_hot_or_not_feed_score
@@ -340,6 +549,7 @@ impl From<CanisterDataForSnapshot> for CanisterData {
                 .clone(),
         };
 
+        // * every field is listed so that a new field does not compile until the snapshot covers it
         Self {
             all_created_posts,
             room_details_map,
@@ -351,6 +561,10 @@ impl From<CanisterDataForSnapshot> for CanisterData {
             follow_data,
             known_principal_ids: canister_data.known_principal_ids,
             my_token_balance,
+            pending_token_transfers: canister_data.pending_token_transfers,
+            pending_post_tips: canister_data.pending_post_tips,
+            next_token_transfer_idempotency_key: canister_data.next_token_transfer_idempotency_key,
+            received_token_transfer_keys: canister_data.received_token_transfer_keys,
             posts_index_sorted_by_home_feed_score,
             posts_index_sorted_by_hot_or_not_feed_score,
             principals_i_follow: canister_data.principals_i_follow,
@@ -362,7 +576,37 @@ impl From<CanisterDataForSnapshot> for CanisterData {
             last_canister_functionality_access_time: canister_data
                 .last_canister_functionality_access_time,
             migration_info: canister_data.migration_info,
-            ..Default::default()
+            app_storage: AppStorage::from(canister_data.app_storage),
+            watch_history,
+            success_history,
+            device_identities: canister_data.device_identities,
+            ml_feed_cache: canister_data.ml_feed_cache,
+            cdao_canisters: canister_data.cdao_canisters,
+            token_roots,
+            hot_or_not_game_config: canister_data.hot_or_not_game_config,
+            slot_tabulation_queue,
+            posts_pending_purge: canister_data.posts_pending_purge,
+            post_reports: canister_data.post_reports,
+            blocked_principals: canister_data.blocked_principals,
+            muted_principals: canister_data.muted_principals,
+            pending_bets: canister_data.pending_bets,
+            pending_bet_withdrawals: canister_data.pending_bet_withdrawals,
+            next_bet_idempotency_key: canister_data.next_bet_idempotency_key,
+            payout_notification_outbox: canister_data.payout_notification_outbox,
+            withdrawn_bets: canister_data.withdrawn_bets,
+            comments_map,
+            comment_rate_limit_windows: canister_data.comment_rate_limit_windows,
+            prediction_details_map,
+            prediction_bet_details_map,
+            all_prediction_bets_placed: canister_data.all_prediction_bets_placed,
+            pending_prediction_bets: canister_data.pending_prediction_bets,
+            prediction_payout_notification_outbox: canister_data
+                .prediction_payout_notification_outbox,
+            prediction_settlement_queue: canister_data.prediction_settlement_queue,
+            // * excluded from the snapshot
+            ingress_frozen_for_migration: false,
+            migrated_to_canister_id: None,
+            former_canister_ids: canister_data.former_canister_ids,
         }
     }
 }
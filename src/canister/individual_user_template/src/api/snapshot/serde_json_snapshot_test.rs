@@ -7,20 +7,35 @@ mod test {
     };
 
     use candid::Principal;
+    use ciborium::value::Value;
     use ic_cdk::api::management_canister::main::CanisterId;
     use shared_utils::{
         canister_specific::individual_user_template::types::{
+            cdao::DeployedCdaoCanisters,
+            comment::{Comment, CommentRateLimitWindow, GlobalCommentId},
             configuration::IndividualUserConfiguration,
+            device_id::DeviceIdentity,
             follow::FollowEntryDetail,
             hot_or_not::{
-                AggregateStats, BetDetails, BetDirection, BetOutcomeForBetMaker, BetPayout,
-                GlobalBetId, GlobalRoomId, PlacedBetDetail, RoomBetPossibleOutcomes, RoomDetailsV1,
-                SlotDetailsV1, SlotId, StablePrincipal,
+                game_config::HotOrNotGameConfig, AggregateStats, BetDetails, BetDirection,
+                BetOutcomeForBetMaker, BetPayout, BetReconciliationDetails, BetWithdrawalKind,
+                GlobalBetId, GlobalRoomId, PendingBet, PendingBetWithdrawal,
+                PendingPayoutNotification, PlacedBetDetail, RoomBetPossibleOutcomes, RoomDetailsV1,
+                SlotDetails, SlotDetailsV1, SlotId, SlotTabulationJob, StablePrincipal,
             },
+            kv_storage::NamespaceAccessLevel,
             migration::MigrationInfo,
-            post::{FeedScore, PostViewStatistics},
+            ml_data::{MLFeedCacheItem, SuccessHistoryItemV1, WatchHistoryItem},
+            post::{FeedScore, Post, PostDetailsFromFrontend, PostViewStatistics},
+            prediction::{
+                GlobalPredictionBetId, PendingPredictionBet, PlacedPredictionBetDetail,
+                PredictionBetDetails, PredictionDetails, PredictionResolutionMode,
+                PredictionSettlementJob,
+            },
             profile::{UserProfile, UserProfileGlobalStats},
+            report::{PostReport, PostReportReason, PostReports},
             session::SessionType,
+            token::{PendingPostTip, PendingTokenTransfer},
         },
         common::types::{
             app_primitive_type::PostId,
@@ -30,17 +45,38 @@ mod test {
             version_details::VersionDetails,
         },
     };
-    use test_utils::setup::test_constants::get_mock_user_alice_canister_id;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
 
     use crate::{
         api::snapshot::{
             CanisterDataForSnapshot, FollowDataForSnapshot, FollowListForSnapshot,
             HotOrNotDetailsForSnapshot, PostForSnapshot, PostScoreIndexForSnapshot,
-            TokenBalanceForSnapshot,
+            TokenBalanceForSnapshot, SNAPSHOT_SCHEMA_VERSION,
         },
-        data_model::CanisterData,
+        data_model::{kv_storage::AppStorage, memory, CanisterData},
+        CANISTER_DATA,
     };
 
+    /// Fields of `CanisterData` that are deliberately not part of the snapshot
+    const FIELDS_EXCLUDED_FROM_SNAPSHOT: [&str; 2] =
+        ["ingress_frozen_for_migration", "migrated_to_canister_id"];
+
+    /// The heap fields of `CanisterData` by name, the ones excluded from the snapshot left out
+    fn heap_fields_in_snapshot(canister_data: &CanisterData) -> Vec<(String, Value)> {
+        let Value::Map(fields) = Value::serialized(canister_data).unwrap() else {
+            panic!("canister data serializes as a map");
+        };
+
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.as_text().unwrap().to_string(), value))
+            .filter(|(name, _)| !FIELDS_EXCLUDED_FROM_SNAPSHOT.contains(&name.as_str()))
+            .collect()
+    }
+
     #[test]
     fn test_serde_json_snapshot() {
         let mut created_posts = BTreeMap::<u64, PostForSnapshot>::new();
@@ -77,9 +113,15 @@ mod test {
                     total_number_of_not_bets: 30,
                     total_amount_bet: 12000,
                 },
+                slot_history: BTreeMap::new(),
             }),
             is_nsfw: false,
             slots_left_to_be_computed: (1..=48).collect(),
+            tip_details: Default::default(),
+            hot_or_not_game_config: Default::default(),
+            revisions: Default::default(),
+            comment_count: 0,
+            commenter_count: 0,
         };
         created_posts.insert(1, post1);
 
@@ -105,6 +147,8 @@ mod test {
                 payout: BetPayout::Calculated(1000),
                 bet_maker_canister_id: temp_principal,
                 bet_maker_informed_status: None,
+                idempotency_key: None,
+                placed_at: None,
             },
         );
 
@@ -179,6 +223,7 @@ mod test {
         principal_list.insert(temp_principal);
 
         let canister_data_snapshot = CanisterDataForSnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
             all_created_posts: created_posts,
             room_details_map: room_details_map,
             bet_details_map: bet_details_map,
@@ -187,6 +232,8 @@ mod test {
             all_hot_or_not_bets_placed: all_hot_or_not_bets_placed,
             configuration: IndividualUserConfiguration {
                 url_to_send_canister_metrics_to: Some("dsfsd".to_string()),
+                post_report_threshold: Some(3),
+                payout_notification_retry_horizon_in_seconds: Some(3600),
             },
             follow_data: FollowDataForSnapshot {
                 follower: FollowListForSnapshot {
@@ -204,6 +251,10 @@ mod test {
                 utility_token_transaction_history: utility_history,
                 lifetime_earnings: 1200,
             },
+            pending_token_transfers: Default::default(),
+            pending_post_tips: Default::default(),
+            next_token_transfer_idempotency_key: 0,
+            received_token_transfer_keys: Default::default(),
             posts_index_sorted_by_home_feed_score: PostScoreIndexForSnapshot {
                 items_sorted_by_score: items_sorted_by_score.clone(),
                 item_presence_index: item_prescence_index.clone(),
@@ -233,6 +284,33 @@ mod test {
             last_access_time: Some(SystemTime::now()),
             last_canister_functionality_access_time: Some(SystemTime::now()),
             migration_info: MigrationInfo::NotMigrated,
+            app_storage: Default::default(),
+            watch_history: vec![],
+            success_history: vec![],
+            device_identities: vec![],
+            ml_feed_cache: vec![],
+            cdao_canisters: vec![],
+            token_roots: vec![],
+            hot_or_not_game_config: Default::default(),
+            slot_tabulation_queue: vec![],
+            posts_pending_purge: Default::default(),
+            comments: vec![],
+            comment_rate_limit_windows: Default::default(),
+            post_reports: Default::default(),
+            blocked_principals: BTreeSet::from([Principal::anonymous()]),
+            muted_principals: Default::default(),
+            pending_bets: Default::default(),
+            pending_bet_withdrawals: Default::default(),
+            next_bet_idempotency_key: 3,
+            payout_notification_outbox: Default::default(),
+            withdrawn_bets: Default::default(),
+            predictions: vec![],
+            prediction_bets: vec![],
+            all_prediction_bets_placed: Default::default(),
+            pending_prediction_bets: Default::default(),
+            prediction_payout_notification_outbox: Default::default(),
+            prediction_settlement_queue: Default::default(),
+            former_canister_ids: vec![],
         };
 
         let serde_str = serde_json::to_string(&canister_data_snapshot);
@@ -245,4 +323,410 @@ mod test {
 
         // println!("canister_data: {:?}", canister_data.all_created_posts);
     }
+
+    #[test]
+    fn test_snapshot_round_trip_is_lossless() {
+        let temp_principal = get_mock_user_alice_canister_id();
+        let now = SystemTime::now();
+
+        let default_heap_fields = heap_fields_in_snapshot(&CanisterData::default());
+        let mut canister_data = CanisterData::default();
+
+        // * a single slot keeps the HashSet in the post serializing in a stable order
+        let mut post = Post::new_with_game_config(
+            1,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &now,
+            HotOrNotGameConfig {
+                version: 1,
+                maximum_number_of_slots: 1,
+                ..Default::default()
+            },
+        );
+        post.hot_or_not_details
+            .as_mut()
+            .unwrap()
+            .slot_history
+            .insert(1, SlotDetails::default());
+        canister_data.all_created_posts.insert(1, post);
+
+        let global_room_id = GlobalRoomId(1, 1, 1);
+        canister_data
+            .room_details_map
+            .insert(global_room_id, RoomDetailsV1::default());
+        canister_data.bet_details_map.insert(
+            GlobalBetId(global_room_id, StablePrincipal(temp_principal)),
+            BetDetails {
+                amount: 100,
+                bet_direction: BetDirection::Hot,
+                payout: BetPayout::NotCalculatedYet,
+                bet_maker_canister_id: temp_principal,
+                bet_maker_informed_status: None,
+                idempotency_key: None,
+                placed_at: None,
+            },
+        );
+        canister_data
+            .post_principal_map
+            .insert((1, StablePrincipal(temp_principal)), ());
+        canister_data
+            .slot_details_map
+            .insert((1, 1), SlotDetailsV1 { active_room_id: 1 });
+        canister_data.profile.principal_id = Some(temp_principal);
+        canister_data.session_type = Some(SessionType::RegisteredSession);
+        canister_data.last_access_time = Some(now);
+        canister_data.watch_history.insert(
+            WatchHistoryItem {
+                post_id: 1,
+                publisher_canister_id: temp_principal,
+                viewed_at: now,
+                cf_video_id: "abcd#1234".into(),
+                percentage_watched: 0.5,
+            },
+            (),
+        );
+        canister_data.success_history.insert(
+            SuccessHistoryItemV1 {
+                post_id: 1,
+                publisher_canister_id: temp_principal,
+                interacted_at: now,
+                cf_video_id: "abcd#1234".into(),
+                item_type: "like_video".into(),
+                percentage_watched: 0.5,
+            },
+            (),
+        );
+        canister_data.device_identities.push(DeviceIdentity {
+            device_id: "device".into(),
+            timestamp: 1,
+        });
+        canister_data.ml_feed_cache.push(MLFeedCacheItem {
+            post_id: 1,
+            canister_id: temp_principal,
+            video_id: "abcd#1234".into(),
+            creator_principal_id: Some(temp_principal),
+        });
+        canister_data.cdao_canisters.push(DeployedCdaoCanisters {
+            governance: temp_principal,
+            ledger: temp_principal,
+            root: temp_principal,
+            swap: temp_principal,
+            index: temp_principal,
+        });
+        canister_data.token_roots.insert(temp_principal, ());
+        canister_data.hot_or_not_game_config.version = 1;
+        canister_data.slot_tabulation_queue.insert(
+            SlotTabulationJob {
+                due_at: now,
+                post_id: 1,
+                slot_id: 1,
+            },
+            (),
+        );
+        canister_data.comments_map.insert(
+            GlobalCommentId(1, 0),
+            Comment::new(0, None, temp_principal, "Nice".into(), now),
+        );
+        let mut prediction_details = PredictionDetails::new(
+            vec!["Yes".into(), "No".into(), "Maybe".into()],
+            PredictionResolutionMode::DecidedByCreator,
+            60 * 60,
+            10,
+            now,
+        )
+        .unwrap();
+        prediction_details.record_bet(2, 100);
+        canister_data
+            .prediction_details_map
+            .insert(1, prediction_details);
+        canister_data.prediction_bet_details_map.insert(
+            GlobalPredictionBetId(1, StablePrincipal(temp_principal)),
+            PredictionBetDetails {
+                amount: 100,
+                outcome_id: 2,
+                payout: BetPayout::NotCalculatedYet,
+                bet_maker_canister_id: temp_principal,
+                placed_at: now,
+                bet_maker_informed_status: None,
+                idempotency_key: Some(0),
+            },
+        );
+        canister_data.all_prediction_bets_placed.insert(
+            (get_mock_user_alice_canister_id(), 0),
+            PlacedPredictionBetDetail {
+                canister_id: get_mock_user_alice_canister_id(),
+                post_id: 0,
+                outcome_id: 1,
+                amount_bet: 50,
+                bet_placed_at: now,
+                outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+            },
+        );
+        canister_data.all_hot_or_not_bets_placed.insert(
+            (get_mock_user_bob_canister_id(), 0),
+            PlacedBetDetail {
+                canister_id: get_mock_user_bob_canister_id(),
+                post_id: 0,
+                slot_id: 1,
+                room_id: 1,
+                amount_bet: 100,
+                bet_direction: BetDirection::Hot,
+                bet_placed_at: now,
+                outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+            },
+        );
+        canister_data.pending_bets.insert(
+            (get_mock_user_bob_canister_id(), 1),
+            PendingBet {
+                post_canister_id: get_mock_user_bob_canister_id(),
+                post_id: 1,
+                amount: 100,
+                bet_direction: BetDirection::Not,
+                idempotency_key: 1,
+                placed_at: now,
+                awaiting_reconciliation: true,
+            },
+        );
+        canister_data.pending_bet_withdrawals.insert(
+            (get_mock_user_bob_canister_id(), 0),
+            PendingBetWithdrawal {
+                post_canister_id: get_mock_user_bob_canister_id(),
+                post_id: 0,
+                withdrawal_kind: BetWithdrawalKind::CashOut,
+                awaiting_reconciliation: false,
+            },
+        );
+        canister_data.next_bet_idempotency_key = 2;
+        canister_data.payout_notification_outbox.insert(
+            GlobalBetId(global_room_id, StablePrincipal(temp_principal)),
+            PendingPayoutNotification {
+                bet_maker_canister_id: temp_principal,
+                post_id: 1,
+                outcome: BetOutcomeForBetMaker::Lost,
+                attempts: 1,
+                first_failed_at: now,
+                next_attempt_at: now,
+                last_error: "unreachable".into(),
+                gave_up_at: None,
+            },
+        );
+        canister_data.withdrawn_bets.insert(
+            (1, get_mock_user_bob_principal_id()),
+            BetReconciliationDetails {
+                slot_id: 1,
+                room_id: 1,
+                amount: 100,
+                bet_direction: BetDirection::Hot,
+                idempotency_key: Some(0),
+                outcome: BetOutcomeForBetMaker::AwaitingResult,
+                withdrawn_amount: Some(100),
+            },
+        );
+        canister_data.configuration.post_report_threshold = Some(3);
+        let bob = FollowEntryDetail {
+            principal_id: get_mock_user_bob_principal_id(),
+            canister_id: get_mock_user_bob_canister_id(),
+        };
+        canister_data.follow_data.follower.add(bob.clone());
+        canister_data.follow_data.following.add(bob);
+        canister_data
+            .known_principal_ids
+            .insert(KnownPrincipalType::CanisterIdPostCache, temp_principal);
+        canister_data
+            .my_token_balance
+            .handle_token_event(TokenEvent::Mint {
+                amount: 1000,
+                details: MintEvent::NewUserSignup {
+                    new_user_principal_id: temp_principal,
+                },
+                timestamp: now,
+            });
+        canister_data.pending_token_transfers.insert(
+            0,
+            PendingTokenTransfer {
+                to_user_principal_id: get_mock_user_bob_principal_id(),
+                recipient_canister_id: get_mock_user_bob_canister_id(),
+                amount: 10,
+                initiated_at: now,
+                awaiting_reconciliation: true,
+            },
+        );
+        canister_data.pending_post_tips.insert(
+            1,
+            PendingPostTip {
+                post_canister_id: get_mock_user_bob_canister_id(),
+                post_id: 0,
+                amount: 10,
+                initiated_at: now,
+                awaiting_reconciliation: false,
+            },
+        );
+        canister_data.next_token_transfer_idempotency_key = 2;
+        canister_data
+            .received_token_transfer_keys
+            .insert(get_mock_user_bob_principal_id(), BTreeSet::from([0]));
+        let post_score_index_item = PostScoreIndexItem {
+            score: 100,
+            post_id: 1,
+            publisher_canister_id: temp_principal,
+        };
+        canister_data
+            .posts_index_sorted_by_home_feed_score
+            .replace(&post_score_index_item);
+        canister_data
+            .posts_index_sorted_by_hot_or_not_feed_score
+            .replace(&post_score_index_item);
+        canister_data
+            .principals_i_follow
+            .insert(get_mock_user_bob_principal_id());
+        canister_data
+            .principals_that_follow_me
+            .insert(get_mock_user_bob_principal_id());
+        canister_data.version_details = VersionDetails {
+            version_number: 1,
+            version: "1.0.0".into(),
+        };
+        canister_data.last_canister_functionality_access_time = Some(now);
+        canister_data.migration_info = MigrationInfo::MigratedFromHotOrNot {
+            account_principal: temp_principal,
+        };
+        canister_data.posts_pending_purge.insert(2);
+        canister_data.post_reports.insert(
+            1,
+            PostReports {
+                reports: vec![PostReport {
+                    reporter_principal_id: get_mock_user_bob_principal_id(),
+                    reason: PostReportReason::Spam,
+                    reported_at: now,
+                }],
+                reports_dismissed: 0,
+            },
+        );
+        canister_data
+            .blocked_principals
+            .insert(Principal::anonymous());
+        canister_data
+            .muted_principals
+            .insert(get_mock_user_bob_principal_id());
+        canister_data.comment_rate_limit_windows.insert(
+            get_mock_user_bob_principal_id(),
+            CommentRateLimitWindow {
+                started_at: now,
+                comment_count: 1,
+            },
+        );
+        canister_data.pending_prediction_bets.insert(
+            (get_mock_user_bob_canister_id(), 1),
+            PendingPredictionBet {
+                post_canister_id: get_mock_user_bob_canister_id(),
+                post_id: 1,
+                outcome_id: 0,
+                amount: 100,
+                idempotency_key: 1,
+                placed_at: now,
+                awaiting_reconciliation: false,
+            },
+        );
+        canister_data.prediction_payout_notification_outbox.insert(
+            GlobalPredictionBetId(1, StablePrincipal(temp_principal)),
+            PendingPayoutNotification {
+                bet_maker_canister_id: temp_principal,
+                post_id: 1,
+                outcome: BetOutcomeForBetMaker::Won(180),
+                attempts: 1,
+                first_failed_at: now,
+                next_attempt_at: now,
+                last_error: "unreachable".into(),
+                gave_up_at: None,
+            },
+        );
+        canister_data
+            .prediction_settlement_queue
+            .insert(PredictionSettlementJob {
+                due_at: now,
+                post_id: 1,
+            });
+        canister_data.former_canister_ids.push(temp_principal);
+        // * the KV store helpers work on the global canister data
+        CANISTER_DATA.with_borrow_mut(|global_canister_data| {
+            global_canister_data.profile.principal_id = Some(temp_principal);
+        });
+        let namespace = AppStorage::create_a_namespace(temp_principal, "app".into()).unwrap();
+        let namespace = AppStorage::get_a_namespace(
+            temp_principal,
+            namespace.id,
+            NamespaceAccessLevel::ReadWrite,
+        )
+        .unwrap();
+        namespace
+            .write_key_value_pair("key".into(), "value".into())
+            .unwrap();
+        namespace
+            .write_blob_chunk("blob".into(), 0, vec![1, 2, 3])
+            .unwrap();
+        AppStorage::grant_namespace_access(
+            temp_principal,
+            namespace.id,
+            Principal::anonymous(),
+            NamespaceAccessLevel::ReadOnly,
+        )
+        .unwrap();
+        canister_data.app_storage = CANISTER_DATA.with_borrow_mut(|global_canister_data| {
+            std::mem::take(&mut global_canister_data.app_storage)
+        });
+
+        // * every heap field is set, so one the snapshot leaves out cannot go unnoticed
+        let heap_fields = heap_fields_in_snapshot(&canister_data);
+        assert_eq!(heap_fields.len(), default_heap_fields.len());
+        heap_fields.iter().zip(&default_heap_fields).for_each(
+            |((name, value), (_, default_value))| {
+                assert_ne!(value, default_value, "{name} is not set by this test");
+            },
+        );
+
+        let snapshot_json =
+            serde_json::to_string(&CanisterDataForSnapshot::from(&canister_data)).unwrap();
+        let snapshot: CanisterDataForSnapshot = serde_json::from_str(&snapshot_json).unwrap();
+        assert_eq!(snapshot.schema_version, SNAPSHOT_SCHEMA_VERSION);
+
+        // * restore onto empty stable memory so nothing survives except through the snapshot
+        drop(canister_data);
+        memory::init_memory_manager();
+        let restored_canister_data = CanisterData::from(snapshot);
+
+        assert_eq!(restored_canister_data.watch_history.len(), 1);
+        assert_eq!(restored_canister_data.slot_tabulation_queue.len(), 1);
+        assert_eq!(restored_canister_data.comments_map.len(), 1);
+        assert_eq!(restored_canister_data.prediction_details_map.len(), 1);
+        assert_eq!(restored_canister_data.prediction_bet_details_map.len(), 1);
+        assert_eq!(restored_canister_data.all_prediction_bets_placed.len(), 1);
+        assert_eq!(
+            heap_fields_in_snapshot(&restored_canister_data),
+            heap_fields
+        );
+        assert_eq!(
+            serde_json::to_string(&CanisterDataForSnapshot::from(&restored_canister_data)).unwrap(),
+            snapshot_json
+        );
+    }
+
+    #[test]
+    fn test_snapshot_without_schema_version_is_version_zero() {
+        let canister_data = CanisterData::default();
+        let mut snapshot_json =
+            serde_json::to_value(CanisterDataForSnapshot::from(&canister_data)).unwrap();
+        snapshot_json
+            .as_object_mut()
+            .unwrap()
+            .remove("schema_version");
+
+        let snapshot: CanisterDataForSnapshot = serde_json::from_value(snapshot_json).unwrap();
+        assert_eq!(snapshot.schema_version, 0);
+    }
 }
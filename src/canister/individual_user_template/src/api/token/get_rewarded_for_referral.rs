@@ -1,6 +1,6 @@
 use crate::{
     api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
-    CANISTER_DATA,
+    guard::migration::is_not_frozen_for_migration, CANISTER_DATA,
 };
 use candid::Principal;
 use ic_cdk_macros::update;
@@ -12,7 +12,7 @@ use shared_utils::common::{
     utils::system_time,
 };
 
-#[update]
+#[update(guard = "is_not_frozen_for_migration")]
 fn get_rewarded_for_referral(referrer: Principal, referree: Principal) {
     // * access control
     let request_maker = ic_cdk::caller();
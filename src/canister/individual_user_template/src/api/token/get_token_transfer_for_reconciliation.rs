@@ -0,0 +1,71 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::individual_user_template::types::token::TokenTransferIdempotencyKey;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Returns the principal of this profile if it received the transfer or tip
+/// sent with `idempotency_key`. Senders' canisters use it to settle
+/// transfers and tips they never got a reply for.
+///
+/// # Access Control
+/// Any caller
+#[query]
+fn get_token_transfer_for_reconciliation(
+    from_user_principal_id: Principal,
+    idempotency_key: TokenTransferIdempotencyKey,
+) -> Option<Principal> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_token_transfer_for_reconciliation_impl(
+            canister_data,
+            from_user_principal_id,
+            idempotency_key,
+        )
+    })
+}
+
+fn get_token_transfer_for_reconciliation_impl(
+    canister_data: &CanisterData,
+    from_user_principal_id: Principal,
+    idempotency_key: TokenTransferIdempotencyKey,
+) -> Option<Principal> {
+    canister_data
+        .received_token_transfer_keys
+        .get(&from_user_principal_id)
+        .filter(|received_keys| received_keys.contains(&idempotency_key))
+        .and(canister_data.profile.principal_id)
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use crate::api::token::receive_utility_tokens_from_user::record_received_token_transfer_impl;
+
+    use super::*;
+
+    #[test]
+    fn test_get_token_transfer_for_reconciliation_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+        canister_data.profile.principal_id = Some(alice);
+
+        record_received_token_transfer_impl(&mut canister_data, bob, 3);
+
+        assert_eq!(
+            get_token_transfer_for_reconciliation_impl(&canister_data, bob, 3),
+            Some(alice)
+        );
+        assert_eq!(
+            get_token_transfer_for_reconciliation_impl(&canister_data, bob, 4),
+            None
+        );
+        assert_eq!(
+            get_token_transfer_for_reconciliation_impl(&canister_data, alice, 3),
+            None
+        );
+    }
+}
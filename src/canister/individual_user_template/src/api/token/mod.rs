@@ -1,4 +1,8 @@
 pub mod get_rewarded_for_referral;
 pub mod get_rewarded_for_signing_up;
+pub mod get_token_transfer_for_reconciliation;
 pub mod get_user_utility_token_transaction_history_with_pagination;
 pub mod get_utility_token_balance;
+pub mod receive_utility_tokens_from_user;
+pub mod reconcile_pending_token_transfers;
+pub mod transfer_utility_tokens_to_user;
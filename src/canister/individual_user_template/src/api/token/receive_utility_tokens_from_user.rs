@@ -0,0 +1,130 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        error::TransferUtilityTokenError,
+        token::{TokenTransferIdempotencyKey, RECEIVED_TOKEN_TRANSFER_KEYS_KEPT_PER_SENDER},
+    },
+    common::{types::utility_token::token_event::TokenEvent, utils::system_time},
+};
+
+use crate::{
+    api::canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+    data_model::CanisterData, guard::migration::is_not_frozen_for_migration,
+    util::user_index::get_user_canister_id_from_user_principal_id, CANISTER_DATA,
+};
+
+/// Credits a transfer once per idempotency key. A transfer already credited
+/// is acknowledged again without being credited twice.
+///
+/// # Access Control
+/// Only the individual canister that user_index has on record for `from_user_principal_id`
+#[update(guard = "is_not_frozen_for_migration")]
+async fn receive_utility_tokens_from_user(
+    from_user_principal_id: Principal,
+    amount: u64,
+    idempotency_key: Option<TokenTransferIdempotencyKey>,
+) -> Result<(), TransferUtilityTokenError> {
+    let calling_canister_principal = ic_cdk::caller();
+
+    if amount == 0 {
+        return Err(TransferUtilityTokenError::InvalidAmount);
+    }
+
+    let sender_canister_id = get_user_canister_id_from_user_principal_id(from_user_principal_id)
+        .await
+        .map_err(|_| TransferUtilityTokenError::UserIndexCrossCanisterCallFailed)?;
+
+    if sender_canister_id != Some(calling_canister_principal) {
+        return Err(TransferUtilityTokenError::SenderCanisterDoesNotMatch);
+    }
+
+    update_last_canister_functionality_access_time();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let is_new_transfer = idempotency_key.map_or(true, |idempotency_key| {
+            record_received_token_transfer_impl(
+                canister_data,
+                from_user_principal_id,
+                idempotency_key,
+            )
+        });
+        if !is_new_transfer {
+            return;
+        }
+
+        canister_data
+            .my_token_balance
+            .handle_token_event(TokenEvent::Receive {
+                amount,
+                from_account: from_user_principal_id,
+                timestamp: system_time::get_current_system_time_from_ic(),
+            });
+    });
+
+    Ok(())
+}
+
+/// Keeps the key of a transfer received from a sender, dropping the oldest ones beyond the limit.
+/// Returns false if the transfer was already received.
+pub fn record_received_token_transfer_impl(
+    canister_data: &mut CanisterData,
+    from_user_principal_id: Principal,
+    idempotency_key: TokenTransferIdempotencyKey,
+) -> bool {
+    let received_keys = canister_data
+        .received_token_transfer_keys
+        .entry(from_user_principal_id)
+        .or_default();
+
+    if !received_keys.insert(idempotency_key) {
+        return false;
+    }
+
+    while received_keys.len() > RECEIVED_TOKEN_TRANSFER_KEYS_KEPT_PER_SENDER {
+        received_keys.pop_first();
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::get_mock_user_bob_principal_id;
+
+    use super::*;
+
+    #[test]
+    fn test_record_received_token_transfer_impl() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+
+        assert!(record_received_token_transfer_impl(
+            &mut canister_data,
+            bob,
+            0
+        ));
+        assert!(!record_received_token_transfer_impl(
+            &mut canister_data,
+            bob,
+            0
+        ));
+
+        (1..=RECEIVED_TOKEN_TRANSFER_KEYS_KEPT_PER_SENDER as u64).for_each(|idempotency_key| {
+            assert!(record_received_token_transfer_impl(
+                &mut canister_data,
+                bob,
+                idempotency_key
+            ));
+        });
+        let received_keys = canister_data
+            .received_token_transfer_keys
+            .get(&bob)
+            .unwrap();
+        assert_eq!(
+            received_keys.len(),
+            RECEIVED_TOKEN_TRANSFER_KEYS_KEPT_PER_SENDER
+        );
+        assert!(!received_keys.contains(&0));
+    }
+}
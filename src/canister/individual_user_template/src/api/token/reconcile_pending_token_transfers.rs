@@ -0,0 +1,129 @@
+use candid::Principal;
+use shared_utils::canister_specific::individual_user_template::types::token::{
+    PendingTokenTransfer, TokenTransferIdempotencyKey,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+use super::transfer_utility_tokens_to_user::{
+    abort_pending_token_transfer_impl, commit_pending_token_transfer_impl,
+};
+
+pub fn get_pending_token_transfers_due_for_reconciliation(
+    canister_data: &CanisterData,
+) -> Vec<(TokenTransferIdempotencyKey, PendingTokenTransfer)> {
+    canister_data
+        .pending_token_transfers
+        .iter()
+        .filter(|(_, pending_token_transfer)| pending_token_transfer.awaiting_reconciliation)
+        .map(|(idempotency_key, pending_token_transfer)| {
+            (*idempotency_key, pending_token_transfer.clone())
+        })
+        .collect()
+}
+
+pub async fn reconcile_pending_token_transfer(
+    idempotency_key: TokenTransferIdempotencyKey,
+    pending_token_transfer: PendingTokenTransfer,
+) {
+    let Some(my_principal_id) =
+        CANISTER_DATA.with_borrow(|canister_data| canister_data.profile.principal_id)
+    else {
+        return;
+    };
+
+    // * on failure the transfer stays pending and is retried on the next run
+    let Ok((recipient_principal_id,)) = ic_cdk::call::<_, (Option<Principal>,)>(
+        pending_token_transfer.recipient_canister_id,
+        "get_token_transfer_for_reconciliation",
+        (my_principal_id, idempotency_key),
+    )
+    .await
+    else {
+        return;
+    };
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        settle_pending_token_transfer_impl(
+            canister_data,
+            idempotency_key,
+            recipient_principal_id.is_some(),
+        );
+    });
+}
+
+/// Records the transfer if the recipient's canister credited it, refunds it otherwise
+fn settle_pending_token_transfer_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+    was_credited: bool,
+) {
+    if was_credited {
+        commit_pending_token_transfer_impl(canister_data, idempotency_key);
+    } else {
+        abort_pending_token_transfer_impl(canister_data, idempotency_key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use test_utils::setup::test_constants::{
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn pending_token_transfer(awaiting_reconciliation: bool) -> PendingTokenTransfer {
+        PendingTokenTransfer {
+            to_user_principal_id: get_mock_user_bob_principal_id(),
+            recipient_canister_id: get_mock_user_bob_canister_id(),
+            amount: 100,
+            initiated_at: SystemTime::now(),
+            awaiting_reconciliation,
+        }
+    }
+
+    #[test]
+    fn test_settle_pending_token_transfer_impl() {
+        let mut canister_data = CanisterData::default();
+        // * the amounts of the three pending transfers below already left the balance
+        canister_data.my_token_balance.utility_token_balance = 700;
+        canister_data
+            .pending_token_transfers
+            .insert(0, pending_token_transfer(true));
+        canister_data
+            .pending_token_transfers
+            .insert(1, pending_token_transfer(true));
+        // * a transfer whose call is still in flight is not reconciled
+        canister_data
+            .pending_token_transfers
+            .insert(2, pending_token_transfer(false));
+
+        let due_pending_token_transfers =
+            get_pending_token_transfers_due_for_reconciliation(&canister_data);
+        assert_eq!(
+            due_pending_token_transfers
+                .iter()
+                .map(|(idempotency_key, _)| *idempotency_key)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        settle_pending_token_transfer_impl(&mut canister_data, 0, true);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 700);
+
+        settle_pending_token_transfer_impl(&mut canister_data, 1, false);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        assert_eq!(
+            canister_data
+                .pending_token_transfers
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::{
+        arg::TransferUtilityTokenArg,
+        error::TransferUtilityTokenError,
+        token::{PendingTokenTransfer, TokenTransferIdempotencyKey},
+    },
+    common::utils::system_time,
+};
+
+use crate::{
+    api::{
+        canister_management::update_last_access_time::update_last_canister_functionality_access_time,
+        hot_or_not_bet::bet_on_currently_viewing_hot_or_not_post::call_rejection_leaves_no_bet_behind,
+    },
+    data_model::CanisterData,
+    guard::migration::is_not_frozen_for_migration,
+    util::user_index::get_user_canister_id_from_user_principal_id,
+    CANISTER_DATA,
+};
+
+/// The amount leaves the balance before the recipient's canister is called
+/// and the transfer is kept as pending until that canister confirms or rejects it.
+/// If the call fails without saying whether the tokens were credited,
+/// `reconcile_pending_token_transfers` settles it later from what the recipient's
+/// canister has on record for the transfer's idempotency key.
+///
+/// # Access Control
+/// Only the user whose profile details are stored in this canister can send their tokens.
+#[update(guard = "is_not_frozen_for_migration")]
+async fn transfer_utility_tokens_to_user(
+    arg: TransferUtilityTokenArg,
+) -> Result<(), TransferUtilityTokenError> {
+    let current_caller = ic_cdk::caller();
+
+    CANISTER_DATA.with_borrow(|canister_data| {
+        validate_transfer_request(canister_data, &current_caller, &arg)
+    })?;
+
+    let recipient_canister_id =
+        get_user_canister_id_from_user_principal_id(arg.to_user_principal_id)
+            .await
+            .map_err(|_| TransferUtilityTokenError::UserIndexCrossCanisterCallFailed)?
+            .ok_or(TransferUtilityTokenError::RecipientNotFound)?;
+
+    // * validated again, the balance may have changed while user_index was called
+    let (my_principal_id, idempotency_key) = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let my_principal_id = validate_transfer_request(canister_data, &current_caller, &arg)?;
+
+        Ok((
+            my_principal_id,
+            prepare_token_transfer_impl(
+                canister_data,
+                &arg,
+                recipient_canister_id,
+                system_time::get_current_system_time_from_ic(),
+            ),
+        ))
+    })?;
+
+    update_last_canister_functionality_access_time();
+
+    let response = ic_cdk::call::<_, (Result<(), TransferUtilityTokenError>,)>(
+        recipient_canister_id,
+        "receive_utility_tokens_from_user",
+        (my_principal_id, arg.amount, Some(idempotency_key)),
+    )
+    .await;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| match response {
+        Ok((Ok(()),)) => {
+            commit_pending_token_transfer_impl(canister_data, idempotency_key);
+
+            Ok(())
+        }
+        Ok((Err(e),)) => {
+            abort_pending_token_transfer_impl(canister_data, idempotency_key);
+
+            Err(e)
+        }
+        Err((rejection_code, _)) => {
+            // * the same rejections that leave no bet behind leave no credit behind
+            if call_rejection_leaves_no_bet_behind(&rejection_code) {
+                abort_pending_token_transfer_impl(canister_data, idempotency_key);
+            } else if let Some(pending_token_transfer) = canister_data
+                .pending_token_transfers
+                .get_mut(&idempotency_key)
+            {
+                pending_token_transfer.awaiting_reconciliation = true;
+            }
+
+            Err(TransferUtilityTokenError::RecipientCanisterCallFailed)
+        }
+    })
+}
+
+/// Takes the amount out of the balance and records the transfer as pending.
+/// Returns the idempotency key the transfer is sent with.
+fn prepare_token_transfer_impl(
+    canister_data: &mut CanisterData,
+    arg: &TransferUtilityTokenArg,
+    recipient_canister_id: Principal,
+    current_time: SystemTime,
+) -> TokenTransferIdempotencyKey {
+    let idempotency_key = canister_data.next_token_transfer_idempotency_key;
+    canister_data.next_token_transfer_idempotency_key += 1;
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_pre_transfer(arg.amount);
+
+    canister_data.pending_token_transfers.insert(
+        idempotency_key,
+        PendingTokenTransfer {
+            to_user_principal_id: arg.to_user_principal_id,
+            recipient_canister_id,
+            amount: arg.amount,
+            initiated_at: current_time,
+            awaiting_reconciliation: false,
+        },
+    );
+
+    idempotency_key
+}
+
+/// Records a pending transfer the recipient's canister credited.
+/// Does nothing if the transfer was already settled.
+pub fn commit_pending_token_transfer_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+) {
+    let Some(pending_token_transfer) = canister_data
+        .pending_token_transfers
+        .remove(&idempotency_key)
+    else {
+        return;
+    };
+
+    canister_data.my_token_balance.record_completed_transfer(
+        pending_token_transfer.amount,
+        pending_token_transfer.to_user_principal_id,
+        pending_token_transfer.initiated_at,
+    );
+}
+
+/// Drops a pending transfer the recipient's canister did not credit and refunds its amount.
+/// Does nothing if the transfer was already settled.
+pub fn abort_pending_token_transfer_impl(
+    canister_data: &mut CanisterData,
+    idempotency_key: TokenTransferIdempotencyKey,
+) {
+    let Some(pending_token_transfer) = canister_data
+        .pending_token_transfers
+        .remove(&idempotency_key)
+    else {
+        return;
+    };
+
+    canister_data
+        .my_token_balance
+        .adjust_balance_for_failed_transfer(pending_token_transfer.amount);
+}
+
+fn validate_transfer_request(
+    canister_data: &CanisterData,
+    current_caller: &Principal,
+    arg: &TransferUtilityTokenArg,
+) -> Result<Principal, TransferUtilityTokenError> {
+    if *current_caller == Principal::anonymous() {
+        return Err(TransferUtilityTokenError::Unauthenticated);
+    }
+
+    let profile_owner = canister_data
+        .profile
+        .principal_id
+        .ok_or(TransferUtilityTokenError::UserPrincipalNotSet)?;
+
+    if *current_caller != profile_owner {
+        return Err(TransferUtilityTokenError::Unauthorized);
+    }
+
+    if arg.amount == 0 {
+        return Err(TransferUtilityTokenError::InvalidAmount);
+    }
+
+    if arg.to_user_principal_id == Principal::anonymous() {
+        return Err(TransferUtilityTokenError::CannotTransferToAnonymous);
+    }
+
+    if arg.to_user_principal_id == profile_owner {
+        return Err(TransferUtilityTokenError::CannotTransferToSelf);
+    }
+
+    if canister_data.my_token_balance.get_utility_token_balance() < arg.amount {
+        return Err(TransferUtilityTokenError::InsufficientBalance);
+    }
+
+    Ok(profile_owner)
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::common::types::utility_token::token_event::TokenEvent;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_canister_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_validate_transfer_request() {
+        let mut canister_data = CanisterData::default();
+        let arg = TransferUtilityTokenArg {
+            to_user_principal_id: get_mock_user_bob_principal_id(),
+            amount: 100,
+        };
+
+        let result = validate_transfer_request(&canister_data, &Principal::anonymous(), &arg);
+        assert_eq!(result, Err(TransferUtilityTokenError::Unauthenticated));
+
+        let result =
+            validate_transfer_request(&canister_data, &get_mock_user_alice_principal_id(), &arg);
+        assert_eq!(result, Err(TransferUtilityTokenError::UserPrincipalNotSet));
+
+        canister_data.profile.principal_id = Some(get_mock_user_alice_principal_id());
+
+        let result =
+            validate_transfer_request(&canister_data, &get_mock_user_bob_principal_id(), &arg);
+        assert_eq!(result, Err(TransferUtilityTokenError::Unauthorized));
+
+        let result = validate_transfer_request(
+            &canister_data,
+            &get_mock_user_alice_principal_id(),
+            &TransferUtilityTokenArg {
+                to_user_principal_id: get_mock_user_bob_principal_id(),
+                amount: 0,
+            },
+        );
+        assert_eq!(result, Err(TransferUtilityTokenError::InvalidAmount));
+
+        let result = validate_transfer_request(
+            &canister_data,
+            &get_mock_user_alice_principal_id(),
+            &TransferUtilityTokenArg {
+                to_user_principal_id: get_mock_user_alice_principal_id(),
+                amount: 100,
+            },
+        );
+        assert_eq!(result, Err(TransferUtilityTokenError::CannotTransferToSelf));
+
+        let result = validate_transfer_request(
+            &canister_data,
+            &get_mock_user_alice_principal_id(),
+            &TransferUtilityTokenArg {
+                to_user_principal_id: Principal::anonymous(),
+                amount: 100,
+            },
+        );
+        assert_eq!(
+            result,
+            Err(TransferUtilityTokenError::CannotTransferToAnonymous)
+        );
+
+        let result =
+            validate_transfer_request(&canister_data, &get_mock_user_alice_principal_id(), &arg);
+        assert_eq!(result, Err(TransferUtilityTokenError::InsufficientBalance));
+
+        canister_data.my_token_balance.utility_token_balance = 1000;
+
+        let result =
+            validate_transfer_request(&canister_data, &get_mock_user_alice_principal_id(), &arg);
+        assert_eq!(result, Ok(get_mock_user_alice_principal_id()));
+    }
+
+    #[test]
+    fn test_pending_token_transfer_is_committed_or_aborted_once() {
+        let mut canister_data = CanisterData::default();
+        canister_data.my_token_balance.utility_token_balance = 1000;
+        let arg = TransferUtilityTokenArg {
+            to_user_principal_id: get_mock_user_bob_principal_id(),
+            amount: 100,
+        };
+
+        let first_key = prepare_token_transfer_impl(
+            &mut canister_data,
+            &arg,
+            get_mock_user_bob_canister_id(),
+            SystemTime::now(),
+        );
+        let second_key = prepare_token_transfer_impl(
+            &mut canister_data,
+            &arg,
+            get_mock_user_bob_canister_id(),
+            SystemTime::now(),
+        );
+        assert_ne!(first_key, second_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+
+        commit_pending_token_transfer_impl(&mut canister_data, first_key);
+        commit_pending_token_transfer_impl(&mut canister_data, first_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 800);
+        assert!(canister_data
+            .my_token_balance
+            .utility_token_transaction_history
+            .values()
+            .any(|token_event| matches!(token_event, TokenEvent::Transfer { amount: 100, .. })));
+
+        abort_pending_token_transfer_impl(&mut canister_data, second_key);
+        abort_pending_token_transfer_impl(&mut canister_data, second_key);
+        assert_eq!(canister_data.my_token_balance.utility_token_balance, 900);
+        assert!(canister_data.pending_token_transfers.is_empty());
+    }
+}
@@ -1,23 +1,44 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound as RangeBound,
+};
 
-use crate::CANISTER_DATA;
+use crate::{data_model::CanisterData, CANISTER_DATA};
 
-use super::memory::{
-    get_kv_storage_namespace_key_value_memory, get_kv_storage_namespace_memory, Memory,
+use super::{
+    memory::{
+        get_kv_storage_namespace_acl_memory, get_kv_storage_namespace_blob_chunk_memory,
+        get_kv_storage_namespace_blob_metadata_memory, get_kv_storage_namespace_key_value_memory,
+        get_kv_storage_namespace_memory, get_kv_storage_namespace_usage_memory,
+        get_kv_storage_namespace_version_memory, Memory,
+    },
+    stable_entries_after,
 };
 use candid::{CandidType, Decode, Encode, Principal};
 use ic_cdk::api::time;
 use ic_stable_structures::{storable::Bound, StableBTreeMap, Storable};
 use serde::{Deserialize, Serialize};
+use serde_json_any_key::*;
 use shared_utils::{
     canister_specific::individual_user_template::types::kv_storage::{
-        NamespaceErrors, NamespaceForFrontend,
+        BlobMetadata, KeyValueTransactionOperation, NamespaceAccessLevel, NamespaceErrors,
+        NamespaceForFrontend, NamespaceKeysPage, NamespaceUsage, VersionedValue,
     },
     common::types::app_primitive_type::PostId,
 };
 
 type NamespaceId = u64;
 
+pub const MAX_KEYS_IN_ONE_LIST_REQUEST: u64 = 100;
+// * keeps NameSpaceKey and BlobChunkKey inside their stable memory bounds
+pub const MAX_KEY_SIZE_IN_BYTES: usize = 50;
+pub const BLOB_CHUNK_SIZE_IN_BYTES: usize = 512 * 1024;
+pub const MAX_BLOB_SIZE_IN_BYTES: u64 = 8 * 1024 * 1024;
+pub const DEFAULT_NAMESPACE_BYTE_QUOTA: u64 = 16 * 1024 * 1024;
+// * keys written before versioning have no stored version
+const LEGACY_KEY_VERSION: u64 = 1;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Namespace {
     id: u64,
@@ -70,15 +91,21 @@ impl Namespace {
         if self.is_value_above_the_size_limit(&value) {
             return Err(NamespaceErrors::ValueTooBig);
         }
+        if namespace_key.key.len() > MAX_KEY_SIZE_IN_BYTES {
+            return Err(NamespaceErrors::KeyTooBig);
+        }
 
-        let prev_value = CANISTER_DATA.with_borrow_mut(|canister_data| {
-            canister_data
-                .app_storage
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
+            let prev_value_size = app_storage
                 .namespace_key_value
-                .insert(namespace_key, value)
-        });
+                .get(&namespace_key)
+                .map_or(0, |prev_value| prev_value.len() as u64);
+            app_storage.adjust_namespace_usage(self.id, prev_value_size, value.len() as u64)?;
 
-        Ok(prev_value)
+            let (prev_value, _) = app_storage.insert_versioned_value(namespace_key, value);
+            Ok(prev_value)
+        })
     }
 
     pub fn delete_key_value_pair(&self, key: String) -> Option<String> {
@@ -87,10 +114,12 @@ impl Namespace {
             namespace_id: self.id,
         };
         CANISTER_DATA.with_borrow_mut(|canister_data| {
-            canister_data
-                .app_storage
-                .namespace_key_value
-                .remove(&namespace_key)
+            let app_storage = &mut canister_data.app_storage;
+            let prev_value = app_storage.remove_versioned_value(&namespace_key);
+            if let Some(prev_value) = &prev_value {
+                app_storage.release_namespace_usage(self.id, prev_value.len() as u64);
+            }
+            prev_value
         })
     }
 
@@ -105,48 +134,326 @@ impl Namespace {
         if invalid_pair {
             return Err(NamespaceErrors::ValueTooBig);
         }
+        if pairs.keys().any(|key| key.len() > MAX_KEY_SIZE_IN_BYTES) {
+            return Err(NamespaceErrors::KeyTooBig);
+        }
 
         CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
+
+            // * the quota is checked for the whole batch before anything is written
+            let (prev_values_size, new_values_size) = pairs.iter().fold(
+                (0, 0),
+                |(prev_values_size, new_values_size), (key, value)| {
+                    let prev_value_size = app_storage
+                        .namespace_key_value
+                        .get(&NameSpaceKey {
+                            key: key.clone(),
+                            namespace_id: self.id,
+                        })
+                        .map_or(0, |prev_value| prev_value.len() as u64);
+                    (
+                        prev_values_size + prev_value_size,
+                        new_values_size + value.len() as u64,
+                    )
+                },
+            );
+            app_storage.adjust_namespace_usage(self.id, prev_values_size, new_values_size)?;
+
             pairs.into_iter().for_each(|pair| {
                 let namespace_key = NameSpaceKey {
                     key: pair.0,
                     namespace_id: self.id,
                 };
 
-                canister_data
-                    .app_storage
-                    .namespace_key_value
-                    .insert(namespace_key, pair.1);
+                app_storage.insert_versioned_value(namespace_key, pair.1);
             });
-        });
 
-        Ok(())
+            Ok(())
+        })
     }
 
     pub fn delete_multiple_keys(&self, keys: Vec<String>) {
         CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
             keys.into_iter().for_each(|key| {
                 let namespace_key = NameSpaceKey {
                     key,
                     namespace_id: self.id,
                 };
-                canister_data
-                    .app_storage
+                if let Some(prev_value) = app_storage.remove_versioned_value(&namespace_key) {
+                    app_storage.release_namespace_usage(self.id, prev_value.len() as u64);
+                }
+            })
+        })
+    }
+
+    /// Writes `value` only if the key is still at `expected_version` and returns the new version
+    pub fn compare_and_swap(
+        &self,
+        key: String,
+        expected_version: u64,
+        value: String,
+    ) -> Result<u64, NamespaceErrors> {
+        let new_versions = self.apply_transaction(vec![KeyValueTransactionOperation::Put {
+            key,
+            value,
+            expected_version: Some(expected_version),
+        }])?;
+        Ok(new_versions[0])
+    }
+
+    /// Applies all operations or none of them.
+    /// Every precondition is checked against the state before the transaction,
+    /// so a key can only appear once.
+    /// Returns the version of each key afterwards, 0 for deleted keys.
+    pub fn apply_transaction(
+        &self,
+        operations: Vec<KeyValueTransactionOperation>,
+    ) -> Result<Vec<u64>, NamespaceErrors> {
+        let mut keys = BTreeSet::new();
+        for operation in operations.iter() {
+            if operation.key().len() > MAX_KEY_SIZE_IN_BYTES {
+                return Err(NamespaceErrors::KeyTooBig);
+            }
+            if matches!(operation, KeyValueTransactionOperation::Put { value, .. } if self.is_value_above_the_size_limit(value))
+            {
+                return Err(NamespaceErrors::ValueTooBig);
+            }
+            if !keys.insert(operation.key()) {
+                return Err(NamespaceErrors::DuplicateKeyInTransaction);
+            }
+        }
+
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
+
+            let mut prev_values_size = 0;
+            let mut new_values_size = 0;
+            for operation in operations.iter() {
+                let namespace_key = NameSpaceKey {
+                    key: operation.key().to_string(),
+                    namespace_id: self.id,
+                };
+                if let Some(expected_version) = operation.expected_version() {
+                    if app_storage.get_key_version(&namespace_key) != expected_version {
+                        return Err(NamespaceErrors::VersionMismatch);
+                    }
+                }
+
+                prev_values_size += app_storage
                     .namespace_key_value
-                    .remove(&namespace_key);
+                    .get(&namespace_key)
+                    .map_or(0, |prev_value| prev_value.len() as u64);
+                if let KeyValueTransactionOperation::Put { value, .. } = operation {
+                    new_values_size += value.len() as u64;
+                }
+            }
+            app_storage.adjust_namespace_usage(self.id, prev_values_size, new_values_size)?;
+
+            Ok(operations
+                .into_iter()
+                .map(|operation| match operation {
+                    KeyValueTransactionOperation::Put { key, value, .. } => {
+                        let namespace_key = NameSpaceKey {
+                            key,
+                            namespace_id: self.id,
+                        };
+                        let (_, new_version) =
+                            app_storage.insert_versioned_value(namespace_key, value);
+                        new_version
+                    }
+                    KeyValueTransactionOperation::Delete { key, .. } => {
+                        let namespace_key = NameSpaceKey {
+                            key,
+                            namespace_id: self.id,
+                        };
+                        app_storage.remove_versioned_value(&namespace_key);
+                        0
+                    }
+                })
+                .collect())
+        })
+    }
+
+    pub fn read_versioned_key_value_pair(&self, key: String) -> Option<VersionedValue> {
+        let namespace_key = NameSpaceKey {
+            key,
+            namespace_id: self.id,
+        };
+        CANISTER_DATA.with_borrow(|canister_data| {
+            let app_storage = &canister_data.app_storage;
+            let value = app_storage.namespace_key_value.get(&namespace_key)?;
+            Some(VersionedValue {
+                value,
+                version: app_storage.get_key_version(&namespace_key),
             })
         })
     }
 
-    pub fn list_keys(&self) -> Vec<String> {
+    /// Blobs are written one chunk at a time so that values bigger than a single message fit.
+    /// A chunk can overwrite an existing one or be appended right after the last one.
+    /// Empty chunks are rejected.
+    pub fn write_blob_chunk(
+        &self,
+        key: String,
+        chunk_index: u32,
+        chunk: Vec<u8>,
+    ) -> Result<BlobMetadata, NamespaceErrors> {
+        if key.len() > MAX_KEY_SIZE_IN_BYTES {
+            return Err(NamespaceErrors::KeyTooBig);
+        }
+        if chunk.is_empty() {
+            return Err(NamespaceErrors::EmptyChunk);
+        }
+        if chunk.len() > BLOB_CHUNK_SIZE_IN_BYTES {
+            return Err(NamespaceErrors::ChunkTooBig);
+        }
+
+        let namespace_key = NameSpaceKey {
+            key,
+            namespace_id: self.id,
+        };
+
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
+            let mut blob_metadata = app_storage
+                .namespace_blob_metadata
+                .get(&namespace_key)
+                .unwrap_or_default();
+
+            if chunk_index > blob_metadata.chunk_count {
+                return Err(NamespaceErrors::ChunkIndexOutOfRange);
+            }
+
+            let chunk_key = BlobChunkKey::new(&namespace_key, chunk_index);
+            let prev_chunk_size = app_storage
+                .namespace_blob_chunks
+                .get(&chunk_key)
+                .map_or(0, |prev_chunk| prev_chunk.len() as u64);
+
+            let new_total_size = blob_metadata.total_size - prev_chunk_size + chunk.len() as u64;
+            if new_total_size > MAX_BLOB_SIZE_IN_BYTES {
+                return Err(NamespaceErrors::ValueTooBig);
+            }
+            app_storage.adjust_namespace_usage(self.id, prev_chunk_size, chunk.len() as u64)?;
+
+            app_storage.namespace_blob_chunks.insert(chunk_key, chunk);
+
+            blob_metadata.total_size = new_total_size;
+            if chunk_index == blob_metadata.chunk_count {
+                blob_metadata.chunk_count += 1;
+            }
+            app_storage
+                .namespace_blob_metadata
+                .insert(namespace_key, blob_metadata);
+
+            Ok(blob_metadata)
+        })
+    }
+
+    pub fn read_blob_chunk(&self, key: String, chunk_index: u32) -> Option<Vec<u8>> {
+        let namespace_key = NameSpaceKey {
+            key,
+            namespace_id: self.id,
+        };
+        CANISTER_DATA.with_borrow(|canister_data| {
+            canister_data
+                .app_storage
+                .namespace_blob_chunks
+                .get(&BlobChunkKey::new(&namespace_key, chunk_index))
+        })
+    }
+
+    pub fn get_blob_metadata(&self, key: String) -> Option<BlobMetadata> {
+        let namespace_key = NameSpaceKey {
+            key,
+            namespace_id: self.id,
+        };
         CANISTER_DATA.with_borrow(|canister_data| {
+            canister_data
+                .app_storage
+                .namespace_blob_metadata
+                .get(&namespace_key)
+        })
+    }
+
+    pub fn delete_blob(&self, key: String) -> Option<BlobMetadata> {
+        let namespace_key = NameSpaceKey {
+            key,
+            namespace_id: self.id,
+        };
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let app_storage = &mut canister_data.app_storage;
+            let blob_metadata = app_storage.namespace_blob_metadata.remove(&namespace_key)?;
+
+            (0..blob_metadata.chunk_count).for_each(|chunk_index| {
+                app_storage
+                    .namespace_blob_chunks
+                    .remove(&BlobChunkKey::new(&namespace_key, chunk_index));
+            });
+            app_storage.release_namespace_usage(self.id, blob_metadata.total_size);
+
+            Some(blob_metadata)
+        })
+    }
+
+    pub fn get_usage(&self) -> NamespaceUsage {
+        CANISTER_DATA
+            .with_borrow(|canister_data| canister_data.app_storage.get_namespace_usage(self.id))
+    }
+
+    /// Lists keys of this namespace in order, starting after `cursor` (exclusive).
+    /// Only keys starting with `prefix` are returned when one is passed.
+    /// A `limit` of 0 is rejected, as its empty page would read as the end of the listing.
+    pub fn list_keys(
+        &self,
+        prefix: Option<String>,
+        cursor: Option<String>,
+        limit: u64,
+    ) -> Result<NamespaceKeysPage, NamespaceErrors> {
+        if limit == 0 {
+            return Err(NamespaceErrors::InvalidLimit);
+        }
+
+        let prefix = prefix.unwrap_or_default();
+        let limit = limit.min(MAX_KEYS_IN_ONE_LIST_REQUEST) as usize;
+
+        // * keys of a namespace are contiguous as NameSpaceKey is ordered by namespace_id first
+        let start = match cursor {
+            Some(cursor) if cursor >= prefix => RangeBound::Excluded(NameSpaceKey {
+                namespace_id: self.id,
+                key: cursor,
+            }),
+            _ => RangeBound::Included(NameSpaceKey {
+                namespace_id: self.id,
+                key: prefix.clone(),
+            }),
+        };
+        let end = RangeBound::Excluded(NameSpaceKey {
+            namespace_id: self.id + 1,
+            key: String::new(),
+        });
+
+        let mut keys: Vec<String> = CANISTER_DATA.with_borrow(|canister_data| {
             canister_data
                 .app_storage
                 .namespace_key_value
-                .iter()
-                .map(|(key, _)| key.key)
+                .range((start, end))
+                .map(|(namespace_key, _)| namespace_key.key)
+                .take_while(|key| key.starts_with(&prefix))
+                .take(limit + 1)
                 .collect()
-        })
+        });
+
+        let next_cursor = if keys.len() > limit {
+            keys.truncate(limit);
+            keys.last().cloned()
+        } else {
+            None
+        };
+
+        Ok(NamespaceKeysPage { keys, next_cursor })
     }
 
     pub fn read_key_value_pair(&self, key: String) -> Option<String> {
@@ -204,12 +511,85 @@ impl Storable for NameSpaceKey {
     };
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NamespaceAclKey {
+    pub namespace_id: u64,
+    pub principal: Principal,
+}
+
+impl Storable for NamespaceAclKey {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(self, &mut bytes).unwrap();
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        let namespace_acl_key: Self = ciborium::de::from_reader(bytes.as_ref()).unwrap();
+        namespace_acl_key
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlobChunkKey {
+    pub namespace_id: u64,
+    pub key: String,
+    pub chunk_index: u32,
+}
+
+impl BlobChunkKey {
+    fn new(namespace_key: &NameSpaceKey, chunk_index: u32) -> Self {
+        Self {
+            namespace_id: namespace_key.namespace_id,
+            key: namespace_key.key.clone(),
+            chunk_index,
+        }
+    }
+}
+
+impl Storable for BlobChunkKey {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(self, &mut bytes).unwrap();
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        let blob_chunk_key: Self = ciborium::de::from_reader(bytes.as_ref()).unwrap();
+        blob_chunk_key
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 150,
+        is_fixed_size: false,
+    };
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct AppStorage {
     #[serde(skip, default = "_default_namespace_list")]
     namespace_list: StableBTreeMap<u64, Namespace, Memory>,
     #[serde(skip, default = "_default_namespace_key_value")]
     namespace_key_value: StableBTreeMap<NameSpaceKey, String, Memory>,
+    #[serde(skip, default = "_default_namespace_acl")]
+    namespace_acl: StableBTreeMap<NamespaceAclKey, NamespaceAccessLevel, Memory>,
+    // * kept apart from Namespace as namespace entries are bounded to 100 bytes
+    #[serde(skip, default = "_default_namespace_usage")]
+    namespace_usage: StableBTreeMap<NamespaceId, NamespaceUsage, Memory>,
+    #[serde(skip, default = "_default_namespace_blob_metadata")]
+    namespace_blob_metadata: StableBTreeMap<NameSpaceKey, BlobMetadata, Memory>,
+    #[serde(skip, default = "_default_namespace_blob_chunks")]
+    namespace_blob_chunks: StableBTreeMap<BlobChunkKey, Vec<u8>, Memory>,
+    #[serde(skip, default = "_default_namespace_key_version")]
+    namespace_key_version: StableBTreeMap<NameSpaceKey, u64, Memory>,
+    // * versions come from one counter so a deleted and recreated key never reuses a version
+    #[serde(default)]
+    last_key_version: u64,
 }
 
 impl Default for AppStorage {
@@ -217,14 +597,121 @@ impl Default for AppStorage {
         Self {
             namespace_list: _default_namespace_list(),
             namespace_key_value: _default_namespace_key_value(),
+            namespace_acl: _default_namespace_acl(),
+            namespace_usage: _default_namespace_usage(),
+            namespace_blob_metadata: _default_namespace_blob_metadata(),
+            namespace_blob_chunks: _default_namespace_blob_chunks(),
+            namespace_key_version: _default_namespace_key_version(),
+            last_key_version: 0,
         }
     }
 }
 
 impl AppStorage {
+    fn get_key_version(&self, namespace_key: &NameSpaceKey) -> u64 {
+        match self.namespace_key_version.get(namespace_key) {
+            Some(version) => version,
+            None if self.namespace_key_value.contains_key(namespace_key) => LEGACY_KEY_VERSION,
+            None => 0,
+        }
+    }
+
+    /// Returns the previous value and the new version.
+    /// Usage has to be accounted for by the caller.
+    fn insert_versioned_value(
+        &mut self,
+        namespace_key: NameSpaceKey,
+        value: String,
+    ) -> (Option<String>, u64) {
+        self.last_key_version = self.last_key_version.max(LEGACY_KEY_VERSION) + 1;
+        self.namespace_key_version
+            .insert(namespace_key.clone(), self.last_key_version);
+
+        (
+            self.namespace_key_value.insert(namespace_key, value),
+            self.last_key_version,
+        )
+    }
+
+    /// Usage has to be accounted for by the caller
+    fn remove_versioned_value(&mut self, namespace_key: &NameSpaceKey) -> Option<String> {
+        self.namespace_key_version.remove(namespace_key);
+        self.namespace_key_value.remove(namespace_key)
+    }
+
+    fn get_namespace_usage(&self, namespace_id: NamespaceId) -> NamespaceUsage {
+        self.namespace_usage
+            .get(&namespace_id)
+            .unwrap_or(NamespaceUsage {
+                bytes_used: 0,
+                byte_quota: DEFAULT_NAMESPACE_BYTE_QUOTA,
+            })
+    }
+
+    /// Swaps `prev_size` bytes for `new_size` bytes in the namespace usage,
+    /// failing without any change if the quota would be exceeded.
+    fn adjust_namespace_usage(
+        &mut self,
+        namespace_id: NamespaceId,
+        prev_size: u64,
+        new_size: u64,
+    ) -> Result<(), NamespaceErrors> {
+        let mut usage = self.get_namespace_usage(namespace_id);
+        // * values written before usage was tracked are not part of bytes_used
+        let bytes_used = usage.bytes_used.saturating_sub(prev_size) + new_size;
+        if new_size > prev_size && bytes_used > usage.byte_quota {
+            return Err(NamespaceErrors::QuotaExceeded);
+        }
+
+        usage.bytes_used = bytes_used;
+        self.namespace_usage.insert(namespace_id, usage);
+        Ok(())
+    }
+
+    fn release_namespace_usage(&mut self, namespace_id: NamespaceId, size: u64) {
+        let mut usage = self.get_namespace_usage(namespace_id);
+        usage.bytes_used = usage.bytes_used.saturating_sub(size);
+        self.namespace_usage.insert(namespace_id, usage);
+    }
+
+    /// # Access Control
+    /// Only the profile owner can change how much a namespace is allowed to store
+    pub fn update_namespace_byte_quota(
+        caller: Principal,
+        namespace_uid: u64,
+        byte_quota: u64,
+    ) -> Result<NamespaceUsage, NamespaceErrors> {
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            let profile_owner = canister_data
+                .profile
+                .principal_id
+                .ok_or(NamespaceErrors::UserNotSignedUp)?;
+            if caller != profile_owner {
+                return Err(NamespaceErrors::Unauthorized);
+            }
+            if !canister_data
+                .app_storage
+                .namespace_list
+                .contains_key(&namespace_uid)
+            {
+                return Err(NamespaceErrors::NamespaceNotFound);
+            }
+
+            let app_storage = &mut canister_data.app_storage;
+            let mut usage = app_storage.get_namespace_usage(namespace_uid);
+            usage.byte_quota = byte_quota;
+            app_storage.namespace_usage.insert(namespace_uid, usage);
+
+            Ok(usage)
+        })
+    }
+
+    /// The profile owner and the namespace owner have full access.
+    /// Anyone else needs a grant at or above `required_access_level`.
     pub fn get_a_namespace(
         caller: Principal,
         namespace_uid: u64,
+        required_access_level: NamespaceAccessLevel,
     ) -> Result<Namespace, NamespaceErrors> {
         CANISTER_DATA.with_borrow(|canister_data| {
             let namespace = canister_data
@@ -232,18 +719,117 @@ impl AppStorage {
                 .namespace_list
                 .get(&namespace_uid)
                 .ok_or(NamespaceErrors::NamespaceNotFound)?;
-            let profile_owner = canister_data
-                .profile
-                .principal_id
-                .ok_or(NamespaceErrors::UserNotSignedUp)?;
-            if caller != profile_owner && namespace.namespace_owner_id != caller {
+            if Self::is_caller_namespace_owner(canister_data, caller, &namespace)? {
+                return Ok(namespace);
+            }
+
+            let granted_access_level = canister_data
+                .app_storage
+                .namespace_acl
+                .get(&NamespaceAclKey {
+                    namespace_id: namespace_uid,
+                    principal: caller,
+                })
+                .ok_or(NamespaceErrors::Unauthorized)?;
+            if granted_access_level < required_access_level {
                 return Err(NamespaceErrors::Unauthorized);
             }
 
-            Ok(namespace.clone())
+            Ok(namespace)
         })
     }
 
+    /// Only the profile owner and the namespace owner can manage who else has access
+    fn get_a_namespace_as_owner(
+        caller: Principal,
+        namespace_uid: u64,
+    ) -> Result<Namespace, NamespaceErrors> {
+        CANISTER_DATA.with_borrow(|canister_data| {
+            let namespace = canister_data
+                .app_storage
+                .namespace_list
+                .get(&namespace_uid)
+                .ok_or(NamespaceErrors::NamespaceNotFound)?;
+            if !Self::is_caller_namespace_owner(canister_data, caller, &namespace)? {
+                return Err(NamespaceErrors::Unauthorized);
+            }
+
+            Ok(namespace)
+        })
+    }
+
+    fn is_caller_namespace_owner(
+        canister_data: &CanisterData,
+        caller: Principal,
+        namespace: &Namespace,
+    ) -> Result<bool, NamespaceErrors> {
+        let profile_owner = canister_data
+            .profile
+            .principal_id
+            .ok_or(NamespaceErrors::UserNotSignedUp)?;
+
+        Ok(caller == profile_owner || namespace.namespace_owner_id == caller)
+    }
+
+    pub fn grant_namespace_access(
+        caller: Principal,
+        namespace_uid: u64,
+        grantee: Principal,
+        access_level: NamespaceAccessLevel,
+    ) -> Result<Option<NamespaceAccessLevel>, NamespaceErrors> {
+        let namespace = Self::get_a_namespace_as_owner(caller, namespace_uid)?;
+        let prev_access_level = CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data.app_storage.namespace_acl.insert(
+                NamespaceAclKey {
+                    namespace_id: namespace.id,
+                    principal: grantee,
+                },
+                access_level,
+            )
+        });
+        Ok(prev_access_level)
+    }
+
+    pub fn revoke_namespace_access(
+        caller: Principal,
+        namespace_uid: u64,
+        grantee: Principal,
+    ) -> Result<Option<NamespaceAccessLevel>, NamespaceErrors> {
+        let namespace = Self::get_a_namespace_as_owner(caller, namespace_uid)?;
+        let prev_access_level = CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data
+                .app_storage
+                .namespace_acl
+                .remove(&NamespaceAclKey {
+                    namespace_id: namespace.id,
+                    principal: grantee,
+                })
+        });
+        Ok(prev_access_level)
+    }
+
+    pub fn list_namespace_access_grants(
+        caller: Principal,
+        namespace_uid: u64,
+    ) -> Result<Vec<(Principal, NamespaceAccessLevel)>, NamespaceErrors> {
+        let namespace = Self::get_a_namespace_as_owner(caller, namespace_uid)?;
+        let grants = CANISTER_DATA.with_borrow(|canister_data| {
+            canister_data
+                .app_storage
+                .namespace_acl
+                .range(
+                    NamespaceAclKey {
+                        namespace_id: namespace.id,
+                        principal: Principal::management_canister(),
+                    }..,
+                )
+                .take_while(|(acl_key, _)| acl_key.namespace_id == namespace.id)
+                .map(|(acl_key, access_level)| (acl_key.principal, access_level))
+                .collect()
+        });
+        Ok(grants)
+    }
+
     pub fn list_namespaces(start_index: usize, limit: usize) -> Vec<NamespaceForFrontend> {
         CANISTER_DATA.with_borrow(|canister_data| {
             canister_data
@@ -272,6 +858,245 @@ impl AppStorage {
     }
 }
 
+/// The heap form of [`AppStorage`] kept in the JSON snapshot
+#[derive(Default, Serialize, Deserialize)]
+pub struct AppStorageForSnapshot {
+    pub namespace_list: BTreeMap<u64, Namespace>,
+    #[serde(with = "any_key_map")]
+    pub namespace_key_value: BTreeMap<NameSpaceKey, String>,
+    #[serde(with = "any_key_map")]
+    pub namespace_acl: BTreeMap<NamespaceAclKey, NamespaceAccessLevel>,
+    pub namespace_usage: BTreeMap<u64, NamespaceUsage>,
+    #[serde(with = "any_key_map")]
+    pub namespace_blob_metadata: BTreeMap<NameSpaceKey, BlobMetadata>,
+    #[serde(with = "any_key_map")]
+    pub namespace_blob_chunks: BTreeMap<BlobChunkKey, Vec<u8>>,
+    #[serde(with = "any_key_map")]
+    pub namespace_key_version: BTreeMap<NameSpaceKey, u64>,
+    pub last_key_version: u64,
+}
+
+impl From<&AppStorage> for AppStorageForSnapshot {
+    fn from(app_storage: &AppStorage) -> Self {
+        Self {
+            namespace_list: app_storage.namespace_list.iter().collect(),
+            namespace_key_value: app_storage.namespace_key_value.iter().collect(),
+            namespace_acl: app_storage.namespace_acl.iter().collect(),
+            namespace_usage: app_storage.namespace_usage.iter().collect(),
+            namespace_blob_metadata: app_storage.namespace_blob_metadata.iter().collect(),
+            namespace_blob_chunks: app_storage.namespace_blob_chunks.iter().collect(),
+            namespace_key_version: app_storage.namespace_key_version.iter().collect(),
+            last_key_version: app_storage.last_key_version,
+        }
+    }
+}
+
+impl AppStorageForSnapshot {
+    pub fn from_heap_state(app_storage: &AppStorage) -> Self {
+        Self {
+            last_key_version: app_storage.last_key_version,
+            ..Default::default()
+        }
+    }
+}
+
+/// One entry of a stable map in [`AppStorage`], as streamed by the binary snapshot
+#[derive(Serialize, Deserialize)]
+pub enum AppStorageSnapshotEntry {
+    Namespace(u64, Namespace),
+    KeyValue(NameSpaceKey, String),
+    Acl(NamespaceAclKey, NamespaceAccessLevel),
+    Usage(u64, NamespaceUsage),
+    BlobMetadata(NameSpaceKey, BlobMetadata),
+    BlobChunk(BlobChunkKey, Vec<u8>),
+    KeyVersion(NameSpaceKey, u64),
+}
+
+/// The stable maps in [`AppStorage`], in the order the binary snapshot streams them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AppStorageSnapshotSection {
+    Namespace,
+    KeyValue,
+    Acl,
+    Usage,
+    BlobMetadata,
+    BlobChunk,
+    KeyVersion,
+}
+
+impl AppStorageSnapshotSection {
+    pub const ALL: [Self; 7] = [
+        Self::Namespace,
+        Self::KeyValue,
+        Self::Acl,
+        Self::Usage,
+        Self::BlobMetadata,
+        Self::BlobChunk,
+        Self::KeyVersion,
+    ];
+}
+
+impl AppStorageSnapshotEntry {
+    pub fn section(&self) -> AppStorageSnapshotSection {
+        match self {
+            Self::Namespace(..) => AppStorageSnapshotSection::Namespace,
+            Self::KeyValue(..) => AppStorageSnapshotSection::KeyValue,
+            Self::Acl(..) => AppStorageSnapshotSection::Acl,
+            Self::Usage(..) => AppStorageSnapshotSection::Usage,
+            Self::BlobMetadata(..) => AppStorageSnapshotSection::BlobMetadata,
+            Self::BlobChunk(..) => AppStorageSnapshotSection::BlobChunk,
+            Self::KeyVersion(..) => AppStorageSnapshotSection::KeyVersion,
+        }
+    }
+
+    /// The entry's key as stored in its stable map
+    pub fn key_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Namespace(id, _) | Self::Usage(id, _) => id.to_bytes().into_owned(),
+            Self::KeyValue(key, _) | Self::BlobMetadata(key, _) | Self::KeyVersion(key, _) => {
+                key.to_bytes().into_owned()
+            }
+            Self::Acl(key, _) => key.to_bytes().into_owned(),
+            Self::BlobChunk(key, _) => key.to_bytes().into_owned(),
+        }
+    }
+}
+
+impl AppStorage {
+    /// Streams the entries of one stable map, starting after `last_key` when it is set
+    pub fn snapshot_entries_after(
+        &self,
+        section: AppStorageSnapshotSection,
+        last_key: Option<&[u8]>,
+    ) -> Box<dyn Iterator<Item = AppStorageSnapshotEntry> + '_> {
+        match section {
+            AppStorageSnapshotSection::Namespace => Box::new(
+                stable_entries_after(&self.namespace_list, last_key)
+                    .map(|(id, namespace)| AppStorageSnapshotEntry::Namespace(id, namespace)),
+            ),
+            AppStorageSnapshotSection::KeyValue => Box::new(
+                stable_entries_after(&self.namespace_key_value, last_key)
+                    .map(|(key, value)| AppStorageSnapshotEntry::KeyValue(key, value)),
+            ),
+            AppStorageSnapshotSection::Acl => Box::new(
+                stable_entries_after(&self.namespace_acl, last_key)
+                    .map(|(key, access_level)| AppStorageSnapshotEntry::Acl(key, access_level)),
+            ),
+            AppStorageSnapshotSection::Usage => Box::new(
+                stable_entries_after(&self.namespace_usage, last_key)
+                    .map(|(id, usage)| AppStorageSnapshotEntry::Usage(id, usage)),
+            ),
+            AppStorageSnapshotSection::BlobMetadata => Box::new(
+                stable_entries_after(&self.namespace_blob_metadata, last_key)
+                    .map(|(key, metadata)| AppStorageSnapshotEntry::BlobMetadata(key, metadata)),
+            ),
+            AppStorageSnapshotSection::BlobChunk => Box::new(
+                stable_entries_after(&self.namespace_blob_chunks, last_key)
+                    .map(|(key, chunk)| AppStorageSnapshotEntry::BlobChunk(key, chunk)),
+            ),
+            AppStorageSnapshotSection::KeyVersion => Box::new(
+                stable_entries_after(&self.namespace_key_version, last_key)
+                    .map(|(key, version)| AppStorageSnapshotEntry::KeyVersion(key, version)),
+            ),
+        }
+    }
+
+    /// Empties every stable map, so a restored snapshot does not mix with earlier entries
+    pub fn clear_stable_structures(&mut self) {
+        self.namespace_list.clear_new();
+        self.namespace_key_value.clear_new();
+        self.namespace_acl.clear_new();
+        self.namespace_usage.clear_new();
+        self.namespace_blob_metadata.clear_new();
+        self.namespace_blob_chunks.clear_new();
+        self.namespace_key_version.clear_new();
+    }
+
+    pub fn restore_snapshot_entry(&mut self, entry: AppStorageSnapshotEntry) {
+        match entry {
+            AppStorageSnapshotEntry::Namespace(id, namespace) => {
+                self.namespace_list.insert(id, namespace);
+            }
+            AppStorageSnapshotEntry::KeyValue(key, value) => {
+                self.namespace_key_value.insert(key, value);
+            }
+            AppStorageSnapshotEntry::Acl(key, access_level) => {
+                self.namespace_acl.insert(key, access_level);
+            }
+            AppStorageSnapshotEntry::Usage(id, usage) => {
+                self.namespace_usage.insert(id, usage);
+            }
+            AppStorageSnapshotEntry::BlobMetadata(key, metadata) => {
+                self.namespace_blob_metadata.insert(key, metadata);
+            }
+            AppStorageSnapshotEntry::BlobChunk(key, chunk) => {
+                self.namespace_blob_chunks.insert(key, chunk);
+            }
+            AppStorageSnapshotEntry::KeyVersion(key, version) => {
+                self.namespace_key_version.insert(key, version);
+            }
+        }
+    }
+}
+
+impl From<AppStorageForSnapshot> for AppStorage {
+    fn from(app_storage_snapshot: AppStorageForSnapshot) -> Self {
+        let mut app_storage = AppStorage {
+            last_key_version: app_storage_snapshot.last_key_version,
+            ..Default::default()
+        };
+
+        app_storage_snapshot
+            .namespace_list
+            .into_iter()
+            .for_each(|(id, namespace)| {
+                app_storage.namespace_list.insert(id, namespace);
+            });
+        app_storage_snapshot
+            .namespace_key_value
+            .into_iter()
+            .for_each(|(namespace_key, value)| {
+                app_storage.namespace_key_value.insert(namespace_key, value);
+            });
+        app_storage_snapshot
+            .namespace_acl
+            .into_iter()
+            .for_each(|(acl_key, access_level)| {
+                app_storage.namespace_acl.insert(acl_key, access_level);
+            });
+        app_storage_snapshot
+            .namespace_usage
+            .into_iter()
+            .for_each(|(id, usage)| {
+                app_storage.namespace_usage.insert(id, usage);
+            });
+        app_storage_snapshot
+            .namespace_blob_metadata
+            .into_iter()
+            .for_each(|(namespace_key, blob_metadata)| {
+                app_storage
+                    .namespace_blob_metadata
+                    .insert(namespace_key, blob_metadata);
+            });
+        app_storage_snapshot
+            .namespace_blob_chunks
+            .into_iter()
+            .for_each(|(chunk_key, chunk)| {
+                app_storage.namespace_blob_chunks.insert(chunk_key, chunk);
+            });
+        app_storage_snapshot
+            .namespace_key_version
+            .into_iter()
+            .for_each(|(namespace_key, version)| {
+                app_storage
+                    .namespace_key_version
+                    .insert(namespace_key, version);
+            });
+
+        app_storage
+    }
+}
+
 pub fn _default_namespace_list() -> StableBTreeMap<u64, Namespace, Memory> {
     ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_memory())
 }
@@ -279,3 +1104,427 @@ pub fn _default_namespace_list() -> StableBTreeMap<u64, Namespace, Memory> {
 pub fn _default_namespace_key_value() -> StableBTreeMap<NameSpaceKey, String, Memory> {
     ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_key_value_memory())
 }
+
+pub fn _default_namespace_usage() -> StableBTreeMap<NamespaceId, NamespaceUsage, Memory> {
+    ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_usage_memory())
+}
+
+pub fn _default_namespace_blob_metadata() -> StableBTreeMap<NameSpaceKey, BlobMetadata, Memory> {
+    ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_blob_metadata_memory())
+}
+
+pub fn _default_namespace_blob_chunks() -> StableBTreeMap<BlobChunkKey, Vec<u8>, Memory> {
+    ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_blob_chunk_memory())
+}
+
+pub fn _default_namespace_key_version() -> StableBTreeMap<NameSpaceKey, u64, Memory> {
+    ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_version_memory())
+}
+
+pub fn _default_namespace_acl() -> StableBTreeMap<NamespaceAclKey, NamespaceAccessLevel, Memory> {
+    ic_stable_structures::StableBTreeMap::init(get_kv_storage_namespace_acl_memory())
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+        get_mock_user_charlie_principal_id,
+    };
+
+    use super::*;
+
+    fn namespace_with_keys(id: u64, keys: &[&str]) -> Namespace {
+        let namespace = Namespace {
+            id,
+            title: format!("namespace {id}"),
+            namespace_owner_id: get_mock_user_alice_principal_id(),
+        };
+        namespace
+            .write_multiple_key_value_pairs(
+                keys.iter()
+                    .map(|key| (key.to_string(), "value".to_string()))
+                    .collect(),
+            )
+            .unwrap();
+        namespace
+    }
+
+    #[test]
+    fn test_list_keys_is_scoped_to_the_namespace() {
+        let first = namespace_with_keys(0, &["a", "b"]);
+        let second = namespace_with_keys(1, &["a", "c"]);
+
+        assert_eq!(
+            first.list_keys(None, None, 10).unwrap().keys,
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            second.list_keys(None, None, 10).unwrap().keys,
+            vec!["a", "c"]
+        );
+    }
+
+    #[test]
+    fn test_list_keys_with_prefix_and_cursor() {
+        let namespace = namespace_with_keys(
+            0,
+            &["draft/1", "draft/2", "draft/3", "settings", "drafts_count"],
+        );
+        namespace_with_keys(1, &["draft/4"]);
+
+        let page = namespace.list_keys(Some("draft/".into()), None, 2).unwrap();
+        assert_eq!(page.keys, vec!["draft/1", "draft/2"]);
+        assert_eq!(page.next_cursor, Some("draft/2".into()));
+
+        let page = namespace
+            .list_keys(Some("draft/".into()), page.next_cursor, 2)
+            .unwrap();
+        assert_eq!(page.keys, vec!["draft/3"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = namespace
+            .list_keys(None, Some("draft/3".into()), 10)
+            .unwrap();
+        assert_eq!(page.keys, vec!["drafts_count", "settings"]);
+        assert_eq!(page.next_cursor, None);
+
+        assert_eq!(
+            namespace.list_keys(None, Some("draft/1".into()), 0),
+            Err(NamespaceErrors::InvalidLimit)
+        );
+    }
+
+    #[test]
+    fn test_namespace_access_grants() {
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+        let charlie = get_mock_user_charlie_principal_id();
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data.profile.principal_id = Some(alice);
+        });
+
+        let namespace = AppStorage::create_a_namespace(charlie, "mini app".into()).unwrap();
+
+        // * namespace owner and profile owner have full access
+        assert!(AppStorage::get_a_namespace(
+            charlie,
+            namespace.id,
+            NamespaceAccessLevel::ReadWrite
+        )
+        .is_ok());
+        assert!(
+            AppStorage::get_a_namespace(alice, namespace.id, NamespaceAccessLevel::ReadWrite)
+                .is_ok()
+        );
+        assert!(
+            AppStorage::get_a_namespace(bob, namespace.id, NamespaceAccessLevel::ReadOnly).is_err()
+        );
+
+        // * only owners can grant
+        assert!(AppStorage::grant_namespace_access(
+            bob,
+            namespace.id,
+            bob,
+            NamespaceAccessLevel::ReadWrite
+        )
+        .is_err());
+
+        let prev_access_level = AppStorage::grant_namespace_access(
+            charlie,
+            namespace.id,
+            bob,
+            NamespaceAccessLevel::ReadOnly,
+        )
+        .unwrap();
+        assert_eq!(prev_access_level, None);
+        assert!(
+            AppStorage::get_a_namespace(bob, namespace.id, NamespaceAccessLevel::ReadOnly).is_ok()
+        );
+        assert!(
+            AppStorage::get_a_namespace(bob, namespace.id, NamespaceAccessLevel::ReadWrite)
+                .is_err()
+        );
+
+        let prev_access_level = AppStorage::grant_namespace_access(
+            alice,
+            namespace.id,
+            bob,
+            NamespaceAccessLevel::ReadWrite,
+        )
+        .unwrap();
+        assert_eq!(prev_access_level, Some(NamespaceAccessLevel::ReadOnly));
+        assert!(
+            AppStorage::get_a_namespace(bob, namespace.id, NamespaceAccessLevel::ReadWrite).is_ok()
+        );
+        assert_eq!(
+            AppStorage::list_namespace_access_grants(charlie, namespace.id).unwrap(),
+            vec![(bob, NamespaceAccessLevel::ReadWrite)]
+        );
+
+        AppStorage::revoke_namespace_access(charlie, namespace.id, bob).unwrap();
+        assert!(
+            AppStorage::get_a_namespace(bob, namespace.id, NamespaceAccessLevel::ReadOnly).is_err()
+        );
+        assert!(
+            AppStorage::list_namespace_access_grants(charlie, namespace.id)
+                .unwrap()
+                .is_empty()
+        );
+    }
+    #[test]
+    fn test_compare_and_swap() {
+        let namespace = namespace_with_keys(0, &[]);
+
+        let first_version = namespace
+            .compare_and_swap("a".into(), 0, "first".into())
+            .unwrap();
+        assert!(first_version > 0);
+        // * a stale writer loses
+        assert_eq!(
+            namespace.compare_and_swap("a".into(), 0, "stale".into()),
+            Err(NamespaceErrors::VersionMismatch)
+        );
+
+        let second_version = namespace
+            .compare_and_swap("a".into(), first_version, "second".into())
+            .unwrap();
+        assert!(second_version > first_version);
+        assert_eq!(
+            namespace.read_versioned_key_value_pair("a".into()),
+            Some(VersionedValue {
+                value: "second".into(),
+                version: second_version
+            })
+        );
+
+        // * unconditional writes bump the version too
+        namespace
+            .write_key_value_pair("a".into(), "third".into())
+            .unwrap();
+        assert_eq!(
+            namespace.compare_and_swap("a".into(), second_version, "stale".into()),
+            Err(NamespaceErrors::VersionMismatch)
+        );
+
+        // * a recreated key does not reuse an old version
+        namespace.delete_key_value_pair("a".into());
+        assert_eq!(namespace.read_versioned_key_value_pair("a".into()), None);
+        let recreated_version = namespace
+            .compare_and_swap("a".into(), 0, "again".into())
+            .unwrap();
+        assert!(recreated_version > second_version);
+    }
+
+    #[test]
+    fn test_keys_written_before_versioning_are_at_version_1() {
+        let namespace = namespace_with_keys(0, &[]);
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data.app_storage.namespace_key_value.insert(
+                NameSpaceKey {
+                    key: "legacy".into(),
+                    namespace_id: 0,
+                },
+                "old".into(),
+            );
+        });
+
+        assert_eq!(
+            namespace.read_versioned_key_value_pair("legacy".into()),
+            Some(VersionedValue {
+                value: "old".into(),
+                version: LEGACY_KEY_VERSION
+            })
+        );
+        // * a create-only write must not overwrite it
+        assert_eq!(
+            namespace.compare_and_swap("legacy".into(), 0, "new".into()),
+            Err(NamespaceErrors::VersionMismatch)
+        );
+
+        let new_version = namespace
+            .compare_and_swap("legacy".into(), LEGACY_KEY_VERSION, "new".into())
+            .unwrap();
+        assert!(new_version > LEGACY_KEY_VERSION);
+    }
+
+    #[test]
+    fn test_transaction_is_all_or_nothing() {
+        let namespace = namespace_with_keys(0, &["a", "b"]);
+        let version_of_a = namespace
+            .read_versioned_key_value_pair("a".into())
+            .unwrap()
+            .version;
+        let version_of_b = namespace
+            .read_versioned_key_value_pair("b".into())
+            .unwrap()
+            .version;
+
+        let result = namespace.apply_transaction(vec![
+            KeyValueTransactionOperation::Put {
+                key: "a".into(),
+                value: "new".into(),
+                expected_version: Some(version_of_a),
+            },
+            KeyValueTransactionOperation::Delete {
+                key: "b".into(),
+                expected_version: Some(version_of_b + 1),
+            },
+        ]);
+        assert_eq!(result, Err(NamespaceErrors::VersionMismatch));
+        assert_eq!(
+            namespace.read_key_value_pair("a".into()),
+            Some("value".into())
+        );
+
+        assert_eq!(
+            namespace.apply_transaction(vec![
+                KeyValueTransactionOperation::Delete {
+                    key: "a".into(),
+                    expected_version: None,
+                },
+                KeyValueTransactionOperation::Put {
+                    key: "a".into(),
+                    value: "new".into(),
+                    expected_version: None,
+                },
+            ]),
+            Err(NamespaceErrors::DuplicateKeyInTransaction)
+        );
+
+        let new_versions = namespace
+            .apply_transaction(vec![
+                KeyValueTransactionOperation::Put {
+                    key: "a".into(),
+                    value: "new".into(),
+                    expected_version: Some(version_of_a),
+                },
+                KeyValueTransactionOperation::Delete {
+                    key: "b".into(),
+                    expected_version: Some(version_of_b),
+                },
+                KeyValueTransactionOperation::Put {
+                    key: "c".into(),
+                    value: "created".into(),
+                    expected_version: Some(0),
+                },
+            ])
+            .unwrap();
+        assert_eq!(new_versions[1], 0);
+        assert!(new_versions[0] > version_of_b && new_versions[2] > new_versions[0]);
+        assert_eq!(
+            namespace.read_key_value_pair("a".into()),
+            Some("new".into())
+        );
+        assert_eq!(namespace.read_key_value_pair("b".into()), None);
+        assert_eq!(namespace.get_usage().bytes_used, 10);
+    }
+
+    #[test]
+    fn test_usage_is_tracked_across_values_and_blobs() {
+        let namespace = namespace_with_keys(0, &[]);
+
+        namespace
+            .write_key_value_pair("a".into(), "12345".into())
+            .unwrap();
+        namespace
+            .write_key_value_pair("a".into(), "123".into())
+            .unwrap();
+        assert_eq!(namespace.get_usage().bytes_used, 3);
+
+        namespace
+            .write_blob_chunk("avatar".into(), 0, vec![0; 10])
+            .unwrap();
+        let blob_metadata = namespace
+            .write_blob_chunk("avatar".into(), 1, vec![1; 4])
+            .unwrap();
+        assert_eq!(
+            blob_metadata,
+            BlobMetadata {
+                total_size: 14,
+                chunk_count: 2
+            }
+        );
+        assert_eq!(namespace.get_usage().bytes_used, 17);
+
+        // * chunks can be overwritten but not skipped
+        assert_eq!(
+            namespace.write_blob_chunk("avatar".into(), 3, vec![3; 2]),
+            Err(NamespaceErrors::ChunkIndexOutOfRange)
+        );
+        assert_eq!(
+            namespace.write_blob_chunk("avatar".into(), 2, vec![]),
+            Err(NamespaceErrors::EmptyChunk)
+        );
+        namespace
+            .write_blob_chunk("avatar".into(), 0, vec![2; 6])
+            .unwrap();
+        assert_eq!(
+            namespace.read_blob_chunk("avatar".into(), 0),
+            Some(vec![2; 6])
+        );
+        assert_eq!(namespace.get_usage().bytes_used, 13);
+
+        namespace.delete_blob("avatar".into()).unwrap();
+        namespace.delete_key_value_pair("a".into());
+        assert_eq!(namespace.get_usage().bytes_used, 0);
+        assert_eq!(namespace.read_blob_chunk("avatar".into(), 1), None);
+    }
+
+    #[test]
+    fn test_writes_are_rejected_over_the_namespace_quota() {
+        let alice = get_mock_user_alice_principal_id();
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            canister_data.profile.principal_id = Some(alice);
+        });
+        let namespace = AppStorage::create_a_namespace(alice, "mini app".into()).unwrap();
+        let namespace =
+            AppStorage::get_a_namespace(alice, namespace.id, NamespaceAccessLevel::ReadWrite)
+                .unwrap();
+
+        assert_eq!(
+            AppStorage::update_namespace_byte_quota(
+                get_mock_user_bob_principal_id(),
+                namespace.id,
+                10
+            ),
+            Err(NamespaceErrors::Unauthorized)
+        );
+        AppStorage::update_namespace_byte_quota(alice, namespace.id, 10).unwrap();
+
+        namespace
+            .write_key_value_pair("a".into(), "12345678".into())
+            .unwrap();
+        assert_eq!(
+            namespace.write_blob_chunk("b".into(), 0, vec![0; 3]),
+            Err(NamespaceErrors::QuotaExceeded)
+        );
+        assert_eq!(
+            namespace.write_multiple_key_value_pairs(
+                [
+                    ("b".to_string(), "1".to_string()),
+                    ("c".to_string(), "22".to_string())
+                ]
+                .into()
+            ),
+            Err(NamespaceErrors::QuotaExceeded)
+        );
+        // * a rejected batch writes nothing
+        assert_eq!(namespace.read_key_value_pair("b".into()), None);
+
+        // * shrinking a value is always allowed
+        namespace
+            .write_key_value_pair("a".into(), "1".into())
+            .unwrap();
+        assert_eq!(namespace.get_usage().bytes_used, 1);
+
+        assert_eq!(
+            namespace.write_key_value_pair("a".repeat(MAX_KEY_SIZE_IN_BYTES + 1), "1".into()),
+            Err(NamespaceErrors::KeyTooBig)
+        );
+        assert_eq!(
+            namespace.write_blob_chunk("b".into(), 0, vec![0; BLOB_CHUNK_SIZE_IN_BYTES + 1]),
+            Err(NamespaceErrors::ChunkTooBig)
+        );
+    }
+}
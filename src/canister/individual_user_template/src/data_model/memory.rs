@@ -19,6 +19,15 @@ const KV_STORAGE_NAMESPACE_KEY_VALUE_MEMORY: MemoryId = MemoryId::new(6);
 const WATCH_HISTORY_MEMORY: MemoryId = MemoryId::new(7);
 const SUCCESS_HISTORY_MEMORY: MemoryId = MemoryId::new(8);
 const TOKEN_LIST_MEMORY: MemoryId = MemoryId::new(9);
+const SLOT_TABULATION_QUEUE_MEMORY: MemoryId = MemoryId::new(10);
+const KV_STORAGE_NAMESPACE_ACL_MEMORY: MemoryId = MemoryId::new(11);
+const KV_STORAGE_NAMESPACE_USAGE_MEMORY: MemoryId = MemoryId::new(12);
+const KV_STORAGE_NAMESPACE_BLOB_METADATA_MEMORY: MemoryId = MemoryId::new(13);
+const KV_STORAGE_NAMESPACE_BLOB_CHUNK_MEMORY: MemoryId = MemoryId::new(14);
+const KV_STORAGE_NAMESPACE_VERSION_MEMORY: MemoryId = MemoryId::new(15);
+const COMMENTS_MEMORY: MemoryId = MemoryId::new(16);
+const PREDICTION_DETAILS_MEMORY: MemoryId = MemoryId::new(17);
+const PREDICTION_BET_DETAILS_MEMORY: MemoryId = MemoryId::new(18);
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -69,6 +78,45 @@ pub fn get_token_list_memory() -> Memory {
     MEMORY_MANAGER.with(|m| m.borrow_mut().get(TOKEN_LIST_MEMORY))
 }
 
+pub fn get_slot_tabulation_queue_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(SLOT_TABULATION_QUEUE_MEMORY))
+}
+
+pub fn get_kv_storage_namespace_acl_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(KV_STORAGE_NAMESPACE_ACL_MEMORY))
+}
+
+pub fn get_kv_storage_namespace_usage_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(KV_STORAGE_NAMESPACE_USAGE_MEMORY))
+}
+
+pub fn get_kv_storage_namespace_blob_metadata_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow_mut()
+            .get(KV_STORAGE_NAMESPACE_BLOB_METADATA_MEMORY)
+    })
+}
+
+pub fn get_kv_storage_namespace_blob_chunk_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(KV_STORAGE_NAMESPACE_BLOB_CHUNK_MEMORY))
+}
+
+pub fn get_kv_storage_namespace_version_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(KV_STORAGE_NAMESPACE_VERSION_MEMORY))
+}
+
+pub fn get_comments_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(COMMENTS_MEMORY))
+}
+
+pub fn get_prediction_details_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(PREDICTION_DETAILS_MEMORY))
+}
+
+pub fn get_prediction_bet_details_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow_mut().get(PREDICTION_BET_DETAILS_MEMORY))
+}
+
 pub fn init_memory_manager() {
     MEMORY_MANAGER.with(|m| {
         *m.borrow_mut() = MemoryManager::init_with_bucket_size(DefaultMemoryImpl::default(), 1);
@@ -1,28 +1,43 @@
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, BTreeSet},
+    ops::Bound,
     time::SystemTime,
 };
 
 use candid::{Deserialize, Principal};
 use ic_cdk::api::management_canister::provisional::CanisterId;
-use memory::{get_success_history_memory, get_token_list_memory, get_watch_history_memory};
+use ic_stable_structures::Storable;
+use memory::{
+    get_comments_memory, get_prediction_bet_details_memory, get_prediction_details_memory,
+    get_slot_tabulation_queue_memory, get_success_history_memory, get_token_list_memory,
+    get_watch_history_memory,
+};
 use serde::Serialize;
 use shared_utils::{
     canister_specific::individual_user_template::types::{
         cdao::DeployedCdaoCanisters,
+        comment::{Comment, CommentRateLimitWindow, GlobalCommentId},
         configuration::IndividualUserConfiguration,
         device_id::DeviceIdentity,
         follow::FollowData,
         hot_or_not::{
-            BetDetails, GlobalBetId, GlobalRoomId, PlacedBetDetail, RoomDetailsV1, RoomId,
-            SlotDetailsV1, SlotId, StablePrincipal,
+            game_config::HotOrNotGameConfig, BetDetails, BetIdempotencyKey,
+            BetReconciliationDetails, GlobalBetId, GlobalRoomId, PendingBet, PendingBetWithdrawal,
+            PendingPayoutNotification, PlacedBetDetail, RoomDetailsV1, RoomId, SlotDetailsV1,
+            SlotId, SlotTabulationJob, StablePrincipal,
         },
         migration::MigrationInfo,
         ml_data::{MLFeedCacheItem, SuccessHistoryItem, SuccessHistoryItemV1, WatchHistoryItem},
         post::{FeedScore, Post, PostViewStatistics},
+        prediction::{
+            GlobalPredictionBetId, PendingPredictionBet, PlacedPredictionBetDetail,
+            PredictionBetDetails, PredictionDetails, PredictionSettlementJob,
+        },
         profile::UserProfile,
+        report::PostReports,
         session::SessionType,
-        token::TokenBalance,
+        token::{PendingPostTip, PendingTokenTransfer, TokenBalance, TokenTransferIdempotencyKey},
     },
     common::types::{
         app_primitive_type::PostId,
@@ -58,10 +73,37 @@ pub struct CanisterData {
     pub slot_details_map:
         ic_stable_structures::btreemap::BTreeMap<(PostId, SlotId), SlotDetailsV1, Memory>,
     pub all_hot_or_not_bets_placed: BTreeMap<(CanisterId, PostId), PlacedBetDetail>,
+    /// Bets sent to a post creator's canister that it has not confirmed yet
+    #[serde(default)]
+    pub pending_bets: BTreeMap<(CanisterId, PostId), PendingBet>,
+    /// Withdrawals sent to a post creator's canister that it has not confirmed yet
+    #[serde(default)]
+    pub pending_bet_withdrawals: BTreeMap<(CanisterId, PostId), PendingBetWithdrawal>,
+    #[serde(default)]
+    pub next_bet_idempotency_key: BetIdempotencyKey,
+    /// Bet outcomes that could not be delivered to the bet makers' canisters yet
+    #[serde(default)]
+    pub payout_notification_outbox: BTreeMap<GlobalBetId, PendingPayoutNotification>,
+    /// Bets withdrawn from the posts of this profile, keyed by post and bet maker.
+    /// Bet makers' canisters settle withdrawals they never got a reply for from these.
+    #[serde(default)]
+    pub withdrawn_bets: BTreeMap<(PostId, Principal), BetReconciliationDetails>,
     pub configuration: IndividualUserConfiguration,
     pub follow_data: FollowData,
     pub known_principal_ids: KnownPrincipalMap,
     pub my_token_balance: TokenBalance,
+    /// Transfers sent to a recipient's canister that it has not confirmed yet
+    #[serde(default)]
+    pub pending_token_transfers: BTreeMap<TokenTransferIdempotencyKey, PendingTokenTransfer>,
+    /// Tips sent to a post creator's canister that it has not confirmed yet
+    #[serde(default)]
+    pub pending_post_tips: BTreeMap<TokenTransferIdempotencyKey, PendingPostTip>,
+    #[serde(default)]
+    pub next_token_transfer_idempotency_key: TokenTransferIdempotencyKey,
+    /// Keys of the latest transfers received, keyed by sender.
+    /// Senders' canisters settle transfers they never got a reply for from these.
+    #[serde(default)]
+    pub received_token_transfer_keys: BTreeMap<Principal, BTreeSet<TokenTransferIdempotencyKey>>,
     pub posts_index_sorted_by_home_feed_score: PostScoreIndex,
     pub posts_index_sorted_by_hot_or_not_feed_score: PostScoreIndex,
     pub principals_i_follow: BTreeSet<Principal>,
@@ -91,6 +133,64 @@ pub struct CanisterData {
     // list of root token canisters
     #[serde(skip, default = "_default_token_list")]
     pub token_roots: ic_stable_structures::btreemap::BTreeMap<Principal, (), Memory>,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    #[serde(skip, default = "_default_slot_tabulation_queue")]
+    pub slot_tabulation_queue:
+        ic_stable_structures::btreemap::BTreeMap<SlotTabulationJob, (), Memory>,
+    /// Deleted posts whose heavy fields have not been dropped yet
+    #[serde(default)]
+    pub posts_pending_purge: BTreeSet<PostId>,
+    #[serde(default)]
+    pub post_reports: BTreeMap<PostId, PostReports>,
+    /// Principals that can no longer follow, bet on or comment on this profile
+    #[serde(default)]
+    pub blocked_principals: BTreeSet<Principal>,
+    /// Creators left out of the feed served from this canister
+    #[serde(default)]
+    pub muted_principals: BTreeSet<Principal>,
+    #[serde(skip, default = "_default_comments_map")]
+    pub comments_map: ic_stable_structures::btreemap::BTreeMap<GlobalCommentId, Comment, Memory>,
+    /// Keyed by comment author, windows that ended are dropped as new comments come in
+    #[serde(default)]
+    pub comment_rate_limit_windows: BTreeMap<Principal, CommentRateLimitWindow>,
+    /// Predictions attached to posts of this profile, keyed by post id
+    #[serde(skip, default = "_default_prediction_details_map")]
+    pub prediction_details_map:
+        ic_stable_structures::btreemap::BTreeMap<PostId, PredictionDetails, Memory>,
+    #[serde(skip, default = "_default_prediction_bet_details_map")]
+    pub prediction_bet_details_map: ic_stable_structures::btreemap::BTreeMap<
+        GlobalPredictionBetId,
+        PredictionBetDetails,
+        Memory,
+    >,
+    /// Bets this profile placed on predictions of other posts
+    #[serde(default)]
+    pub all_prediction_bets_placed: BTreeMap<(CanisterId, PostId), PlacedPredictionBetDetail>,
+    /// Prediction bets sent to a post creator's canister that it has not confirmed yet
+    #[serde(default)]
+    pub pending_prediction_bets: BTreeMap<(CanisterId, PostId), PendingPredictionBet>,
+    /// Prediction outcomes that could not be delivered to the bet makers' canisters yet
+    #[serde(default)]
+    pub prediction_payout_notification_outbox:
+        BTreeMap<GlobalPredictionBetId, PendingPayoutNotification>,
+    /// Open predictions by when the settlement timer settles them, earliest first
+    #[serde(default)]
+    pub prediction_settlement_queue: BTreeSet<PredictionSettlementJob>,
+    /// Set by user_index while this canister is copied to another subnet.
+    /// Updates from anyone but the controller are rejected and the timers pause meanwhile.
+    /// Not part of the snapshot, a restored canister always starts unfrozen.
+    #[serde(default)]
+    pub ingress_frozen_for_migration: bool,
+    /// Set by user_index once the user's canister on another subnet has taken over.
+    /// This canister then stays frozen so that other canisters can follow the user there.
+    /// Not part of the snapshot.
+    #[serde(default)]
+    pub migrated_to_canister_id: Option<Principal>,
+    /// Canisters this profile lived in before it was migrated here, oldest first.
+    /// Bets placed on the posts of this profile may still refer to them.
+    #[serde(default)]
+    pub former_canister_ids: Vec<Principal>,
 }
 
 pub fn _default_room_details(
@@ -133,6 +233,62 @@ pub fn _default_success_history_v1(
     ic_stable_structures::btreemap::BTreeMap::init(get_success_history_memory())
 }
 
+pub fn _default_slot_tabulation_queue(
+) -> ic_stable_structures::btreemap::BTreeMap<SlotTabulationJob, (), Memory> {
+    ic_stable_structures::btreemap::BTreeMap::init(get_slot_tabulation_queue_memory())
+}
+
+pub fn _default_comments_map(
+) -> ic_stable_structures::btreemap::BTreeMap<GlobalCommentId, Comment, Memory> {
+    ic_stable_structures::btreemap::BTreeMap::init(get_comments_memory())
+}
+
+pub fn _default_prediction_details_map(
+) -> ic_stable_structures::btreemap::BTreeMap<PostId, PredictionDetails, Memory> {
+    ic_stable_structures::btreemap::BTreeMap::init(get_prediction_details_memory())
+}
+
+pub fn _default_prediction_bet_details_map(
+) -> ic_stable_structures::btreemap::BTreeMap<GlobalPredictionBetId, PredictionBetDetails, Memory> {
+    ic_stable_structures::btreemap::BTreeMap::init(get_prediction_bet_details_memory())
+}
+
+/// Iterates a stable map in key order, starting after `last_key` when it is set.
+/// `last_key` is the key as stored in the map.
+pub fn stable_entries_after<'a, K, V>(
+    map: &'a ic_stable_structures::btreemap::BTreeMap<K, V, Memory>,
+    last_key: Option<&[u8]>,
+) -> impl Iterator<Item = (K, V)> + 'a
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    let start = match last_key {
+        Some(last_key) => Bound::Excluded(K::from_bytes(Cow::Borrowed(last_key))),
+        None => Bound::Unbounded,
+    };
+
+    map.range((start, Bound::Unbounded))
+}
+
+impl CanisterData {
+    /// Empties every stable structure, so a restored snapshot does not mix with earlier entries
+    pub fn clear_stable_structures(&mut self) {
+        self.room_details_map.clear_new();
+        self.bet_details_map.clear_new();
+        self.post_principal_map.clear_new();
+        self.slot_details_map.clear_new();
+        self.watch_history.clear_new();
+        self.success_history.clear_new();
+        self.token_roots.clear_new();
+        self.slot_tabulation_queue.clear_new();
+        self.app_storage.clear_stable_structures();
+        self.comments_map.clear_new();
+        self.prediction_details_map.clear_new();
+        self.prediction_bet_details_map.clear_new();
+    }
+}
+
 impl Default for CanisterData {
     fn default() -> Self {
         Self {
@@ -142,10 +298,19 @@ impl Default for CanisterData {
             post_principal_map: _default_post_principal_map(),
             slot_details_map: _default_slot_details_map(),
             all_hot_or_not_bets_placed: BTreeMap::new(),
+            pending_bets: BTreeMap::new(),
+            pending_bet_withdrawals: BTreeMap::new(),
+            next_bet_idempotency_key: 0,
+            payout_notification_outbox: BTreeMap::new(),
+            withdrawn_bets: BTreeMap::new(),
             configuration: IndividualUserConfiguration::default(),
             follow_data: FollowData::default(),
             known_principal_ids: KnownPrincipalMap::default(),
             my_token_balance: TokenBalance::default(),
+            pending_token_transfers: BTreeMap::new(),
+            pending_post_tips: BTreeMap::new(),
+            next_token_transfer_idempotency_key: 0,
+            received_token_transfer_keys: BTreeMap::new(),
             posts_index_sorted_by_home_feed_score: PostScoreIndex::default(),
             posts_index_sorted_by_hot_or_not_feed_score: PostScoreIndex::default(),
             principals_i_follow: BTreeSet::new(),
@@ -163,6 +328,23 @@ impl Default for CanisterData {
             ml_feed_cache: Vec::new(),
             cdao_canisters: Vec::new(),
             token_roots: _default_token_list(),
+            hot_or_not_game_config: HotOrNotGameConfig::default(),
+            slot_tabulation_queue: _default_slot_tabulation_queue(),
+            posts_pending_purge: BTreeSet::new(),
+            post_reports: BTreeMap::new(),
+            blocked_principals: BTreeSet::new(),
+            muted_principals: BTreeSet::new(),
+            comments_map: _default_comments_map(),
+            comment_rate_limit_windows: BTreeMap::new(),
+            prediction_details_map: _default_prediction_details_map(),
+            prediction_bet_details_map: _default_prediction_bet_details_map(),
+            all_prediction_bets_placed: BTreeMap::new(),
+            pending_prediction_bets: BTreeMap::new(),
+            prediction_payout_notification_outbox: BTreeMap::new(),
+            prediction_settlement_queue: BTreeSet::new(),
+            ingress_frozen_for_migration: false,
+            migrated_to_canister_id: None,
+            former_canister_ids: Vec::new(),
         }
     }
 }
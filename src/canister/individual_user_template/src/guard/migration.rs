@@ -0,0 +1,62 @@
+use ic_cdk::api::is_controller;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Rejects updates while the canister is copied to another subnet or after it moved there,
+/// so that nothing written in the meantime is left behind.
+/// The controller (user_index) drives the migration and is let through.
+pub fn is_not_frozen_for_migration() -> Result<(), String> {
+    if is_controller(&ic_cdk::caller()) {
+        return Ok(());
+    }
+
+    CANISTER_DATA.with_borrow(is_not_frozen_for_migration_impl)
+}
+
+/// For the timers, which run with no caller
+pub fn is_not_frozen_for_migration_impl(canister_data: &CanisterData) -> Result<(), String> {
+    if let Some(migrated_to_canister_id) = canister_data.migrated_to_canister_id {
+        return Err(format!(
+            "Canister migrated to {}",
+            migrated_to_canister_id.to_text()
+        ));
+    }
+
+    if canister_data.ingress_frozen_for_migration {
+        return Err("Canister is frozen for migration".into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::get_mock_user_alice_canister_id;
+
+    use super::*;
+
+    #[test]
+    fn test_is_not_frozen_for_migration_impl() {
+        let mut canister_data = CanisterData::default();
+        assert!(is_not_frozen_for_migration_impl(&canister_data).is_ok());
+
+        canister_data.ingress_frozen_for_migration = true;
+        assert_eq!(
+            is_not_frozen_for_migration_impl(&canister_data),
+            Err("Canister is frozen for migration".into())
+        );
+
+        // * a rolled back migration unfreezes the canister
+        canister_data.ingress_frozen_for_migration = false;
+        assert!(is_not_frozen_for_migration_impl(&canister_data).is_ok());
+
+        canister_data.migrated_to_canister_id = Some(get_mock_user_alice_canister_id());
+        assert_eq!(
+            is_not_frozen_for_migration_impl(&canister_data),
+            Err(format!(
+                "Canister migrated to {}",
+                get_mock_user_alice_canister_id().to_text()
+            ))
+        );
+    }
+}
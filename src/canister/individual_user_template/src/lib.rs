@@ -4,6 +4,7 @@ use std::{collections::BTreeMap, time::SystemTime};
 use api::{
     follow::update_profiles_that_follow_me_toggle_list_with_specified_profile::FollowerArg,
     profile::update_profile_display_details::UpdateProfileDetailsError,
+    snapshot::chunked_snapshot::SnapshotData,
 };
 use candid::{Nat, Principal};
 use data_model::CanisterData;
@@ -16,26 +17,49 @@ use ic_sns_init::pb::v1::SnsInitPayload;
 use icrc_ledger_types::icrc1::transfer::Memo;
 use shared_utils::{
     canister_specific::individual_user_template::types::{
-        arg::{FolloweeArg, IndividualUserTemplateInitArgs, PlaceBetArg},
+        arg::{
+            AddCommentToPostArg, CreatePredictionArg, FolloweeArg, IndividualUserTemplateInitArgs,
+            PlaceBetArg, PlacePredictionBetArg, ReportPostArg, TipPostArg, TransferUtilityTokenArg,
+            UpdatePostDetailsArg,
+        },
         cdao::DeployedCdaoCanisters,
+        comment::{CommentDetailsForFrontend, CommentId, CommentStatus},
         device_id::DeviceIdentity,
         error::{
-            BetOnCurrentlyViewingPostError, CdaoDeployError, CdaoTokenError,
-            FollowAnotherUserProfileError, GetPostsOfUserProfileError,
+            BetOnCurrentlyViewingPostError, BetOnPredictionError, BlockOrMuteProfileError,
+            CdaoDeployError, CdaoTokenError, CommentOnPostError, CreatePredictionError,
+            DeletePostError, FollowAnotherUserProfileError, GetCommentsOfPostError,
+            GetPostsOfUserProfileError, GetTipsForPostError, ReportPostError,
+            ResolvePredictionError, TipPostError, TransferUtilityTokenError,
+            UpdatePostDetailsError, WithdrawHotOrNotBetError,
         },
         follow::{FollowEntryDetail, FollowEntryId},
-        hot_or_not::{BetDetails, BetOutcomeForBetMaker, BettingStatus, PlacedBetDetail},
-        kv_storage::{NamespaceErrors, NamespaceForFrontend},
-        migration::MigrationErrors,
+        hot_or_not::{
+            game_config::HotOrNotGameConfig, BetDetails, BetIdempotencyKey, BetOutcomeForBetMaker,
+            BetReconciliationDetails, BetWithdrawalKind, BettingStatus, GlobalBetId,
+            PendingPayoutNotification, PlacedBetDetail,
+        },
+        kv_storage::{
+            BlobMetadata, KeyValueTransactionOperation, NamespaceAccessLevel, NamespaceErrors,
+            NamespaceForFrontend, NamespaceKeysPage, NamespaceUsage, VersionedValue,
+        },
+        migration::{CanisterMigrationLinks, MigrationErrors},
         ml_data::{MLFeedCacheItem, SuccessHistoryItemV1, WatchHistoryItem},
         post::{
-            Post, PostDetailsForFrontend, PostDetailsFromFrontend, PostViewDetailsFromFrontend,
+            Post, PostDetailsForFrontend, PostDetailsFromFrontend, PostRevision, PostTip,
+            PostViewDetailsFromFrontend,
         },
+        prediction::{PlacedPredictionBetDetail, PredictionDetails, PredictionOutcomeId},
         profile::{
             UserCanisterDetails, UserProfile, UserProfileDetailsForFrontend,
             UserProfileDetailsForFrontendV2, UserProfileUpdateDetailsFromFrontend,
         },
+        report::{PostReportReason, PostReportReviewDecision},
         session::SessionType,
+        snapshot::{
+            SnapshotError, SnapshotManifest, SnapshotRestoreProgress, SnapshotSaveProgress,
+        },
+        token::TokenTransferIdempotencyKey,
     },
     common::types::{
         app_primitive_type::PostId,
@@ -52,11 +76,12 @@ use shared_utils::{
 
 mod api;
 pub mod data_model;
+mod guard;
 mod util;
 
 thread_local! {
     static CANISTER_DATA: RefCell<CanisterData> = RefCell::default();
-    static SNAPSHOT_DATA: RefCell<Vec<u8>> = RefCell::default();
+    static SNAPSHOT_DATA: RefCell<SnapshotData> = RefCell::default();
 }
 
 export_candid!();
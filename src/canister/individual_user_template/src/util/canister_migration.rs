@@ -0,0 +1,221 @@
+use candid::Principal;
+use shared_utils::canister_specific::individual_user_template::types::migration::CanisterMigrationLinks;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+// * a profile is not expected to move more often than this
+const MAX_MIGRATION_LINKS_FOLLOWED: usize = 8;
+
+async fn get_canister_migration_links(
+    canister_id: Principal,
+) -> Result<CanisterMigrationLinks, String> {
+    let (canister_migration_links,) = ic_cdk::call::<_, (CanisterMigrationLinks,)>(
+        canister_id,
+        "get_canister_migration_links",
+        (),
+    )
+    .await
+    .map_err(|e| e.1)?;
+
+    Ok(canister_migration_links)
+}
+
+/// Follows a user canister that was migrated to other subnets to where the profile lives now.
+/// Returns `None` if the canister was not migrated.
+pub async fn get_current_canister_id_of_migrated_canister(
+    canister_id: Principal,
+) -> Option<Principal> {
+    let mut current_canister_id = None;
+
+    for _ in 0..MAX_MIGRATION_LINKS_FOLLOWED {
+        let Ok(canister_migration_links) =
+            get_canister_migration_links(current_canister_id.unwrap_or(canister_id)).await
+        else {
+            break;
+        };
+
+        match canister_migration_links.migrated_to_canister_id {
+            Some(migrated_to_canister_id) => current_canister_id = Some(migrated_to_canister_id),
+            None => break,
+        }
+    }
+
+    current_canister_id
+}
+
+/// Moves the bets placed on the posts of a profile that was migrated since
+/// from the canisters it left over to `post_creator_canister_id`.
+/// Only the former canisters that confirm they were migrated to it are trusted,
+/// so no canister can take over bets placed with another profile.
+pub async fn move_bets_placed_with_former_canisters(post_creator_canister_id: Principal) {
+    let Ok(canister_migration_links) = get_canister_migration_links(post_creator_canister_id).await
+    else {
+        return;
+    };
+
+    let mut verified_former_canister_ids = vec![];
+    let mut successor_canister_id = post_creator_canister_id;
+
+    for former_canister_id in canister_migration_links
+        .former_canister_ids
+        .into_iter()
+        .rev()
+        .take(MAX_MIGRATION_LINKS_FOLLOWED)
+    {
+        match get_canister_migration_links(former_canister_id).await {
+            Ok(former_canister_migration_links)
+                if former_canister_migration_links.migrated_to_canister_id
+                    == Some(successor_canister_id) =>
+            {
+                verified_former_canister_ids.push(former_canister_id);
+                successor_canister_id = former_canister_id;
+            }
+            _ => break,
+        }
+    }
+
+    if verified_former_canister_ids.is_empty() {
+        return;
+    }
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        move_bets_placed_with_former_canisters_impl(
+            canister_data,
+            &verified_former_canister_ids,
+            post_creator_canister_id,
+        )
+    });
+}
+
+fn move_bets_placed_with_former_canisters_impl(
+    canister_data: &mut CanisterData,
+    former_canister_ids: &[Principal],
+    post_creator_canister_id: Principal,
+) {
+    let placed_bet_keys = canister_data
+        .all_hot_or_not_bets_placed
+        .keys()
+        .filter(|(canister_id, _)| former_canister_ids.contains(canister_id))
+        .copied()
+        .collect::<Vec<_>>();
+
+    placed_bet_keys.into_iter().for_each(|key| {
+        let Some(mut placed_bet_detail) = canister_data.all_hot_or_not_bets_placed.remove(&key)
+        else {
+            return;
+        };
+        placed_bet_detail.canister_id = post_creator_canister_id;
+        canister_data
+            .all_hot_or_not_bets_placed
+            .entry((post_creator_canister_id, key.1))
+            .or_insert(placed_bet_detail);
+    });
+
+    let placed_prediction_bet_keys = canister_data
+        .all_prediction_bets_placed
+        .keys()
+        .filter(|(canister_id, _)| former_canister_ids.contains(canister_id))
+        .copied()
+        .collect::<Vec<_>>();
+
+    placed_prediction_bet_keys.into_iter().for_each(|key| {
+        let Some(mut placed_prediction_bet_detail) =
+            canister_data.all_prediction_bets_placed.remove(&key)
+        else {
+            return;
+        };
+        placed_prediction_bet_detail.canister_id = post_creator_canister_id;
+        canister_data
+            .all_prediction_bets_placed
+            .entry((post_creator_canister_id, key.1))
+            .or_insert(placed_prediction_bet_detail);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::individual_user_template::types::{
+        hot_or_not::{BetDirection, BetOutcomeForBetMaker, PlacedBetDetail},
+        prediction::PlacedPredictionBetDetail,
+    };
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+        get_mock_user_charlie_canister_id,
+    };
+
+    use super::*;
+
+    fn placed_bet_detail(canister_id: Principal, post_id: u64) -> PlacedBetDetail {
+        PlacedBetDetail {
+            canister_id,
+            post_id,
+            slot_id: 1,
+            room_id: 1,
+            amount_bet: 100,
+            bet_direction: BetDirection::Hot,
+            bet_placed_at: SystemTime::now(),
+            outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+        }
+    }
+
+    #[test]
+    fn test_move_bets_placed_with_former_canisters_impl() {
+        let mut canister_data = CanisterData::default();
+        let former_canister_id = get_mock_user_alice_canister_id();
+        let post_creator_canister_id = get_mock_user_bob_canister_id();
+        let other_canister_id = get_mock_user_charlie_canister_id();
+
+        canister_data.all_hot_or_not_bets_placed.insert(
+            (former_canister_id, 0),
+            placed_bet_detail(former_canister_id, 0),
+        );
+        canister_data.all_hot_or_not_bets_placed.insert(
+            (other_canister_id, 0),
+            placed_bet_detail(other_canister_id, 0),
+        );
+        canister_data.all_prediction_bets_placed.insert(
+            (former_canister_id, 1),
+            PlacedPredictionBetDetail {
+                canister_id: former_canister_id,
+                post_id: 1,
+                outcome_id: 0,
+                amount_bet: 100,
+                bet_placed_at: SystemTime::now(),
+                outcome_received: BetOutcomeForBetMaker::AwaitingResult,
+            },
+        );
+
+        move_bets_placed_with_former_canisters_impl(
+            &mut canister_data,
+            &[former_canister_id],
+            post_creator_canister_id,
+        );
+
+        // * bets placed with unrelated canisters are left alone
+        assert_eq!(canister_data.all_hot_or_not_bets_placed.len(), 2);
+        assert!(canister_data
+            .all_hot_or_not_bets_placed
+            .contains_key(&(other_canister_id, 0)));
+        assert_eq!(
+            canister_data
+                .all_hot_or_not_bets_placed
+                .get(&(post_creator_canister_id, 0))
+                .unwrap()
+                .canister_id,
+            post_creator_canister_id
+        );
+        assert_eq!(
+            canister_data
+                .all_prediction_bets_placed
+                .get(&(post_creator_canister_id, 1))
+                .unwrap()
+                .canister_id,
+            post_creator_canister_id
+        );
+        assert!(!canister_data
+            .all_prediction_bets_placed
+            .contains_key(&(former_canister_id, 1)));
+    }
+}
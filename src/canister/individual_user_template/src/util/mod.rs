@@ -1,4 +1,6 @@
+pub mod canister_migration;
 pub mod cycles;
 pub mod migration;
 pub mod periodic_update;
 pub mod score_ranking;
+pub mod user_index;
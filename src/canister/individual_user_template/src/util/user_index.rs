@@ -0,0 +1,28 @@
+use candid::Principal;
+use ic_cdk::call;
+use shared_utils::common::types::known_principal::KnownPrincipalType;
+
+use crate::CANISTER_DATA;
+
+pub async fn get_user_canister_id_from_user_principal_id(
+    user_principal_id: Principal,
+) -> Result<Option<Principal>, String> {
+    let subnet_orchestrator_canister_id = CANISTER_DATA
+        .with_borrow(|canister_data| {
+            canister_data
+                .known_principal_ids
+                .get(&KnownPrincipalType::CanisterIdUserIndex)
+                .copied()
+        })
+        .ok_or("Subnet Orchestrator Canister Id not found".to_owned())?;
+
+    let (user_canister_id,) = call::<_, (Option<Principal>,)>(
+        subnet_orchestrator_canister_id,
+        "get_user_canister_id_from_user_principal_id",
+        (user_principal_id,),
+    )
+    .await
+    .map_err(|e| e.1)?;
+
+    Ok(user_canister_id)
+}
@@ -0,0 +1,72 @@
+use candid::Principal;
+use ic_cdk::{api::call::CallResult, call};
+use ic_cdk_macros::{query, update};
+use shared_utils::canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig;
+
+use crate::{
+    data_model::CanisterData, guard::is_caller::is_caller_global_admin_or_controller, CANISTER_DATA,
+};
+
+#[query]
+fn get_hot_or_not_game_config() -> HotOrNotGameConfig {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.hot_or_not_game_config.clone())
+}
+
+/// Pushes the game config to every subnet orchestrator, which in turn pushes it to its individual canisters.
+/// Calling this again with the same config retries the push, e.g. for newly registered subnets.
+#[update(guard = "is_caller_global_admin_or_controller")]
+async fn update_hot_or_not_game_config(game_config: HotOrNotGameConfig) -> Result<String, String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_hot_or_not_game_config_impl(canister_data, game_config.clone())
+    })?;
+
+    let subnet_orchestrator_list = CANISTER_DATA
+        .with_borrow(|canister_data| canister_data.all_subnet_orchestrator_canisters_list.clone());
+
+    let mut failed_subnet_orchestrators: Vec<(Principal, String)> = vec![];
+    for subnet_orchestrator in subnet_orchestrator_list {
+        let result: CallResult<(Result<(), String>,)> = call(
+            subnet_orchestrator,
+            "update_hot_or_not_game_config",
+            (game_config.clone(),),
+        )
+        .await;
+
+        match result {
+            Ok((Ok(()),)) => {}
+            Ok((Err(e),)) => failed_subnet_orchestrators.push((subnet_orchestrator, e)),
+            Err(e) => failed_subnet_orchestrators.push((subnet_orchestrator, e.1)),
+        }
+    }
+
+    if !failed_subnet_orchestrators.is_empty() {
+        return Err(format!(
+            "failed to update hot or not game config for {:?}",
+            failed_subnet_orchestrators
+        ));
+    }
+
+    Ok("Success".into())
+}
+
+fn update_hot_or_not_game_config_impl(
+    canister_data: &mut CanisterData,
+    game_config: HotOrNotGameConfig,
+) -> Result<(), String> {
+    game_config.validate()?;
+
+    let current_game_config = &canister_data.hot_or_not_game_config;
+    if game_config.version < current_game_config.version
+        || (game_config.version == current_game_config.version
+            && game_config != *current_game_config)
+    {
+        return Err(format!(
+            "Game config version {} is not newer than the current version {}",
+            game_config.version, current_game_config.version
+        ));
+    }
+
+    canister_data.hot_or_not_game_config = game_config;
+
+    Ok(())
+}
@@ -3,3 +3,5 @@ pub mod canister_management;
 pub mod cycle_management;
 pub mod generic_proposal;
 pub mod monitoring;
+pub mod user_directory;
+pub mod user_migration;
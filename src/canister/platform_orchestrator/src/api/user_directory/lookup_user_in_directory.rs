@@ -0,0 +1,18 @@
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::platform_orchestrator::types::user_directory::{
+    UserDirectoryEntry, UserDirectoryLookup,
+};
+
+use crate::CANISTER_DATA;
+
+/// Resolves a user on any subnet by principal or username.
+/// Usernames reserved after a rename are only resolved by the user's own subnet orchestrator.
+#[query]
+pub fn lookup_user_in_directory(lookup: UserDirectoryLookup) -> Option<UserDirectoryEntry> {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.user_directory.lookup(lookup))
+}
+
+#[query]
+pub fn get_user_directory_size() -> u64 {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.user_directory.len())
+}
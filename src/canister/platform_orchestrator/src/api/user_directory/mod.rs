@@ -0,0 +1,3 @@
+pub mod lookup_user_in_directory;
+pub mod sync_user_directory_from_all_subnets;
+pub mod update_user_directory;
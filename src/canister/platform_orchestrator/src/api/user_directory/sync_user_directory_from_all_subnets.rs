@@ -0,0 +1,29 @@
+use candid::Principal;
+use ic_cdk::notify;
+use ic_cdk_macros::update;
+
+use crate::{guard::is_caller::is_caller_global_admin_or_controller, CANISTER_DATA};
+
+/// Asks every subnet orchestrator to send all of its users to the directory.
+/// Needed once for users who signed up before the directory existed.
+#[update(guard = "is_caller_global_admin_or_controller")]
+pub fn sync_user_directory_from_all_subnets() -> Result<(), String> {
+    let subnet_orchestrators: Vec<Principal> = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .all_subnet_orchestrator_canisters_list
+            .iter()
+            .copied()
+            .collect()
+    });
+
+    subnet_orchestrators
+        .into_iter()
+        .try_for_each(|subnet_orchestrator_canister_id| {
+            notify(
+                subnet_orchestrator_canister_id,
+                "sync_user_directory_with_platform_orchestrator",
+                (),
+            )
+            .map_err(|e| format!("{:?}", e))
+        })
+}
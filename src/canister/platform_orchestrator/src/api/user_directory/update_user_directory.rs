@@ -0,0 +1,22 @@
+use ic_cdk::caller;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::platform_orchestrator::types::user_directory::UserDirectoryUpdate;
+
+use crate::{utils::registered_subnet_orchestrator::RegisteredSubnetOrchestrator, CANISTER_DATA};
+
+/// # Access Control
+/// Only registered subnet orchestrators, for their own users
+#[update]
+pub fn update_user_directory(updates: Vec<UserDirectoryUpdate>) -> Result<(), String> {
+    let registered_subnet_orchestrator = RegisteredSubnetOrchestrator::new(caller())?;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        updates.into_iter().for_each(|update| {
+            canister_data
+                .user_directory
+                .apply_update(registered_subnet_orchestrator.get_canister_id(), update)
+        })
+    });
+
+    Ok(())
+}
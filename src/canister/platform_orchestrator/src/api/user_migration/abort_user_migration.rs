@@ -0,0 +1,116 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::platform_orchestrator::types::user_migration::UserMigrationStep;
+
+use crate::{
+    data_model::CanisterData, guard::is_caller::is_caller_global_admin_or_controller, CANISTER_DATA,
+};
+
+use super::run_user_migration::run_user_migration;
+
+/// Rolls back a stopped migration that has not switched over to the target subnet yet.
+/// The canister reserved on the target subnet is wiped and returned to its pool,
+/// and the user's canister on the source subnet is unfrozen.
+/// The rollback runs in the background and is resumed with `resume_user_migration` if it fails.
+#[update(guard = "is_caller_global_admin_or_controller")]
+pub fn abort_user_migration(user_principal_id: Principal) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        start_user_migration_rollback(canister_data, user_principal_id)
+    })?;
+
+    ic_cdk::spawn(run_user_migration(user_principal_id));
+
+    Ok(())
+}
+
+fn start_user_migration_rollback(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+) -> Result<(), String> {
+    if canister_data
+        .running_user_migrations
+        .contains(&user_principal_id)
+    {
+        return Err("Migration is running, it can only be rolled back once it stopped".into());
+    }
+
+    let user_migration = canister_data
+        .user_migrations
+        .get_mut(&user_principal_id)
+        .ok_or("No migration found for this user")?;
+
+    if !user_migration.step.can_be_rolled_back() {
+        return Err("Migration has switched over and can no longer be rolled back".into());
+    }
+
+    user_migration.step = UserMigrationStep::RollBackTargetCanister;
+    user_migration.last_error = None;
+    canister_data
+        .running_user_migrations
+        .insert(user_principal_id);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::platform_orchestrator::types::user_migration::UserMigration;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use crate::data_model::memory;
+
+    use super::*;
+
+    #[test]
+    fn test_start_user_migration_rollback() {
+        memory::init_memory_manager();
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+
+        assert!(start_user_migration_rollback(&mut canister_data, alice).is_err());
+
+        canister_data.user_migrations.insert(
+            alice,
+            UserMigration {
+                user_principal_id: alice,
+                source_subnet_orchestrator_canister_id: Principal::from_slice(&[1; 10]),
+                source_canister_id: get_mock_user_alice_canister_id(),
+                target_subnet_orchestrator_canister_id: Principal::from_slice(&[2; 10]),
+                target_canister_id: None,
+                unique_user_name: None,
+                snapshot_manifest: None,
+                step: UserMigrationStep::SwitchOverToTarget,
+                started_at: SystemTime::now(),
+                last_error: Some("Target subnet unreachable".into()),
+            },
+        );
+
+        // * the target canister may already have taken over
+        assert!(start_user_migration_rollback(&mut canister_data, alice).is_err());
+
+        canister_data.user_migrations.get_mut(&alice).unwrap().step =
+            UserMigrationStep::CopySnapshotChunks {
+                next_chunk_index: 2,
+            };
+        canister_data.running_user_migrations.insert(alice);
+        assert!(start_user_migration_rollback(&mut canister_data, alice).is_err());
+
+        canister_data.running_user_migrations.remove(&alice);
+        assert!(start_user_migration_rollback(&mut canister_data, alice).is_ok());
+        let user_migration = canister_data.user_migrations.get(&alice).unwrap();
+        assert_eq!(
+            user_migration.step,
+            UserMigrationStep::RollBackTargetCanister
+        );
+        assert_eq!(user_migration.last_error, None);
+        assert!(canister_data.running_user_migrations.contains(&alice));
+
+        // * a rollback in progress is not rolled back again
+        canister_data.running_user_migrations.remove(&alice);
+        assert!(start_user_migration_rollback(&mut canister_data, alice).is_err());
+    }
+}
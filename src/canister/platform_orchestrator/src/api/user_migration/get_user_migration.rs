@@ -0,0 +1,15 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::platform_orchestrator::types::user_migration::UserMigration;
+
+use crate::CANISTER_DATA;
+
+#[query]
+pub fn get_user_migration(user_principal_id: Principal) -> Option<UserMigration> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .user_migrations
+            .get(&user_principal_id)
+            .cloned()
+    })
+}
@@ -0,0 +1,5 @@
+pub mod abort_user_migration;
+pub mod get_user_migration;
+pub mod resume_user_migration;
+pub mod run_user_migration;
+pub mod start_user_migration;
@@ -0,0 +1,90 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::platform_orchestrator::types::user_migration::UserMigrationStep;
+
+use crate::{
+    data_model::CanisterData, guard::is_caller::is_caller_global_admin_or_controller, CANISTER_DATA,
+};
+
+use super::run_user_migration::run_user_migration;
+
+/// Picks a stopped migration up again from the step that failed.
+#[update(guard = "is_caller_global_admin_or_controller")]
+pub fn resume_user_migration(user_principal_id: Principal) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        claim_user_migration_run(canister_data, user_principal_id)
+    })?;
+
+    ic_cdk::spawn(run_user_migration(user_principal_id));
+
+    Ok(())
+}
+
+fn claim_user_migration_run(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+) -> Result<(), String> {
+    let user_migration = canister_data
+        .user_migrations
+        .get(&user_principal_id)
+        .ok_or("No migration found for this user")?;
+
+    if user_migration.step.is_finished() {
+        return Err("Migration already finished".into());
+    }
+
+    if !canister_data
+        .running_user_migrations
+        .insert(user_principal_id)
+    {
+        return Err("Migration is already running".into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::platform_orchestrator::types::user_migration::UserMigration;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use crate::data_model::memory;
+
+    use super::*;
+
+    #[test]
+    fn test_claim_user_migration_run() {
+        memory::init_memory_manager();
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+
+        assert!(claim_user_migration_run(&mut canister_data, alice).is_err());
+
+        canister_data.user_migrations.insert(
+            alice,
+            UserMigration {
+                user_principal_id: alice,
+                source_subnet_orchestrator_canister_id: Principal::from_slice(&[1; 10]),
+                source_canister_id: get_mock_user_alice_canister_id(),
+                target_subnet_orchestrator_canister_id: Principal::from_slice(&[2; 10]),
+                target_canister_id: None,
+                unique_user_name: None,
+                snapshot_manifest: None,
+                step: UserMigrationStep::RestoreTargetCanister,
+                started_at: SystemTime::now(),
+                last_error: Some("Out of cycles".into()),
+            },
+        );
+
+        assert!(claim_user_migration_run(&mut canister_data, alice).is_ok());
+        assert!(claim_user_migration_run(&mut canister_data, alice).is_err());
+
+        canister_data.running_user_migrations.remove(&alice);
+        canister_data.user_migrations.get_mut(&alice).unwrap().step = UserMigrationStep::Completed;
+        assert!(claim_user_migration_run(&mut canister_data, alice).is_err());
+    }
+}
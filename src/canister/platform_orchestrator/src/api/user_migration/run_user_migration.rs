@@ -0,0 +1,320 @@
+use candid::{utils::ArgumentEncoder, CandidType, Principal};
+use serde::de::DeserializeOwned;
+use shared_utils::canister_specific::{
+    individual_user_template::types::snapshot::{SnapshotManifest, SnapshotRestoreProgress},
+    platform_orchestrator::types::user_migration::{UserMigration, UserMigrationStep},
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+const MAX_SNAPSHOT_CHUNKS_RESTORED_PER_CALL: u64 = 4;
+
+/// Runs the migration one step at a time and stores it after every step.
+/// Stops at the first failing step and keeps its error, `resume_user_migration` retries that step.
+pub async fn run_user_migration(user_principal_id: Principal) {
+    loop {
+        let user_migration = CANISTER_DATA.with_borrow(|canister_data| {
+            canister_data
+                .user_migrations
+                .get(&user_principal_id)
+                .cloned()
+        });
+
+        let Some(user_migration) = user_migration else {
+            break;
+        };
+
+        let result = run_user_migration_step(user_migration).await;
+
+        let should_continue = CANISTER_DATA.with_borrow_mut(|canister_data| {
+            record_user_migration_step_result(canister_data, user_principal_id, result)
+        });
+
+        if !should_continue {
+            break;
+        }
+    }
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data
+            .running_user_migrations
+            .remove(&user_principal_id)
+    });
+}
+
+async fn run_user_migration_step(
+    mut user_migration: UserMigration,
+) -> Result<UserMigration, String> {
+    let user_principal_id = user_migration.user_principal_id;
+    let source = user_migration.source_subnet_orchestrator_canister_id;
+    let target = user_migration.target_subnet_orchestrator_canister_id;
+
+    user_migration.step = match user_migration.step {
+        UserMigrationStep::ReserveTargetCanister => {
+            let target_canister_id: Principal = call_subnet_orchestrator(
+                target,
+                "reserve_canister_for_incoming_user_migration",
+                (user_principal_id,),
+            )
+            .await?;
+            user_migration.target_canister_id = Some(target_canister_id);
+
+            UserMigrationStep::SnapshotSourceCanister
+        }
+        UserMigrationStep::SnapshotSourceCanister => {
+            let manifest: SnapshotManifest = call_subnet_orchestrator(
+                source,
+                "snapshot_user_canister_for_migration",
+                (user_principal_id,),
+            )
+            .await?;
+            user_migration.snapshot_manifest = Some(manifest);
+
+            UserMigrationStep::SendSnapshotManifest
+        }
+        UserMigrationStep::SendSnapshotManifest => {
+            let manifest = user_migration
+                .snapshot_manifest
+                .clone()
+                .ok_or("Snapshot manifest missing")?;
+            call_subnet_orchestrator::<_, ()>(
+                target,
+                "receive_user_canister_snapshot_manifest",
+                (user_principal_id, manifest),
+            )
+            .await?;
+
+            UserMigrationStep::CopySnapshotChunks {
+                next_chunk_index: 0,
+            }
+        }
+        UserMigrationStep::CopySnapshotChunks { next_chunk_index } => {
+            let total_chunks = user_migration
+                .snapshot_manifest
+                .as_ref()
+                .map_or(0, |manifest| manifest.chunks.len() as u64);
+
+            if next_chunk_index >= total_chunks {
+                UserMigrationStep::RestoreTargetCanister
+            } else {
+                let chunk: Vec<u8> = call_subnet_orchestrator(
+                    source,
+                    "download_user_canister_snapshot_chunk",
+                    (user_principal_id, next_chunk_index),
+                )
+                .await?;
+                call_subnet_orchestrator::<_, ()>(
+                    target,
+                    "upload_user_canister_snapshot_chunk",
+                    (user_principal_id, next_chunk_index, chunk),
+                )
+                .await?;
+
+                UserMigrationStep::CopySnapshotChunks {
+                    next_chunk_index: next_chunk_index + 1,
+                }
+            }
+        }
+        UserMigrationStep::RestoreTargetCanister => {
+            let progress: SnapshotRestoreProgress = call_subnet_orchestrator(
+                target,
+                "restore_user_canister_snapshot",
+                (user_principal_id, MAX_SNAPSHOT_CHUNKS_RESTORED_PER_CALL),
+            )
+            .await?;
+
+            if progress.is_complete() {
+                UserMigrationStep::SwitchOverToTarget
+            } else {
+                UserMigrationStep::RestoreTargetCanister
+            }
+        }
+        UserMigrationStep::SwitchOverToTarget => {
+            let target_canister_id = user_migration
+                .target_canister_id
+                .ok_or("Target canister missing")?;
+            call_subnet_orchestrator::<_, ()>(
+                target,
+                "complete_incoming_user_migration",
+                (
+                    user_principal_id,
+                    user_migration.unique_user_name.clone(),
+                    user_migration.source_canister_id,
+                ),
+            )
+            .await?;
+            call_subnet_orchestrator::<_, ()>(
+                source,
+                "redirect_outgoing_user_migration",
+                (user_principal_id, target_canister_id),
+            )
+            .await?;
+
+            UserMigrationStep::RecycleSourceCanister
+        }
+        UserMigrationStep::RecycleSourceCanister => {
+            let target_canister_id = user_migration
+                .target_canister_id
+                .ok_or("Target canister missing")?;
+            call_subnet_orchestrator::<_, ()>(
+                source,
+                "complete_outgoing_user_migration",
+                (user_principal_id, target_canister_id),
+            )
+            .await?;
+
+            UserMigrationStep::Completed
+        }
+        UserMigrationStep::Completed => UserMigrationStep::Completed,
+        UserMigrationStep::RollBackTargetCanister => {
+            call_subnet_orchestrator::<_, ()>(
+                target,
+                "abort_incoming_user_migration",
+                (user_principal_id,),
+            )
+            .await?;
+            user_migration.target_canister_id = None;
+
+            UserMigrationStep::RollBackSourceCanister
+        }
+        UserMigrationStep::RollBackSourceCanister => {
+            call_subnet_orchestrator::<_, ()>(
+                source,
+                "abort_outgoing_user_migration",
+                (user_principal_id,),
+            )
+            .await?;
+            user_migration.snapshot_manifest = None;
+
+            UserMigrationStep::RolledBack
+        }
+        UserMigrationStep::RolledBack => UserMigrationStep::RolledBack,
+    };
+
+    Ok(user_migration)
+}
+
+async fn call_subnet_orchestrator<T: ArgumentEncoder, R: CandidType + DeserializeOwned>(
+    subnet_orchestrator_canister_id: Principal,
+    method: &str,
+    args: T,
+) -> Result<R, String> {
+    let (result,): (Result<R, String>,) =
+        ic_cdk::call(subnet_orchestrator_canister_id, method, args)
+            .await
+            .map_err(|e| e.1)?;
+
+    result
+}
+
+/// Returns whether the next step should be run.
+fn record_user_migration_step_result(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+    result: Result<UserMigration, String>,
+) -> bool {
+    match result {
+        Ok(mut user_migration) => {
+            user_migration.last_error = None;
+            let should_continue = !user_migration.step.is_finished();
+            canister_data
+                .user_migrations
+                .insert(user_principal_id, user_migration);
+
+            should_continue
+        }
+        Err(e) => {
+            if let Some(user_migration) = canister_data.user_migrations.get_mut(&user_principal_id)
+            {
+                user_migration.last_error = Some(e);
+            }
+
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use crate::data_model::memory;
+
+    use super::*;
+
+    fn user_migration(step: UserMigrationStep) -> UserMigration {
+        UserMigration {
+            user_principal_id: get_mock_user_alice_principal_id(),
+            source_subnet_orchestrator_canister_id: Principal::from_slice(&[1; 10]),
+            source_canister_id: get_mock_user_alice_canister_id(),
+            target_subnet_orchestrator_canister_id: Principal::from_slice(&[2; 10]),
+            target_canister_id: None,
+            unique_user_name: None,
+            snapshot_manifest: None,
+            step,
+            started_at: SystemTime::now(),
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_record_user_migration_step_result() {
+        memory::init_memory_manager();
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        canister_data.user_migrations.insert(
+            alice,
+            user_migration(UserMigrationStep::CopySnapshotChunks {
+                next_chunk_index: 3,
+            }),
+        );
+
+        // * a failed step keeps its place so it can be resumed
+        assert!(!record_user_migration_step_result(
+            &mut canister_data,
+            alice,
+            Err("Canister is stopped".into())
+        ));
+        let stored = canister_data.user_migrations.get(&alice).unwrap();
+        assert_eq!(
+            stored.step,
+            UserMigrationStep::CopySnapshotChunks {
+                next_chunk_index: 3
+            }
+        );
+        assert_eq!(stored.last_error, Some("Canister is stopped".into()));
+
+        let mut next = stored.clone();
+        next.step = UserMigrationStep::CopySnapshotChunks {
+            next_chunk_index: 4,
+        };
+        assert!(record_user_migration_step_result(
+            &mut canister_data,
+            alice,
+            Ok(next)
+        ));
+        assert_eq!(
+            canister_data
+                .user_migrations
+                .get(&alice)
+                .unwrap()
+                .last_error,
+            None
+        );
+
+        assert!(!record_user_migration_step_result(
+            &mut canister_data,
+            alice,
+            Ok(user_migration(UserMigrationStep::Completed))
+        ));
+        assert!(!record_user_migration_step_result(
+            &mut canister_data,
+            alice,
+            Ok(user_migration(UserMigrationStep::RolledBack))
+        ));
+    }
+}
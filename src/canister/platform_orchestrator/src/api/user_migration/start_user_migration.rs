@@ -0,0 +1,196 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::platform_orchestrator::types::{
+        user_directory::UserDirectoryLookup,
+        user_migration::{UserMigration, UserMigrationStep},
+    },
+    common::utils::system_time,
+};
+
+use crate::{
+    data_model::CanisterData, guard::is_caller::is_caller_global_admin_or_controller, CANISTER_DATA,
+};
+
+use super::run_user_migration::run_user_migration;
+
+/// Moves a user's canister to the subnet of `target_subnet_orchestrator_canister_id`.
+/// The migration runs in the background and is tracked with `get_user_migration`.
+/// The user's current subnet is taken from the user directory.
+#[update(guard = "is_caller_global_admin_or_controller")]
+pub fn start_user_migration(
+    user_principal_id: Principal,
+    target_subnet_orchestrator_canister_id: Principal,
+) -> Result<UserMigration, String> {
+    let current_time = system_time::get_current_system_time();
+
+    let user_migration = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        start_user_migration_impl(
+            canister_data,
+            user_principal_id,
+            target_subnet_orchestrator_canister_id,
+            current_time,
+        )
+    })?;
+
+    ic_cdk::spawn(run_user_migration(user_principal_id));
+
+    Ok(user_migration)
+}
+
+fn start_user_migration_impl(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+    target_subnet_orchestrator_canister_id: Principal,
+    current_time: SystemTime,
+) -> Result<UserMigration, String> {
+    if !canister_data
+        .all_subnet_orchestrator_canisters_list
+        .contains(&target_subnet_orchestrator_canister_id)
+    {
+        return Err("Target is not a registered subnet orchestrator".into());
+    }
+
+    if canister_data
+        .user_migrations
+        .get(&user_principal_id)
+        .is_some_and(|user_migration| !user_migration.step.is_finished())
+    {
+        return Err("User already has a migration in progress".into());
+    }
+
+    let user_directory_entry = canister_data
+        .user_directory
+        .lookup(UserDirectoryLookup::UserPrincipalId(user_principal_id))
+        .ok_or("User not found in the user directory")?;
+
+    if user_directory_entry.subnet_orchestrator_canister_id
+        == target_subnet_orchestrator_canister_id
+    {
+        return Err("User is already on the target subnet".into());
+    }
+
+    let user_migration = UserMigration {
+        user_principal_id,
+        source_subnet_orchestrator_canister_id: user_directory_entry
+            .subnet_orchestrator_canister_id,
+        source_canister_id: user_directory_entry.user_canister_id,
+        target_subnet_orchestrator_canister_id,
+        target_canister_id: None,
+        unique_user_name: user_directory_entry.unique_user_name,
+        snapshot_manifest: None,
+        step: UserMigrationStep::ReserveTargetCanister,
+        started_at: current_time,
+        last_error: None,
+    };
+
+    canister_data
+        .user_migrations
+        .insert(user_principal_id, user_migration.clone());
+    canister_data
+        .running_user_migrations
+        .insert(user_principal_id);
+
+    Ok(user_migration)
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::platform_orchestrator::types::user_directory::UserDirectoryUpdate;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use crate::data_model::memory;
+
+    use super::*;
+
+    fn subnet_orchestrator(id: u8) -> Principal {
+        Principal::from_slice(&[id; 10])
+    }
+
+    #[test]
+    fn test_start_user_migration_impl() {
+        memory::init_memory_manager();
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let current_time = SystemTime::now();
+        canister_data
+            .all_subnet_orchestrator_canisters_list
+            .insert(subnet_orchestrator(1));
+        canister_data
+            .all_subnet_orchestrator_canisters_list
+            .insert(subnet_orchestrator(2));
+
+        assert!(start_user_migration_impl(
+            &mut canister_data,
+            alice,
+            subnet_orchestrator(3),
+            current_time
+        )
+        .is_err());
+        assert!(start_user_migration_impl(
+            &mut canister_data,
+            alice,
+            subnet_orchestrator(2),
+            current_time
+        )
+        .is_err());
+
+        canister_data.user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: alice,
+                user_canister_id: get_mock_user_alice_canister_id(),
+            },
+        );
+        canister_data.user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: alice,
+                unique_user_name: "alice".into(),
+            },
+        );
+
+        assert!(start_user_migration_impl(
+            &mut canister_data,
+            alice,
+            subnet_orchestrator(1),
+            current_time
+        )
+        .is_err());
+
+        let user_migration = start_user_migration_impl(
+            &mut canister_data,
+            alice,
+            subnet_orchestrator(2),
+            current_time,
+        )
+        .unwrap();
+        assert_eq!(
+            user_migration.source_subnet_orchestrator_canister_id,
+            subnet_orchestrator(1)
+        );
+        assert_eq!(
+            user_migration.source_canister_id,
+            get_mock_user_alice_canister_id()
+        );
+        assert_eq!(user_migration.unique_user_name, Some("alice".into()));
+        assert_eq!(
+            user_migration.step,
+            UserMigrationStep::ReserveTargetCanister
+        );
+        assert!(canister_data.running_user_migrations.contains(&alice));
+
+        // * only one migration per user at a time
+        assert!(start_user_migration_impl(
+            &mut canister_data,
+            alice,
+            subnet_orchestrator(2),
+            current_time
+        )
+        .is_err());
+    }
+}
@@ -14,6 +14,10 @@ const CANISTER_UPGRADE_LOG_INDEX: MemoryId = MemoryId::new(2);
 //A memory for canister upgrade log 
 const CANISTER_UPGRADE_LOG: MemoryId = MemoryId::new(3);
 
+// Memories for the user directory
+const USER_DIRECTORY: MemoryId = MemoryId::new(4);
+const USER_DIRECTORY_UNIQUE_USER_NAME: MemoryId = MemoryId::new(5);
+
 // A memory for the StableBTreeMap we're using. A new memory should be created for
 // every additional stable structure.
 
@@ -43,6 +47,15 @@ pub fn get_canister_upgrade_log_memory() -> Memory {
     MEMORY_MANAGER.with_borrow_mut(|memory_manager| memory_manager.get(CANISTER_UPGRADE_LOG))
 }
 
+pub fn get_user_directory_memory() -> Memory {
+    MEMORY_MANAGER.with_borrow_mut(|memory_manager| memory_manager.get(USER_DIRECTORY))
+}
+
+pub fn get_user_directory_unique_user_name_memory() -> Memory {
+    MEMORY_MANAGER
+        .with_borrow_mut(|memory_manager| memory_manager.get(USER_DIRECTORY_UNIQUE_USER_NAME))
+}
+
 pub fn init_memory_manager() {
     MEMORY_MANAGER.with(|m| {
         *m.borrow_mut() = MemoryManager::init_with_bucket_size(DefaultMemoryImpl::default(), 1);
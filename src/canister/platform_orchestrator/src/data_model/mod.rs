@@ -2,16 +2,17 @@ use ciborium::de;
 use ic_stable_structures::{storable::Bound, StableBTreeMap, StableLog, Storable};
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use candid::{CandidType, Principal};
 use serde::{Deserialize, Serialize};
 use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig,
     canister_specific::platform_orchestrator::types::{
-        args::UpgradeCanisterArg, well_known_principal::PlatformOrchestratorKnownPrincipal,
-        SubnetUpgradeReport,
+        args::UpgradeCanisterArg, user_migration::UserMigration,
+        well_known_principal::PlatformOrchestratorKnownPrincipal, SubnetUpgradeReport,
     },
     common::types::wasm::{CanisterWasm, WasmType},
 };
@@ -20,8 +21,10 @@ use self::memory::{
     get_canister_upgrade_log_index_memory, get_canister_upgrade_log_memory,
     get_subnet_orchestrator_wasm_memory, Memory,
 };
+use self::user_directory::UserDirectory;
 
 pub mod memory;
+pub mod user_directory;
 
 #[derive(Serialize, Deserialize)]
 pub struct CanisterData {
@@ -40,12 +43,25 @@ pub struct CanisterData {
     pub known_principals: PlatformOrchestratorKnownPrincipal,
     #[serde(default)]
     pub subnets_upgrade_report: SubnetUpgradeReport,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    #[serde(skip, default = "_default_user_directory")]
+    pub user_directory: UserDirectory,
+    #[serde(default)]
+    pub user_migrations: BTreeMap<Principal, UserMigration>,
+    /// Migrations with a run in flight, a run does not survive an upgrade
+    #[serde(skip)]
+    pub running_user_migrations: HashSet<Principal>,
 }
 
 fn _default_wasms() -> StableBTreeMap<WasmType, CanisterWasm, Memory> {
     StableBTreeMap::init(get_subnet_orchestrator_wasm_memory())
 }
 
+fn _default_user_directory() -> UserDirectory {
+    UserDirectory::default()
+}
+
 fn _default_canister_upgrade_log() -> StableLog<CanisterUpgradeStatus, Memory, Memory> {
     StableLog::init(
         get_canister_upgrade_log_index_memory(),
@@ -67,6 +83,10 @@ impl Default for CanisterData {
             known_principals: Default::default(),
             platform_global_admins: Default::default(),
             subnets_upgrade_report: SubnetUpgradeReport::default(),
+            hot_or_not_game_config: HotOrNotGameConfig::default(),
+            user_directory: _default_user_directory(),
+            user_migrations: Default::default(),
+            running_user_migrations: Default::default(),
         }
     }
 }
@@ -0,0 +1,324 @@
+use std::borrow::Cow;
+
+use candid::Principal;
+use ciborium::de;
+use ic_stable_structures::{storable::Bound, StableBTreeMap, Storable};
+use serde::{Deserialize, Serialize};
+use shared_utils::canister_specific::platform_orchestrator::types::user_directory::{
+    UserDirectoryEntry, UserDirectoryLookup, UserDirectoryUpdate,
+};
+
+use super::memory::{
+    get_user_directory_memory, get_user_directory_unique_user_name_memory, Memory,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+struct UserDirectoryRecord {
+    user_canister_id: Principal,
+    subnet_orchestrator_canister_id: Principal,
+    unique_user_name: Option<String>,
+}
+
+impl Storable for UserDirectoryRecord {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(self, &mut bytes).unwrap();
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        de::from_reader(bytes.as_ref()).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Platform wide user directory, fed by the subnet orchestrators.
+/// Lives in stable memory so it can grow with the number of users on all subnets.
+pub struct UserDirectory {
+    users: StableBTreeMap<Principal, UserDirectoryRecord, Memory>,
+    unique_user_names: StableBTreeMap<String, Principal, Memory>,
+}
+
+impl Default for UserDirectory {
+    fn default() -> Self {
+        Self {
+            users: StableBTreeMap::init(get_user_directory_memory()),
+            unique_user_names: StableBTreeMap::init(get_user_directory_unique_user_name_memory()),
+        }
+    }
+}
+
+impl UserDirectory {
+    /// Subnet orchestrators can only change the usernames of and delete their own users.
+    /// A username already pointing to a user on another subnet is left as is.
+    pub fn apply_update(
+        &mut self,
+        subnet_orchestrator_canister_id: Principal,
+        update: UserDirectoryUpdate,
+    ) {
+        match update {
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id,
+                user_canister_id,
+            } => {
+                let unique_user_name = match self.users.get(&user_principal_id) {
+                    Some(record)
+                        if record.subnet_orchestrator_canister_id
+                            == subnet_orchestrator_canister_id =>
+                    {
+                        record.unique_user_name
+                    }
+                    Some(record) => {
+                        // * the user moved to another subnet, where they have no username yet
+                        self.remove_unique_user_name(user_principal_id, record.unique_user_name);
+                        None
+                    }
+                    None => None,
+                };
+
+                self.users.insert(
+                    user_principal_id,
+                    UserDirectoryRecord {
+                        user_canister_id,
+                        subnet_orchestrator_canister_id,
+                        unique_user_name,
+                    },
+                );
+            }
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id,
+                unique_user_name,
+            } => {
+                let Some(mut record) =
+                    self.owned_record(subnet_orchestrator_canister_id, user_principal_id)
+                else {
+                    return;
+                };
+
+                let taken_by_another_user = self
+                    .unique_user_names
+                    .get(&unique_user_name)
+                    .is_some_and(|owner| owner != user_principal_id);
+                if taken_by_another_user {
+                    return;
+                }
+
+                self.remove_unique_user_name(user_principal_id, record.unique_user_name.take());
+                self.unique_user_names
+                    .insert(unique_user_name.clone(), user_principal_id);
+                record.unique_user_name = Some(unique_user_name);
+                self.users.insert(user_principal_id, record);
+            }
+            UserDirectoryUpdate::UserDeleted { user_principal_id } => {
+                let Some(record) =
+                    self.owned_record(subnet_orchestrator_canister_id, user_principal_id)
+                else {
+                    return;
+                };
+
+                self.remove_unique_user_name(user_principal_id, record.unique_user_name);
+                self.users.remove(&user_principal_id);
+            }
+        }
+    }
+
+    pub fn lookup(&self, lookup: UserDirectoryLookup) -> Option<UserDirectoryEntry> {
+        let user_principal_id = match lookup {
+            UserDirectoryLookup::UserPrincipalId(user_principal_id) => user_principal_id,
+            UserDirectoryLookup::UniqueUserName(unique_user_name) => {
+                self.unique_user_names.get(&unique_user_name)?
+            }
+        };
+
+        let record = self.users.get(&user_principal_id)?;
+
+        Some(UserDirectoryEntry {
+            user_principal_id,
+            user_canister_id: record.user_canister_id,
+            subnet_orchestrator_canister_id: record.subnet_orchestrator_canister_id,
+            unique_user_name: record.unique_user_name,
+        })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.users.len()
+    }
+
+    fn owned_record(
+        &self,
+        subnet_orchestrator_canister_id: Principal,
+        user_principal_id: Principal,
+    ) -> Option<UserDirectoryRecord> {
+        self.users.get(&user_principal_id).filter(|record| {
+            record.subnet_orchestrator_canister_id == subnet_orchestrator_canister_id
+        })
+    }
+
+    fn remove_unique_user_name(
+        &mut self,
+        user_principal_id: Principal,
+        unique_user_name: Option<String>,
+    ) {
+        let Some(unique_user_name) = unique_user_name else {
+            return;
+        };
+
+        if self.unique_user_names.get(&unique_user_name) == Some(user_principal_id) {
+            self.unique_user_names.remove(&unique_user_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use crate::data_model::memory;
+
+    use super::*;
+
+    fn subnet_orchestrator(id: u8) -> Principal {
+        Principal::from_slice(&[id; 10])
+    }
+
+    #[test]
+    fn test_user_directory_tracks_signups_usernames_and_deletions() {
+        memory::init_memory_manager();
+        let mut user_directory = UserDirectory::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: alice,
+                user_canister_id: get_mock_user_alice_canister_id(),
+            },
+        );
+        user_directory.apply_update(
+            subnet_orchestrator(2),
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: bob,
+                user_canister_id: get_mock_user_bob_canister_id(),
+            },
+        );
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: alice,
+                unique_user_name: "alice".into(),
+            },
+        );
+
+        let alice_entry = UserDirectoryEntry {
+            user_principal_id: alice,
+            user_canister_id: get_mock_user_alice_canister_id(),
+            subnet_orchestrator_canister_id: subnet_orchestrator(1),
+            unique_user_name: Some("alice".into()),
+        };
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UserPrincipalId(alice)),
+            Some(alice_entry.clone())
+        );
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UniqueUserName("alice".into())),
+            Some(alice_entry)
+        );
+
+        // * another subnet can neither take alice's username nor change alice's records
+        user_directory.apply_update(
+            subnet_orchestrator(2),
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: bob,
+                unique_user_name: "alice".into(),
+            },
+        );
+        user_directory.apply_update(
+            subnet_orchestrator(2),
+            UserDirectoryUpdate::UserDeleted {
+                user_principal_id: alice,
+            },
+        );
+        assert_eq!(
+            user_directory
+                .lookup(UserDirectoryLookup::UniqueUserName("alice".into()))
+                .map(|entry| entry.user_principal_id),
+            Some(alice)
+        );
+
+        // * renaming releases the previous username
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: alice,
+                unique_user_name: "alice_2".into(),
+            },
+        );
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UniqueUserName("alice".into())),
+            None
+        );
+
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UserDeleted {
+                user_principal_id: alice,
+            },
+        );
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UserPrincipalId(alice)),
+            None
+        );
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UniqueUserName("alice_2".into())),
+            None
+        );
+        assert_eq!(user_directory.len(), 1);
+    }
+
+    #[test]
+    fn test_signing_up_on_another_subnet_moves_the_user() {
+        memory::init_memory_manager();
+        let mut user_directory = UserDirectory::default();
+        let alice = get_mock_user_alice_principal_id();
+
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: alice,
+                user_canister_id: get_mock_user_alice_canister_id(),
+            },
+        );
+        user_directory.apply_update(
+            subnet_orchestrator(1),
+            UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: alice,
+                unique_user_name: "alice".into(),
+            },
+        );
+        user_directory.apply_update(
+            subnet_orchestrator(2),
+            UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: alice,
+                user_canister_id: get_mock_user_bob_canister_id(),
+            },
+        );
+
+        let alice_entry = user_directory
+            .lookup(UserDirectoryLookup::UserPrincipalId(alice))
+            .unwrap();
+        assert_eq!(
+            alice_entry.subnet_orchestrator_canister_id,
+            subnet_orchestrator(2)
+        );
+        assert_eq!(alice_entry.unique_user_name, None);
+        assert_eq!(
+            user_directory.lookup(UserDirectoryLookup::UniqueUserName("alice".into())),
+            None
+        );
+    }
+}
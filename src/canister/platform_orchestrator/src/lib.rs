@@ -8,9 +8,14 @@ use crate::data_model::CanisterUpgradeStatus;
 use data_model::CanisterData;
 use ic_cdk_macros::export_candid;
 use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig,
     canister_specific::platform_orchestrator::types::args::{
         PlatformOrchestratorInitArgs, UpgradeCanisterArg,
     },
+    canister_specific::platform_orchestrator::types::user_directory::{
+        UserDirectoryEntry, UserDirectoryLookup, UserDirectoryUpdate,
+    },
+    canister_specific::platform_orchestrator::types::user_migration::UserMigration,
     canister_specific::platform_orchestrator::types::SubnetUpgradeReport,
     canister_specific::user_index::types::UpgradeStatus,
     common::types::http::{HttpRequest, HttpResponse},
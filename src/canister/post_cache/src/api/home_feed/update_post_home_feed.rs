@@ -26,7 +26,10 @@ fn update_post_home_feed_impl(post: PostScoreIndexItemV1, canister_data: &mut Ca
 
     let global_id = (post.publisher_canister_id, post.post_id);
     if let Some(_) = item_prescence_index.get(&global_id) {
-        if post.status == PostStatus::BannedDueToUserReporting {
+        if matches!(
+            post.status,
+            PostStatus::BannedDueToUserReporting | PostStatus::Deleted
+        ) {
             canister_data
                 .posts_index_sorted_by_home_feed_score_v1
                 .remove(&post);
@@ -128,4 +131,41 @@ mod tests {
 
         assert_eq!(iter_posts.len(), 0);
     }
+
+    #[test]
+    fn test_update_post_home_feed_impl_deleted() {
+        let mut canister_data = CanisterData::default();
+        let created_at_now = SystemTime::now();
+
+        let post = PostScoreIndexItemV1 {
+            post_id: 1,
+            score: 1,
+            publisher_canister_id: Principal::from_text("aaaaa-aa").unwrap(),
+            is_nsfw: false,
+            status: PostStatus::ReadyToView,
+            created_at: Some(created_at_now),
+        };
+
+        canister_data
+            .posts_index_sorted_by_home_feed_score_v1
+            .replace(&post);
+
+        let new_post = PostScoreIndexItemV1 {
+            post_id: 1,
+            score: 10,
+            publisher_canister_id: Principal::from_text("aaaaa-aa").unwrap(),
+            is_nsfw: false,
+            status: PostStatus::Deleted,
+            created_at: Some(created_at_now),
+        };
+
+        update_post_home_feed_impl(new_post, &mut canister_data);
+
+        let iter_posts = canister_data
+            .posts_index_sorted_by_home_feed_score_v1
+            .iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(iter_posts.len(), 0);
+    }
 }
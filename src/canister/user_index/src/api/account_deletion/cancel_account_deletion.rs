@@ -0,0 +1,98 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::types::canister_specific::user_index::error_types::AccountDeletionError;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Withdraws the caller's account deletion request while it is still in its grace period.
+/// Once a deletion attempt has started the posts of the profile are already tombstoned
+/// and their bets refunded, so a deletion that is waiting to be retried can not be withdrawn.
+///
+/// # Access Control
+/// Only the user who requested the deletion
+#[update]
+fn cancel_account_deletion() -> Result<(), AccountDeletionError> {
+    let api_caller = ic_cdk::caller();
+
+    CANISTER_DATA
+        .with_borrow_mut(|canister_data| cancel_account_deletion_impl(canister_data, api_caller))
+}
+
+fn cancel_account_deletion_impl(
+    canister_data: &mut CanisterData,
+    api_caller: Principal,
+) -> Result<(), AccountDeletionError> {
+    let account_deletion_request = canister_data
+        .pending_account_deletions
+        .get(&api_caller)
+        .ok_or(AccountDeletionError::NoDeletionRequested)?;
+
+    if account_deletion_request.in_progress || account_deletion_request.last_error.is_some() {
+        return Err(AccountDeletionError::DeletionAlreadyInProgress);
+    }
+
+    canister_data.pending_account_deletions.remove(&api_caller);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::user_index::types::AccountDeletionRequest;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cancel_account_deletion_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+
+        assert_eq!(
+            cancel_account_deletion_impl(&mut canister_data, alice),
+            Err(AccountDeletionError::NoDeletionRequested)
+        );
+
+        canister_data.pending_account_deletions.insert(
+            alice,
+            AccountDeletionRequest {
+                canister_id: get_mock_user_alice_canister_id(),
+                requested_at: SystemTime::now(),
+                scheduled_for: SystemTime::now(),
+                in_progress: true,
+                last_error: None,
+            },
+        );
+        assert_eq!(
+            cancel_account_deletion_impl(&mut canister_data, alice),
+            Err(AccountDeletionError::DeletionAlreadyInProgress)
+        );
+
+        canister_data
+            .pending_account_deletions
+            .get_mut(&alice)
+            .unwrap()
+            .in_progress = false;
+        canister_data
+            .pending_account_deletions
+            .get_mut(&alice)
+            .unwrap()
+            .last_error = Some("Bets on the posts of this profile are still being settled".into());
+        assert_eq!(
+            cancel_account_deletion_impl(&mut canister_data, alice),
+            Err(AccountDeletionError::DeletionAlreadyInProgress)
+        );
+
+        canister_data
+            .pending_account_deletions
+            .get_mut(&alice)
+            .unwrap()
+            .last_error = None;
+        assert!(cancel_account_deletion_impl(&mut canister_data, alice).is_ok());
+        assert!(canister_data.pending_account_deletions.is_empty());
+    }
+}
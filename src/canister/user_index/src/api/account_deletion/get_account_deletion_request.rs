@@ -0,0 +1,16 @@
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::user_index::types::AccountDeletionRequest;
+
+use crate::CANISTER_DATA;
+
+#[query]
+fn get_account_deletion_request() -> Option<AccountDeletionRequest> {
+    let api_caller = ic_cdk::caller();
+
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .pending_account_deletions
+            .get(&api_caller)
+            .cloned()
+    })
+}
@@ -0,0 +1,4 @@
+pub mod cancel_account_deletion;
+pub mod get_account_deletion_request;
+pub mod process_due_account_deletions;
+pub mod request_account_deletion;
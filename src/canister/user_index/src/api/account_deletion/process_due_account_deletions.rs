@@ -0,0 +1,226 @@
+use std::time::{Duration, SystemTime};
+
+use candid::Principal;
+use shared_utils::{
+    canister_specific::platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+    common::utils::system_time::get_current_system_time_from_ic,
+};
+
+use crate::{
+    data_model::CanisterData,
+    util::{
+        canister_management::reset_individual_user_canister,
+        user_directory::notify_platform_orchestrator_of_user_directory_updates,
+    },
+    CANISTER_DATA,
+};
+
+const ACCOUNT_DELETION_RUN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const MAX_ACCOUNT_DELETIONS_PER_RUN: usize = 20;
+
+/// Starts the recurring timer that deletes accounts whose grace period has ended.
+/// Timers do not survive upgrades, so this has to be called from both `init` and `post_upgrade`.
+pub fn start_account_deletion_timer() {
+    ic_cdk_timers::set_timer_interval(ACCOUNT_DELETION_RUN_INTERVAL, || {
+        ic_cdk::spawn(process_due_account_deletions())
+    });
+}
+
+async fn process_due_account_deletions() {
+    let current_time = get_current_system_time_from_ic();
+
+    let due_account_deletions = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        start_due_account_deletions(canister_data, current_time, MAX_ACCOUNT_DELETIONS_PER_RUN)
+    });
+
+    let deletions = due_account_deletions
+        .into_iter()
+        .map(|(user_principal_id, canister_id)| delete_account(user_principal_id, canister_id));
+
+    futures::future::join_all(deletions).await;
+}
+
+async fn delete_account(user_principal_id: Principal, canister_id: Principal) {
+    let result = delete_account_impl(user_principal_id, canister_id).await;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        finish_account_deletion(canister_data, user_principal_id, canister_id, result)
+    });
+}
+
+async fn delete_account_impl(
+    user_principal_id: Principal,
+    canister_id: Principal,
+) -> Result<(), String> {
+    // * tombstone posts in post_cache, refund open rooms and predictions and drop follow edges
+    // * from other canisters. Fails until every bet maker has been told about their payout,
+    // * nothing is released until then and the deletion is retried on the next run.
+    let (prepare_result,): (Result<(), String>,) =
+        ic_cdk::call(canister_id, "prepare_profile_for_deletion", ())
+            .await
+            .map_err(|e| e.1)?;
+    prepare_result?;
+
+    CANISTER_DATA
+        .with_borrow_mut(|canister_data| release_user_records(canister_data, user_principal_id));
+    notify_platform_orchestrator_of_user_directory_updates(vec![
+        UserDirectoryUpdate::UserDeleted { user_principal_id },
+    ]);
+
+    // * reinstalling drops posts, bets and follow data along with the stable memory
+    reset_individual_user_canister(canister_id).await
+}
+
+fn start_due_account_deletions(
+    canister_data: &mut CanisterData,
+    current_time: SystemTime,
+    max_deletions: usize,
+) -> Vec<(Principal, Principal)> {
+    canister_data
+        .pending_account_deletions
+        .iter_mut()
+        .filter(|(_, request)| !request.in_progress && request.scheduled_for <= current_time)
+        .take(max_deletions)
+        .map(|(user_principal_id, request)| {
+            request.in_progress = true;
+            (*user_principal_id, request.canister_id)
+        })
+        .collect()
+}
+
+pub(crate) fn release_user_records(canister_data: &mut CanisterData, user_principal_id: Principal) {
+    canister_data
+        .user_principal_id_to_canister_id_map
+        .remove(&user_principal_id);
+    canister_data
+        .unique_user_name_to_user_principal_id_map
+        .retain(|_, principal_id| *principal_id != user_principal_id);
+    canister_data
+        .reserved_usernames
+        .retain(|_, reserved_username| reserved_username.user_principal_id != user_principal_id);
+    canister_data.username_history.remove(&user_principal_id);
+}
+
+fn finish_account_deletion(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+    canister_id: Principal,
+    result: Result<(), String>,
+) {
+    match result {
+        Ok(()) => {
+            canister_data
+                .pending_account_deletions
+                .remove(&user_principal_id);
+            canister_data.available_canisters.insert(canister_id);
+        }
+        Err(e) => {
+            // * retried on the next run
+            if let Some(request) = canister_data
+                .pending_account_deletions
+                .get_mut(&user_principal_id)
+            {
+                request.in_progress = false;
+                request.last_error = Some(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::user_index::types::AccountDeletionRequest;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn account_deletion_request(
+        canister_id: Principal,
+        scheduled_for: SystemTime,
+    ) -> AccountDeletionRequest {
+        AccountDeletionRequest {
+            canister_id,
+            requested_at: scheduled_for,
+            scheduled_for,
+            in_progress: false,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_start_due_account_deletions() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        canister_data.pending_account_deletions.insert(
+            get_mock_user_alice_principal_id(),
+            account_deletion_request(get_mock_user_alice_canister_id(), current_time),
+        );
+        canister_data.pending_account_deletions.insert(
+            get_mock_user_bob_principal_id(),
+            account_deletion_request(
+                get_mock_user_bob_canister_id(),
+                current_time + Duration::from_secs(1),
+            ),
+        );
+
+        let due_account_deletions =
+            start_due_account_deletions(&mut canister_data, current_time, 10);
+        assert_eq!(
+            due_account_deletions,
+            vec![(
+                get_mock_user_alice_principal_id(),
+                get_mock_user_alice_canister_id()
+            )]
+        );
+
+        // * deletions in progress are not started twice
+        let due_account_deletions =
+            start_due_account_deletions(&mut canister_data, current_time, 10);
+        assert!(due_account_deletions.is_empty());
+    }
+
+    #[test]
+    fn test_finish_account_deletion() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let alice_canister_id = get_mock_user_alice_canister_id();
+        canister_data
+            .user_principal_id_to_canister_id_map
+            .insert(alice, alice_canister_id);
+        canister_data
+            .unique_user_name_to_user_principal_id_map
+            .insert("alice".into(), alice);
+        canister_data.pending_account_deletions.insert(
+            alice,
+            account_deletion_request(alice_canister_id, SystemTime::now()),
+        );
+        start_due_account_deletions(&mut canister_data, SystemTime::now(), 10);
+
+        finish_account_deletion(
+            &mut canister_data,
+            alice,
+            alice_canister_id,
+            Err("Canister is stopped".into()),
+        );
+        let request = canister_data.pending_account_deletions.get(&alice).unwrap();
+        assert!(!request.in_progress);
+        assert_eq!(request.last_error, Some("Canister is stopped".into()));
+        assert!(canister_data.available_canisters.is_empty());
+
+        release_user_records(&mut canister_data, alice);
+        finish_account_deletion(&mut canister_data, alice, alice_canister_id, Ok(()));
+        assert!(canister_data.pending_account_deletions.is_empty());
+        assert!(canister_data
+            .user_principal_id_to_canister_id_map
+            .is_empty());
+        assert!(canister_data
+            .unique_user_name_to_user_principal_id_map
+            .is_empty());
+        assert!(canister_data
+            .available_canisters
+            .contains(&alice_canister_id));
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, SystemTime};
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::user_index::types::AccountDeletionRequest,
+    common::utils::system_time::get_current_system_time_from_ic,
+    types::canister_specific::user_index::error_types::AccountDeletionError,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+pub const ACCOUNT_DELETION_GRACE_PERIOD: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Schedules the caller's account for deletion once the grace period ends.
+/// Until then the request can be withdrawn with `cancel_account_deletion`.
+///
+/// # Access Control
+/// Only the user whose account is to be deleted
+#[update]
+fn request_account_deletion() -> Result<AccountDeletionRequest, AccountDeletionError> {
+    let api_caller = ic_cdk::caller();
+    let current_time = get_current_system_time_from_ic();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        request_account_deletion_impl(canister_data, api_caller, current_time)
+    })
+}
+
+fn request_account_deletion_impl(
+    canister_data: &mut CanisterData,
+    api_caller: Principal,
+    current_time: SystemTime,
+) -> Result<AccountDeletionRequest, AccountDeletionError> {
+    if api_caller == Principal::anonymous() {
+        return Err(AccountDeletionError::Unauthenticated);
+    }
+
+    let canister_id = *canister_data
+        .user_principal_id_to_canister_id_map
+        .get(&api_caller)
+        .ok_or(AccountDeletionError::UserCanisterEntryDoesNotExist)?;
+
+    if canister_data
+        .pending_account_deletions
+        .contains_key(&api_caller)
+    {
+        return Err(AccountDeletionError::DeletionAlreadyRequested);
+    }
+
+    let account_deletion_request = AccountDeletionRequest {
+        canister_id,
+        requested_at: current_time,
+        scheduled_for: current_time + ACCOUNT_DELETION_GRACE_PERIOD,
+        in_progress: false,
+        last_error: None,
+    };
+    canister_data
+        .pending_account_deletions
+        .insert(api_caller, account_deletion_request.clone());
+
+    Ok(account_deletion_request)
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_request_account_deletion_impl() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        canister_data.user_principal_id_to_canister_id_map.insert(
+            get_mock_user_alice_principal_id(),
+            get_mock_user_alice_canister_id(),
+        );
+
+        assert_eq!(
+            request_account_deletion_impl(&mut canister_data, Principal::anonymous(), current_time),
+            Err(AccountDeletionError::Unauthenticated)
+        );
+        assert_eq!(
+            request_account_deletion_impl(
+                &mut canister_data,
+                get_mock_user_bob_principal_id(),
+                current_time
+            ),
+            Err(AccountDeletionError::UserCanisterEntryDoesNotExist)
+        );
+
+        let account_deletion_request = request_account_deletion_impl(
+            &mut canister_data,
+            get_mock_user_alice_principal_id(),
+            current_time,
+        )
+        .unwrap();
+        assert_eq!(
+            account_deletion_request.canister_id,
+            get_mock_user_alice_canister_id()
+        );
+        assert_eq!(
+            account_deletion_request.scheduled_for,
+            current_time + ACCOUNT_DELETION_GRACE_PERIOD
+        );
+
+        assert_eq!(
+            request_account_deletion_impl(
+                &mut canister_data,
+                get_mock_user_alice_principal_id(),
+                current_time
+            ),
+            Err(AccountDeletionError::DeletionAlreadyRequested)
+        );
+    }
+}
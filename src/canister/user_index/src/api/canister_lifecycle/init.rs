@@ -1,7 +1,10 @@
 use ic_cdk_macros::init;
 use shared_utils::canister_specific::user_index::types::args::UserIndexInitArgs;
 
-use crate::{data_model::CanisterData, CANISTER_DATA};
+use crate::{
+    api::account_deletion::process_due_account_deletions::start_account_deletion_timer,
+    data_model::CanisterData, CANISTER_DATA,
+};
 
 #[init]
 fn init(init_args: UserIndexInitArgs) {
@@ -9,6 +12,7 @@ fn init(init_args: UserIndexInitArgs) {
         let mut data = canister_data_ref_cell.borrow_mut();
         init_impl(init_args, &mut data);
     });
+    start_account_deletion_timer();
 }
 
 fn init_impl(init_args: UserIndexInitArgs, data: &mut CanisterData) {
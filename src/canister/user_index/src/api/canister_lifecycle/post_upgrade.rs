@@ -8,12 +8,16 @@ use shared_utils::{
     common::utils::system_time,
 };
 
-use crate::{data_model::memory, CANISTER_DATA};
+use crate::{
+    api::account_deletion::process_due_account_deletions::start_account_deletion_timer,
+    data_model::memory, CANISTER_DATA,
+};
 
 #[post_upgrade]
 fn post_upgrade() {
     restore_data_from_stable_memory();
     update_version_from_args();
+    start_account_deletion_timer();
 }
 
 fn update_version_from_args() {
@@ -18,7 +18,11 @@ async fn request_cycles(cycle_amount: u128) -> Result<(), String> {
             .iter()
             .find(|(_, id)| **id == canister_id);
 
+        // * canisters of migrated users answer for them until nobody refers to them anymore
         res.is_some()
+            || canister_data
+                .migrated_user_canisters
+                .contains_key(&canister_id)
     });
 
     if !found_canister_id {
@@ -0,0 +1,2 @@
+pub mod get_hot_or_not_game_config;
+pub mod update_hot_or_not_game_config;
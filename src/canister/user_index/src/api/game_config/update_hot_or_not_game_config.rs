@@ -0,0 +1,88 @@
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig,
+    common::utils::permissions::is_caller_controller,
+};
+
+use crate::{
+    data_model::CanisterData, util::canister_management::broadcast_to_all_individual_canisters,
+    CANISTER_DATA,
+};
+
+/// # Access Control
+/// Only the controller (platform orchestrator) can update the game config.
+/// The config is stored here so that canisters handed out later get it too,
+/// and is broadcast to all the individual canisters on this subnet.
+#[update(guard = "is_caller_controller")]
+fn update_hot_or_not_game_config(game_config: HotOrNotGameConfig) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_hot_or_not_game_config_impl(canister_data, game_config.clone())
+    })?;
+
+    ic_cdk::spawn(broadcast_to_all_individual_canisters(
+        "update_hot_or_not_game_config",
+        (game_config,),
+    ));
+
+    Ok(())
+}
+
+fn update_hot_or_not_game_config_impl(
+    canister_data: &mut CanisterData,
+    game_config: HotOrNotGameConfig,
+) -> Result<(), String> {
+    game_config.validate()?;
+
+    // * the same version can be pushed again to retry a failed broadcast
+    if game_config.version < canister_data.hot_or_not_game_config.version
+        || (game_config.version == canister_data.hot_or_not_game_config.version
+            && game_config != canister_data.hot_or_not_game_config)
+    {
+        return Err(format!(
+            "Game config version {} is not newer than the current version {}",
+            game_config.version, canister_data.hot_or_not_game_config.version
+        ));
+    }
+
+    canister_data.hot_or_not_game_config = game_config;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_hot_or_not_game_config_impl() {
+        let mut canister_data = CanisterData::default();
+
+        let new_config = HotOrNotGameConfig {
+            version: 2,
+            duration_of_each_slot_in_seconds: 30 * 60,
+            ..Default::default()
+        };
+
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, new_config.clone()).is_ok());
+        assert_eq!(canister_data.hot_or_not_game_config, new_config);
+
+        // * retrying the same config is allowed
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, new_config.clone()).is_ok());
+
+        let older_config = HotOrNotGameConfig {
+            version: 1,
+            ..Default::default()
+        };
+        assert!(update_hot_or_not_game_config_impl(&mut canister_data, older_config).is_err());
+
+        let conflicting_config = HotOrNotGameConfig {
+            version: 2,
+            winnings_multiplier: 5,
+            ..Default::default()
+        };
+        assert!(
+            update_hot_or_not_game_config_impl(&mut canister_data, conflicting_config).is_err()
+        );
+        assert_eq!(canister_data.hot_or_not_game_config, new_config);
+    }
+}
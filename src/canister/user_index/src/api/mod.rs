@@ -1,9 +1,13 @@
+pub mod account_deletion;
 pub mod canister_lifecycle;
 pub mod canister_management;
 pub mod cycle_management;
+pub mod game_config;
 pub mod http;
 pub mod monitoring;
+pub mod post_report;
 pub mod upgrade_individual_user_template;
+pub mod user_migration;
 pub mod user_record;
 pub mod user_signup;
 pub mod well_known_principal;
@@ -0,0 +1,87 @@
+use ic_cdk_macros::query;
+use shared_utils::{
+    canister_specific::individual_user_template::types::report::PostReportSummary,
+    common::utils::permissions::is_caller_controller_or_global_admin,
+    constant::MAX_POSTS_IN_ONE_REQUEST,
+};
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Lists the posts on this subnet with reports awaiting review,
+/// most reported first.
+///
+/// # Access Control
+/// Only the controller or a global admin
+#[query(guard = "is_caller_controller_or_global_admin")]
+fn get_pending_post_reports(from_inclusive_index: u64, limit: u64) -> Vec<PostReportSummary> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        get_pending_post_reports_impl(canister_data, from_inclusive_index, limit)
+    })
+}
+
+fn get_pending_post_reports_impl(
+    canister_data: &CanisterData,
+    from_inclusive_index: u64,
+    limit: u64,
+) -> Vec<PostReportSummary> {
+    let mut pending_post_reports: Vec<&PostReportSummary> =
+        canister_data.pending_post_reports.values().collect();
+    pending_post_reports.sort_by(|a, b| b.pending_report_count.cmp(&a.pending_report_count));
+
+    pending_post_reports
+        .into_iter()
+        .skip(from_inclusive_index as usize)
+        .take(limit.min(MAX_POSTS_IN_ONE_REQUEST) as usize)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_bob_canister_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_get_pending_post_reports_impl() {
+        let mut canister_data = CanisterData::default();
+        assert!(get_pending_post_reports_impl(&canister_data, 0, 10).is_empty());
+
+        for (canister_id, post_id, pending_report_count) in [
+            (get_mock_user_alice_canister_id(), 0, 1),
+            (get_mock_user_alice_canister_id(), 1, 5),
+            (get_mock_user_bob_canister_id(), 0, 3),
+        ] {
+            canister_data.pending_post_reports.insert(
+                (canister_id, post_id),
+                PostReportSummary {
+                    post_creator_canister_id: canister_id,
+                    post_id,
+                    pending_report_count,
+                    pending_reports: vec![],
+                    banned: pending_report_count >= 5,
+                },
+            );
+        }
+
+        let result = get_pending_post_reports_impl(&canister_data, 0, 10);
+        assert_eq!(
+            result
+                .iter()
+                .map(|summary| summary.pending_report_count)
+                .collect::<Vec<_>>(),
+            vec![5, 3, 1]
+        );
+
+        let result = get_pending_post_reports_impl(&canister_data, 1, 1);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].post_creator_canister_id,
+            get_mock_user_bob_canister_id()
+        );
+
+        assert!(get_pending_post_reports_impl(&canister_data, 3, 10).is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod get_pending_post_reports;
+pub mod receive_post_report_summary;
+pub mod review_post_report;
+pub mod update_post_report_threshold;
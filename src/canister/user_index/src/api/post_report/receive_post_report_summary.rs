@@ -0,0 +1,101 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::canister_specific::individual_user_template::types::report::PostReportSummary;
+
+use crate::{data_model::CanisterData, CANISTER_DATA};
+
+/// Records the latest report summary for a post so that admins can review it.
+/// Summaries with no pending reports clear the post from the review queue.
+///
+/// # Access Control
+/// Only the individual canister on this subnet that owns the post
+#[update]
+fn receive_post_report_summary(summary: PostReportSummary) -> Result<(), String> {
+    let api_caller = ic_cdk::caller();
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        receive_post_report_summary_impl(canister_data, api_caller, summary)
+    })
+}
+
+fn receive_post_report_summary_impl(
+    canister_data: &mut CanisterData,
+    api_caller: Principal,
+    summary: PostReportSummary,
+) -> Result<(), String> {
+    if api_caller != summary.post_creator_canister_id
+        || !canister_data
+            .user_principal_id_to_canister_id_map
+            .values()
+            .any(|canister_id| *canister_id == api_caller)
+    {
+        return Err("Unauthorized".into());
+    }
+
+    let key = (summary.post_creator_canister_id, summary.post_id);
+    if summary.pending_report_count == 0 {
+        canister_data.pending_post_reports.remove(&key);
+    } else {
+        canister_data.pending_post_reports.insert(key, summary);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_receive_post_report_summary_impl() {
+        let mut canister_data = CanisterData::default();
+        canister_data.user_principal_id_to_canister_id_map.insert(
+            get_mock_user_alice_principal_id(),
+            get_mock_user_alice_canister_id(),
+        );
+
+        let mut summary = PostReportSummary {
+            post_creator_canister_id: get_mock_user_alice_canister_id(),
+            post_id: 0,
+            pending_report_count: 1,
+            pending_reports: vec![],
+            banned: false,
+        };
+
+        // * only the canister that owns the post can send its summary
+        assert!(receive_post_report_summary_impl(
+            &mut canister_data,
+            get_mock_user_bob_canister_id(),
+            summary.clone()
+        )
+        .is_err());
+        assert!(canister_data.pending_post_reports.is_empty());
+
+        assert!(receive_post_report_summary_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            summary.clone()
+        )
+        .is_ok());
+        assert_eq!(
+            canister_data
+                .pending_post_reports
+                .get(&(get_mock_user_alice_canister_id(), 0)),
+            Some(&summary)
+        );
+
+        summary.pending_report_count = 0;
+        assert!(receive_post_report_summary_impl(
+            &mut canister_data,
+            get_mock_user_alice_canister_id(),
+            summary
+        )
+        .is_ok());
+        assert!(canister_data.pending_post_reports.is_empty());
+    }
+}
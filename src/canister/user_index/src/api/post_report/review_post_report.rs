@@ -0,0 +1,46 @@
+use candid::Principal;
+use ic_cdk::api::call;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::report::PostReportReviewDecision,
+    common::{
+        types::app_primitive_type::PostId, utils::permissions::is_caller_controller_or_global_admin,
+    },
+};
+
+use crate::CANISTER_DATA;
+
+/// Relays an admin decision on a reported post to the canister that owns it
+/// and clears the post from the review queue once applied.
+///
+/// # Access Control
+/// Only the controller or a global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn review_post_report(
+    post_creator_canister_id: Principal,
+    post_id: PostId,
+    decision: PostReportReviewDecision,
+) -> Result<(), String> {
+    let key = (post_creator_canister_id, post_id);
+
+    if !CANISTER_DATA
+        .with_borrow(|canister_data| canister_data.pending_post_reports.contains_key(&key))
+    {
+        return Err("No pending reports for this post".into());
+    }
+
+    let (result,): (Result<(), String>,) = call::call(
+        post_creator_canister_id,
+        "review_post_reports",
+        (post_id, decision),
+    )
+    .await
+    .map_err(|e| e.1)?;
+    result?;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data.pending_post_reports.remove(&key);
+    });
+
+    Ok(())
+}
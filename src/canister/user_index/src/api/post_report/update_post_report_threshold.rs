@@ -0,0 +1,54 @@
+use ic_cdk_macros::update;
+use shared_utils::common::utils::permissions::is_caller_controller;
+
+use crate::{
+    data_model::CanisterData, util::canister_management::broadcast_to_all_individual_canisters,
+    CANISTER_DATA,
+};
+
+/// # Access Control
+/// Only the controller (platform orchestrator) can update the report threshold.
+/// It is stored here so that canisters handed out later get it too,
+/// and is broadcast to all the individual canisters on this subnet.
+#[update(guard = "is_caller_controller")]
+fn update_post_report_threshold(threshold: u64) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_post_report_threshold_impl(canister_data, threshold)
+    })?;
+
+    ic_cdk::spawn(broadcast_to_all_individual_canisters(
+        "update_post_report_threshold",
+        (threshold,),
+    ));
+
+    Ok(())
+}
+
+fn update_post_report_threshold_impl(
+    canister_data: &mut CanisterData,
+    threshold: u64,
+) -> Result<(), String> {
+    if threshold == 0 {
+        return Err("Report threshold must be at least 1".into());
+    }
+
+    canister_data.post_report_threshold = Some(threshold);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_post_report_threshold_impl() {
+        let mut canister_data = CanisterData::default();
+
+        assert!(update_post_report_threshold_impl(&mut canister_data, 0).is_err());
+        assert_eq!(canister_data.post_report_threshold, None);
+
+        assert!(update_post_report_threshold_impl(&mut canister_data, 3).is_ok());
+        assert_eq!(canister_data.post_report_threshold, Some(3));
+    }
+}
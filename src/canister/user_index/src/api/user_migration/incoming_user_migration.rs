@@ -0,0 +1,420 @@
+use std::time::SystemTime;
+
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::{
+        individual_user_template::types::snapshot::{
+            SnapshotError, SnapshotManifest, SnapshotRestoreProgress,
+        },
+        platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+    },
+    common::utils::{
+        permissions::is_caller_controller_or_global_admin,
+        system_time::get_current_system_time_from_ic,
+    },
+};
+
+use crate::{
+    data_model::CanisterData,
+    util::{
+        canister_management::reset_individual_user_canister,
+        user_directory::notify_platform_orchestrator_of_user_directory_updates,
+    },
+    CANISTER_DATA,
+};
+
+/// Takes a canister out of the pool for a user migrating to this subnet.
+/// Returns the same canister when called again for the same user.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn reserve_canister_for_incoming_user_migration(
+    user_principal_id: Principal,
+) -> Result<Principal, String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        reserve_canister_for_incoming_user_migration_impl(canister_data, user_principal_id)
+    })
+}
+
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn receive_user_canister_snapshot_manifest(
+    user_principal_id: Principal,
+    manifest: SnapshotManifest,
+) -> Result<(), String> {
+    let canister_id = incoming_user_migration_canister_id(user_principal_id)?;
+
+    let (result,): (Result<(), SnapshotError>,) =
+        ic_cdk::call(canister_id, "receive_snapshot_manifest", (manifest,))
+            .await
+            .map_err(|e| e.1)?;
+
+    result.map_err(|e| format!("{:?}", e))
+}
+
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn upload_user_canister_snapshot_chunk(
+    user_principal_id: Principal,
+    chunk_index: u64,
+    chunk: Vec<u8>,
+) -> Result<(), String> {
+    let canister_id = incoming_user_migration_canister_id(user_principal_id)?;
+
+    let (result,): (Result<(), SnapshotError>,) = ic_cdk::call(
+        canister_id,
+        "receive_and_save_snaphot",
+        (chunk_index, chunk),
+    )
+    .await
+    .map_err(|e| e.1)?;
+
+    result.map_err(|e| format!("{:?}", e))
+}
+
+/// Restores at most `max_chunks` chunks per call.
+/// The restored state carries the source subnet's well known principals,
+/// so they are replaced with this subnet's once the restore is complete.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn restore_user_canister_snapshot(
+    user_principal_id: Principal,
+    max_chunks: u64,
+) -> Result<SnapshotRestoreProgress, String> {
+    let canister_id = incoming_user_migration_canister_id(user_principal_id)?;
+
+    let (result,): (Result<SnapshotRestoreProgress, SnapshotError>,) =
+        ic_cdk::call(canister_id, "load_snapshot", (max_chunks,))
+            .await
+            .map_err(|e| e.1)?;
+    let progress = result.map_err(|e| format!("{:?}", e))?;
+
+    if !progress.is_complete() {
+        return Ok(progress);
+    }
+
+    let known_principal_ids = CANISTER_DATA
+        .with_borrow(|canister_data| canister_data.configuration.known_principal_ids.clone());
+
+    for (known_principal_type, value) in known_principal_ids {
+        ic_cdk::call::<_, ()>(
+            canister_id,
+            "update_well_known_principal",
+            (known_principal_type, value),
+        )
+        .await
+        .map_err(|e| e.1)?;
+    }
+
+    ic_cdk::call::<_, ()>(canister_id, "clear_snapshot", ())
+        .await
+        .map_err(|e| e.1)?;
+
+    Ok(progress)
+}
+
+/// Makes the restored canister the user's canister on this subnet.
+/// The unique username is only kept if it is still free here.
+/// The restored canister remembers `former_canister_id`, so that bets placed with it
+/// can be moved over when their outcome is sent from here.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn complete_incoming_user_migration(
+    user_principal_id: Principal,
+    unique_user_name: Option<String>,
+    former_canister_id: Principal,
+) -> Result<(), String> {
+    if let Ok(canister_id) = incoming_user_migration_canister_id(user_principal_id) {
+        ic_cdk::call::<_, ()>(
+            canister_id,
+            "record_former_canister_id",
+            (former_canister_id,),
+        )
+        .await
+        .map_err(|e| e.1)?;
+    }
+
+    let current_time = get_current_system_time_from_ic();
+
+    let updates = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        complete_incoming_user_migration_impl(
+            canister_data,
+            user_principal_id,
+            unique_user_name,
+            current_time,
+        )
+    })?;
+
+    notify_platform_orchestrator_of_user_directory_updates(updates);
+
+    Ok(())
+}
+
+/// Wipes the canister reserved for the user and returns it to the pool.
+/// Must only be called before the canister has taken over as the user's canister.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn abort_incoming_user_migration(user_principal_id: Principal) -> Result<(), String> {
+    // * already aborted by an earlier call, or nothing was reserved
+    let Ok(canister_id) = incoming_user_migration_canister_id(user_principal_id) else {
+        return Ok(());
+    };
+
+    reset_individual_user_canister(canister_id).await?;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        release_canister_reserved_for_incoming_user_migration(canister_data, user_principal_id)
+    });
+
+    Ok(())
+}
+
+fn incoming_user_migration_canister_id(user_principal_id: Principal) -> Result<Principal, String> {
+    CANISTER_DATA
+        .with_borrow(|canister_data| {
+            canister_data
+                .incoming_user_migrations
+                .get(&user_principal_id)
+                .copied()
+        })
+        .ok_or("No incoming migration for this user".into())
+}
+
+fn reserve_canister_for_incoming_user_migration_impl(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+) -> Result<Principal, String> {
+    if let Some(canister_id) = canister_data
+        .incoming_user_migrations
+        .get(&user_principal_id)
+    {
+        return Ok(*canister_id);
+    }
+
+    if canister_data
+        .user_principal_id_to_canister_id_map
+        .contains_key(&user_principal_id)
+    {
+        return Err("User already has a canister on this subnet".into());
+    }
+
+    let canister_id = canister_data
+        .available_canisters
+        .iter()
+        .next()
+        .copied()
+        .ok_or("No available canisters on this subnet")?;
+
+    canister_data.available_canisters.remove(&canister_id);
+    canister_data
+        .incoming_user_migrations
+        .insert(user_principal_id, canister_id);
+
+    Ok(canister_id)
+}
+
+fn release_canister_reserved_for_incoming_user_migration(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+) {
+    if let Some(canister_id) = canister_data
+        .incoming_user_migrations
+        .remove(&user_principal_id)
+    {
+        canister_data.available_canisters.insert(canister_id);
+    }
+}
+
+fn complete_incoming_user_migration_impl(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+    unique_user_name: Option<String>,
+    current_time: SystemTime,
+) -> Result<Vec<UserDirectoryUpdate>, String> {
+    let Some(user_canister_id) = canister_data
+        .incoming_user_migrations
+        .remove(&user_principal_id)
+    else {
+        // * already completed by an earlier call
+        return canister_data
+            .user_principal_id_to_canister_id_map
+            .contains_key(&user_principal_id)
+            .then(Vec::new)
+            .ok_or("No incoming migration for this user".into());
+    };
+
+    canister_data
+        .user_principal_id_to_canister_id_map
+        .insert(user_principal_id, user_canister_id);
+
+    let mut updates = vec![UserDirectoryUpdate::UserSignedUp {
+        user_principal_id,
+        user_canister_id,
+    }];
+
+    let unique_user_name = unique_user_name.filter(|unique_user_name| {
+        !canister_data
+            .unique_user_name_to_user_principal_id_map
+            .contains_key(unique_user_name)
+            && !canister_data
+                .reserved_usernames
+                .get(unique_user_name)
+                .is_some_and(|reserved_username| reserved_username.reserved_until > current_time)
+    });
+
+    if let Some(unique_user_name) = unique_user_name {
+        canister_data
+            .unique_user_name_to_user_principal_id_map
+            .insert(unique_user_name.clone(), user_principal_id);
+        updates.push(UserDirectoryUpdate::UniqueUserNameSet {
+            user_principal_id,
+            unique_user_name,
+        });
+    }
+
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::user_index::types::ReservedUsername;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_reserve_canister_for_incoming_user_migration_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+
+        assert!(
+            reserve_canister_for_incoming_user_migration_impl(&mut canister_data, alice).is_err()
+        );
+
+        canister_data
+            .available_canisters
+            .insert(get_mock_user_alice_canister_id());
+        canister_data
+            .available_canisters
+            .insert(get_mock_user_bob_canister_id());
+
+        let reserved_canister_id =
+            reserve_canister_for_incoming_user_migration_impl(&mut canister_data, alice).unwrap();
+        assert!(!canister_data
+            .available_canisters
+            .contains(&reserved_canister_id));
+        assert_eq!(canister_data.available_canisters.len(), 1);
+
+        // * calling again hands out the same canister
+        assert_eq!(
+            reserve_canister_for_incoming_user_migration_impl(&mut canister_data, alice),
+            Ok(reserved_canister_id)
+        );
+        assert_eq!(canister_data.available_canisters.len(), 1);
+
+        // * an aborted migration gives the canister back
+        release_canister_reserved_for_incoming_user_migration(&mut canister_data, alice);
+        assert!(canister_data.incoming_user_migrations.is_empty());
+        assert_eq!(canister_data.available_canisters.len(), 2);
+    }
+
+    #[test]
+    fn test_complete_incoming_user_migration_impl() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let alice_canister_id = get_mock_user_alice_canister_id();
+        let current_time = SystemTime::now();
+
+        assert!(complete_incoming_user_migration_impl(
+            &mut canister_data,
+            alice,
+            None,
+            current_time
+        )
+        .is_err());
+
+        canister_data
+            .incoming_user_migrations
+            .insert(alice, alice_canister_id);
+        let updates = complete_incoming_user_migration_impl(
+            &mut canister_data,
+            alice,
+            Some("alice".into()),
+            current_time,
+        )
+        .unwrap();
+
+        assert_eq!(
+            updates,
+            vec![
+                UserDirectoryUpdate::UserSignedUp {
+                    user_principal_id: alice,
+                    user_canister_id: alice_canister_id,
+                },
+                UserDirectoryUpdate::UniqueUserNameSet {
+                    user_principal_id: alice,
+                    unique_user_name: "alice".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            canister_data
+                .user_principal_id_to_canister_id_map
+                .get(&alice),
+            Some(&alice_canister_id)
+        );
+        assert!(canister_data.incoming_user_migrations.is_empty());
+
+        // * a resumed migration completes without sending the updates again
+        assert_eq!(
+            complete_incoming_user_migration_impl(&mut canister_data, alice, None, current_time),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_complete_incoming_user_migration_impl_drops_taken_username() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let bob = get_mock_user_bob_principal_id();
+        let current_time = SystemTime::now();
+        canister_data.reserved_usernames.insert(
+            "alice".into(),
+            ReservedUsername {
+                user_principal_id: bob,
+                reserved_until: current_time + Duration::from_secs(1),
+            },
+        );
+        canister_data
+            .incoming_user_migrations
+            .insert(alice, get_mock_user_alice_canister_id());
+
+        let updates = complete_incoming_user_migration_impl(
+            &mut canister_data,
+            alice,
+            Some("alice".into()),
+            current_time,
+        )
+        .unwrap();
+
+        assert_eq!(updates.len(), 1);
+        assert!(canister_data
+            .unique_user_name_to_user_principal_id_map
+            .is_empty());
+    }
+}
@@ -0,0 +1,7 @@
+//! Both halves of moving a user's canister to another subnet.
+//! The platform orchestrator drives a migration by calling the source subnet's
+//! outgoing endpoints and the target subnet's incoming endpoints in turn.
+//! Every endpoint can be called again after a failure.
+
+pub mod incoming_user_migration;
+pub mod outgoing_user_migration;
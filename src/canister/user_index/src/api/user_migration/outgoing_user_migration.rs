@@ -0,0 +1,285 @@
+use candid::Principal;
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::individual_user_template::types::snapshot::{
+        SnapshotError, SnapshotManifest, SnapshotSaveProgress,
+    },
+    common::utils::permissions::is_caller_controller_or_global_admin,
+};
+
+use crate::{
+    api::account_deletion::process_due_account_deletions::release_user_records,
+    data_model::CanisterData, CANISTER_DATA,
+};
+
+/// Keeps every call to the user's canister well within the instruction limit
+const SNAPSHOT_CHUNKS_SAVED_PER_CALL: u64 = 8;
+
+/// Freezes ingress on the user's canister and takes a snapshot of it.
+/// A fresh snapshot is taken every time this is called.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn snapshot_user_canister_for_migration(
+    user_principal_id: Principal,
+) -> Result<SnapshotManifest, String> {
+    let canister_id = CANISTER_DATA.with_borrow_mut(|canister_data| {
+        start_outgoing_user_migration(canister_data, user_principal_id)
+    })?;
+
+    ic_cdk::call::<_, ()>(canister_id, "update_ingress_freeze_for_migration", (true,))
+        .await
+        .map_err(|e| e.1)?;
+
+    // * drop whatever an earlier attempt left half saved
+    ic_cdk::call::<_, ()>(canister_id, "clear_snapshot", ())
+        .await
+        .map_err(|e| e.1)?;
+
+    loop {
+        let (progress,): (SnapshotSaveProgress,) = ic_cdk::call(
+            canister_id,
+            "save_snapshot",
+            (SNAPSHOT_CHUNKS_SAVED_PER_CALL,),
+        )
+        .await
+        .map_err(|e| e.1)?;
+
+        if let Some(manifest) = progress.manifest {
+            return Ok(manifest);
+        }
+    }
+}
+
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn download_user_canister_snapshot_chunk(
+    user_principal_id: Principal,
+    chunk_index: u64,
+) -> Result<Vec<u8>, String> {
+    let canister_id = outgoing_user_migration_canister_id(user_principal_id)?;
+
+    let (chunk,): (Result<Vec<u8>, SnapshotError>,) =
+        ic_cdk::call(canister_id, "download_snapshot", (chunk_index,))
+            .await
+            .map_err(|e| e.1)?;
+
+    chunk.map_err(|e| format!("{:?}", e))
+}
+
+/// Points the user's canister here at their canister on the target subnet,
+/// so that other canisters holding bets or follows with it can find the user there.
+/// Must only be called once the user's canister on the target subnet has taken over.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn redirect_outgoing_user_migration(
+    user_principal_id: Principal,
+    target_canister_id: Principal,
+) -> Result<(), String> {
+    let canister_id = outgoing_user_migration_canister_id(user_principal_id)?;
+
+    ic_cdk::call::<_, ()>(
+        canister_id,
+        "mark_canister_as_migrated",
+        (target_canister_id,),
+    )
+    .await
+    .map_err(|e| e.1)
+}
+
+/// Drops the user's records on this subnet.
+/// Their canister is kept out of the pool and stays frozen, pointing at the target subnet,
+/// for as long as other canisters may still refer to it.
+/// Must only be called once the user's canister here was redirected.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn complete_outgoing_user_migration(
+    user_principal_id: Principal,
+    target_canister_id: Principal,
+) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        complete_outgoing_user_migration_impl(canister_data, user_principal_id, target_canister_id)
+    });
+
+    Ok(())
+}
+
+/// Unfreezes the user's canister and drops its snapshot.
+/// Must only be called before the user's canister on the target subnet has taken over.
+///
+/// # Access Control
+/// Only the controller (platform orchestrator) or the global admin
+#[update(guard = "is_caller_controller_or_global_admin")]
+async fn abort_outgoing_user_migration(user_principal_id: Principal) -> Result<(), String> {
+    let canister_id = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .outgoing_user_migrations
+            .get(&user_principal_id)
+            .copied()
+    });
+
+    // * already aborted by an earlier call, or never started
+    let Some(canister_id) = canister_id else {
+        return Ok(());
+    };
+
+    ic_cdk::call::<_, ()>(canister_id, "clear_snapshot", ())
+        .await
+        .map_err(|e| e.1)?;
+    ic_cdk::call::<_, ()>(canister_id, "update_ingress_freeze_for_migration", (false,))
+        .await
+        .map_err(|e| e.1)?;
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data
+            .outgoing_user_migrations
+            .remove(&user_principal_id)
+    });
+
+    Ok(())
+}
+
+fn outgoing_user_migration_canister_id(user_principal_id: Principal) -> Result<Principal, String> {
+    CANISTER_DATA
+        .with_borrow(|canister_data| {
+            canister_data
+                .outgoing_user_migrations
+                .get(&user_principal_id)
+                .copied()
+        })
+        .ok_or("No outgoing migration for this user".into())
+}
+
+fn start_outgoing_user_migration(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+) -> Result<Principal, String> {
+    if let Some(canister_id) = canister_data
+        .outgoing_user_migrations
+        .get(&user_principal_id)
+    {
+        return Ok(*canister_id);
+    }
+
+    if canister_data
+        .pending_account_deletions
+        .contains_key(&user_principal_id)
+    {
+        return Err("User has requested account deletion".into());
+    }
+
+    let canister_id = canister_data
+        .user_principal_id_to_canister_id_map
+        .get(&user_principal_id)
+        .copied()
+        .ok_or("User canister not found on this subnet")?;
+
+    canister_data
+        .outgoing_user_migrations
+        .insert(user_principal_id, canister_id);
+
+    Ok(canister_id)
+}
+
+fn complete_outgoing_user_migration_impl(
+    canister_data: &mut CanisterData,
+    user_principal_id: Principal,
+    target_canister_id: Principal,
+) {
+    // * already completed by an earlier call
+    let Some(canister_id) = canister_data
+        .outgoing_user_migrations
+        .remove(&user_principal_id)
+    else {
+        return;
+    };
+
+    release_user_records(canister_data, user_principal_id);
+    canister_data
+        .migrated_user_canisters
+        .insert(canister_id, target_canister_id);
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use shared_utils::canister_specific::user_index::types::AccountDeletionRequest;
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+        get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_start_and_complete_outgoing_user_migration() {
+        let mut canister_data = CanisterData::default();
+        let alice = get_mock_user_alice_principal_id();
+        let alice_canister_id = get_mock_user_alice_canister_id();
+        let target_canister_id = get_mock_user_bob_canister_id();
+
+        assert!(start_outgoing_user_migration(&mut canister_data, alice).is_err());
+
+        canister_data
+            .user_principal_id_to_canister_id_map
+            .insert(alice, alice_canister_id);
+        assert_eq!(
+            start_outgoing_user_migration(&mut canister_data, alice),
+            Ok(alice_canister_id)
+        );
+        // * a retried snapshot finds the same canister
+        assert_eq!(
+            start_outgoing_user_migration(&mut canister_data, alice),
+            Ok(alice_canister_id)
+        );
+
+        complete_outgoing_user_migration_impl(&mut canister_data, alice, target_canister_id);
+        assert!(canister_data.outgoing_user_migrations.is_empty());
+        assert!(!canister_data
+            .user_principal_id_to_canister_id_map
+            .contains_key(&alice));
+        // * the canister stays behind as a pointer to the target subnet
+        assert!(!canister_data
+            .available_canisters
+            .contains(&alice_canister_id));
+        assert_eq!(
+            canister_data
+                .migrated_user_canisters
+                .get(&alice_canister_id),
+            Some(&target_canister_id)
+        );
+
+        // * completing again changes nothing
+        complete_outgoing_user_migration_impl(&mut canister_data, alice, target_canister_id);
+        assert_eq!(canister_data.migrated_user_canisters.len(), 1);
+    }
+
+    #[test]
+    fn test_start_outgoing_user_migration_rejects_pending_account_deletion() {
+        let mut canister_data = CanisterData::default();
+        let bob = get_mock_user_bob_principal_id();
+        canister_data
+            .user_principal_id_to_canister_id_map
+            .insert(bob, get_mock_user_bob_canister_id());
+        canister_data.pending_account_deletions.insert(
+            bob,
+            AccountDeletionRequest {
+                canister_id: get_mock_user_bob_canister_id(),
+                requested_at: SystemTime::now(),
+                scheduled_for: SystemTime::now(),
+                in_progress: false,
+                last_error: None,
+            },
+        );
+
+        assert!(start_outgoing_user_migration(&mut canister_data, bob).is_err());
+        assert!(canister_data.outgoing_user_migrations.is_empty());
+    }
+}
@@ -1,13 +1,22 @@
 use ic_cdk_macros::query;
+use shared_utils::common::utils::system_time::get_current_system_time_from_ic;
 
 use crate::CANISTER_DATA;
 
+/// Usernames reserved after a rename count as taken.
 #[query]
 fn get_index_details_is_user_name_taken(user_name: String) -> bool {
+    let current_time = get_current_system_time_from_ic();
+
     CANISTER_DATA.with(|canister_data_ref_cell| {
-        canister_data_ref_cell
-            .borrow()
+        let canister_data = canister_data_ref_cell.borrow();
+
+        canister_data
             .unique_user_name_to_user_principal_id_map
             .contains_key(&user_name)
+            || canister_data
+                .reserved_usernames
+                .get(&user_name)
+                .is_some_and(|reserved_username| reserved_username.reserved_until > current_time)
     })
 }
@@ -1,7 +1,10 @@
 use crate::{
-    util::canister_management::{
-        check_and_request_cycles_from_platform_orchestrator, create_empty_user_canister,
-        install_canister_wasm, provision_number_of_empty_canisters, recharge_canister,
+    util::{
+        canister_management::{
+            check_and_request_cycles_from_platform_orchestrator, create_empty_user_canister,
+            install_canister_wasm, provision_number_of_empty_canisters, recharge_canister,
+        },
+        user_directory::notify_platform_orchestrator_of_user_directory_updates,
     },
     CANISTER_DATA,
 };
@@ -14,13 +17,16 @@ use ic_cdk::api::{
 };
 use ic_cdk_macros::update;
 use shared_utils::{
-    canister_specific::individual_user_template::types::session::SessionType,
+    canister_specific::{
+        individual_user_template::types::session::SessionType,
+        platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+    },
     common::{
         types::{
             known_principal::KnownPrincipalType,
             wasm::{CanisterWasm, WasmType},
         },
-        utils::task::run_task_concurrently,
+        utils::{system_time::get_current_system_time, task::run_task_concurrently},
     },
     constant::{
         get_backup_individual_user_canister_batch_size,
@@ -33,6 +39,21 @@ use shared_utils::{
 
 #[update]
 async fn get_requester_principals_canister_id_create_if_not_exists() -> Result<Principal, String> {
+    get_requester_principals_canister_id_create_if_not_exists_impl(None).await
+}
+
+/// Same as `get_requester_principals_canister_id_create_if_not_exists`, for callers
+/// answering a proof of work signup challenge. See `SignupChallenge::ProofOfWork`.
+#[update]
+async fn get_requester_principals_canister_id_create_if_not_exists_with_proof_of_work(
+    nonce: u64,
+) -> Result<Principal, String> {
+    get_requester_principals_canister_id_create_if_not_exists_impl(Some(nonce)).await
+}
+
+async fn get_requester_principals_canister_id_create_if_not_exists_impl(
+    proof_of_work_nonce: Option<u64>,
+) -> Result<Principal, String> {
     let api_caller = ic_cdk::caller();
 
     if api_caller == Principal::anonymous() {
@@ -52,7 +73,7 @@ async fn get_requester_principals_canister_id_create_if_not_exists() -> Result<P
         Some(canister_id) => Ok(canister_id),
         None => {
             // * create new canister
-            let created_canister_id = new_user_signup(api_caller).await?;
+            let created_canister_id = new_user_signup(api_caller, proof_of_work_nonce).await?;
             // * reward user for signing up
             call::notify(created_canister_id, "get_rewarded_for_signing_up", ()).ok();
 
@@ -61,40 +82,20 @@ async fn get_requester_principals_canister_id_create_if_not_exists() -> Result<P
     }
 }
 
+/// Traps when the signup is rejected, existing clients only decode a `Principal`
 #[deprecated(note = "use get_requester_principals_canister_id_create_if_not_exists instead")]
 #[update]
 async fn get_requester_principals_canister_id_create_if_not_exists_and_optionally_allow_referrer(
 ) -> Principal {
-    let api_caller = ic_cdk::caller();
-
-    if api_caller == Principal::anonymous() {
-        panic!("Anonymous principal is not allowed to call this method");
-    }
-
-    let canister_id_for_this_caller = CANISTER_DATA.with(|canister_data_ref_cell| {
-        canister_data_ref_cell
-            .borrow()
-            .user_principal_id_to_canister_id_map
-            .get(&api_caller)
-            .cloned()
-    });
-
-    match canister_id_for_this_caller {
-        // * canister already exists
-        Some(canister_id) => canister_id,
-        None => {
-            // * create new canister
-            let created_canister_id = new_user_signup(api_caller).await.unwrap();
-
-            // * reward user for signing up
-            call::notify(created_canister_id, "get_rewarded_for_signing_up", ()).ok();
-
-            created_canister_id
-        }
-    }
+    get_requester_principals_canister_id_create_if_not_exists_impl(None)
+        .await
+        .unwrap_or_else(|e| ic_cdk::trap(&e))
 }
 
-async fn new_user_signup(user_id: Principal) -> Result<Principal, String> {
+async fn new_user_signup(
+    user_id: Principal,
+    proof_of_work_nonce: Option<u64>,
+) -> Result<Principal, String> {
     //check if sigups are enabled on this subnet
     let is_signup_enabled = CANISTER_DATA
         .with_borrow(|canister_data| canister_data.configuration.signups_open_on_this_subnet);
@@ -114,11 +115,36 @@ async fn new_user_signup(user_id: Principal) -> Result<Principal, String> {
         return Ok(user_canister_id.unwrap());
     }
 
+    let is_migrating_to_this_subnet = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .incoming_user_migrations
+            .contains_key(&user_id)
+    });
+
+    if is_migrating_to_this_subnet {
+        return Err("User is being migrated to this subnet".into());
+    }
+
+    let now = get_current_system_time();
+    CANISTER_DATA
+        .with_borrow_mut(|canister_data| {
+            canister_data
+                .signup_rate_limit
+                .admit_signup(user_id, now, proof_of_work_nonce)
+        })
+        .map_err(|reason| reason.to_string())?;
+
     let canister_id_res = CANISTER_DATA
         .with_borrow_mut(|canister_data| {
             let mut available_canisters = canister_data.available_canisters.iter().cloned();
             let canister_id = available_canisters.next();
             canister_data.available_canisters = available_canisters.collect();
+            if canister_id.is_some() {
+                let available_canisters_left = canister_data.available_canisters.len() as u64;
+                canister_data
+                    .signup_rate_limit
+                    .record_signup(now, available_canisters_left);
+            }
             canister_id
         })
         .ok_or("Not Available".into());
@@ -134,6 +160,12 @@ async fn new_user_signup(user_id: Principal) -> Result<Principal, String> {
                     .user_principal_id_to_canister_id_map
                     .insert(user_id, canister_id)
             });
+            notify_platform_orchestrator_of_user_directory_updates(vec![
+                UserDirectoryUpdate::UserSignedUp {
+                    user_principal_id: user_id,
+                    user_canister_id: canister_id,
+                },
+            ]);
 
             //update session type for the user
             call::call(
@@ -144,6 +176,29 @@ async fn new_user_signup(user_id: Principal) -> Result<Principal, String> {
             .await
             .map_err(|e| e.1)?;
 
+            // * canisters installed after the last game config broadcast still have the default
+            let hot_or_not_game_config = CANISTER_DATA
+                .with_borrow(|canister_data| canister_data.hot_or_not_game_config.clone());
+            if hot_or_not_game_config.version > 0 {
+                call::notify(
+                    canister_id,
+                    "update_hot_or_not_game_config",
+                    (hot_or_not_game_config,),
+                )
+                .ok();
+            }
+
+            let post_report_threshold =
+                CANISTER_DATA.with_borrow(|canister_data| canister_data.post_report_threshold);
+            if let Some(post_report_threshold) = post_report_threshold {
+                call::notify(
+                    canister_id,
+                    "update_post_report_threshold",
+                    (post_report_threshold,),
+                )
+                .ok();
+            }
+
             Ok(canister_id)
         }
         Err(e) => Err(e),
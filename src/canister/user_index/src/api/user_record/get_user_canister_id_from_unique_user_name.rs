@@ -1,23 +1,41 @@
+use std::time::SystemTime;
+
 use candid::Principal;
 use ic_cdk_macros::query;
+use shared_utils::common::utils::system_time::get_current_system_time_from_ic;
 
 use crate::{data_model::CanisterData, CANISTER_DATA};
 
+/// Usernames released by a rename keep resolving to their previous owner while reserved.
 #[query]
 fn get_user_canister_id_from_unique_user_name(user_name: String) -> Option<Principal> {
+    let current_time = get_current_system_time_from_ic();
+
     CANISTER_DATA.with(|canister_data_ref_cell| {
-        get_user_canister_id_from_unique_user_name_impl(user_name, &canister_data_ref_cell.borrow())
+        get_user_canister_id_from_unique_user_name_impl(
+            user_name,
+            &canister_data_ref_cell.borrow(),
+            current_time,
+        )
     })
 }
 
 fn get_user_canister_id_from_unique_user_name_impl(
     user_name: String,
     canister_data: &CanisterData,
+    current_time: SystemTime,
 ) -> Option<Principal> {
     let profile_principal_id = canister_data
         .unique_user_name_to_user_principal_id_map
         .get(&user_name)
-        .cloned()?;
+        .cloned()
+        .or_else(|| {
+            canister_data
+                .reserved_usernames
+                .get(&user_name)
+                .filter(|reserved_username| reserved_username.reserved_until > current_time)
+                .map(|reserved_username| reserved_username.user_principal_id)
+        })?;
 
     canister_data
         .user_principal_id_to_canister_id_map
@@ -31,6 +49,10 @@ mod test {
         get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
     };
 
+    use std::time::Duration;
+
+    use shared_utils::canister_specific::user_index::types::ReservedUsername;
+
     use super::*;
 
     #[test]
@@ -41,6 +63,7 @@ mod test {
         let result = get_user_canister_id_from_unique_user_name_impl(
             alice_user_name.clone(),
             &canister_data,
+            SystemTime::now(),
         );
         assert_eq!(result, None);
 
@@ -50,6 +73,7 @@ mod test {
         let result = get_user_canister_id_from_unique_user_name_impl(
             alice_user_name.clone(),
             &canister_data,
+            SystemTime::now(),
         );
         assert_eq!(result, None);
 
@@ -60,7 +84,42 @@ mod test {
         let result = get_user_canister_id_from_unique_user_name_impl(
             alice_user_name.clone(),
             &canister_data,
+            SystemTime::now(),
         );
         assert_eq!(result, Some(get_mock_user_alice_canister_id()));
     }
+
+    #[test]
+    fn test_reserved_user_name_redirects_to_previous_owner_until_it_expires() {
+        let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
+        canister_data.user_principal_id_to_canister_id_map.insert(
+            get_mock_user_alice_principal_id(),
+            get_mock_user_alice_canister_id(),
+        );
+        canister_data.reserved_usernames.insert(
+            "old_alice".to_string(),
+            ReservedUsername {
+                user_principal_id: get_mock_user_alice_principal_id(),
+                reserved_until: current_time + Duration::from_secs(60),
+            },
+        );
+
+        assert_eq!(
+            get_user_canister_id_from_unique_user_name_impl(
+                "old_alice".to_string(),
+                &canister_data,
+                current_time,
+            ),
+            Some(get_mock_user_alice_canister_id())
+        );
+        assert_eq!(
+            get_user_canister_id_from_unique_user_name_impl(
+                "old_alice".to_string(),
+                &canister_data,
+                current_time + Duration::from_secs(60),
+            ),
+            None
+        );
+    }
 }
@@ -0,0 +1,17 @@
+use candid::Principal;
+use ic_cdk_macros::query;
+use shared_utils::canister_specific::user_index::types::UsernameChange;
+
+use crate::CANISTER_DATA;
+
+/// Oldest change first. Usernames set before history was kept are not included.
+#[query]
+fn get_username_history(user_principal_id: Principal) -> Vec<UsernameChange> {
+    CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .username_history
+            .get(&user_principal_id)
+            .cloned()
+            .unwrap_or_default()
+    })
+}
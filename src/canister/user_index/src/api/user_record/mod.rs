@@ -4,6 +4,8 @@ pub mod get_user_canister_id_from_unique_user_name;
 pub mod get_user_canister_id_from_user_principal_id;
 pub mod get_user_canister_list;
 pub mod get_user_index_canister_count;
+pub mod get_username_history;
+pub mod sync_user_directory_with_platform_orchestrator;
 pub mod update_index_with_unique_user_name_corresponding_to_user_principal_id;
 pub mod update_profile_owner_for_individual_canisters;
 pub mod issue_rewards_for_referral;
@@ -0,0 +1,78 @@
+use ic_cdk_macros::update;
+use shared_utils::{
+    canister_specific::platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+    common::utils::permissions::is_caller_controller_or_global_admin,
+};
+
+use crate::{
+    data_model::CanisterData,
+    util::user_directory::notify_platform_orchestrator_of_user_directory_updates, CANISTER_DATA,
+};
+
+/// Sends every user of this subnet to the platform orchestrator's user directory.
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn sync_user_directory_with_platform_orchestrator() {
+    let updates = CANISTER_DATA.with_borrow(user_directory_updates);
+
+    notify_platform_orchestrator_of_user_directory_updates(updates);
+}
+
+fn user_directory_updates(canister_data: &CanisterData) -> Vec<UserDirectoryUpdate> {
+    let signups = canister_data
+        .user_principal_id_to_canister_id_map
+        .iter()
+        .map(
+            |(user_principal_id, user_canister_id)| UserDirectoryUpdate::UserSignedUp {
+                user_principal_id: *user_principal_id,
+                user_canister_id: *user_canister_id,
+            },
+        );
+
+    let unique_user_names = canister_data
+        .unique_user_name_to_user_principal_id_map
+        .iter()
+        .map(
+            |(unique_user_name, user_principal_id)| UserDirectoryUpdate::UniqueUserNameSet {
+                user_principal_id: *user_principal_id,
+                unique_user_name: unique_user_name.clone(),
+            },
+        );
+
+    // * signups first, the directory ignores usernames of users it does not know
+    signups.chain(unique_user_names).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_user_directory_updates() {
+        let mut canister_data = CanisterData::default();
+        canister_data.user_principal_id_to_canister_id_map.insert(
+            get_mock_user_alice_principal_id(),
+            get_mock_user_alice_canister_id(),
+        );
+        canister_data
+            .unique_user_name_to_user_principal_id_map
+            .insert("alice".into(), get_mock_user_alice_principal_id());
+
+        assert_eq!(
+            user_directory_updates(&canister_data),
+            vec![
+                UserDirectoryUpdate::UserSignedUp {
+                    user_principal_id: get_mock_user_alice_principal_id(),
+                    user_canister_id: get_mock_user_alice_canister_id(),
+                },
+                UserDirectoryUpdate::UniqueUserNameSet {
+                    user_principal_id: get_mock_user_alice_principal_id(),
+                    unique_user_name: "alice".into(),
+                },
+            ]
+        );
+    }
+}
@@ -1,24 +1,57 @@
+use std::time::{Duration, SystemTime};
+
 use candid::Principal;
 use ic_cdk_macros::update;
-use shared_utils::types::canister_specific::user_index::error_types::SetUniqueUsernameError;
+use shared_utils::{
+    canister_specific::{
+        platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+        user_index::types::{ReservedUsername, UsernameChange},
+    },
+    common::utils::{system_time::get_current_system_time_from_ic, username::validate_username},
+    types::canister_specific::user_index::error_types::SetUniqueUsernameError,
+};
+
+use crate::{
+    data_model::CanisterData,
+    util::user_directory::notify_platform_orchestrator_of_user_directory_updates, CANISTER_DATA,
+};
 
-use crate::{data_model::CanisterData, CANISTER_DATA};
+pub const USERNAME_CHANGE_COOLDOWN: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+pub const USERNAME_RESERVATION_PERIOD: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+const MAX_USERNAME_HISTORY_LENGTH: usize = 20;
 
+/// Sets or changes the username of `user_principal_id`.
+/// A changed username can only be changed again after `USERNAME_CHANGE_COOLDOWN`.
+/// The previous username stays reserved for the user for `USERNAME_RESERVATION_PERIOD`.
+///
+/// # Access Control
+/// Only the user's own canister
 #[update]
 fn update_index_with_unique_user_name_corresponding_to_user_principal_id(
     unique_user_name: String,
     user_principal_id: Principal,
 ) -> Result<(), SetUniqueUsernameError> {
     let request_makers_canister_id = ic_cdk::caller();
+    let current_time = get_current_system_time_from_ic();
 
     CANISTER_DATA.with(|canister_data_ref_cell| {
         update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
-            unique_user_name,
+            unique_user_name.clone(),
             user_principal_id,
             request_makers_canister_id,
             &mut canister_data_ref_cell.borrow_mut(),
+            current_time,
         )
-    })
+    })?;
+
+    notify_platform_orchestrator_of_user_directory_updates(vec![
+        UserDirectoryUpdate::UniqueUserNameSet {
+            user_principal_id,
+            unique_user_name,
+        },
+    ]);
+
+    Ok(())
 }
 
 fn update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
@@ -26,6 +59,7 @@ fn update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
     user_principal_id: Principal,
     request_makers_canister_id: Principal,
     canister_data: &mut CanisterData,
+    current_time: SystemTime,
 ) -> Result<(), SetUniqueUsernameError> {
     if !canister_data
         .user_principal_id_to_canister_id_map
@@ -42,20 +76,100 @@ fn update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
         return Err(SetUniqueUsernameError::SendingCanisterDoesNotMatchUserCanisterId);
     }
 
-    if canister_data
+    validate_username(&unique_user_name).map_err(SetUniqueUsernameError::InvalidUsername)?;
+
+    match canister_data
         .unique_user_name_to_user_principal_id_map
-        .contains_key(&unique_user_name)
+        .get(&unique_user_name)
     {
-        return Err(SetUniqueUsernameError::UsernameAlreadyTaken);
+        Some(owner) if *owner == user_principal_id => return Ok(()),
+        Some(_) => return Err(SetUniqueUsernameError::UsernameAlreadyTaken),
+        None => {}
+    }
+
+    if let Some(reserved_username) = canister_data.reserved_usernames.get(&unique_user_name) {
+        if reserved_username.user_principal_id != user_principal_id
+            && reserved_username.reserved_until > current_time
+        {
+            return Err(SetUniqueUsernameError::UsernameAlreadyTaken);
+        }
+    }
+
+    let current_user_name = current_user_name(canister_data, user_principal_id);
+
+    if current_user_name.is_some() {
+        let last_changed_at = canister_data
+            .username_history
+            .get(&user_principal_id)
+            .and_then(|history| history.last())
+            .map(|change| change.changed_at);
+        if let Some(last_changed_at) = last_changed_at {
+            if current_time < last_changed_at + USERNAME_CHANGE_COOLDOWN {
+                return Err(SetUniqueUsernameError::UsernameChangeOnCooldown);
+            }
+        }
+    }
+
+    canister_data
+        .reserved_usernames
+        .retain(|_, reserved_username| reserved_username.reserved_until > current_time);
+    canister_data.reserved_usernames.remove(&unique_user_name);
+
+    if let Some(current_user_name) = current_user_name {
+        canister_data
+            .unique_user_name_to_user_principal_id_map
+            .remove(&current_user_name);
+        canister_data.reserved_usernames.insert(
+            current_user_name,
+            ReservedUsername {
+                user_principal_id,
+                reserved_until: current_time + USERNAME_RESERVATION_PERIOD,
+            },
+        );
     }
 
     canister_data
         .unique_user_name_to_user_principal_id_map
         .insert(unique_user_name.clone(), user_principal_id);
 
+    let history = canister_data
+        .username_history
+        .entry(user_principal_id)
+        .or_default();
+    history.push(UsernameChange {
+        username: unique_user_name,
+        changed_at: current_time,
+    });
+    if history.len() > MAX_USERNAME_HISTORY_LENGTH {
+        history.remove(0);
+    }
+
     Ok(())
 }
 
+fn current_user_name(canister_data: &CanisterData, user_principal_id: Principal) -> Option<String> {
+    let last_known_user_name = canister_data
+        .username_history
+        .get(&user_principal_id)
+        .and_then(|history| history.last())
+        .map(|change| change.username.clone())
+        .filter(|user_name| {
+            canister_data
+                .unique_user_name_to_user_principal_id_map
+                .get(user_name)
+                == Some(&user_principal_id)
+        });
+
+    // * usernames set before history was kept are only in the index
+    last_known_user_name.or_else(|| {
+        canister_data
+            .unique_user_name_to_user_principal_id_map
+            .iter()
+            .find(|(_, owner)| **owner == user_principal_id)
+            .map(|(user_name, _)| user_name.clone())
+    })
+}
+
 #[cfg(test)]
 mod test {
     use test_utils::setup::test_constants::{
@@ -63,6 +177,8 @@ mod test {
         get_mock_user_bob_canister_id, get_mock_user_bob_principal_id,
     };
 
+    use shared_utils::common::utils::username::UsernameValidationError;
+
     use super::*;
 
     #[test]
@@ -72,12 +188,14 @@ mod test {
         let user_principal_id = get_mock_user_alice_principal_id();
         let request_makers_canister_id = get_mock_user_alice_canister_id();
         let mut canister_data = CanisterData::default();
+        let current_time = SystemTime::now();
 
         let result = update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
             unique_user_name_1.clone(),
             user_principal_id,
             request_makers_canister_id,
             &mut canister_data,
+            current_time,
         );
         assert!(result.is_err());
         assert_eq!(
@@ -94,6 +212,7 @@ mod test {
             user_principal_id,
             get_mock_user_bob_canister_id(),
             &mut canister_data,
+            current_time,
         );
         assert!(result.is_err());
         assert_eq!(
@@ -110,6 +229,7 @@ mod test {
             user_principal_id,
             request_makers_canister_id,
             &mut canister_data,
+            current_time,
         );
         assert!(result.is_err());
         assert_eq!(
@@ -122,6 +242,7 @@ mod test {
             user_principal_id,
             request_makers_canister_id,
             &mut canister_data,
+            current_time,
         );
         assert!(result.is_ok());
         assert_eq!(
@@ -132,4 +253,77 @@ mod test {
             &user_principal_id
         );
     }
+
+    #[test]
+    fn test_username_change_respects_cooldown_and_reserves_the_old_name() {
+        let alice = get_mock_user_alice_principal_id();
+        let alice_canister_id = get_mock_user_alice_canister_id();
+        let bob = get_mock_user_bob_principal_id();
+        let bob_canister_id = get_mock_user_bob_canister_id();
+        let mut canister_data = CanisterData::default();
+        canister_data
+            .user_principal_id_to_canister_id_map
+            .insert(alice, alice_canister_id);
+        canister_data
+            .user_principal_id_to_canister_id_map
+            .insert(bob, bob_canister_id);
+        let current_time = SystemTime::now();
+
+        let mut set_username = |user_name: &str, user, canister_id, at| {
+            update_index_with_unique_user_name_corresponding_to_user_principal_id_impl(
+                user_name.to_string(),
+                user,
+                canister_id,
+                &mut canister_data,
+                at,
+            )
+        };
+
+        assert_eq!(
+            set_username("Alice!", alice, alice_canister_id, current_time),
+            Err(SetUniqueUsernameError::InvalidUsername(
+                UsernameValidationError::InvalidCharacters
+            ))
+        );
+        assert!(set_username("alice", alice, alice_canister_id, current_time).is_ok());
+        assert_eq!(
+            set_username("alice_2", alice, alice_canister_id, current_time),
+            Err(SetUniqueUsernameError::UsernameChangeOnCooldown)
+        );
+
+        let after_cooldown = current_time + USERNAME_CHANGE_COOLDOWN;
+        assert!(set_username("alice_2", alice, alice_canister_id, after_cooldown).is_ok());
+
+        // * the old name is reserved for alice
+        assert_eq!(
+            set_username("alice", bob, bob_canister_id, after_cooldown),
+            Err(SetUniqueUsernameError::UsernameAlreadyTaken)
+        );
+        let after_reservation = after_cooldown + USERNAME_RESERVATION_PERIOD;
+        assert!(set_username("alice", bob, bob_canister_id, after_reservation).is_ok());
+
+        assert_eq!(
+            canister_data
+                .unique_user_name_to_user_principal_id_map
+                .get("alice_2"),
+            Some(&alice)
+        );
+        assert_eq!(
+            canister_data
+                .unique_user_name_to_user_principal_id_map
+                .get("alice"),
+            Some(&bob)
+        );
+        assert!(canister_data.reserved_usernames.is_empty());
+        assert_eq!(
+            canister_data
+                .username_history
+                .get(&alice)
+                .unwrap()
+                .iter()
+                .map(|change| change.username.as_str())
+                .collect::<Vec<_>>(),
+            vec!["alice", "alice_2"]
+        );
+    }
 }
@@ -1,2 +1,3 @@
 pub mod are_signups_enabled;
-pub mod toggle_signups_enabled;
\ No newline at end of file
+pub mod signup_rate_limit;
+pub mod toggle_signups_enabled;
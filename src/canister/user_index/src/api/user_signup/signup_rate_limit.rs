@@ -0,0 +1,83 @@
+use candid::Principal;
+use ic_cdk_macros::{query, update};
+use shared_utils::{
+    canister_specific::user_index::types::{SignupRateLimitConfig, SignupRejectionStats},
+    common::utils::permissions::is_caller_controller_or_global_admin,
+};
+
+use crate::{
+    data_model::{signup_rate_limit::validate_signup_rate_limit_config, CanisterData},
+    CANISTER_DATA,
+};
+
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn update_signup_rate_limit_config(config: SignupRateLimitConfig) -> Result<(), String> {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        update_signup_rate_limit_config_impl(config, canister_data)
+    })
+}
+
+fn update_signup_rate_limit_config_impl(
+    config: SignupRateLimitConfig,
+    canister_data: &mut CanisterData,
+) -> Result<(), String> {
+    validate_signup_rate_limit_config(&config)?;
+    canister_data.signup_rate_limit.config = config;
+
+    Ok(())
+}
+
+#[update(guard = "is_caller_controller_or_global_admin")]
+fn update_signup_allow_list(
+    principals_to_add: Vec<Principal>,
+    principals_to_remove: Vec<Principal>,
+) {
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        let allow_list = &mut canister_data.signup_rate_limit.allow_list;
+        allow_list.extend(principals_to_add);
+        principals_to_remove.iter().for_each(|principal| {
+            allow_list.remove(principal);
+        });
+    })
+}
+
+#[query]
+fn get_signup_rate_limit_config() -> SignupRateLimitConfig {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.signup_rate_limit.config.clone())
+}
+
+/// Signup rejections per hour, keyed by the start of the hour in seconds since epoch.
+/// Only the last week is kept.
+#[query(guard = "is_caller_controller_or_global_admin")]
+fn get_signup_rejection_stats() -> Vec<(u64, SignupRejectionStats)> {
+    CANISTER_DATA.with_borrow(|canister_data| canister_data.signup_rate_limit.rejection_stats())
+}
+
+#[cfg(test)]
+mod test {
+    use shared_utils::canister_specific::user_index::types::SignupChallenge;
+
+    use super::*;
+
+    #[test]
+    fn test_update_signup_rate_limit_config_impl() {
+        let mut canister_data = CanisterData::default();
+
+        let config = SignupRateLimitConfig {
+            max_signups_per_window: Some(100),
+            challenge: SignupChallenge::ProofOfWork {
+                difficulty_bits: 16,
+            },
+            ..Default::default()
+        };
+        assert!(update_signup_rate_limit_config_impl(config.clone(), &mut canister_data).is_ok());
+        assert_eq!(canister_data.signup_rate_limit.config, config);
+
+        let invalid_config = SignupRateLimitConfig {
+            window_duration_in_seconds: 0,
+            ..Default::default()
+        };
+        assert!(update_signup_rate_limit_config_impl(invalid_config, &mut canister_data).is_err());
+        assert_eq!(canister_data.signup_rate_limit.config, config);
+    }
+}
@@ -3,16 +3,22 @@ use std::collections::{BTreeMap, HashSet};
 use candid::{Deserialize, Principal};
 use ic_stable_structures::StableBTreeMap;
 use serde::Serialize;
+use shared_utils::canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig;
+use shared_utils::canister_specific::individual_user_template::types::report::PostReportSummary;
 use shared_utils::canister_specific::user_index::types::{
-    BroadcastCallStatus, RecycleStatus, UpgradeStatus,
+    AccountDeletionRequest, BroadcastCallStatus, RecycleStatus, ReservedUsername, UpgradeStatus,
+    UsernameChange,
 };
+use shared_utils::common::types::app_primitive_type::PostId;
 use shared_utils::common::types::wasm::{CanisterWasm, WasmType};
 
 use self::memory::get_wasm_memory;
+use self::signup_rate_limit::SignupRateLimit;
 use self::{configuration::Configuration, memory::Memory};
 
 pub mod configuration;
 pub mod memory;
+pub mod signup_rate_limit;
 
 const fn _default_true() -> bool {
     return true;
@@ -38,6 +44,32 @@ pub struct CanisterData {
     pub recycle_status: RecycleStatus,
     #[serde(default)]
     pub last_broadcast_call_status: BroadcastCallStatus,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    #[serde(default)]
+    pub signup_rate_limit: SignupRateLimit,
+    #[serde(default)]
+    pub pending_account_deletions: BTreeMap<Principal, AccountDeletionRequest>,
+    #[serde(default)]
+    pub reserved_usernames: BTreeMap<String, ReservedUsername>,
+    #[serde(default)]
+    pub username_history: BTreeMap<Principal, Vec<UsernameChange>>,
+    /// User principal to the canister reserved for them on this subnet while they migrate in
+    #[serde(default)]
+    pub incoming_user_migrations: BTreeMap<Principal, Principal>,
+    /// User principal to their canister on this subnet while they migrate out
+    #[serde(default)]
+    pub outgoing_user_migrations: BTreeMap<Principal, Principal>,
+    /// Overrides the default report threshold on the individual canisters of this subnet
+    #[serde(default)]
+    pub post_report_threshold: Option<u64>,
+    /// (Post creator canister, post) to the reports awaiting admin review
+    #[serde(default)]
+    pub pending_post_reports: BTreeMap<(Principal, PostId), PostReportSummary>,
+    /// Canisters of users who migrated to another subnet, to their canister there.
+    /// They are kept frozen and out of the pool so that other canisters can still follow them.
+    #[serde(default)]
+    pub migrated_user_canisters: BTreeMap<Principal, Principal>,
 }
 
 impl Default for CanisterData {
@@ -53,6 +85,16 @@ impl Default for CanisterData {
             backup_canister_pool: Default::default(),
             recycle_status: Default::default(),
             last_broadcast_call_status: Default::default(),
+            hot_or_not_game_config: Default::default(),
+            signup_rate_limit: Default::default(),
+            pending_account_deletions: Default::default(),
+            reserved_usernames: Default::default(),
+            username_history: Default::default(),
+            incoming_user_migrations: Default::default(),
+            outgoing_user_migrations: Default::default(),
+            post_report_threshold: Default::default(),
+            pending_post_reports: Default::default(),
+            migrated_user_canisters: Default::default(),
         }
     }
 }
@@ -0,0 +1,340 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use candid::{Deserialize, Principal};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use shared_utils::canister_specific::user_index::types::{
+    SignupChallenge, SignupRateLimitConfig, SignupRejectionReason, SignupRejectionStats,
+};
+
+const SIGNUP_REJECTION_BUCKET_DURATION_IN_SECONDS: u64 = 60 * 60;
+const MAX_SIGNUP_REJECTION_BUCKETS: usize = 7 * 24;
+pub const MAX_PROOF_OF_WORK_DIFFICULTY_BITS: u8 = 32;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SignupRateLimit {
+    pub config: SignupRateLimitConfig,
+    pub allow_list: HashSet<Principal>,
+    window_started_at: Option<SystemTime>,
+    signups_in_window: u64,
+    pool_drain_cooldown_until: Option<SystemTime>,
+    // * keyed by the start of the hour the rejections happened in, in seconds since epoch
+    rejection_stats: BTreeMap<u64, SignupRejectionStats>,
+}
+
+impl SignupRateLimit {
+    /// Checks whether `caller` may take a canister from the pool right now.
+    /// Rejections are recorded in the rejection stats.
+    pub fn admit_signup(
+        &mut self,
+        caller: Principal,
+        now: SystemTime,
+        proof_of_work_nonce: Option<u64>,
+    ) -> Result<(), SignupRejectionReason> {
+        let result = self.check_signup(caller, now, proof_of_work_nonce);
+        if let Err(reason) = result {
+            self.record_rejection(now, reason);
+        }
+        result
+    }
+
+    fn check_signup(
+        &self,
+        caller: Principal,
+        now: SystemTime,
+        proof_of_work_nonce: Option<u64>,
+    ) -> Result<(), SignupRejectionReason> {
+        let limited_by = if self.is_pool_drain_cooldown_active(now) {
+            SignupRejectionReason::PoolDrainCooldown
+        } else if self
+            .config
+            .max_signups_per_window
+            .is_some_and(|max_signups| self.signups_in_current_window(now) >= max_signups)
+        {
+            SignupRejectionReason::QuotaExceeded
+        } else {
+            return Ok(());
+        };
+
+        let challenge_passed = match self.config.challenge {
+            SignupChallenge::Reject => return Err(limited_by),
+            SignupChallenge::AllowList => self.allow_list.contains(&caller),
+            SignupChallenge::ProofOfWork { difficulty_bits } => proof_of_work_nonce
+                .is_some_and(|nonce| is_proof_of_work_valid(caller, nonce, difficulty_bits)),
+        };
+
+        if challenge_passed {
+            Ok(())
+        } else {
+            Err(SignupRejectionReason::ChallengeFailed)
+        }
+    }
+
+    /// Counts a signup that took a canister from the pool and starts the pool
+    /// drain cooldown if the pool has shrunk to the configured threshold.
+    pub fn record_signup(&mut self, now: SystemTime, available_canisters_left: u64) {
+        let window_expired = self.window_started_at.map_or(true, |started_at| {
+            now >= started_at + Duration::from_secs(self.config.window_duration_in_seconds)
+        });
+        if window_expired {
+            self.window_started_at = Some(now);
+            self.signups_in_window = 0;
+        }
+        self.signups_in_window += 1;
+
+        if self.config.pool_drain_cooldown_in_seconds > 0
+            && available_canisters_left <= self.config.pool_drain_threshold
+            && !self.is_pool_drain_cooldown_active(now)
+        {
+            self.pool_drain_cooldown_until =
+                Some(now + Duration::from_secs(self.config.pool_drain_cooldown_in_seconds));
+        }
+    }
+
+    pub fn record_rejection(&mut self, now: SystemTime, reason: SignupRejectionReason) {
+        let seconds_since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let bucket =
+            seconds_since_epoch - seconds_since_epoch % SIGNUP_REJECTION_BUCKET_DURATION_IN_SECONDS;
+
+        let stats = self.rejection_stats.entry(bucket).or_default();
+        match reason {
+            SignupRejectionReason::QuotaExceeded => stats.quota_exceeded += 1,
+            SignupRejectionReason::PoolDrainCooldown => stats.pool_drain_cooldown += 1,
+            SignupRejectionReason::ChallengeFailed => stats.challenge_failed += 1,
+        }
+
+        while self.rejection_stats.len() > MAX_SIGNUP_REJECTION_BUCKETS {
+            self.rejection_stats.pop_first();
+        }
+    }
+
+    pub fn rejection_stats(&self) -> Vec<(u64, SignupRejectionStats)> {
+        self.rejection_stats
+            .iter()
+            .map(|(bucket, stats)| (*bucket, stats.clone()))
+            .collect()
+    }
+
+    fn signups_in_current_window(&self, now: SystemTime) -> u64 {
+        match self.window_started_at {
+            Some(started_at)
+                if now
+                    < started_at + Duration::from_secs(self.config.window_duration_in_seconds) =>
+            {
+                self.signups_in_window
+            }
+            _ => 0,
+        }
+    }
+
+    fn is_pool_drain_cooldown_active(&self, now: SystemTime) -> bool {
+        self.pool_drain_cooldown_until
+            .is_some_and(|cooldown_until| now < cooldown_until)
+    }
+}
+
+pub fn validate_signup_rate_limit_config(config: &SignupRateLimitConfig) -> Result<(), String> {
+    if config.window_duration_in_seconds == 0 {
+        return Err("Window duration must be greater than zero".into());
+    }
+
+    if let SignupChallenge::ProofOfWork { difficulty_bits } = config.challenge {
+        if difficulty_bits == 0 || difficulty_bits > MAX_PROOF_OF_WORK_DIFFICULTY_BITS {
+            return Err(format!(
+                "Proof of work difficulty must be between 1 and {} bits",
+                MAX_PROOF_OF_WORK_DIFFICULTY_BITS
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn is_proof_of_work_valid(caller: Principal, nonce: u64, difficulty_bits: u8) -> bool {
+    let mut hasher = Sha256::new();
+    hasher.update(caller.as_slice());
+    hasher.update(nonce.to_be_bytes());
+    let hash = hasher.finalize();
+
+    let mut leading_zero_bits = 0;
+    for byte in hash.iter() {
+        leading_zero_bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+
+    leading_zero_bits >= difficulty_bits as u32
+}
+
+#[cfg(test)]
+mod test {
+    use test_utils::setup::test_constants::{
+        get_mock_user_alice_principal_id, get_mock_user_bob_principal_id,
+    };
+
+    use super::*;
+
+    fn rate_limit_with(config: SignupRateLimitConfig) -> SignupRateLimit {
+        SignupRateLimit {
+            config,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_config_admits_every_signup() {
+        let mut rate_limit = SignupRateLimit::default();
+        let now = SystemTime::now();
+
+        for _ in 0..1000 {
+            assert!(rate_limit
+                .admit_signup(get_mock_user_alice_principal_id(), now, None)
+                .is_ok());
+            rate_limit.record_signup(now, 0);
+        }
+        assert!(rate_limit.rejection_stats().is_empty());
+    }
+
+    #[test]
+    fn test_signups_over_the_quota_are_rejected_until_the_window_ends() {
+        let mut rate_limit = rate_limit_with(SignupRateLimitConfig {
+            window_duration_in_seconds: 60,
+            max_signups_per_window: Some(2),
+            ..Default::default()
+        });
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+
+        for _ in 0..2 {
+            assert!(rate_limit.admit_signup(alice, now, None).is_ok());
+            rate_limit.record_signup(now, 100);
+        }
+        assert_eq!(
+            rate_limit.admit_signup(alice, now, None),
+            Err(SignupRejectionReason::QuotaExceeded)
+        );
+
+        let next_window = now + Duration::from_secs(60);
+        assert!(rate_limit.admit_signup(alice, next_window, None).is_ok());
+
+        let stats = rate_limit.rejection_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].1.quota_exceeded, 1);
+    }
+
+    #[test]
+    fn test_draining_the_pool_starts_a_cooldown() {
+        let mut rate_limit = rate_limit_with(SignupRateLimitConfig {
+            pool_drain_threshold: 5,
+            pool_drain_cooldown_in_seconds: 300,
+            ..Default::default()
+        });
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+
+        rate_limit.record_signup(now, 6);
+        assert!(rate_limit.admit_signup(alice, now, None).is_ok());
+
+        rate_limit.record_signup(now, 5);
+        assert_eq!(
+            rate_limit.admit_signup(alice, now + Duration::from_secs(299), None),
+            Err(SignupRejectionReason::PoolDrainCooldown)
+        );
+        assert!(rate_limit
+            .admit_signup(alice, now + Duration::from_secs(300), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_allow_list_challenge_admits_listed_principals_over_the_quota() {
+        let mut rate_limit = rate_limit_with(SignupRateLimitConfig {
+            max_signups_per_window: Some(0),
+            challenge: SignupChallenge::AllowList,
+            ..Default::default()
+        });
+        rate_limit
+            .allow_list
+            .insert(get_mock_user_alice_principal_id());
+        let now = SystemTime::now();
+
+        assert!(rate_limit
+            .admit_signup(get_mock_user_alice_principal_id(), now, None)
+            .is_ok());
+        assert_eq!(
+            rate_limit.admit_signup(get_mock_user_bob_principal_id(), now, None),
+            Err(SignupRejectionReason::ChallengeFailed)
+        );
+        assert_eq!(rate_limit.rejection_stats()[0].1.challenge_failed, 1);
+    }
+
+    #[test]
+    fn test_proof_of_work_challenge_admits_a_valid_nonce() {
+        let difficulty_bits = 8;
+        let mut rate_limit = rate_limit_with(SignupRateLimitConfig {
+            max_signups_per_window: Some(0),
+            challenge: SignupChallenge::ProofOfWork { difficulty_bits },
+            ..Default::default()
+        });
+        let alice = get_mock_user_alice_principal_id();
+        let now = SystemTime::now();
+
+        let valid_nonce = (0..u64::MAX)
+            .find(|nonce| is_proof_of_work_valid(alice, *nonce, difficulty_bits))
+            .unwrap();
+        let invalid_nonce = (0..u64::MAX)
+            .find(|nonce| !is_proof_of_work_valid(alice, *nonce, difficulty_bits))
+            .unwrap();
+
+        assert!(rate_limit
+            .admit_signup(alice, now, Some(valid_nonce))
+            .is_ok());
+        assert_eq!(
+            rate_limit.admit_signup(alice, now, Some(invalid_nonce)),
+            Err(SignupRejectionReason::ChallengeFailed)
+        );
+        assert_eq!(
+            rate_limit.admit_signup(alice, now, None),
+            Err(SignupRejectionReason::ChallengeFailed)
+        );
+    }
+
+    #[test]
+    fn test_rejection_stats_are_bucketed_by_hour_and_capped() {
+        let mut rate_limit = SignupRateLimit::default();
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        for hour in 0..(MAX_SIGNUP_REJECTION_BUCKETS as u64 + 10) {
+            let now =
+                start + Duration::from_secs(hour * SIGNUP_REJECTION_BUCKET_DURATION_IN_SECONDS);
+            rate_limit.record_rejection(now, SignupRejectionReason::QuotaExceeded);
+            rate_limit.record_rejection(now, SignupRejectionReason::PoolDrainCooldown);
+        }
+
+        let stats = rate_limit.rejection_stats();
+        assert_eq!(stats.len(), MAX_SIGNUP_REJECTION_BUCKETS);
+        assert!(stats
+            .iter()
+            .all(|(_, stats)| stats.quota_exceeded == 1 && stats.pool_drain_cooldown == 1));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(validate_signup_rate_limit_config(&SignupRateLimitConfig::default()).is_ok());
+        assert!(validate_signup_rate_limit_config(&SignupRateLimitConfig {
+            window_duration_in_seconds: 0,
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate_signup_rate_limit_config(&SignupRateLimitConfig {
+            challenge: SignupChallenge::ProofOfWork {
+                difficulty_bits: MAX_PROOF_OF_WORK_DIFFICULTY_BITS + 1
+            },
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
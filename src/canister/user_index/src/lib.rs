@@ -8,12 +8,23 @@ use ic_cdk::api::{
 };
 use ic_cdk_macros::export_candid;
 use shared_utils::{
+    canister_specific::individual_user_template::types::hot_or_not::game_config::HotOrNotGameConfig,
+    canister_specific::individual_user_template::types::report::{
+        PostReportReviewDecision, PostReportSummary,
+    },
+    canister_specific::individual_user_template::types::snapshot::{
+        SnapshotManifest, SnapshotRestoreProgress,
+    },
     canister_specific::user_index::types::{
-        args::UserIndexInitArgs, BroadcastCallStatus, RecycleStatus, UpgradeStatus,
+        args::UserIndexInitArgs, AccountDeletionRequest, BroadcastCallStatus, RecycleStatus,
+        SignupRateLimitConfig, SignupRejectionStats, UpgradeStatus, UsernameChange,
     },
+    common::types::app_primitive_type::PostId,
     common::types::http::{HttpRequest, HttpResponse},
     common::types::known_principal::KnownPrincipalType,
-    types::canister_specific::user_index::error_types::SetUniqueUsernameError,
+    types::canister_specific::user_index::error_types::{
+        AccountDeletionError, SetUniqueUsernameError,
+    },
 };
 
 mod api;
@@ -1,4 +1,4 @@
-use candid::{CandidType, Principal};
+use candid::{utils::ArgumentEncoder, CandidType, Principal};
 use ic_cdk::{
     api::{
         self,
@@ -18,9 +18,13 @@ use ic_cdk::{
 use serde::{Deserialize, Serialize};
 use shared_utils::{
     canister_specific::{
-        individual_user_template::types::arg::IndividualUserTemplateInitArgs, platform_orchestrator,
+        individual_user_template::types::arg::IndividualUserTemplateInitArgs,
+        platform_orchestrator, user_index::types::BroadcastCallStatus,
+    },
+    common::{
+        types::{known_principal::KnownPrincipalType, wasm::WasmType},
+        utils::{system_time::get_current_system_time, task::run_task_concurrently},
     },
-    common::{types::known_principal::KnownPrincipalType, utils::task::run_task_concurrently},
     constant::{
         EMPTY_CANISTER_RECHARGE_AMOUNT, INDIVIDUAL_USER_CANISTER_RECHARGE_AMOUNT,
         SUBNET_ORCHESTRATOR_CANISTER_CYCLES_THRESHOLD,
@@ -189,6 +193,26 @@ pub async fn reinstall_canister_wasm(
     }
 }
 
+/// Recharges the canister if needed and reinstalls the latest individual user wasm on it.
+/// All of its state, stable memory included, is dropped.
+pub async fn reset_individual_user_canister(canister_id: Principal) -> Result<(), String> {
+    let individual_user_template_canister_wasm = CANISTER_DATA
+        .with_borrow(|canister_data| canister_data.wasms.get(&WasmType::IndividualUserWasm))
+        .ok_or("Individual user canister wasm not found")?;
+
+    recharge_canister_if_below_threshold(&canister_id).await?;
+
+    reinstall_canister_wasm(
+        canister_id,
+        None,
+        individual_user_template_canister_wasm.version,
+        individual_user_template_canister_wasm.wasm_blob,
+    )
+    .await?;
+
+    Ok(())
+}
+
 pub async fn upgrade_individual_user_canister(
     canister_id: Principal,
     install_mode: CanisterInstallMode,
@@ -289,3 +313,74 @@ pub async fn recharge_canister(
     .await
     .map_err(|e| e.1)
 }
+
+/// Calls `method_name` on every individual canister of this subnet, handed out or available,
+/// and records the outcome in `last_broadcast_call_status`.
+/// The method has to return `Result<(), String>`.
+pub async fn broadcast_to_all_individual_canisters<T: ArgumentEncoder + Clone>(
+    method_name: &str,
+    args: T,
+) {
+    let all_canisters = CANISTER_DATA.with_borrow(|canister_data| {
+        let mut all_canisters: Vec<Principal> = canister_data
+            .user_principal_id_to_canister_id_map
+            .values()
+            .copied()
+            .collect();
+        let mut available_canisters: Vec<Principal> =
+            canister_data.available_canisters.iter().copied().collect();
+        all_canisters.append(&mut available_canisters);
+        all_canisters
+    });
+
+    let futures = all_canisters.iter().map(|individual_canister| {
+        let args = args.clone();
+        async move {
+            let res =
+                call::<_, (Result<(), String>,)>(*individual_canister, method_name, args).await;
+
+            match res {
+                Ok((Ok(()),)) => Ok(*individual_canister),
+                Ok((Err(e),)) => Err((*individual_canister, e)),
+                Err(e) => Err((*individual_canister, e.1)),
+            }
+        }
+    });
+
+    CANISTER_DATA.with_borrow_mut(|canister_data| {
+        canister_data.last_broadcast_call_status = BroadcastCallStatus {
+            method_name: method_name.into(),
+            timestamp: get_current_system_time(),
+            ..Default::default()
+        }
+    });
+
+    let result_callback = |res| {
+        CANISTER_DATA.with_borrow_mut(|canister_data| {
+            match res {
+                Ok(canister_id) => {
+                    canister_data
+                        .last_broadcast_call_status
+                        .successful_canister_ids
+                        .push(canister_id);
+
+                    canister_data
+                        .last_broadcast_call_status
+                        .successful_canisters_count += 1;
+                }
+                Err(e) => {
+                    canister_data
+                        .last_broadcast_call_status
+                        .failed_canister_ids
+                        .push(e);
+                    canister_data
+                        .last_broadcast_call_status
+                        .failed_canisters_count += 1;
+                }
+            }
+            canister_data.last_broadcast_call_status.total_canisters += 1;
+        })
+    };
+
+    run_task_concurrently(futures, 10, result_callback, || false).await;
+}
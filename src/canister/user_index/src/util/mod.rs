@@ -1,2 +1,3 @@
 pub mod canister_management;
 pub mod types;
+pub mod user_directory;
@@ -0,0 +1,33 @@
+use shared_utils::{
+    canister_specific::platform_orchestrator::types::user_directory::UserDirectoryUpdate,
+    common::types::known_principal::KnownPrincipalType,
+};
+
+use crate::CANISTER_DATA;
+
+pub const MAX_USER_DIRECTORY_UPDATES_PER_MESSAGE: usize = 1000;
+
+/// Best effort. Users missed here are picked up by `sync_user_directory_with_platform_orchestrator`.
+pub fn notify_platform_orchestrator_of_user_directory_updates(updates: Vec<UserDirectoryUpdate>) {
+    let platform_orchestrator_canister_id = CANISTER_DATA.with_borrow(|canister_data| {
+        canister_data
+            .configuration
+            .known_principal_ids
+            .get(&KnownPrincipalType::CanisterIdPlatformOrchestrator)
+            .copied()
+    });
+
+    let Some(platform_orchestrator_canister_id) = platform_orchestrator_canister_id else {
+        return;
+    };
+
+    updates
+        .chunks(MAX_USER_DIRECTORY_UPDATES_PER_MESSAGE)
+        .for_each(|updates| {
+            let _ = ic_cdk::notify(
+                platform_orchestrator_canister_id,
+                "update_user_directory",
+                (updates.to_vec(),),
+            );
+        });
+}
@@ -14,6 +14,9 @@ use shared_utils::{
             hot_or_not::{BetDirection, BettingStatus, PlacedBetDetail},
             post::{PostDetailsForFrontend, PostDetailsFromFrontend},
             profile::UserProfileDetailsForFrontend,
+            snapshot::{
+                SnapshotError, SnapshotManifest, SnapshotRestoreProgress, SnapshotSaveProgress,
+            },
         },
         post_cache::types::arg::PostCacheInitArgs,
     },
@@ -514,124 +517,28 @@ fn download_snapshot_test() {
     // Save snapshot
     let reclaim_principal_id = Principal::from_text(RECLAIM_CANISTER_PRINCIPAL_ID).unwrap();
 
-    let alice_snap_len = pic
-        .update_call(
-            alice_individual_template_canister_id,
-            reclaim_principal_id,
-            "save_snapshot_json",
-            encode_one(()).unwrap(),
-        )
-        .map(|reply_payload| {
-            let bet_status: u32 = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            bet_status
-        })
-        .unwrap();
-    println!("save_snapshot_json len: {:?}", alice_snap_len);
-
-    let bob_snap_len = pic
-        .update_call(
-            bob_individual_template_canister_id,
-            reclaim_principal_id,
-            "save_snapshot_json",
-            encode_one(()).unwrap(),
-        )
-        .map(|reply_payload| {
-            let bet_status: u32 = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            bet_status
-        })
-        .unwrap();
-    println!("save_snapshot_json len: {:?}", bob_snap_len);
-
-    let dan_snap_len = pic
-        .update_call(
-            dan_individual_template_canister_id,
-            reclaim_principal_id,
-            "save_snapshot_json",
-            encode_one(()).unwrap(),
-        )
-        .map(|reply_payload| {
-            let bet_status: u32 = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            bet_status
-        })
-        .unwrap();
-    println!("save_snapshot_json len: {:?}", dan_snap_len);
-
     // Download Snapshots
     //
-    let mut alice_snapshot = vec![];
-    let CHUNK_SIZE = 50;
-    let num_iters = (alice_snap_len as f64 / CHUNK_SIZE as f64).ceil() as u32;
-    for i in 0..num_iters {
-        let start = i * CHUNK_SIZE;
-        let mut end = (i + 1) * CHUNK_SIZE;
-        if end > alice_snap_len {
-            end = alice_snap_len;
-        }
-        println!("i {} start {} end-start {}", i, start, end - start);
-
-        let chunk = pic
-            .update_call(
-                alice_individual_template_canister_id,
-                reclaim_principal_id,
-                "download_snapshot",
-                candid::encode_args((start as u64, (end - start) as u64)).unwrap(),
-            )
-            .map(|reply_payload| {
-                let payload: Vec<u8> = match reply_payload {
-                    WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                    _ => panic!("\n🛑 place_bet failed\n"),
-                };
-                payload
-            })
-            .unwrap();
-        println!("iter {} chunk: {:?}", i, chunk.len());
-        alice_snapshot.extend(chunk);
-    }
-    println!("alice_snapshot: {:?}", alice_snapshot.len());
-
-    let bob_snapshot = pic
-        .update_call(
-            bob_individual_template_canister_id,
-            reclaim_principal_id,
-            "download_snapshot",
-            candid::encode_args((0 as u64, bob_snap_len as u64)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: Vec<u8> = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
+    let (alice_manifest, alice_snapshot) = save_and_download_snapshot(
+        &pic,
+        alice_individual_template_canister_id,
+        reclaim_principal_id,
+    );
+    println!("alice_snapshot: {:?}", alice_manifest.total_length);
 
-    println!("bob_snapshot: {:?}", bob_snapshot.len());
+    let (bob_manifest, bob_snapshot) = save_and_download_snapshot(
+        &pic,
+        bob_individual_template_canister_id,
+        reclaim_principal_id,
+    );
+    println!("bob_snapshot: {:?}", bob_manifest.total_length);
 
-    let dan_snapshot = pic
-        .update_call(
-            dan_individual_template_canister_id,
-            reclaim_principal_id,
-            "download_snapshot",
-            candid::encode_args((0 as u64, dan_snap_len as u64)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: Vec<u8> = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-    println!("dan_snapshot: {:?}", dan_snapshot.len());
+    let (dan_manifest, dan_snapshot) = save_and_download_snapshot(
+        &pic,
+        dan_individual_template_canister_id,
+        reclaim_principal_id,
+    );
+    println!("dan_snapshot: {:?}", dan_manifest.total_length);
 
     // Clear snapshot
 
@@ -652,20 +559,21 @@ fn download_snapshot_test() {
         .unwrap();
 
     let res = pic
-        .update_call(
+        .query_call(
             alice_individual_template_canister_id,
             reclaim_principal_id,
             "download_snapshot",
-            candid::encode_args((0 as u64, 10 as u64)).unwrap(),
+            candid::encode_one(0 as u64).unwrap(),
         )
         .map(|reply_payload| {
-            let payload: Vec<u8> = match reply_payload {
+            let payload: Result<Vec<u8>, SnapshotError> = match reply_payload {
                 WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
+                _ => panic!("\n🛑 download_snapshot failed\n"),
             };
             payload
-        });
-    assert_eq!(res.is_err(), true);
+        })
+        .unwrap();
+    assert_eq!(res, Err(SnapshotError::NoSnapshot));
 
     // Query Alice canister for info
 
@@ -960,103 +868,27 @@ fn download_snapshot_test() {
 
     // Restore state
 
-    let res = pic
-        .update_call(
-            alice2_individual_template_canister_id,
-            reclaim_principal_id,
-            "receive_and_save_snaphot",
-            candid::encode_args((0 as u64, alice_snapshot)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-
-    let res = pic
-        .update_call(
-            bob2_individual_template_canister_id,
-            reclaim_principal_id,
-            "receive_and_save_snaphot",
-            candid::encode_args((0 as u64, bob_snapshot)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-
-    let res = pic
-        .update_call(
-            dan2_individual_template_canister_id,
-            reclaim_principal_id,
-            "receive_and_save_snaphot",
-            candid::encode_args((0 as u64, dan_snapshot)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-
-    // Load snapshots
-
-    let res = pic
-        .update_call(
-            alice2_individual_template_canister_id,
-            reclaim_principal_id,
-            "load_snapshot",
-            candid::encode_args((alice_snap_len as u64,)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-
-    let res = pic
-        .update_call(
-            bob2_individual_template_canister_id,
-            reclaim_principal_id,
-            "load_snapshot",
-            candid::encode_args((bob_snap_len as u64,)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
-
-    let res = pic
-        .update_call(
-            dan2_individual_template_canister_id,
-            reclaim_principal_id,
-            "load_snapshot",
-            candid::encode_args((dan_snap_len as u64,)).unwrap(),
-        )
-        .map(|reply_payload| {
-            let payload: _ = match reply_payload {
-                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
-                _ => panic!("\n🛑 place_bet failed\n"),
-            };
-            payload
-        })
-        .unwrap();
+    upload_and_restore_snapshot(
+        &pic,
+        alice2_individual_template_canister_id,
+        reclaim_principal_id,
+        alice_manifest,
+        alice_snapshot,
+    );
+    upload_and_restore_snapshot(
+        &pic,
+        bob2_individual_template_canister_id,
+        reclaim_principal_id,
+        bob_manifest,
+        bob_snapshot,
+    );
+    upload_and_restore_snapshot(
+        &pic,
+        dan2_individual_template_canister_id,
+        reclaim_principal_id,
+        dan_manifest,
+        dan_snapshot,
+    );
 
     // Query Alice canister for info
 
@@ -1252,3 +1084,118 @@ fn individual_template_canister_wasm() -> Vec<u8> {
 fn post_cache_canister_wasm() -> Vec<u8> {
     std::fs::read(POST_CACHE_WASM_PATH).unwrap()
 }
+
+fn save_and_download_snapshot(
+    pic: &PocketIc,
+    canister_id: Principal,
+    reclaim_principal_id: Principal,
+) -> (SnapshotManifest, Vec<Vec<u8>>) {
+    // * one chunk per call, the way a large canister would be saved
+    let manifest = loop {
+        let progress = pic
+            .update_call(
+                canister_id,
+                reclaim_principal_id,
+                "save_snapshot",
+                encode_one(1 as u64).unwrap(),
+            )
+            .map(|reply_payload| {
+                let progress: SnapshotSaveProgress = match reply_payload {
+                    WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
+                    _ => panic!("\n🛑 save_snapshot failed\n"),
+                };
+                progress
+            })
+            .unwrap();
+        if let Some(manifest) = progress.manifest {
+            break manifest;
+        }
+    };
+
+    let chunks = (0..manifest.chunks.len() as u64)
+        .map(|chunk_index| {
+            pic.query_call(
+                canister_id,
+                reclaim_principal_id,
+                "download_snapshot",
+                encode_one(chunk_index).unwrap(),
+            )
+            .map(|reply_payload| {
+                let chunk: Result<Vec<u8>, SnapshotError> = match reply_payload {
+                    WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
+                    _ => panic!("\n🛑 download_snapshot failed\n"),
+                };
+                chunk.unwrap()
+            })
+            .unwrap()
+        })
+        .collect();
+
+    (manifest, chunks)
+}
+
+fn upload_and_restore_snapshot(
+    pic: &PocketIc,
+    canister_id: Principal,
+    reclaim_principal_id: Principal,
+    manifest: SnapshotManifest,
+    chunks: Vec<Vec<u8>>,
+) {
+    let res = pic
+        .update_call(
+            canister_id,
+            reclaim_principal_id,
+            "receive_snapshot_manifest",
+            encode_one(manifest).unwrap(),
+        )
+        .map(|reply_payload| {
+            let res: Result<(), SnapshotError> = match reply_payload {
+                WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
+                _ => panic!("\n🛑 receive_snapshot_manifest failed\n"),
+            };
+            res
+        })
+        .unwrap();
+    assert_eq!(res, Ok(()));
+
+    for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+        let res = pic
+            .update_call(
+                canister_id,
+                reclaim_principal_id,
+                "receive_and_save_snaphot",
+                candid::encode_args((chunk_index as u64, chunk)).unwrap(),
+            )
+            .map(|reply_payload| {
+                let res: Result<(), SnapshotError> = match reply_payload {
+                    WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
+                    _ => panic!("\n🛑 receive_and_save_snaphot failed\n"),
+                };
+                res
+            })
+            .unwrap();
+        assert_eq!(res, Ok(()));
+    }
+
+    // * one chunk per call, the way a large canister would be restored
+    loop {
+        let progress = pic
+            .update_call(
+                canister_id,
+                reclaim_principal_id,
+                "load_snapshot",
+                encode_one(1 as u64).unwrap(),
+            )
+            .map(|reply_payload| {
+                let progress: Result<SnapshotRestoreProgress, SnapshotError> = match reply_payload {
+                    WasmResult::Reply(payload) => candid::decode_one(&payload).unwrap(),
+                    _ => panic!("\n🛑 load_snapshot failed\n"),
+                };
+                progress.unwrap()
+            })
+            .unwrap();
+        if progress.is_complete() {
+            break;
+        }
+    }
+}
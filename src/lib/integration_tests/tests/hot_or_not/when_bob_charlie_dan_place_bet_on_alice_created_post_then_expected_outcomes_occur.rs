@@ -436,7 +436,8 @@ fn when_bob_charlie_dan_place_bet_on_alice_created_post_then_expected_outcomes_o
                 slot_id: 1,
                 room_id: 1,
                 event_outcome: BetOutcomeForBetMaker::Won(90),
-                winnings_amount: 90
+                winnings_amount: 90,
+                amount_bet: 50
             },
             timestamp: if let TokenEvent::HotOrNotOutcomePayout { timestamp, .. } =
                 bob_token_transaction_history.get(0).unwrap().1.clone()
@@ -525,7 +526,8 @@ fn when_bob_charlie_dan_place_bet_on_alice_created_post_then_expected_outcomes_o
                 slot_id: 1,
                 room_id: 1,
                 event_outcome: BetOutcomeForBetMaker::Lost,
-                winnings_amount: 0
+                winnings_amount: 0,
+                amount_bet: 100
             },
             timestamp: if let TokenEvent::HotOrNotOutcomePayout { timestamp, .. } =
                 charlie_token_transaction_history.get(0).unwrap().1.clone()
@@ -614,7 +616,8 @@ fn when_bob_charlie_dan_place_bet_on_alice_created_post_then_expected_outcomes_o
                 slot_id: 1,
                 room_id: 1,
                 event_outcome: BetOutcomeForBetMaker::Won(18),
-                winnings_amount: 18
+                winnings_amount: 18,
+                amount_bet: 10
             },
             timestamp: if let TokenEvent::HotOrNotOutcomePayout { timestamp, .. } =
                 dan_token_transaction_history.get(0).unwrap().1.clone()
@@ -2,7 +2,12 @@ use candid::{CandidType, Deserialize, Principal};
 
 use crate::common::types::known_principal::KnownPrincipalMap;
 
-use super::hot_or_not::BetDirection;
+use super::{
+    comment::CommentId,
+    hot_or_not::BetDirection,
+    prediction::{PredictionOutcomeId, PredictionResolutionMode},
+    report::PostReportReason,
+};
 
 #[derive(Deserialize, CandidType)]
 pub struct IndividualUserTemplateInitArgs {
@@ -21,8 +26,59 @@ pub struct PlaceBetArg {
     pub bet_direction: BetDirection,
 }
 
+#[derive(Deserialize, CandidType, Clone)]
+pub struct CreatePredictionArg {
+    pub post_id: u64,
+    pub outcomes: Vec<String>,
+    pub resolution_mode: PredictionResolutionMode,
+    pub duration_in_seconds: u64,
+}
+
+#[derive(Deserialize, CandidType, Clone)]
+pub struct PlacePredictionBetArg {
+    pub post_canister_id: Principal,
+    pub post_id: u64,
+    pub outcome_id: PredictionOutcomeId,
+    pub bet_amount: u64,
+}
+
 #[derive(CandidType, Deserialize, Clone)]
 pub struct FolloweeArg {
     pub followee_principal_id: Principal,
     pub followee_canister_id: Principal,
 }
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct TransferUtilityTokenArg {
+    pub to_user_principal_id: Principal,
+    pub amount: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct AddCommentToPostArg {
+    pub post_id: u64,
+    pub parent_comment_id: Option<CommentId>,
+    pub text: String,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct UpdatePostDetailsArg {
+    pub post_id: u64,
+    pub description: String,
+    pub hashtags: Vec<String>,
+    pub is_nsfw: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct TipPostArg {
+    pub post_canister_id: Principal,
+    pub post_id: u64,
+    pub amount: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct ReportPostArg {
+    pub post_canister_id: Principal,
+    pub post_id: u64,
+    pub reason: PostReportReason,
+}
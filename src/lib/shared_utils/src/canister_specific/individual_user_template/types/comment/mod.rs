@@ -0,0 +1,220 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    time::{Duration, SystemTime},
+};
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+
+use crate::common::types::app_primitive_type::PostId;
+
+pub type CommentId = u64;
+
+pub const MAX_COMMENT_LENGTH_IN_CHARACTERS: usize = 1000;
+/// Hidden and deleted comments count as well, they keep their ids
+pub const MAX_COMMENTS_PER_POST: u64 = 5000;
+pub const MAX_COMMENTS_PER_AUTHOR_PER_WINDOW: u64 = 10;
+pub const COMMENT_RATE_LIMIT_WINDOW_IN_SECONDS: u64 = 60;
+
+#[derive(
+    CandidType,
+    Clone,
+    Deserialize,
+    Debug,
+    Serialize,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Default,
+    Copy,
+    Hash,
+)]
+pub struct GlobalCommentId(pub PostId, pub CommentId);
+
+impl Storable for GlobalCommentId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 50,
+        is_fixed_size: false,
+    };
+}
+
+#[derive(CandidType, Clone, Copy, Deserialize, Debug, Serialize, PartialEq, Eq, Default)]
+pub enum CommentStatus {
+    #[default]
+    Visible,
+    /// Only the post creator still sees the comment and can make it visible again
+    HiddenByPostCreator,
+    /// The text is dropped and the comment can not be restored
+    Deleted,
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct Comment {
+    pub id: CommentId,
+    pub parent_comment_id: Option<CommentId>,
+    pub author_principal_id: Principal,
+    pub text: String,
+    pub created_at: SystemTime,
+    pub likes: BTreeSet<Principal>,
+    /// Number of visible direct replies
+    pub reply_count: u64,
+    pub status: CommentStatus,
+}
+
+impl Storable for Comment {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Comments an author added to the posts of a profile since their current window started
+#[derive(Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct CommentRateLimitWindow {
+    pub started_at: SystemTime,
+    pub comment_count: u64,
+}
+
+impl CommentRateLimitWindow {
+    pub fn new(started_at: SystemTime) -> Self {
+        Self {
+            started_at,
+            comment_count: 0,
+        }
+    }
+
+    pub fn has_ended(&self, current_time: &SystemTime) -> bool {
+        current_time
+            .duration_since(self.started_at)
+            .unwrap_or_default()
+            >= Duration::from_secs(COMMENT_RATE_LIMIT_WINDOW_IN_SECONDS)
+    }
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct CommentDetailsForFrontend {
+    pub post_id: PostId,
+    pub id: CommentId,
+    pub parent_comment_id: Option<CommentId>,
+    pub author_principal_id: Principal,
+    pub text: String,
+    pub created_at: SystemTime,
+    pub like_count: u64,
+    pub liked_by_me: bool,
+    pub reply_count: u64,
+    pub status: CommentStatus,
+}
+
+impl Comment {
+    pub fn new(
+        id: CommentId,
+        parent_comment_id: Option<CommentId>,
+        author_principal_id: Principal,
+        text: String,
+        created_at: SystemTime,
+    ) -> Self {
+        Self {
+            id,
+            parent_comment_id,
+            author_principal_id,
+            text,
+            created_at,
+            likes: BTreeSet::new(),
+            reply_count: 0,
+            status: CommentStatus::Visible,
+        }
+    }
+
+    /// Returns true if the caller likes the comment after the toggle
+    pub fn toggle_like_status(&mut self, caller: &Principal) -> bool {
+        if self.likes.remove(caller) {
+            false
+        } else {
+            self.likes.insert(*caller);
+            true
+        }
+    }
+
+    pub fn update_status(&mut self, status: CommentStatus) {
+        if status == CommentStatus::Deleted {
+            self.text.clear();
+            self.likes.clear();
+        }
+        self.status = status;
+    }
+
+    pub fn get_comment_details_for_frontend(
+        &self,
+        post_id: PostId,
+        caller: &Principal,
+    ) -> CommentDetailsForFrontend {
+        CommentDetailsForFrontend {
+            post_id,
+            id: self.id,
+            parent_comment_id: self.parent_comment_id,
+            author_principal_id: self.author_principal_id,
+            text: self.text.clone(),
+            created_at: self.created_at,
+            like_count: self.likes.len() as u64,
+            liked_by_me: self.likes.contains(caller),
+            reply_count: self.reply_count,
+            status: self.status,
+        }
+    }
+}
+
+pub fn is_comment_text_valid(text: &str) -> bool {
+    !text.trim().is_empty() && text.chars().count() <= MAX_COMMENT_LENGTH_IN_CHARACTERS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_toggle_like_status_and_delete() {
+        let author = Principal::self_authenticating((0u64).to_ne_bytes());
+        let liker = Principal::self_authenticating((1u64).to_ne_bytes());
+        let mut comment = Comment::new(0, None, author, "Nice".into(), SystemTime::now());
+
+        assert!(comment.toggle_like_status(&liker));
+        assert!(
+            comment
+                .get_comment_details_for_frontend(0, &liker)
+                .liked_by_me
+        );
+        assert!(!comment.toggle_like_status(&liker));
+        assert_eq!(comment.likes.len(), 0);
+
+        comment.toggle_like_status(&liker);
+        comment.update_status(CommentStatus::Deleted);
+        assert!(comment.text.is_empty());
+        assert!(comment.likes.is_empty());
+        assert_eq!(comment.status, CommentStatus::Deleted);
+    }
+
+    #[test]
+    fn test_is_comment_text_valid() {
+        assert!(is_comment_text_valid("Doggos and puppers"));
+        assert!(!is_comment_text_valid("   "));
+        assert!(!is_comment_text_valid(
+            &"a".repeat(MAX_COMMENT_LENGTH_IN_CHARACTERS + 1)
+        ));
+    }
+}
@@ -3,4 +3,12 @@ use serde::{Deserialize, Serialize};
 #[derive(Default, Deserialize, Serialize, Clone)]
 pub struct IndividualUserConfiguration {
     pub url_to_send_canister_metrics_to: Option<String>,
+    /// Distinct registered reporters needed before a post is sent to admins for review,
+    /// falls back to `DEFAULT_POST_REPORT_THRESHOLD`
+    #[serde(default)]
+    pub post_report_threshold: Option<u64>,
+    /// How long failed payout notifications are retried for,
+    /// falls back to `DEFAULT_PAYOUT_NOTIFICATION_RETRY_HORIZON_IN_SECONDS`
+    #[serde(default)]
+    pub payout_notification_retry_horizon_in_seconds: Option<u64>,
 }
@@ -31,6 +31,159 @@ pub enum BetOnCurrentlyViewingPostError {
     UserNotLoggedIn,
     UserPrincipalNotSet,
     PostCreatorCanisterCallFailed,
+    BlockedByPostCreator,
+    PostNotFound,
+}
+
+#[derive(CandidType, PartialEq, Eq, Debug, Deserialize)]
+pub enum WithdrawHotOrNotBetError {
+    UserNotLoggedIn,
+    Unauthorized,
+    UserPrincipalNotSet,
+    BetNotFound,
+    SlotClosed,
+    CancellationWindowClosed,
+    CashOutNotAvailable,
+    PostCreatorCanisterCallFailed,
+    WithdrawalInProgress,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum CreatePredictionError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    PostNotFound,
+    PostNotAvailableForPredictions,
+    PredictionAlreadyExists,
+    InvalidNumberOfOutcomes,
+    InvalidOutcomeLabel,
+    InvalidDuration,
+}
+
+#[derive(CandidType, PartialEq, Eq, Debug, Deserialize)]
+pub enum BetOnPredictionError {
+    UserNotLoggedIn,
+    Unauthorized,
+    UserPrincipalNotSet,
+    InsufficientBalance,
+    PredictionNotFound,
+    BettingClosed,
+    InvalidOutcome,
+    UserAlreadyParticipatedInThisPrediction,
+    BlockedByPostCreator,
+    PostCreatorCanisterCallFailed,
+    CreatorCannotBetOnOwnPrediction,
+    PredictionFull,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum ResolvePredictionError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    PredictionNotFound,
+    NotDecidedByCreator,
+    PredictionStillOpen,
+    PredictionAlreadySettled,
+    InvalidOutcome,
+    ResolutionDeadlinePassed,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum TransferUtilityTokenError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    InvalidAmount,
+    InsufficientBalance,
+    CannotTransferToSelf,
+    RecipientNotFound,
+    SenderCanisterDoesNotMatch,
+    UserIndexCrossCanisterCallFailed,
+    RecipientCanisterCallFailed,
+    CannotTransferToAnonymous,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum TipPostError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    InvalidAmount,
+    InsufficientBalance,
+    CannotTipOwnPost,
+    PostNotFound,
+    PostNotAvailableForTips,
+    TipperCanisterDoesNotMatch,
+    UserIndexCrossCanisterCallFailed,
+    PostCreatorCanisterCallFailed,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum DeletePostError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    PostNotFound,
+    PostAlreadyDeleted,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum UpdatePostDetailsError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    PostNotFound,
+    PostNotEditable,
+    NothingToUpdate,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum CommentOnPostError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    PostNotFound,
+    PostNotAvailableForComments,
+    ParentCommentNotFound,
+    CommentNotFound,
+    CommentAlreadyDeleted,
+    InvalidCommentText,
+    BlockedByPostCreator,
+    CommentLimitReachedForPost,
+    CommentRateLimitExceeded,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum GetCommentsOfPostError {
+    PostNotFound,
+    InvalidBoundsPassed,
+    ReachedEndOfItemsList,
+    ExceededMaxNumberOfItemsAllowedInOneRequest,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum ReportPostError {
+    Unauthenticated,
+    UserPrincipalNotSet,
+    PostNotFound,
+    PostNotAvailableForReporting,
+    CannotReportOwnPost,
+    AlreadyReported,
+    InvalidReason,
+    Unauthorized,
+    ReporterCanisterDoesNotMatch,
+    UserIndexCrossCanisterCallFailed,
+    PostCreatorCanisterCallFailed,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum GetTipsForPostError {
+    PostNotFound,
+    InvalidBoundsPassed,
+    ReachedEndOfItemsList,
+    ExceededMaxNumberOfItemsAllowedInOneRequest,
 }
 
 #[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
@@ -40,6 +193,16 @@ pub enum FollowAnotherUserProfileError {
     UsersICanFollowListIsFull,
     UserITriedToFollowCrossCanisterCallFailed,
     UserITriedToFollowHasTheirFollowersListFull,
+    UserITriedToFollowHasBlockedMe,
+}
+
+#[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
+pub enum BlockOrMuteProfileError {
+    Unauthenticated,
+    Unauthorized,
+    UserPrincipalNotSet,
+    CannotBlockOrMuteSelf,
+    ListIsFull,
 }
 
 #[derive(CandidType, Deserialize, PartialEq, Eq, Debug)]
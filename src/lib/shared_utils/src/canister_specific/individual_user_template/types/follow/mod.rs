@@ -52,6 +52,20 @@ impl FollowList {
     pub fn is_empty(&self) -> bool {
         self.members.is_empty()
     }
+
+    /// Removes every follow entry of the principal, whichever canister it was recorded with.
+    pub fn remove_all_with_principal_id(&mut self, principal_id: &Principal) {
+        let follow_entry_details: Vec<FollowEntryDetail> = self
+            .members
+            .keys()
+            .filter(|follow_entry_detail| follow_entry_detail.principal_id == *principal_id)
+            .cloned()
+            .collect();
+
+        for follow_entry_detail in follow_entry_details {
+            self.remove(&follow_entry_detail);
+        }
+    }
 }
 
 pub type FollowEntryId = u64;
@@ -158,6 +172,32 @@ mod test {
             assert!(!follow_list.contains(&follow_entry_detail));
         }
 
+        #[test]
+        fn test_remove_all_with_principal_id() {
+            let mut follow_list = FollowList::default();
+
+            let principal_id = Principal::self_authenticating((0u64).to_ne_bytes());
+            follow_list.add(FollowEntryDetail {
+                principal_id,
+                canister_id: Principal::self_authenticating((0u64).to_ne_bytes()),
+            });
+            follow_list.add(FollowEntryDetail {
+                principal_id,
+                canister_id: Principal::self_authenticating((1u64).to_ne_bytes()),
+            });
+            let other_follow_entry_detail = FollowEntryDetail {
+                principal_id: Principal::self_authenticating((2u64).to_ne_bytes()),
+                canister_id: Principal::self_authenticating((2u64).to_ne_bytes()),
+            };
+            follow_list.add(other_follow_entry_detail.clone());
+
+            follow_list.remove_all_with_principal_id(&principal_id);
+
+            assert_eq!(follow_list.len(), 1);
+            assert_eq!(follow_list.sorted_index.len(), 1);
+            assert!(follow_list.contains(&other_follow_entry_detail));
+        }
+
         #[test]
         fn test_len() {
             let mut follow_list = FollowList::default();
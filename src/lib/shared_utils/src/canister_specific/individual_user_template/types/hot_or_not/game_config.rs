@@ -0,0 +1,399 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+use crate::common::types::utility_token::token_event::{
+    HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE, HOT_OR_NOT_BET_WINNINGS_MULTIPLIER,
+};
+
+use super::{
+    BetDirection, BetPayout, RoomBetPossibleOutcomes, DURATION_OF_EACH_SLOT_IN_SECONDS,
+    MAXIMUM_NUMBER_OF_SLOTS,
+};
+
+pub const MAXIMUM_NUMBER_OF_BETS_PER_ROOM: u64 = 100;
+/// Below this the pot on each side says too little about the outcome to price a cash out
+pub const MINIMUM_NUMBER_OF_BETS_IN_ROOM_FOR_CASH_OUT: u64 = 3;
+
+/// How the pot of a room is split once its outcome is known
+#[derive(CandidType, Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Default)]
+pub enum HotOrNotPayoutMode {
+    /// Winners get their stake times `winnings_multiplier`, minus commission,
+    /// however lopsided the room was
+    #[default]
+    FixedMultiplier,
+    /// Winners get their stake back plus a share of the losing side's pot,
+    /// minus commission, in proportion to their stake
+    Parimutuel,
+}
+
+/// Economics of the hot or not game.
+/// A copy is taken onto every post when it is created so that a running game
+/// keeps the rules it started with even if the canister wide config changes.
+#[derive(CandidType, Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct HotOrNotGameConfig {
+    pub version: u64,
+    pub maximum_number_of_slots: u8,
+    pub duration_of_each_slot_in_seconds: u64,
+    pub maximum_number_of_bets_per_room: u64,
+    pub creator_commission_percentage: u64,
+    pub winnings_multiplier: u64,
+    /// How long after placing it a bet can be cancelled for a full refund, 0 disables cancelling
+    #[serde(default)]
+    pub bet_cancellation_window_in_seconds: u64,
+    /// Cut taken from the value of a bet that is cashed out before its slot closes,
+    /// `None` disables cashing out
+    #[serde(default)]
+    pub cash_out_discount_percentage: Option<u64>,
+    #[serde(default)]
+    pub payout_mode: HotOrNotPayoutMode,
+}
+
+impl Default for HotOrNotGameConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            maximum_number_of_slots: MAXIMUM_NUMBER_OF_SLOTS,
+            duration_of_each_slot_in_seconds: DURATION_OF_EACH_SLOT_IN_SECONDS,
+            maximum_number_of_bets_per_room: MAXIMUM_NUMBER_OF_BETS_PER_ROOM,
+            creator_commission_percentage: HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE,
+            winnings_multiplier: HOT_OR_NOT_BET_WINNINGS_MULTIPLIER,
+            bet_cancellation_window_in_seconds: 0,
+            cash_out_discount_percentage: None,
+            payout_mode: HotOrNotPayoutMode::FixedMultiplier,
+        }
+    }
+}
+
+impl HotOrNotGameConfig {
+    pub fn total_duration_of_all_slots_in_seconds(&self) -> u64 {
+        self.maximum_number_of_slots as u64 * self.duration_of_each_slot_in_seconds
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        // * slot ids are u8 and tabulation looks at slot_id + 1
+        if self.maximum_number_of_slots == 0 || self.maximum_number_of_slots == u8::MAX {
+            return Err("maximum_number_of_slots must be between 1 and 254".into());
+        }
+
+        if self.duration_of_each_slot_in_seconds == 0 {
+            return Err("duration_of_each_slot_in_seconds must be greater than 0".into());
+        }
+
+        // * number of participants is reported as u8
+        if self.maximum_number_of_bets_per_room == 0
+            || self.maximum_number_of_bets_per_room > u8::MAX as u64
+        {
+            return Err("maximum_number_of_bets_per_room must be between 1 and 255".into());
+        }
+
+        if self.creator_commission_percentage >= 100 {
+            return Err("creator_commission_percentage must be less than 100".into());
+        }
+
+        if self.winnings_multiplier == 0 {
+            return Err("winnings_multiplier must be at least 1".into());
+        }
+
+        if self
+            .cash_out_discount_percentage
+            .is_some_and(|cash_out_discount_percentage| cash_out_discount_percentage >= 100)
+        {
+            return Err("cash_out_discount_percentage must be less than 100".into());
+        }
+
+        Ok(())
+    }
+
+    pub fn commission_for_room_pot(&self, room_pot_total_amount: u64) -> u64 {
+        room_pot_total_amount * self.creator_commission_percentage / 100
+    }
+
+    pub fn payout_for_winning_bet(&self, bet_amount: u64) -> u64 {
+        bet_amount * self.winnings_multiplier * (100 - self.creator_commission_percentage) / 100
+    }
+
+    pub fn payout_for_draw(&self, bet_amount: u64) -> u64 {
+        bet_amount * (100 - self.creator_commission_percentage) / 100
+    }
+
+    /// Payouts for the bets of a room, given as stake and direction, in the same order.
+    /// Nothing is calculated while the room is still ongoing.
+    pub fn get_payouts_for_room(
+        &self,
+        bet_outcome: &RoomBetPossibleOutcomes,
+        bets: &[(u64, BetDirection)],
+    ) -> Vec<BetPayout> {
+        let winning_direction = match bet_outcome {
+            RoomBetPossibleOutcomes::BetOngoing => {
+                return bets.iter().map(|_| BetPayout::NotCalculatedYet).collect();
+            }
+            RoomBetPossibleOutcomes::Draw => {
+                return bets
+                    .iter()
+                    .map(|(amount, _)| BetPayout::Calculated(self.payout_for_draw(*amount)))
+                    .collect();
+            }
+            RoomBetPossibleOutcomes::HotWon => BetDirection::Hot,
+            RoomBetPossibleOutcomes::NotWon => BetDirection::Not,
+        };
+
+        let (winning_pot, losing_pot) = bets.iter().fold(
+            (0u64, 0u64),
+            |(winning_pot, losing_pot), (amount, direction)| {
+                if *direction == winning_direction {
+                    (winning_pot + amount, losing_pot)
+                } else {
+                    (winning_pot, losing_pot + amount)
+                }
+            },
+        );
+        let losing_pot_to_distribute = losing_pot - self.commission_for_room_pot(losing_pot);
+
+        bets.iter()
+            .map(|(amount, direction)| {
+                if *direction != winning_direction {
+                    return BetPayout::Calculated(0);
+                }
+
+                BetPayout::Calculated(match self.payout_mode {
+                    HotOrNotPayoutMode::FixedMultiplier => self.payout_for_winning_bet(*amount),
+                    // * rounded down, the remainder goes to the creator along with the commission
+                    HotOrNotPayoutMode::Parimutuel => {
+                        amount
+                            + (losing_pot_to_distribute as u128 * *amount as u128)
+                                .checked_div(winning_pot as u128)
+                                .unwrap_or_default() as u64
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// What the creator earns from a settled room with the given payouts
+    pub fn get_creator_commission_for_room(
+        &self,
+        room_pot_total_amount: u64,
+        payouts: &[BetPayout],
+    ) -> u64 {
+        match self.payout_mode {
+            HotOrNotPayoutMode::FixedMultiplier => {
+                self.commission_for_room_pot(room_pot_total_amount)
+            }
+            HotOrNotPayoutMode::Parimutuel => {
+                let total_paid_out: u64 = payouts
+                    .iter()
+                    .map(|payout| match payout {
+                        BetPayout::Calculated(amount) => *amount,
+                        BetPayout::NotCalculatedYet => 0,
+                    })
+                    .sum();
+
+                room_pot_total_amount.saturating_sub(total_paid_out)
+            }
+        }
+    }
+
+    /// What the bet would win if its side won, weighted by the share of the room's pot
+    /// staked on that side, capped at the stake and minus the cash out discount.
+    /// `None` when cashing out is disabled or the room is too small to price the bet.
+    pub fn cash_out_value(
+        &self,
+        bet_amount: u64,
+        amount_bet_on_same_side: u64,
+        room_bets_total_pot: u64,
+        total_number_of_bets: u64,
+    ) -> Option<u64> {
+        let cash_out_discount_percentage = self.cash_out_discount_percentage?;
+
+        if total_number_of_bets < MINIMUM_NUMBER_OF_BETS_IN_ROOM_FOR_CASH_OUT
+            || amount_bet_on_same_side < bet_amount
+            || room_bets_total_pot < amount_bet_on_same_side
+            || amount_bet_on_same_side == 0
+        {
+            return None;
+        }
+
+        let payout_if_same_side_wins = match self.payout_mode {
+            HotOrNotPayoutMode::FixedMultiplier => self.payout_for_winning_bet(bet_amount),
+            HotOrNotPayoutMode::Parimutuel => {
+                let losing_pot = room_bets_total_pot - amount_bet_on_same_side;
+                let losing_pot_to_distribute =
+                    losing_pot - self.commission_for_room_pot(losing_pot);
+
+                bet_amount
+                    + (losing_pot_to_distribute as u128 * bet_amount as u128
+                        / amount_bet_on_same_side as u128) as u64
+            }
+        };
+        let weighted_payout = (payout_if_same_side_wins as u128 * amount_bet_on_same_side as u128
+            / room_bets_total_pot as u128) as u64;
+
+        // * never worth more than the stake, cashing out must not mint tokens
+        Some(weighted_payout.min(bet_amount) * (100 - cash_out_discount_percentage) / 100)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_config_matches_legacy_constants() {
+        let config = HotOrNotGameConfig::default();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.total_duration_of_all_slots_in_seconds(),
+            48 * 60 * 60
+        );
+        assert_eq!(config.commission_for_room_pot(1000), 100);
+        assert_eq!(config.payout_for_winning_bet(100), 180);
+        assert_eq!(config.payout_for_draw(100), 90);
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = HotOrNotGameConfig {
+            version: 1,
+            maximum_number_of_slots: 24,
+            duration_of_each_slot_in_seconds: 30 * 60,
+            maximum_number_of_bets_per_room: 50,
+            creator_commission_percentage: 5,
+            winnings_multiplier: 3,
+            bet_cancellation_window_in_seconds: 60,
+            cash_out_discount_percentage: Some(20),
+            payout_mode: HotOrNotPayoutMode::Parimutuel,
+        };
+        assert!(valid.validate().is_ok());
+
+        assert!(HotOrNotGameConfig {
+            maximum_number_of_slots: 0,
+            ..valid.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(HotOrNotGameConfig {
+            duration_of_each_slot_in_seconds: 0,
+            ..valid.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(HotOrNotGameConfig {
+            maximum_number_of_bets_per_room: 256,
+            ..valid.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(HotOrNotGameConfig {
+            creator_commission_percentage: 100,
+            ..valid.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(HotOrNotGameConfig {
+            winnings_multiplier: 0,
+            ..valid.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(HotOrNotGameConfig {
+            cash_out_discount_percentage: Some(100),
+            ..valid
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_cash_out_value() {
+        let config = HotOrNotGameConfig::default();
+        assert_eq!(config.cash_out_value(100, 200, 400, 4), None);
+
+        let config = HotOrNotGameConfig {
+            cash_out_discount_percentage: Some(20),
+            ..Default::default()
+        };
+        // * 180 winning payout, a third of the pot on the same side, 20% off
+        assert_eq!(config.cash_out_value(100, 100, 300, 3), Some(48));
+        // * capped at the stake however lopsided the room is
+        assert_eq!(config.cash_out_value(100, 300, 400, 4), Some(80));
+        assert_eq!(config.cash_out_value(100, 400, 400, 4), Some(80));
+        // * too few bets to price
+        assert_eq!(config.cash_out_value(100, 100, 200, 2), None);
+        assert_eq!(config.cash_out_value(100, 100, 100, 1), None);
+        assert_eq!(config.cash_out_value(100, 0, 0, 0), None);
+
+        // * 100 staked on hot and 200 on not, hot winning would pay 100 + 180
+        // * weighted by a third of the pot is 93, 20% off
+        let parimutuel_config = HotOrNotGameConfig {
+            payout_mode: HotOrNotPayoutMode::Parimutuel,
+            ..config
+        };
+        assert_eq!(parimutuel_config.cash_out_value(100, 100, 300, 3), Some(74));
+        assert_eq!(parimutuel_config.cash_out_value(100, 400, 400, 4), Some(80));
+    }
+
+    #[test]
+    fn test_get_payouts_for_room() {
+        let bets = [
+            (100, BetDirection::Hot),
+            (300, BetDirection::Hot),
+            (200, BetDirection::Not),
+        ];
+
+        let fixed_multiplier_config = HotOrNotGameConfig::default();
+        let payouts =
+            fixed_multiplier_config.get_payouts_for_room(&RoomBetPossibleOutcomes::HotWon, &bets);
+        assert_eq!(
+            payouts,
+            vec![
+                BetPayout::Calculated(180),
+                BetPayout::Calculated(540),
+                BetPayout::Calculated(0)
+            ]
+        );
+        assert_eq!(
+            fixed_multiplier_config.get_creator_commission_for_room(600, &payouts),
+            60
+        );
+
+        // * 180 of the 200 losing pot is split 1:3 between the winners
+        let parimutuel_config = HotOrNotGameConfig {
+            payout_mode: HotOrNotPayoutMode::Parimutuel,
+            ..Default::default()
+        };
+        let payouts =
+            parimutuel_config.get_payouts_for_room(&RoomBetPossibleOutcomes::HotWon, &bets);
+        assert_eq!(
+            payouts,
+            vec![
+                BetPayout::Calculated(145),
+                BetPayout::Calculated(435),
+                BetPayout::Calculated(0)
+            ]
+        );
+        assert_eq!(
+            parimutuel_config.get_creator_commission_for_room(600, &payouts),
+            20
+        );
+
+        let payouts = parimutuel_config.get_payouts_for_room(&RoomBetPossibleOutcomes::Draw, &bets);
+        assert_eq!(
+            payouts,
+            vec![
+                BetPayout::Calculated(90),
+                BetPayout::Calculated(270),
+                BetPayout::Calculated(180)
+            ]
+        );
+        assert_eq!(
+            parimutuel_config.get_creator_commission_for_room(600, &payouts),
+            60
+        );
+
+        assert_eq!(
+            parimutuel_config.get_payouts_for_room(&RoomBetPossibleOutcomes::BetOngoing, &bets),
+            vec![BetPayout::NotCalculatedYet; 3]
+        );
+    }
+}
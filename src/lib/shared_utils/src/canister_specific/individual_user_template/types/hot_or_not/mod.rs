@@ -1,4 +1,9 @@
-use std::{borrow::Cow, cmp::Ordering, collections::BTreeMap, time::SystemTime};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
 
 use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_cdk::api::management_canister::provisional::CanisterId;
@@ -9,14 +14,14 @@ use serde::Serialize;
 
 use crate::common::types::{
     app_primitive_type::PostId,
-    utility_token::token_event::{
-        HotOrNotOutcomePayoutEvent, TokenEvent, HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE,
-        HOT_OR_NOT_BET_WINNINGS_MULTIPLIER,
-    },
+    top_posts::post_score_index_item::PostStatus,
+    utility_token::token_event::{HotOrNotOutcomePayoutEvent, TokenEvent},
 };
 
+pub mod game_config;
+
 use super::{
-    error::BetOnCurrentlyViewingPostError,
+    error::{BetOnCurrentlyViewingPostError, WithdrawHotOrNotBetError},
     post::{FeedScore, Post},
     token::TokenBalance,
 };
@@ -157,6 +162,10 @@ impl Storable for RoomDetailsV1 {
 
 pub type BetMaker = Principal;
 
+/// Chosen by the bet maker's canister so that the post creator's canister
+/// can tell a retried bet apart from a new one
+pub type BetIdempotencyKey = u64;
+
 #[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
 pub enum BetMakerInformedStatus {
     InformedSuccessfully,
@@ -171,9 +180,37 @@ pub struct BetDetails {
     pub bet_maker_canister_id: CanisterId,
     #[serde(default)]
     pub bet_maker_informed_status: Option<BetMakerInformedStatus>,
+    #[serde(default)]
+    pub idempotency_key: Option<BetIdempotencyKey>,
+    /// Not known for bets placed before bets could be cancelled
+    #[serde(default)]
+    pub placed_at: Option<SystemTime>,
 }
 const MAX_BET_DETAILS_VALUE_SIZE: u32 = 200 as u32;
 
+impl BetDetails {
+    pub fn get_outcome_for_bet_maker(
+        &self,
+        room_bet_outcome: &RoomBetPossibleOutcomes,
+    ) -> BetOutcomeForBetMaker {
+        let payout_amount = match self.payout {
+            BetPayout::Calculated(amount) => amount,
+            BetPayout::NotCalculatedYet => 0,
+        };
+
+        match (room_bet_outcome, &self.bet_direction) {
+            (RoomBetPossibleOutcomes::BetOngoing, _) => BetOutcomeForBetMaker::AwaitingResult,
+            (RoomBetPossibleOutcomes::Draw, _) => BetOutcomeForBetMaker::Draw(payout_amount),
+            (RoomBetPossibleOutcomes::HotWon, BetDirection::Hot)
+            | (RoomBetPossibleOutcomes::NotWon, BetDirection::Not) => {
+                BetOutcomeForBetMaker::Won(payout_amount)
+            }
+            (RoomBetPossibleOutcomes::HotWon, BetDirection::Not)
+            | (RoomBetPossibleOutcomes::NotWon, BetDirection::Hot) => BetOutcomeForBetMaker::Lost,
+        }
+    }
+}
+
 impl Storable for BetDetails {
     fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
         Cow::Owned(Encode!(self).unwrap())
@@ -245,6 +282,32 @@ impl Storable for GlobalRoomId {
     };
 }
 
+/// A slot of a post waiting to be tabulated.
+/// `due_at` comes first so that ordering the jobs orders them by when they are due.
+#[derive(
+    CandidType, Clone, Deserialize, Debug, Serialize, Ord, PartialOrd, Eq, PartialEq, Copy,
+)]
+pub struct SlotTabulationJob {
+    pub due_at: SystemTime,
+    pub post_id: PostId,
+    pub slot_id: SlotId,
+}
+
+impl Storable for SlotTabulationJob {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
+
 #[derive(
     CandidType, Clone, Deserialize, Debug, Serialize, Ord, PartialOrd, Eq, PartialEq, Default,
 )]
@@ -293,6 +356,164 @@ pub struct PlacedBetDetail {
     pub outcome_received: BetOutcomeForBetMaker,
 }
 
+/// A bet whose amount has left the bet maker's balance
+/// but that the post creator's canister has not confirmed yet
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PendingBet {
+    pub post_canister_id: CanisterId,
+    pub post_id: PostId,
+    pub amount: u64,
+    pub bet_direction: BetDirection,
+    pub idempotency_key: BetIdempotencyKey,
+    pub placed_at: SystemTime,
+    /// Set once the call placing the bet has failed without saying whether the bet was taken.
+    /// Only these are reconciled, a bet whose call is still in flight is left alone.
+    #[serde(default)]
+    pub awaiting_reconciliation: bool,
+}
+
+/// How a bet maker takes back a bet before its slot closes
+#[derive(Deserialize, Serialize, Clone, Copy, CandidType, Debug, PartialEq, Eq)]
+pub enum BetWithdrawalKind {
+    /// Full refund, only within the cancellation window of the post's game config
+    Cancel,
+    /// Discounted payout based on how the room is split at the time
+    CashOut,
+}
+
+/// A withdrawal sent to a post creator's canister that it has not confirmed yet
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PendingBetWithdrawal {
+    pub post_canister_id: CanisterId,
+    pub post_id: PostId,
+    pub withdrawal_kind: BetWithdrawalKind,
+    /// Set once the call withdrawing the bet has failed without saying whether it went through
+    #[serde(default)]
+    pub awaiting_reconciliation: bool,
+}
+
+/// What the post creator's canister has on record for a bet,
+/// used by the bet maker's canister to settle a bet it never got a reply for
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct BetReconciliationDetails {
+    pub slot_id: SlotId,
+    pub room_id: RoomId,
+    pub amount: u64,
+    pub bet_direction: BetDirection,
+    pub idempotency_key: Option<BetIdempotencyKey>,
+    pub outcome: BetOutcomeForBetMaker,
+    /// Set once the bet was withdrawn, to the amount the bet maker's canister was owed for it
+    #[serde(default)]
+    pub withdrawn_amount: Option<u64>,
+}
+
+const PAYOUT_NOTIFICATION_BASE_BACKOFF: Duration = Duration::from_secs(60);
+const PAYOUT_NOTIFICATION_MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// A bet outcome the bet maker's canister could not be told about yet
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PendingPayoutNotification {
+    pub bet_maker_canister_id: CanisterId,
+    pub post_id: PostId,
+    pub outcome: BetOutcomeForBetMaker,
+    pub attempts: u32,
+    pub first_failed_at: SystemTime,
+    pub next_attempt_at: SystemTime,
+    pub last_error: String,
+    /// Set once the retry horizon has passed, the notification is then only retried on request
+    pub gave_up_at: Option<SystemTime>,
+}
+
+impl PendingPayoutNotification {
+    pub fn new(
+        bet_maker_canister_id: CanisterId,
+        post_id: PostId,
+        outcome: BetOutcomeForBetMaker,
+        error: String,
+        current_time: SystemTime,
+    ) -> Self {
+        Self {
+            bet_maker_canister_id,
+            post_id,
+            outcome,
+            attempts: 1,
+            first_failed_at: current_time,
+            next_attempt_at: current_time + get_payout_notification_backoff(1),
+            last_error: error,
+            gave_up_at: None,
+        }
+    }
+
+    /// A notification that is sent from the outbox right away, without a failed attempt first.
+    /// Its retry horizon counts from when it was queued.
+    pub fn queued(
+        bet_maker_canister_id: CanisterId,
+        post_id: PostId,
+        outcome: BetOutcomeForBetMaker,
+        current_time: SystemTime,
+    ) -> Self {
+        Self {
+            bet_maker_canister_id,
+            post_id,
+            outcome,
+            attempts: 0,
+            first_failed_at: current_time,
+            next_attempt_at: current_time,
+            last_error: String::new(),
+            gave_up_at: None,
+        }
+    }
+
+    pub fn is_due(&self, current_time: &SystemTime) -> bool {
+        self.gave_up_at.is_none() && self.next_attempt_at <= *current_time
+    }
+
+    /// Pushes the next attempt back by the backoff that would follow a failure,
+    /// so an attempt still in flight is not sent again meanwhile
+    pub fn record_attempt_started(&mut self, current_time: SystemTime) {
+        self.next_attempt_at =
+            current_time + get_payout_notification_backoff(self.attempts.saturating_add(1));
+    }
+
+    pub fn is_expired(&self, current_time: &SystemTime, retention: Duration) -> bool {
+        self.gave_up_at
+            .is_some_and(|gave_up_at| gave_up_at + retention <= *current_time)
+    }
+
+    pub fn record_failed_attempt(
+        &mut self,
+        error: String,
+        current_time: SystemTime,
+        retry_horizon: Duration,
+    ) {
+        self.attempts += 1;
+        self.last_error = error;
+
+        if current_time >= self.first_failed_at + retry_horizon {
+            self.gave_up_at = Some(current_time);
+        } else {
+            self.next_attempt_at = current_time + get_payout_notification_backoff(self.attempts);
+        }
+    }
+
+    /// Starts retrying afresh, with a new horizon
+    pub fn requeue(&mut self, current_time: SystemTime) {
+        self.attempts = 0;
+        self.first_failed_at = current_time;
+        self.next_attempt_at = current_time;
+        self.gave_up_at = None;
+    }
+}
+
+/// Doubles with every attempt, up to `PAYOUT_NOTIFICATION_MAX_BACKOFF`
+fn get_payout_notification_backoff(attempts: u32) -> Duration {
+    PAYOUT_NOTIFICATION_BASE_BACKOFF
+        .checked_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .map_or(PAYOUT_NOTIFICATION_MAX_BACKOFF, |backoff| {
+            backoff.min(PAYOUT_NOTIFICATION_MAX_BACKOFF)
+        })
+}
+
 #[derive(Deserialize, Serialize, Default, CandidType, PartialEq, Eq, Clone, Debug)]
 pub enum BetOutcomeForBetMaker {
     #[default]
@@ -323,20 +544,29 @@ impl Post {
             VirtualMemory<DefaultMemoryImpl>,
         >,
     ) -> BettingStatus {
+        if self.status == PostStatus::Deleted {
+            return BettingStatus::BettingClosed;
+        }
+
         let betting_status = match current_time_when_request_being_made
             .duration_since(self.created_at)
             .unwrap()
             .as_secs()
         {
             // * contest is still ongoing
-            0..=TOTAL_DURATION_OF_ALL_SLOTS_IN_SECONDS => {
+            elapsed_seconds
+                if elapsed_seconds
+                    <= self
+                        .hot_or_not_game_config
+                        .total_duration_of_all_slots_in_seconds() =>
+            {
                 let started_at = self.created_at;
                 let numerator = current_time_when_request_being_made
                     .duration_since(started_at)
                     .unwrap()
                     .as_secs();
 
-                let denominator = DURATION_OF_EACH_SLOT_IN_SECONDS;
+                let denominator = self.hot_or_not_game_config.duration_of_each_slot_in_seconds;
                 let currently_ongoing_slot = ((numerator / denominator) + 1) as u8;
 
                 // let temp_room_details_default = RoomDetailsV1::default();
@@ -455,8 +685,10 @@ impl Post {
 
                 let mut room_detail = room_details_map.get(&global_room_id).unwrap_or_default();
                 let num_bets_made = room_detail.total_hot_bets + room_detail.total_not_bets;
+                let maximum_number_of_bets_per_room =
+                    self.hot_or_not_game_config.maximum_number_of_bets_per_room;
 
-                if num_bets_made < 100 {
+                if num_bets_made < maximum_number_of_bets_per_room {
                     room_detail.room_bets_total_pot += bet_amount;
                 } else {
                     let new_room_number = ongoing_room + 1;
@@ -477,6 +709,8 @@ impl Post {
                         payout: BetPayout::default(),
                         bet_maker_canister_id: *bet_maker_canister_id,
                         bet_maker_informed_status: None,
+                        idempotency_key: None,
+                        placed_at: Some(*current_time_when_request_being_made),
                     },
                 );
 
@@ -506,8 +740,8 @@ impl Post {
                 self.hot_or_not_details = Some(hot_or_not_details);
 
                 let started_at = self.created_at;
-                let number_of_participants = if num_bets_made >= 100 {
-                    ((num_bets_made + 1) % 100) as u8
+                let number_of_participants = if num_bets_made >= maximum_number_of_bets_per_room {
+                    ((num_bets_made + 1) % maximum_number_of_bets_per_room) as u8
                 } else {
                     (num_bets_made + 1) as u8
                 } as u8;
@@ -544,6 +778,7 @@ impl Post {
             VirtualMemory<DefaultMemoryImpl>,
         >,
     ) {
+        let game_config = &self.hot_or_not_game_config;
         let start_global_room_id = GlobalRoomId(self.id, *slot_id, 1);
         let end_global_room_id = GlobalRoomId(self.id, *slot_id + 1, 1);
 
@@ -554,6 +789,15 @@ impl Post {
             let mut room_detail = room_detail.clone();
             let room_id = groomid.2;
 
+            let bets_map: Vec<(GlobalBetId, BetDetails)> = bet_details_map
+                .iter()
+                .filter(|(global_bet_id, _)| global_bet_id.0 == *groomid)
+                .collect();
+            let bets = bets_map
+                .iter()
+                .map(|(_, bet_detail)| (bet_detail.amount, bet_detail.bet_direction.clone()))
+                .collect::<Vec<_>>();
+
             if room_detail.bet_outcome == RoomBetPossibleOutcomes::BetOngoing {
                 // * Figure out which side won
                 match room_detail.total_hot_bets.cmp(&room_detail.total_not_bets) {
@@ -566,11 +810,11 @@ impl Post {
                     Ordering::Equal => room_detail.bet_outcome = RoomBetPossibleOutcomes::Draw,
                 }
 
-                // * Reward creator with commission. Commission is 10% of total pot
+                // * Reward creator with commission as set in the post's game config
+                let payouts = game_config.get_payouts_for_room(&room_detail.bet_outcome, &bets);
                 token_balance.handle_token_event(TokenEvent::HotOrNotOutcomePayout {
-                    amount: room_detail.room_bets_total_pot
-                        * HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE
-                        / 100,
+                    amount: game_config
+                        .get_creator_commission_for_room(room_detail.room_bets_total_pot, &payouts),
                     details: HotOrNotOutcomePayoutEvent::CommissionFromHotOrNotBet {
                         post_canister_id: *post_canister_id,
                         post_id: self.id,
@@ -585,51 +829,216 @@ impl Post {
             }
 
             // * Reward individual participants
+            let payouts = game_config.get_payouts_for_room(&room_detail.bet_outcome, &bets);
+
+            bets_map
+                .into_iter()
+                .zip(payouts)
+                .for_each(|((gbetid, mut bet_detail), payout)| {
+                    bet_detail.payout = payout;
+                    bet_details_map.insert(gbetid, bet_detail);
+                });
+        });
+    }
 
-            let bets_map: Vec<(GlobalBetId, BetDetails)> = bet_details_map
-                .iter()
-                .filter(|(global_bet_id, _)| global_bet_id.0 == *groomid)
-                .collect();
+    /// The slot bets currently go into, `None` once all slots are over
+    pub fn get_currently_ongoing_slot(&self, current_time: &SystemTime) -> Option<SlotId> {
+        let elapsed_seconds = current_time
+            .duration_since(self.created_at)
+            .unwrap_or_default()
+            .as_secs();
 
-            bets_map.into_iter().for_each(|(gbetid, mut bet_detail)| {
-                match &room_detail.bet_outcome {
-                    RoomBetPossibleOutcomes::HotWon => {
-                        if bet_detail.bet_direction == BetDirection::Hot {
-                            bet_detail.payout = BetPayout::Calculated(
-                                bet_detail.amount
-                                    * HOT_OR_NOT_BET_WINNINGS_MULTIPLIER
-                                    * (100 - HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE)
-                                    / 100,
-                            );
-                        } else {
-                            bet_detail.payout = BetPayout::Calculated(0);
-                        }
-                    }
-                    RoomBetPossibleOutcomes::NotWon => {
-                        if bet_detail.bet_direction == BetDirection::Not {
-                            bet_detail.payout = BetPayout::Calculated(
-                                bet_detail.amount
-                                    * HOT_OR_NOT_BET_WINNINGS_MULTIPLIER
-                                    * (100 - HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE)
-                                    / 100,
-                            );
-                        } else {
-                            bet_detail.payout = BetPayout::Calculated(0);
-                        }
-                    }
-                    RoomBetPossibleOutcomes::Draw => {
-                        bet_detail.payout = BetPayout::Calculated(
-                            bet_detail.amount
-                                * (100 - HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE)
-                                / 100,
-                        );
-                    }
-                    RoomBetPossibleOutcomes::BetOngoing => {}
-                };
+        if elapsed_seconds
+            > self
+                .hot_or_not_game_config
+                .total_duration_of_all_slots_in_seconds()
+        {
+            return None;
+        }
+
+        Some(
+            (elapsed_seconds / self.hot_or_not_game_config.duration_of_each_slot_in_seconds + 1)
+                as SlotId,
+        )
+    }
 
-                bet_details_map.insert(gbetid, bet_detail);
+    /// Takes a bet out of its room before the slot closes and returns it along with the
+    /// amount owed to the bet maker. A cancelled bet is forgotten entirely so the bet maker may bet again,
+    /// a cashed out one still counts as their participation in this post.
+    pub fn withdraw_hot_or_not_bet_v1(
+        &mut self,
+        bet_maker_principal_id: &Principal,
+        bet_maker_canister_id: &CanisterId,
+        withdrawal_kind: BetWithdrawalKind,
+        current_time: &SystemTime,
+        room_details_map: &mut ic_stable_structures::btreemap::BTreeMap<
+            GlobalRoomId,
+            RoomDetailsV1,
+            VirtualMemory<DefaultMemoryImpl>,
+        >,
+        bet_details_map: &mut ic_stable_structures::btreemap::BTreeMap<
+            GlobalBetId,
+            BetDetails,
+            VirtualMemory<DefaultMemoryImpl>,
+        >,
+        post_principal_map: &mut ic_stable_structures::btreemap::BTreeMap<
+            (PostId, StablePrincipal),
+            (),
+            VirtualMemory<DefaultMemoryImpl>,
+        >,
+    ) -> Result<(BetDetails, u64), WithdrawHotOrNotBetError> {
+        if *bet_maker_principal_id == Principal::anonymous() {
+            return Err(WithdrawHotOrNotBetError::UserNotLoggedIn);
+        }
+
+        let bet_maker = StablePrincipal(*bet_maker_principal_id);
+        let start_global_bet_id =
+            GlobalBetId(GlobalRoomId(self.id, 0, 0), StablePrincipal::default());
+        let end_global_bet_id =
+            GlobalBetId(GlobalRoomId(self.id + 1, 0, 0), StablePrincipal::default());
+
+        let (global_bet_id, bet_details) = bet_details_map
+            .range(start_global_bet_id..end_global_bet_id)
+            .find(|(global_bet_id, _)| global_bet_id.1 == bet_maker)
+            .ok_or(WithdrawHotOrNotBetError::BetNotFound)?;
+
+        if bet_details.bet_maker_canister_id != *bet_maker_canister_id {
+            return Err(WithdrawHotOrNotBetError::Unauthorized);
+        }
+
+        let global_room_id = global_bet_id.0;
+        let mut room_detail = room_details_map.get(&global_room_id).unwrap_or_default();
+
+        if self.status == PostStatus::Deleted
+            || room_detail.bet_outcome != RoomBetPossibleOutcomes::BetOngoing
+            || self.get_currently_ongoing_slot(current_time) != Some(global_room_id.1)
+        {
+            return Err(WithdrawHotOrNotBetError::SlotClosed);
+        }
+
+        let amount_owed = match withdrawal_kind {
+            BetWithdrawalKind::Cancel => {
+                let cancellation_window = Duration::from_secs(
+                    self.hot_or_not_game_config
+                        .bet_cancellation_window_in_seconds,
+                );
+                let is_within_cancellation_window =
+                    bet_details.placed_at.is_some_and(|placed_at| {
+                        current_time.duration_since(placed_at).unwrap_or_default()
+                            < cancellation_window
+                    });
+
+                if !is_within_cancellation_window {
+                    return Err(WithdrawHotOrNotBetError::CancellationWindowClosed);
+                }
+
+                bet_details.amount
+            }
+            BetWithdrawalKind::CashOut => {
+                let amount_bet_on_same_side: u64 = bet_details_map
+                    .range(
+                        GlobalBetId(global_room_id, StablePrincipal::default())
+                            ..GlobalBetId(
+                                GlobalRoomId(
+                                    global_room_id.0,
+                                    global_room_id.1,
+                                    global_room_id.2 + 1,
+                                ),
+                                StablePrincipal::default(),
+                            ),
+                    )
+                    .filter(|(_, room_bet_details)| {
+                        room_bet_details.bet_direction == bet_details.bet_direction
+                    })
+                    .map(|(_, room_bet_details)| room_bet_details.amount)
+                    .sum();
+
+                self.hot_or_not_game_config
+                    .cash_out_value(
+                        bet_details.amount,
+                        amount_bet_on_same_side,
+                        room_detail.room_bets_total_pot,
+                        room_detail.total_hot_bets + room_detail.total_not_bets,
+                    )
+                    .ok_or(WithdrawHotOrNotBetError::CashOutNotAvailable)?
+            }
+        };
+
+        let mut hot_or_not_details = self.hot_or_not_details.take().unwrap_or_default();
+        let aggregate_stats = &mut hot_or_not_details.aggregate_stats;
+
+        aggregate_stats.total_amount_bet = aggregate_stats
+            .total_amount_bet
+            .saturating_sub(bet_details.amount);
+        room_detail.room_bets_total_pot = room_detail
+            .room_bets_total_pot
+            .saturating_sub(bet_details.amount);
+        match bet_details.bet_direction {
+            BetDirection::Hot => {
+                aggregate_stats.total_number_of_hot_bets =
+                    aggregate_stats.total_number_of_hot_bets.saturating_sub(1);
+                room_detail.total_hot_bets = room_detail.total_hot_bets.saturating_sub(1);
+            }
+            BetDirection::Not => {
+                aggregate_stats.total_number_of_not_bets =
+                    aggregate_stats.total_number_of_not_bets.saturating_sub(1);
+                room_detail.total_not_bets = room_detail.total_not_bets.saturating_sub(1);
+            }
+        }
+
+        self.hot_or_not_details = Some(hot_or_not_details);
+        room_details_map.insert(global_room_id, room_detail);
+        bet_details_map.remove(&global_bet_id);
+
+        if withdrawal_kind == BetWithdrawalKind::Cancel {
+            post_principal_map.remove(&(self.id, bet_maker));
+        }
+
+        Ok((bet_details, amount_owed))
+    }
+
+    /// Settles every open room of the slot as a draw that returns the full stake.
+    /// No commission is taken. Used when the post is deleted before the slot ends.
+    pub fn refund_hot_or_not_bets_for_slot(
+        &self,
+        slot_id: &u8,
+        room_details_map: &mut ic_stable_structures::btreemap::BTreeMap<
+            GlobalRoomId,
+            RoomDetailsV1,
+            VirtualMemory<DefaultMemoryImpl>,
+        >,
+        bet_details_map: &mut ic_stable_structures::btreemap::BTreeMap<
+            GlobalBetId,
+            BetDetails,
+            VirtualMemory<DefaultMemoryImpl>,
+        >,
+    ) {
+        let start_global_room_id = GlobalRoomId(self.id, *slot_id, 1);
+        let end_global_room_id = GlobalRoomId(self.id, *slot_id + 1, 1);
+
+        let open_rooms = room_details_map
+            .range(start_global_room_id..end_global_room_id)
+            .filter(|(_, room_detail)| {
+                room_detail.bet_outcome == RoomBetPossibleOutcomes::BetOngoing
+            })
+            .collect::<Vec<_>>();
+
+        open_rooms
+            .into_iter()
+            .for_each(|(groomid, mut room_detail)| {
+                room_detail.bet_outcome = RoomBetPossibleOutcomes::Draw;
+                room_details_map.insert(groomid, room_detail);
+
+                let bets_map: Vec<(GlobalBetId, BetDetails)> = bet_details_map
+                    .iter()
+                    .filter(|(global_bet_id, _)| global_bet_id.0 == groomid)
+                    .collect();
+
+                bets_map.into_iter().for_each(|(gbetid, mut bet_detail)| {
+                    bet_detail.payout = BetPayout::Calculated(bet_detail.amount);
+                    bet_details_map.insert(gbetid, bet_detail);
+                });
             });
-        });
     }
 }
 
@@ -638,6 +1047,7 @@ pub mod test_hot_or_not {
     use std::{cell::RefCell, time::Duration};
 
     use ic_stable_structures::memory_manager::{MemoryId, MemoryManager};
+    use proptest::prelude::*;
     use test_utils::setup::test_constants::{
         get_mock_user_alice_canister_id, get_mock_user_alice_principal_id,
     };
@@ -2249,6 +2659,261 @@ pub mod test_hot_or_not {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_game_rules_follow_the_game_config_snapshotted_on_the_post() {
+        let (
+            mut room_details_map,
+            mut bet_details_map,
+            mut post_principal_map,
+            mut slot_details_map,
+        ) = setup_room_and_bet_details_map();
+
+        let game_config = game_config::HotOrNotGameConfig {
+            version: 1,
+            maximum_number_of_slots: 2,
+            duration_of_each_slot_in_seconds: 60,
+            maximum_number_of_bets_per_room: 2,
+            creator_commission_percentage: 20,
+            winnings_multiplier: 3,
+            ..Default::default()
+        };
+        let post_created_at = SystemTime::now();
+        let mut post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            game_config,
+        );
+
+        assert_eq!(post.slots_left_to_be_computed.len(), 2);
+
+        let results = (1..=3u8)
+            .map(|i| {
+                post.place_hot_or_not_bet_v1(
+                    &Principal::from_slice(&[i]),
+                    &Principal::from_slice(&[i, i]),
+                    100,
+                    &BetDirection::Hot,
+                    &post_created_at,
+                    &mut room_details_map,
+                    &mut bet_details_map,
+                    &mut post_principal_map,
+                    &mut slot_details_map,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // * third bet overflows into a new room as the room cap is 2
+        assert_eq!(
+            results[2],
+            Ok(BettingStatus::BettingOpen {
+                started_at: post_created_at,
+                number_of_participants: 1,
+                ongoing_slot: 1,
+                ongoing_room: 2,
+                has_this_user_participated_in_this_post: Some(true)
+            })
+        );
+
+        let result = post.get_hot_or_not_betting_status_for_this_post_v1(
+            &post_created_at
+                .checked_add(Duration::from_secs(2 * 60 + 1))
+                .unwrap(),
+            &Principal::anonymous(),
+            &room_details_map,
+            &post_principal_map,
+            &slot_details_map,
+        );
+        assert_eq!(result, BettingStatus::BettingClosed);
+
+        let mut token_balance = TokenBalance::default();
+        post.tabulate_hot_or_not_outcome_for_slot_v1(
+            &get_mock_user_alice_canister_id(),
+            &1,
+            &mut token_balance,
+            &post_created_at,
+            &mut room_details_map,
+            &mut bet_details_map,
+        );
+
+        // * 20% of the 200 pot in room 1 and of the 100 pot in room 2
+        assert_eq!(token_balance.utility_token_balance, 40 + 20);
+        bet_details_map.iter().for_each(|(_, bet_detail)| {
+            assert_eq!(bet_detail.payout, BetPayout::Calculated(240));
+        });
+    }
+
+    #[test]
+    fn test_tabulate_hot_or_not_outcome_for_slot_with_parimutuel_payouts() {
+        let (
+            mut room_details_map,
+            mut bet_details_map,
+            mut post_principal_map,
+            mut slot_details_map,
+        ) = setup_room_and_bet_details_map();
+
+        let post_created_at = SystemTime::now();
+        let mut post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            game_config::HotOrNotGameConfig {
+                payout_mode: game_config::HotOrNotPayoutMode::Parimutuel,
+                ..Default::default()
+            },
+        );
+
+        let data_set = [
+            (1u8, BetDirection::Hot, 100),
+            (2, BetDirection::Hot, 200),
+            (3, BetDirection::Hot, 300),
+            (4, BetDirection::Not, 150),
+            (5, BetDirection::Not, 250),
+        ];
+        data_set.iter().for_each(|(i, bet_direction, bet_amount)| {
+            assert!(post
+                .place_hot_or_not_bet_v1(
+                    &Principal::from_slice(&[*i]),
+                    &Principal::from_slice(&[*i, *i]),
+                    *bet_amount,
+                    bet_direction,
+                    &post_created_at,
+                    &mut room_details_map,
+                    &mut bet_details_map,
+                    &mut post_principal_map,
+                    &mut slot_details_map,
+                )
+                .is_ok());
+        });
+
+        let mut token_balance = TokenBalance::default();
+        post.tabulate_hot_or_not_outcome_for_slot_v1(
+            &get_mock_user_alice_canister_id(),
+            &1,
+            &mut token_balance,
+            &post_created_at,
+            &mut room_details_map,
+            &mut bet_details_map,
+        );
+
+        // * 360 of the 400 losing pot goes to hot bets by stake, 40 to the creator
+        let expected_payouts = [160, 320, 480, 0, 0];
+        data_set
+            .iter()
+            .zip(expected_payouts)
+            .for_each(|((i, _, _), expected_payout)| {
+                let bet_detail = bet_details_map
+                    .get(&GlobalBetId(
+                        GlobalRoomId(0, 1, 1),
+                        StablePrincipal(Principal::from_slice(&[*i])),
+                    ))
+                    .unwrap();
+                assert_eq!(bet_detail.payout, BetPayout::Calculated(expected_payout));
+            });
+        assert_eq!(token_balance.utility_token_balance, 40);
+        assert_eq!(
+            expected_payouts.iter().sum::<u64>() + token_balance.utility_token_balance,
+            1000
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_tabulate_hot_or_not_outcome_for_slot_with_parimutuel_payouts_conserves_tokens(
+            creator_commission_percentage in 0u64..100,
+            creator_balance_before in 0u64..1_000_000,
+            bets in prop::collection::vec(
+                (
+                    1u64..1_000_000,
+                    prop_oneof![Just(BetDirection::Hot), Just(BetDirection::Not)],
+                ),
+                1..=250,
+            ),
+        ) {
+            // * fresh memory for every case, the thread local one keeps the bets of earlier cases
+            let memory_manager = MemoryManager::init(DefaultMemoryImpl::default());
+            let memory = |id| memory_manager.get(MemoryId::new(id));
+            let mut room_details_map = ic_stable_structures::btreemap::BTreeMap::init(memory(0));
+            let mut bet_details_map = ic_stable_structures::btreemap::BTreeMap::init(memory(1));
+            let mut post_principal_map = ic_stable_structures::btreemap::BTreeMap::init(memory(3));
+            let mut slot_details_map = ic_stable_structures::btreemap::BTreeMap::init(memory(4));
+
+            let post_created_at = SystemTime::now();
+            let mut post = Post::new_with_game_config(
+                0,
+                &PostDetailsFromFrontend {
+                    is_nsfw: false,
+                    description: "Doggos and puppers".into(),
+                    hashtags: vec!["doggo".into(), "pupper".into()],
+                    video_uid: "abcd#1234".into(),
+                    creator_consent_for_inclusion_in_hot_or_not: true,
+                },
+                &post_created_at,
+                game_config::HotOrNotGameConfig {
+                    creator_commission_percentage,
+                    payout_mode: game_config::HotOrNotPayoutMode::Parimutuel,
+                    ..Default::default()
+                },
+            );
+
+            for (i, (bet_amount, bet_direction)) in bets.iter().enumerate() {
+                let bet_maker = (i as u32 + 1).to_be_bytes();
+                prop_assert!(post
+                    .place_hot_or_not_bet_v1(
+                        &Principal::from_slice(&bet_maker),
+                        &Principal::from_slice(&[bet_maker, bet_maker].concat()),
+                        *bet_amount,
+                        bet_direction,
+                        &post_created_at,
+                        &mut room_details_map,
+                        &mut bet_details_map,
+                        &mut post_principal_map,
+                        &mut slot_details_map,
+                    )
+                    .is_ok());
+            }
+
+            let mut token_balance = TokenBalance {
+                utility_token_balance: creator_balance_before,
+                ..Default::default()
+            };
+            post.tabulate_hot_or_not_outcome_for_slot_v1(
+                &get_mock_user_alice_canister_id(),
+                &1,
+                &mut token_balance,
+                &post_created_at,
+                &mut room_details_map,
+                &mut bet_details_map,
+            );
+
+            let mut total_paid_out = 0;
+            for (_, bet_detail) in bet_details_map.iter() {
+                let BetPayout::Calculated(payout) = bet_detail.payout else {
+                    panic!("a tabulated slot pays out every bet");
+                };
+                total_paid_out += payout;
+            }
+            let pot: u64 = bets.iter().map(|(bet_amount, _)| bet_amount).sum();
+
+            prop_assert_eq!(
+                total_paid_out + token_balance.utility_token_balance - creator_balance_before,
+                pot
+            );
+        }
+    }
+
     #[test]
     fn test_tabulate_hot_or_not_outcome_for_slot_case_1_v1() {
         let (
@@ -3020,4 +3685,327 @@ pub mod test_hot_or_not {
                 );
             });
     }
+
+    #[test]
+    fn test_refund_hot_or_not_bets_for_slot() {
+        let (
+            mut room_details_map,
+            mut bet_details_map,
+            mut post_principal_map,
+            mut slot_details_map,
+        ) = setup_room_and_bet_details_map();
+
+        let post_creation_time = SystemTime::now();
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_creation_time,
+        );
+
+        let data_set: Vec<(u64, BetDirection, u64)> = vec![
+            (1, BetDirection::Not, 10),
+            (2, BetDirection::Hot, 100),
+            (3, BetDirection::Hot, 50),
+        ];
+
+        data_set
+            .iter()
+            .for_each(|(user_id, bet_direction, bet_amount)| {
+                let result = post.place_hot_or_not_bet_v1(
+                    &Principal::self_authenticating(user_id.to_ne_bytes()),
+                    &Principal::self_authenticating(user_id.to_ne_bytes()),
+                    *bet_amount,
+                    bet_direction,
+                    &post_creation_time,
+                    &mut room_details_map,
+                    &mut bet_details_map,
+                    &mut post_principal_map,
+                    &mut slot_details_map,
+                );
+                assert!(result.is_ok());
+            });
+
+        post.refund_hot_or_not_bets_for_slot(&1, &mut room_details_map, &mut bet_details_map);
+
+        let room_detail = room_details_map.get(&GlobalRoomId(0, 1, 1)).unwrap();
+        assert_eq!(room_detail.bet_outcome, RoomBetPossibleOutcomes::Draw);
+
+        data_set.iter().for_each(|(user_id, _, bet_amount)| {
+            let bet_detail = bet_details_map
+                .get(&GlobalBetId(
+                    GlobalRoomId(0, 1, 1),
+                    StablePrincipal(Principal::self_authenticating(user_id.to_ne_bytes())),
+                ))
+                .unwrap();
+            assert_eq!(bet_detail.payout, BetPayout::Calculated(*bet_amount));
+        });
+
+        // * betting is closed on deleted posts
+        post.update_status(PostStatus::Deleted);
+        let result = post.place_hot_or_not_bet_v1(
+            &Principal::self_authenticating(4u64.to_ne_bytes()),
+            &Principal::self_authenticating(4u64.to_ne_bytes()),
+            10,
+            &BetDirection::Hot,
+            &post_creation_time,
+            &mut room_details_map,
+            &mut bet_details_map,
+            &mut post_principal_map,
+            &mut slot_details_map,
+        );
+        assert_eq!(result, Err(BetOnCurrentlyViewingPostError::BettingClosed));
+    }
+
+    #[test]
+    fn test_withdraw_hot_or_not_bet_v1() {
+        let (
+            mut room_details_map,
+            mut bet_details_map,
+            mut post_principal_map,
+            mut slot_details_map,
+        ) = setup_room_and_bet_details_map();
+
+        let post_created_at = SystemTime::now();
+        let mut post = Post::new_with_game_config(
+            0,
+            &PostDetailsFromFrontend {
+                is_nsfw: false,
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+            },
+            &post_created_at,
+            game_config::HotOrNotGameConfig {
+                duration_of_each_slot_in_seconds: 60,
+                bet_cancellation_window_in_seconds: 10,
+                cash_out_discount_percentage: Some(20),
+                ..Default::default()
+            },
+        );
+
+        let bet_makers = (1..=4u8)
+            .map(|i| (Principal::from_slice(&[i]), Principal::from_slice(&[i, i])))
+            .collect::<Vec<_>>();
+        bet_makers
+            .iter()
+            .zip([
+                BetDirection::Hot,
+                BetDirection::Hot,
+                BetDirection::Hot,
+                BetDirection::Not,
+            ])
+            .for_each(
+                |((bet_maker_principal_id, bet_maker_canister_id), bet_direction)| {
+                    assert!(post
+                        .place_hot_or_not_bet_v1(
+                            bet_maker_principal_id,
+                            bet_maker_canister_id,
+                            100,
+                            &bet_direction,
+                            &post_created_at,
+                            &mut room_details_map,
+                            &mut bet_details_map,
+                            &mut post_principal_map,
+                            &mut slot_details_map,
+                        )
+                        .is_ok());
+                },
+            );
+
+        let within_cancellation_window = post_created_at + Duration::from_secs(5);
+        let past_cancellation_window = post_created_at + Duration::from_secs(30);
+        let (first_principal_id, first_canister_id) = bet_makers[0];
+
+        // * only the canister that placed the bet can withdraw it
+        assert_eq!(
+            post.withdraw_hot_or_not_bet_v1(
+                &first_principal_id,
+                &bet_makers[1].1,
+                BetWithdrawalKind::Cancel,
+                &within_cancellation_window,
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            ),
+            Err(WithdrawHotOrNotBetError::Unauthorized)
+        );
+        assert_eq!(
+            post.withdraw_hot_or_not_bet_v1(
+                &first_principal_id,
+                &first_canister_id,
+                BetWithdrawalKind::Cancel,
+                &past_cancellation_window,
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            ),
+            Err(WithdrawHotOrNotBetError::CancellationWindowClosed)
+        );
+
+        let (bet_details, refund) = post
+            .withdraw_hot_or_not_bet_v1(
+                &first_principal_id,
+                &first_canister_id,
+                BetWithdrawalKind::Cancel,
+                &within_cancellation_window,
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            )
+            .unwrap();
+        assert_eq!(bet_details.bet_maker_canister_id, first_canister_id);
+        assert_eq!(refund, 100);
+        assert!(!post.has_this_principal_already_bet_on_this_post_v1(
+            &first_principal_id,
+            &post_principal_map
+        ));
+        assert_eq!(
+            post.withdraw_hot_or_not_bet_v1(
+                &first_principal_id,
+                &first_canister_id,
+                BetWithdrawalKind::Cancel,
+                &within_cancellation_window,
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            ),
+            Err(WithdrawHotOrNotBetError::BetNotFound)
+        );
+
+        // * 200 of the 300 left in the pot is on hot, 180 * 2 / 3 is capped at the stake
+        // * and then 20% off
+        let (second_principal_id, second_canister_id) = bet_makers[1];
+        let (_, cash_out_amount) = post
+            .withdraw_hot_or_not_bet_v1(
+                &second_principal_id,
+                &second_canister_id,
+                BetWithdrawalKind::CashOut,
+                &past_cancellation_window,
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            )
+            .unwrap();
+        assert_eq!(cash_out_amount, 80);
+        assert!(post.has_this_principal_already_bet_on_this_post_v1(
+            &second_principal_id,
+            &post_principal_map
+        ));
+
+        let room_detail = room_details_map.get(&GlobalRoomId(0, 1, 1)).unwrap();
+        assert_eq!(room_detail.total_hot_bets, 1);
+        assert_eq!(room_detail.total_not_bets, 1);
+        assert_eq!(room_detail.room_bets_total_pot, 200);
+        let aggregate_stats = &post.hot_or_not_details.as_ref().unwrap().aggregate_stats;
+        assert_eq!(aggregate_stats.total_number_of_hot_bets, 1);
+        assert_eq!(aggregate_stats.total_number_of_not_bets, 1);
+        assert_eq!(aggregate_stats.total_amount_bet, 200);
+        assert_eq!(bet_details_map.len(), 2);
+
+        // * bets can not be withdrawn once their slot is over
+        let (third_principal_id, third_canister_id) = bet_makers[2];
+        assert_eq!(
+            post.withdraw_hot_or_not_bet_v1(
+                &third_principal_id,
+                &third_canister_id,
+                BetWithdrawalKind::CashOut,
+                &(post_created_at + Duration::from_secs(61)),
+                &mut room_details_map,
+                &mut bet_details_map,
+                &mut post_principal_map,
+            ),
+            Err(WithdrawHotOrNotBetError::SlotClosed)
+        );
+    }
+
+    #[test]
+    fn test_get_outcome_for_bet_maker() {
+        let mut bet_details = BetDetails {
+            amount: 100,
+            bet_direction: BetDirection::Hot,
+            payout: BetPayout::NotCalculatedYet,
+            bet_maker_canister_id: get_mock_user_alice_canister_id(),
+            bet_maker_informed_status: None,
+            idempotency_key: Some(0),
+            placed_at: None,
+        };
+
+        assert_eq!(
+            bet_details.get_outcome_for_bet_maker(&RoomBetPossibleOutcomes::BetOngoing),
+            BetOutcomeForBetMaker::AwaitingResult
+        );
+
+        bet_details.payout = BetPayout::Calculated(180);
+        assert_eq!(
+            bet_details.get_outcome_for_bet_maker(&RoomBetPossibleOutcomes::HotWon),
+            BetOutcomeForBetMaker::Won(180)
+        );
+        assert_eq!(
+            bet_details.get_outcome_for_bet_maker(&RoomBetPossibleOutcomes::NotWon),
+            BetOutcomeForBetMaker::Lost
+        );
+
+        bet_details.payout = BetPayout::Calculated(95);
+        assert_eq!(
+            bet_details.get_outcome_for_bet_maker(&RoomBetPossibleOutcomes::Draw),
+            BetOutcomeForBetMaker::Draw(95)
+        );
+    }
+
+    #[test]
+    fn test_pending_payout_notification_backoff_and_horizon() {
+        let first_failed_at = SystemTime::now();
+        let retry_horizon = Duration::from_secs(60 * 60);
+        let mut pending_payout_notification = PendingPayoutNotification::new(
+            get_mock_user_alice_canister_id(),
+            0,
+            BetOutcomeForBetMaker::Won(180),
+            "unreachable".into(),
+            first_failed_at,
+        );
+
+        assert_eq!(
+            pending_payout_notification.next_attempt_at,
+            first_failed_at + Duration::from_secs(60)
+        );
+        assert!(!pending_payout_notification.is_due(&first_failed_at));
+        assert!(pending_payout_notification.is_due(&(first_failed_at + Duration::from_secs(60))));
+
+        let second_attempt_at = first_failed_at + Duration::from_secs(60);
+        pending_payout_notification.record_failed_attempt(
+            "unreachable".into(),
+            second_attempt_at,
+            retry_horizon,
+        );
+        assert_eq!(pending_payout_notification.attempts, 2);
+        assert_eq!(
+            pending_payout_notification.next_attempt_at,
+            second_attempt_at + Duration::from_secs(120)
+        );
+
+        let past_horizon = first_failed_at + retry_horizon;
+        pending_payout_notification.record_failed_attempt(
+            "still unreachable".into(),
+            past_horizon,
+            retry_horizon,
+        );
+        assert_eq!(pending_payout_notification.gave_up_at, Some(past_horizon));
+        assert_eq!(pending_payout_notification.last_error, "still unreachable");
+        assert!(!pending_payout_notification.is_due(&(past_horizon + retry_horizon)));
+
+        pending_payout_notification.requeue(past_horizon);
+        assert!(pending_payout_notification.is_due(&past_horizon));
+
+        assert_eq!(
+            get_payout_notification_backoff(100),
+            PAYOUT_NOTIFICATION_MAX_BACKOFF
+        );
+    }
 }
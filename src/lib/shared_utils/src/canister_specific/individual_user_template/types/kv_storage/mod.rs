@@ -1,12 +1,72 @@
-use candid::{CandidType, Principal};
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
 
-#[derive(CandidType, Copy, Clone, Serialize, Deserialize, Debug)]
+#[derive(CandidType, Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum NamespaceErrors {
     ValueTooBig,
     Unauthorized,
     NamespaceNotFound,
     UserNotSignedUp,
+    KeyTooBig,
+    ChunkTooBig,
+    ChunkIndexOutOfRange,
+    QuotaExceeded,
+    VersionMismatch,
+    DuplicateKeyInTransaction,
+    EmptyChunk,
+    InvalidLimit,
+}
+
+/// Every write gives the key a new, higher version.
+/// A key that does not exist is at version 0, one written before versioning is at version 1.
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct VersionedValue {
+    pub value: String,
+    pub version: u64,
+}
+
+/// One step of a KV transaction.
+/// `expected_version` is checked before anything is applied, `None` skips the check.
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum KeyValueTransactionOperation {
+    Put {
+        key: String,
+        value: String,
+        expected_version: Option<u64>,
+    },
+    Delete {
+        key: String,
+        expected_version: Option<u64>,
+    },
+}
+
+impl KeyValueTransactionOperation {
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Put { key, .. } | Self::Delete { key, .. } => key,
+        }
+    }
+
+    pub fn expected_version(&self) -> Option<u64> {
+        match self {
+            Self::Put {
+                expected_version, ..
+            }
+            | Self::Delete {
+                expected_version, ..
+            } => *expected_version,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct NamespaceKeysPage {
+    pub keys: Vec<String>,
+    // * pass this back as the cursor to get the next page, `None` once all keys are listed
+    pub next_cursor: Option<String>,
 }
 
 #[derive(CandidType, Serialize, Deserialize)]
@@ -15,3 +75,71 @@ pub struct NamespaceForFrontend {
     pub title: String,
     pub owner_id: Principal,
 }
+
+/// Access granted to a principal on a namespace it does not own.
+/// Ordered so that a higher level includes everything a lower level allows.
+#[derive(
+    CandidType, Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum NamespaceAccessLevel {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl Storable for NamespaceAccessLevel {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 50,
+        is_fixed_size: false,
+    };
+}
+
+#[derive(CandidType, Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub struct BlobMetadata {
+    pub total_size: u64,
+    pub chunk_count: u32,
+}
+
+impl Storable for BlobMetadata {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
+
+/// Bytes stored in a namespace, string values and blobs combined
+#[derive(CandidType, Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct NamespaceUsage {
+    pub bytes_used: u64,
+    pub byte_quota: u64,
+}
+
+impl Storable for NamespaceUsage {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
@@ -28,3 +28,12 @@ pub enum MigrationErrors {
     UserIndexCanisterIdNotFound,
     RequestCycleFromUserIndexFailed(String),
 }
+
+/// How a user canister is linked to the canisters of the same profile on other subnets
+#[derive(Default, Serialize, Deserialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct CanisterMigrationLinks {
+    /// Where the profile moved to, set once this canister was migrated away
+    pub migrated_to_canister_id: Option<Principal>,
+    /// Where the profile lived before it was migrated to this canister, oldest first
+    pub former_canister_ids: Vec<Principal>,
+}
@@ -1,4 +1,5 @@
 pub mod arg;
+pub mod comment;
 pub mod configuration;
 pub mod error;
 pub mod follow;
@@ -7,8 +8,11 @@ pub mod kv_storage;
 pub mod migration;
 pub mod ml_data;
 pub mod post;
+pub mod prediction;
 pub mod profile;
+pub mod report;
 pub mod session;
+pub mod snapshot;
 pub mod token;
 pub mod cdao;
 pub mod device_id;
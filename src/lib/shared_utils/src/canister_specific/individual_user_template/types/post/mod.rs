@@ -12,8 +12,8 @@ use crate::{
 };
 
 use super::hot_or_not::{
-    BettingStatus, GlobalRoomId, HotOrNotDetails, RoomDetailsV1, SlotDetailsV1, SlotId,
-    StablePrincipal,
+    game_config::HotOrNotGameConfig, BettingStatus, GlobalRoomId, HotOrNotDetails, RoomDetailsV1,
+    SlotDetailsV1, SlotId, StablePrincipal,
 };
 
 #[derive(CandidType, Clone, Deserialize, Debug, Serialize)]
@@ -33,6 +33,49 @@ pub struct Post {
     pub is_nsfw: bool,
     #[serde(default)]
     pub slots_left_to_be_computed: HashSet<SlotId>,
+    #[serde(default)]
+    pub tip_details: PostTipDetails,
+    #[serde(default)]
+    pub hot_or_not_game_config: HotOrNotGameConfig,
+    /// Earlier versions of the editable details, oldest first
+    #[serde(default)]
+    pub revisions: Vec<PostRevision>,
+    /// Visible comments, replies included
+    #[serde(default)]
+    pub comment_count: u64,
+    /// Principals with at least one visible comment, this is what the home feed score counts
+    #[serde(default)]
+    pub commenter_count: u64,
+}
+
+pub const MAX_REVISIONS_KEPT_PER_POST: usize = 20;
+
+/// Details of a post as they were before an edit.
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct PostRevision {
+    pub description: String,
+    pub hashtags: Vec<String>,
+    pub is_nsfw: bool,
+    pub replaced_at: SystemTime,
+}
+
+pub const MAX_TIPS_KEPT_PER_POST: usize = 100;
+
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, Default)]
+pub struct PostTipDetails {
+    pub total_tip_amount: u64,
+    #[serde(default)]
+    pub tip_count: u64,
+    /// The most recent tips, oldest first
+    pub tip_history: Vec<PostTip>,
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct PostTip {
+    pub tipper_principal_id: Principal,
+    pub tipper_canister_id: Principal,
+    pub amount: u64,
+    pub tipped_at: SystemTime,
 }
 
 #[derive(CandidType, Clone, Deserialize, Debug, Serialize)]
@@ -90,6 +133,9 @@ pub struct PostDetailsForFrontend {
     pub hot_or_not_feed_ranking_score: Option<u64>,
     pub hot_or_not_betting_status: Option<BettingStatus>,
     pub is_nsfw: bool,
+    pub tip_count: u64,
+    pub total_tip_amount: u64,
+    pub comment_count: u64,
 }
 
 #[derive(Serialize, CandidType, Deserialize)]
@@ -196,7 +242,20 @@ impl Post {
                 post_principal_map,
                 slot_details_map,
             )),
+            tip_count: self.tip_details.tip_count,
+            total_tip_amount: self.tip_details.total_tip_amount,
+            comment_count: self.comment_count,
+        }
+    }
+
+    pub fn add_tip(&mut self, tip: PostTip) -> u64 {
+        self.tip_details.total_tip_amount += tip.amount;
+        self.tip_details.tip_count += 1;
+        self.tip_details.tip_history.push(tip);
+        if self.tip_details.tip_history.len() > MAX_TIPS_KEPT_PER_POST {
+            self.tip_details.tip_history.remove(0);
         }
+        self.tip_details.total_tip_amount
     }
 
     pub fn increment_share_count(&mut self) -> u64 {
@@ -208,6 +267,20 @@ impl Post {
         id: u64,
         post_details_from_frontend: &PostDetailsFromFrontend,
         current_time: &SystemTime,
+    ) -> Self {
+        Self::new_with_game_config(
+            id,
+            post_details_from_frontend,
+            current_time,
+            HotOrNotGameConfig::default(),
+        )
+    }
+
+    pub fn new_with_game_config(
+        id: u64,
+        post_details_from_frontend: &PostDetailsFromFrontend,
+        current_time: &SystemTime,
+        hot_or_not_game_config: HotOrNotGameConfig,
     ) -> Self {
         Post {
             id,
@@ -226,7 +299,13 @@ impl Post {
             },
             home_feed_score: FeedScore::default(),
             hot_or_not_details: Some(HotOrNotDetails::default()),
-            slots_left_to_be_computed: (1..=48).collect(), // 48 slots
+            slots_left_to_be_computed: (1..=hot_or_not_game_config.maximum_number_of_slots)
+                .collect(),
+            tip_details: PostTipDetails::default(),
+            hot_or_not_game_config,
+            revisions: Vec::new(),
+            comment_count: 0,
+            commenter_count: 0,
         }
     }
 
@@ -273,6 +352,16 @@ impl Post {
         };
         // println!("🥫 post_share_component: {}", post_share_component);
 
+        let tips_component = match self.view_stats.total_view_count {
+            0 => 0,
+            _ => (1000 * 100 * self.tip_details.tip_count) / self.view_stats.total_view_count,
+        };
+
+        let comments_component = match self.view_stats.total_view_count {
+            0 => 0,
+            _ => (1000 * 50 * self.commenter_count) / self.view_stats.total_view_count,
+        };
+
         let age_of_video_in_hours = (current_time
             .duration_since(self.created_at)
             .unwrap_or(Duration::ZERO)
@@ -314,6 +403,8 @@ impl Post {
             + threshold_views_component
             + average_percent_viewed_component
             + post_share_component
+            + tips_component
+            + comments_component
             + age_of_video_component
             + hot_or_not_participation_component;
     }
@@ -412,6 +503,47 @@ impl Post {
     pub fn update_status(&mut self, status: PostStatus) {
         self.status = status;
     }
+
+    /// Drops everything a deleted post no longer needs.
+    /// Identifiers, the game config and tips are kept so settled bets and wallets still resolve.
+    pub fn purge_heavy_fields(&mut self) {
+        self.description = String::new();
+        self.hashtags = Vec::new();
+        self.video_uid = String::new();
+        self.likes = HashSet::new();
+        self.revisions = Vec::new();
+        if let Some(hot_or_not_details) = self.hot_or_not_details.as_mut() {
+            hot_or_not_details.slot_history = Default::default();
+        }
+    }
+
+    /// Keeps the current details as a revision before replacing them.
+    /// Returns false and keeps no revision if nothing changed.
+    pub fn update_details(
+        &mut self,
+        description: String,
+        hashtags: Vec<String>,
+        is_nsfw: bool,
+        current_time: SystemTime,
+    ) -> bool {
+        if self.description == description && self.hashtags == hashtags && self.is_nsfw == is_nsfw {
+            return false;
+        }
+
+        let revision = PostRevision {
+            description: std::mem::replace(&mut self.description, description),
+            hashtags: std::mem::replace(&mut self.hashtags, hashtags),
+            is_nsfw: std::mem::replace(&mut self.is_nsfw, is_nsfw),
+            replaced_at: current_time,
+        };
+
+        self.revisions.push(revision);
+        if self.revisions.len() > MAX_REVISIONS_KEPT_PER_POST {
+            self.revisions.remove(0);
+        }
+
+        true
+    }
 }
 
 #[cfg(test)]
@@ -455,6 +587,150 @@ mod test {
         assert!(post.hot_or_not_details.is_some());
     }
 
+    #[test]
+    fn test_update_details() {
+        let created_at = SystemTime::now();
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                description: "Tpyo in caption".to_string(),
+                hashtags: vec!["#fun".to_string()],
+                video_uid: "abcd1234".to_string(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+                is_nsfw: false,
+            },
+            &created_at,
+        );
+
+        assert!(!post.update_details(
+            "Tpyo in caption".to_string(),
+            vec!["#fun".to_string()],
+            false,
+            created_at,
+        ));
+        assert!(post.revisions.is_empty());
+
+        let edited_at = created_at + Duration::from_secs(60);
+        assert!(post.update_details(
+            "Typo in caption".to_string(),
+            vec!["#fun".to_string(), "#fixed".to_string()],
+            true,
+            edited_at,
+        ));
+        assert_eq!(post.description, "Typo in caption");
+        assert!(post.is_nsfw);
+        assert_eq!(
+            post.revisions,
+            vec![PostRevision {
+                description: "Tpyo in caption".to_string(),
+                hashtags: vec!["#fun".to_string()],
+                is_nsfw: false,
+                replaced_at: edited_at,
+            }]
+        );
+
+        for i in 0..MAX_REVISIONS_KEPT_PER_POST {
+            post.update_details(format!("Edit {}", i), vec![], false, edited_at);
+        }
+        assert_eq!(post.revisions.len(), MAX_REVISIONS_KEPT_PER_POST);
+        assert_eq!(post.revisions[0].description, "Typo in caption");
+    }
+
+    #[test]
+    fn test_add_tip() {
+        let post_created_at = SystemTime::now();
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+                is_nsfw: false,
+            },
+            &post_created_at,
+        );
+        post.view_stats.total_view_count = 100;
+
+        post.recalculate_home_feed_score(&post_created_at);
+        let score_without_tips = post.home_feed_score.current_score;
+
+        let total = post.add_tip(PostTip {
+            tipper_principal_id: Principal::from_slice(&[1]),
+            tipper_canister_id: Principal::from_slice(&[2]),
+            amount: 50,
+            tipped_at: post_created_at,
+        });
+        assert_eq!(total, 50);
+
+        let total = post.add_tip(PostTip {
+            tipper_principal_id: Principal::from_slice(&[3]),
+            tipper_canister_id: Principal::from_slice(&[4]),
+            amount: 25,
+            tipped_at: post_created_at,
+        });
+        assert_eq!(total, 75);
+        assert_eq!(post.tip_details.tip_history.len(), 2);
+
+        post.recalculate_home_feed_score(&post_created_at);
+        assert_eq!(
+            post.home_feed_score.current_score,
+            score_without_tips + (1000 * 100 * 2) / 100
+        );
+
+        for _ in 0..MAX_TIPS_KEPT_PER_POST {
+            post.add_tip(PostTip {
+                tipper_principal_id: Principal::from_slice(&[5]),
+                tipper_canister_id: Principal::from_slice(&[6]),
+                amount: 1,
+                tipped_at: post_created_at,
+            });
+        }
+        // * only the most recent tips are kept, the totals cover all of them
+        assert_eq!(post.tip_details.tip_history.len(), MAX_TIPS_KEPT_PER_POST);
+        assert_eq!(
+            post.tip_details.tip_history[0].tipper_principal_id,
+            Principal::from_slice(&[3])
+        );
+        assert_eq!(
+            post.tip_details.tip_count,
+            MAX_TIPS_KEPT_PER_POST as u64 + 2
+        );
+        assert_eq!(
+            post.tip_details.total_tip_amount,
+            MAX_TIPS_KEPT_PER_POST as u64 + 75
+        );
+    }
+
+    #[test]
+    fn test_commenter_count_feeds_home_feed_score() {
+        let post_created_at = SystemTime::now();
+        let mut post = Post::new(
+            0,
+            &PostDetailsFromFrontend {
+                description: "Doggos and puppers".into(),
+                hashtags: vec!["doggo".into(), "pupper".into()],
+                video_uid: "abcd#1234".into(),
+                creator_consent_for_inclusion_in_hot_or_not: true,
+                is_nsfw: false,
+            },
+            &post_created_at,
+        );
+        post.view_stats.total_view_count = 100;
+
+        post.recalculate_home_feed_score(&post_created_at);
+        let score_without_comments = post.home_feed_score.current_score;
+
+        // * many comments by the same few commenters do not inflate the score
+        post.comment_count = 40;
+        post.commenter_count = 4;
+        post.recalculate_home_feed_score(&post_created_at);
+        assert_eq!(
+            post.home_feed_score.current_score,
+            score_without_comments + (1000 * 50 * 4) / 100
+        );
+    }
+
     #[test]
     fn test_recalculate_home_feed_score_case_1() {
         let (
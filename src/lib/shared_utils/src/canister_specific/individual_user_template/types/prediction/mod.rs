@@ -0,0 +1,565 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    ops::Range,
+    time::{Duration, SystemTime},
+};
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+
+use crate::common::types::app_primitive_type::PostId;
+
+use super::{
+    error::CreatePredictionError,
+    hot_or_not::{
+        BetIdempotencyKey, BetMakerInformedStatus, BetOutcomeForBetMaker, BetPayout,
+        StablePrincipal,
+    },
+};
+
+/// Index of an outcome in `PredictionDetails::outcomes`
+pub type PredictionOutcomeId = u8;
+
+pub const MINIMUM_NUMBER_OF_PREDICTION_OUTCOMES: usize = 2;
+pub const MAXIMUM_NUMBER_OF_PREDICTION_OUTCOMES: usize = 6;
+pub const MAX_PREDICTION_OUTCOME_LABEL_LENGTH_IN_CHARACTERS: usize = 50;
+pub const MAXIMUM_PREDICTION_DURATION_IN_SECONDS: u64 = 30 * 24 * 60 * 60;
+/// Settling a prediction goes over all of its bets in one message
+pub const MAXIMUM_NUMBER_OF_BETS_PER_PREDICTION: u64 = 500;
+/// How long the creator has to decide a prediction once it closes before every bet is refunded
+pub const PREDICTION_RESOLUTION_DEADLINE_IN_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(CandidType, Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum PredictionResolutionMode {
+    /// The creator picks the winning outcome once betting closes.
+    /// Bettors trust the creator to pick honestly, the creator can not bet on it
+    /// but nothing else keeps them from favouring one side.
+    DecidedByCreator,
+    /// The outcome with the most bets at close wins, a tie for the lead refunds every bet
+    DecidedByVoteShare,
+}
+
+#[derive(CandidType, Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum PredictionStatus {
+    Open,
+    Resolved(PredictionOutcomeId),
+    /// Every bet got its stake back and no commission was taken
+    Refunded,
+}
+
+/// A poll or prediction attached to a post, with one pot per named outcome.
+/// Kept apart from the hot or not rooms, so posts without one play exactly as before.
+#[derive(CandidType, Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PredictionDetails {
+    pub outcomes: Vec<String>,
+    pub resolution_mode: PredictionResolutionMode,
+    pub created_at: SystemTime,
+    pub closes_at: SystemTime,
+    /// Taken from the post's game config when the prediction is created
+    pub creator_commission_percentage: u64,
+    pub status: PredictionStatus,
+    pub total_amount_per_outcome: Vec<u64>,
+    pub total_bets_per_outcome: Vec<u64>,
+}
+
+impl Storable for PredictionDetails {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl PredictionDetails {
+    pub fn new(
+        outcomes: Vec<String>,
+        resolution_mode: PredictionResolutionMode,
+        duration_in_seconds: u64,
+        creator_commission_percentage: u64,
+        created_at: SystemTime,
+    ) -> Result<Self, CreatePredictionError> {
+        if !(MINIMUM_NUMBER_OF_PREDICTION_OUTCOMES..=MAXIMUM_NUMBER_OF_PREDICTION_OUTCOMES)
+            .contains(&outcomes.len())
+        {
+            return Err(CreatePredictionError::InvalidNumberOfOutcomes);
+        }
+
+        let outcomes = outcomes
+            .iter()
+            .map(|outcome| outcome.trim().to_string())
+            .collect::<Vec<_>>();
+        let are_labels_valid = outcomes.iter().all(|outcome| {
+            !outcome.is_empty()
+                && outcome.chars().count() <= MAX_PREDICTION_OUTCOME_LABEL_LENGTH_IN_CHARACTERS
+        });
+        let are_labels_unique = outcomes.iter().collect::<BTreeSet<_>>().len() == outcomes.len();
+
+        if !are_labels_valid || !are_labels_unique {
+            return Err(CreatePredictionError::InvalidOutcomeLabel);
+        }
+
+        if duration_in_seconds == 0 || duration_in_seconds > MAXIMUM_PREDICTION_DURATION_IN_SECONDS
+        {
+            return Err(CreatePredictionError::InvalidDuration);
+        }
+
+        let number_of_outcomes = outcomes.len();
+
+        Ok(Self {
+            outcomes,
+            resolution_mode,
+            created_at,
+            closes_at: created_at + Duration::from_secs(duration_in_seconds),
+            creator_commission_percentage,
+            status: PredictionStatus::Open,
+            total_amount_per_outcome: vec![0; number_of_outcomes],
+            total_bets_per_outcome: vec![0; number_of_outcomes],
+        })
+    }
+
+    pub fn is_open_for_bets(&self, current_time: &SystemTime) -> bool {
+        self.status == PredictionStatus::Open && *current_time < self.closes_at
+    }
+
+    /// Past this point a prediction decided by the creator can only be refunded
+    pub fn resolution_deadline(&self) -> SystemTime {
+        self.closes_at + Duration::from_secs(PREDICTION_RESOLUTION_DEADLINE_IN_SECONDS)
+    }
+
+    /// When the settlement timer settles the prediction if nothing else did before
+    pub fn settlement_due_at(&self) -> SystemTime {
+        match self.resolution_mode {
+            PredictionResolutionMode::DecidedByVoteShare => self.closes_at,
+            PredictionResolutionMode::DecidedByCreator => self.resolution_deadline(),
+        }
+    }
+
+    pub fn is_valid_outcome(&self, outcome_id: PredictionOutcomeId) -> bool {
+        (outcome_id as usize) < self.outcomes.len()
+    }
+
+    pub fn record_bet(&mut self, outcome_id: PredictionOutcomeId, amount: u64) {
+        self.total_amount_per_outcome[outcome_id as usize] += amount;
+        self.total_bets_per_outcome[outcome_id as usize] += 1;
+    }
+
+    pub fn total_pot(&self) -> u64 {
+        self.total_amount_per_outcome.iter().sum()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.total_bets_per_outcome.iter().sum::<u64>() >= MAXIMUM_NUMBER_OF_BETS_PER_PREDICTION
+    }
+
+    /// The outcome with the most bets wins. Without a single leader every bet is refunded.
+    pub fn get_status_decided_by_vote_share(&self) -> PredictionStatus {
+        let Some(most_bets) = self.total_bets_per_outcome.iter().max().copied() else {
+            return PredictionStatus::Refunded;
+        };
+
+        let mut leaders = self
+            .total_bets_per_outcome
+            .iter()
+            .enumerate()
+            .filter(|(_, number_of_bets)| **number_of_bets == most_bets);
+
+        match (leaders.next(), leaders.next()) {
+            (Some((outcome_id, _)), None) if most_bets > 0 => {
+                PredictionStatus::Resolved(outcome_id as PredictionOutcomeId)
+            }
+            _ => PredictionStatus::Refunded,
+        }
+    }
+
+    /// Payouts for the bets of the prediction, given as stake and outcome, in the same order.
+    /// Winners get their stake back plus a share of the other outcomes' pots, minus commission,
+    /// in proportion to their stake. If nobody backed the winning outcome every bet is refunded.
+    pub fn get_payouts(&self, bets: &[(u64, PredictionOutcomeId)]) -> Vec<BetPayout> {
+        let refund_every_bet = || {
+            bets.iter()
+                .map(|(amount, _)| BetPayout::Calculated(*amount))
+                .collect()
+        };
+
+        let winning_outcome_id = match self.status {
+            PredictionStatus::Open => {
+                return bets.iter().map(|_| BetPayout::NotCalculatedYet).collect();
+            }
+            PredictionStatus::Refunded => return refund_every_bet(),
+            PredictionStatus::Resolved(winning_outcome_id) => winning_outcome_id,
+        };
+
+        let (winning_pot, losing_pot) = bets.iter().fold(
+            (0u64, 0u64),
+            |(winning_pot, losing_pot), (amount, outcome_id)| {
+                if *outcome_id == winning_outcome_id {
+                    (winning_pot + amount, losing_pot)
+                } else {
+                    (winning_pot, losing_pot + amount)
+                }
+            },
+        );
+
+        if winning_pot == 0 {
+            return refund_every_bet();
+        }
+
+        let distributable_pot = losing_pot - losing_pot * self.creator_commission_percentage / 100;
+
+        bets.iter()
+            .map(|(amount, outcome_id)| {
+                if *outcome_id != winning_outcome_id {
+                    return BetPayout::Calculated(0);
+                }
+
+                let share_of_losing_pot = (distributable_pot as u128 * *amount as u128)
+                    .checked_div(winning_pot as u128)
+                    .unwrap_or_default() as u64;
+
+                BetPayout::Calculated(amount + share_of_losing_pot)
+            })
+            .collect()
+    }
+
+    /// Whatever the bets are not paid out goes to the creator, rounding dust included
+    pub fn get_creator_commission(&self, payouts: &[BetPayout]) -> u64 {
+        let total_paid_out = payouts
+            .iter()
+            .map(|payout| match payout {
+                BetPayout::Calculated(amount) => *amount,
+                BetPayout::NotCalculatedYet => 0,
+            })
+            .sum::<u64>();
+
+        self.total_pot().saturating_sub(total_paid_out)
+    }
+}
+
+/// A prediction waiting to be settled by the settlement timer.
+/// `due_at` comes first so that ordering the jobs orders them by when they are due.
+#[derive(
+    CandidType, Clone, Deserialize, Debug, Serialize, Ord, PartialOrd, Eq, PartialEq, Copy,
+)]
+pub struct PredictionSettlementJob {
+    pub due_at: SystemTime,
+    pub post_id: PostId,
+}
+
+#[derive(
+    CandidType, Clone, Deserialize, Debug, Serialize, Ord, PartialOrd, Eq, PartialEq, Hash,
+)]
+pub struct GlobalPredictionBetId(pub PostId, pub StablePrincipal);
+
+impl Storable for GlobalPredictionBetId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
+
+impl GlobalPredictionBetId {
+    /// Covers every bet on the prediction of the post.
+    /// The management canister id is the smallest principal there is.
+    pub fn range_for_post(post_id: PostId) -> Range<Self> {
+        let lowest_principal = StablePrincipal(Principal::management_canister());
+
+        Self(post_id, lowest_principal.clone())..Self(post_id + 1, lowest_principal)
+    }
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq)]
+pub struct PredictionBetDetails {
+    pub amount: u64,
+    pub outcome_id: PredictionOutcomeId,
+    pub payout: BetPayout,
+    pub bet_maker_canister_id: Principal,
+    pub placed_at: SystemTime,
+    pub bet_maker_informed_status: Option<BetMakerInformedStatus>,
+    #[serde(default)]
+    pub idempotency_key: Option<BetIdempotencyKey>,
+}
+
+impl Storable for PredictionBetDetails {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl PredictionBetDetails {
+    pub fn get_outcome_for_bet_maker(&self, status: &PredictionStatus) -> BetOutcomeForBetMaker {
+        let payout_amount = match self.payout {
+            BetPayout::Calculated(amount) => amount,
+            BetPayout::NotCalculatedYet => 0,
+        };
+
+        match status {
+            PredictionStatus::Open => BetOutcomeForBetMaker::AwaitingResult,
+            PredictionStatus::Refunded => BetOutcomeForBetMaker::Draw(payout_amount),
+            PredictionStatus::Resolved(winning_outcome_id)
+                if *winning_outcome_id == self.outcome_id =>
+            {
+                BetOutcomeForBetMaker::Won(payout_amount)
+            }
+            // * nobody backed the winning outcome, so every stake came back
+            PredictionStatus::Resolved(_) if payout_amount > 0 => {
+                BetOutcomeForBetMaker::Draw(payout_amount)
+            }
+            PredictionStatus::Resolved(_) => BetOutcomeForBetMaker::Lost,
+        }
+    }
+}
+
+/// A bet this profile placed on someone's prediction
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct PlacedPredictionBetDetail {
+    pub canister_id: Principal,
+    pub post_id: PostId,
+    pub outcome_id: PredictionOutcomeId,
+    pub amount_bet: u64,
+    pub bet_placed_at: SystemTime,
+    pub outcome_received: BetOutcomeForBetMaker,
+}
+
+/// A prediction bet whose amount has left the bet maker's balance
+/// but that the post creator's canister has not confirmed yet
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct PendingPredictionBet {
+    pub post_canister_id: Principal,
+    pub post_id: PostId,
+    pub outcome_id: PredictionOutcomeId,
+    pub amount: u64,
+    pub idempotency_key: BetIdempotencyKey,
+    pub placed_at: SystemTime,
+    /// Set once the call placing the bet has failed without saying whether the bet was taken
+    pub awaiting_reconciliation: bool,
+}
+
+/// What the post creator's canister has on record for a prediction bet,
+/// used by the bet maker's canister to settle a bet it never got a reply for
+#[derive(CandidType, Clone, Deserialize, Debug, Serialize, PartialEq, Eq)]
+pub struct PredictionBetReconciliationDetails {
+    pub outcome_id: PredictionOutcomeId,
+    pub amount: u64,
+    pub idempotency_key: Option<BetIdempotencyKey>,
+    pub outcome: BetOutcomeForBetMaker,
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn prediction_details(number_of_outcomes: usize) -> PredictionDetails {
+        PredictionDetails::new(
+            (0..number_of_outcomes)
+                .map(|i| format!("Outcome {i}"))
+                .collect(),
+            PredictionResolutionMode::DecidedByCreator,
+            60 * 60,
+            10,
+            SystemTime::now(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_prediction_details() {
+        let now = SystemTime::now();
+        let new = |outcomes: &[&str], duration_in_seconds| {
+            PredictionDetails::new(
+                outcomes.iter().map(|outcome| outcome.to_string()).collect(),
+                PredictionResolutionMode::DecidedByVoteShare,
+                duration_in_seconds,
+                10,
+                now,
+            )
+        };
+
+        assert_eq!(
+            new(&["Yes"], 60).unwrap_err(),
+            CreatePredictionError::InvalidNumberOfOutcomes
+        );
+        assert_eq!(
+            new(&["1", "2", "3", "4", "5", "6", "7"], 60).unwrap_err(),
+            CreatePredictionError::InvalidNumberOfOutcomes
+        );
+        assert_eq!(
+            new(&["Yes", "  "], 60).unwrap_err(),
+            CreatePredictionError::InvalidOutcomeLabel
+        );
+        assert_eq!(
+            new(&["Yes", " Yes"], 60).unwrap_err(),
+            CreatePredictionError::InvalidOutcomeLabel
+        );
+        assert_eq!(
+            new(&["Yes", &"a".repeat(51)], 60).unwrap_err(),
+            CreatePredictionError::InvalidOutcomeLabel
+        );
+        assert_eq!(
+            new(&["Yes", "No"], 0).unwrap_err(),
+            CreatePredictionError::InvalidDuration
+        );
+        assert_eq!(
+            new(&["Yes", "No"], MAXIMUM_PREDICTION_DURATION_IN_SECONDS + 1).unwrap_err(),
+            CreatePredictionError::InvalidDuration
+        );
+
+        let prediction_details = new(&["Yes ", "No"], 60).unwrap();
+        assert_eq!(prediction_details.outcomes, vec!["Yes", "No"]);
+        assert_eq!(prediction_details.total_amount_per_outcome, vec![0, 0]);
+        assert!(prediction_details.is_open_for_bets(&now));
+        assert!(!prediction_details.is_open_for_bets(&(now + Duration::from_secs(60))));
+        assert!(prediction_details.is_valid_outcome(1));
+        assert!(!prediction_details.is_valid_outcome(2));
+    }
+
+    #[test]
+    fn test_get_status_decided_by_vote_share() {
+        let mut prediction_details = prediction_details(3);
+        assert_eq!(
+            prediction_details.get_status_decided_by_vote_share(),
+            PredictionStatus::Refunded
+        );
+
+        prediction_details.record_bet(1, 100);
+        prediction_details.record_bet(2, 500);
+        assert_eq!(
+            prediction_details.get_status_decided_by_vote_share(),
+            PredictionStatus::Refunded
+        );
+
+        // * the number of bets decides, not the amount staked
+        prediction_details.record_bet(1, 10);
+        assert_eq!(
+            prediction_details.get_status_decided_by_vote_share(),
+            PredictionStatus::Resolved(1)
+        );
+        assert_eq!(prediction_details.total_pot(), 610);
+    }
+
+    #[test]
+    fn test_get_payouts() {
+        let mut prediction_details = prediction_details(3);
+        let bets = [(100, 0), (300, 0), (200, 1), (400, 2)];
+        bets.iter().for_each(|(amount, outcome_id)| {
+            prediction_details.record_bet(*outcome_id, *amount);
+        });
+
+        assert_eq!(
+            prediction_details.get_payouts(&bets),
+            vec![BetPayout::NotCalculatedYet; 4]
+        );
+
+        // * 540 of the 600 losing pot goes to outcome 0 by stake, 60 to the creator
+        prediction_details.status = PredictionStatus::Resolved(0);
+        let payouts = prediction_details.get_payouts(&bets);
+        assert_eq!(
+            payouts,
+            vec![
+                BetPayout::Calculated(235),
+                BetPayout::Calculated(705),
+                BetPayout::Calculated(0),
+                BetPayout::Calculated(0),
+            ]
+        );
+        assert_eq!(prediction_details.get_creator_commission(&payouts), 60);
+
+        prediction_details.status = PredictionStatus::Refunded;
+        let payouts = prediction_details.get_payouts(&bets);
+        assert_eq!(payouts[3], BetPayout::Calculated(400));
+        assert_eq!(prediction_details.get_creator_commission(&payouts), 0);
+    }
+
+    #[test]
+    fn test_get_outcome_for_bet_maker() {
+        let mut prediction_bet_details = PredictionBetDetails {
+            amount: 100,
+            outcome_id: 1,
+            payout: BetPayout::NotCalculatedYet,
+            bet_maker_canister_id: Principal::anonymous(),
+            placed_at: SystemTime::now(),
+            bet_maker_informed_status: None,
+            idempotency_key: None,
+        };
+
+        assert_eq!(
+            prediction_bet_details.get_outcome_for_bet_maker(&PredictionStatus::Open),
+            BetOutcomeForBetMaker::AwaitingResult
+        );
+
+        prediction_bet_details.payout = BetPayout::Calculated(0);
+        assert_eq!(
+            prediction_bet_details.get_outcome_for_bet_maker(&PredictionStatus::Resolved(0)),
+            BetOutcomeForBetMaker::Lost
+        );
+
+        prediction_bet_details.payout = BetPayout::Calculated(100);
+        assert_eq!(
+            prediction_bet_details.get_outcome_for_bet_maker(&PredictionStatus::Resolved(0)),
+            BetOutcomeForBetMaker::Draw(100)
+        );
+        assert_eq!(
+            prediction_bet_details.get_outcome_for_bet_maker(&PredictionStatus::Refunded),
+            BetOutcomeForBetMaker::Draw(100)
+        );
+
+        prediction_bet_details.payout = BetPayout::Calculated(250);
+        assert_eq!(
+            prediction_bet_details.get_outcome_for_bet_maker(&PredictionStatus::Resolved(1)),
+            BetOutcomeForBetMaker::Won(250)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_prediction_payouts_conserve_tokens(
+            bets in prop::collection::vec((1u64..1_000_000, 0u8..6), 0..50),
+            winning_outcome_id in 0u8..6,
+            creator_commission_percentage in 0u64..100,
+        ) {
+            let mut prediction_details = prediction_details(6);
+            prediction_details.creator_commission_percentage = creator_commission_percentage;
+            bets.iter().for_each(|(amount, outcome_id)| {
+                prediction_details.record_bet(*outcome_id, *amount);
+            });
+            prediction_details.status = PredictionStatus::Resolved(winning_outcome_id);
+
+            let payouts = prediction_details.get_payouts(&bets);
+            let commission = prediction_details.get_creator_commission(&payouts);
+
+            let mut total_paid_out = 0;
+            for ((amount, outcome_id), payout) in bets.iter().zip(&payouts) {
+                let BetPayout::Calculated(payout) = payout else {
+                    panic!("payout not calculated for a resolved prediction");
+                };
+                if *outcome_id == winning_outcome_id {
+                    prop_assert!(payout >= amount);
+                }
+                total_paid_out += payout;
+            }
+
+            prop_assert_eq!(total_paid_out + commission, prediction_details.total_pot());
+        }
+    }
+}
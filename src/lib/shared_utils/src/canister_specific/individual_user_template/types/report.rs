@@ -0,0 +1,116 @@
+use std::time::SystemTime;
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::common::types::app_primitive_type::PostId;
+
+pub const MAX_POST_REPORT_REASON_LENGTH_IN_CHARACTERS: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub enum PostReportReason {
+    Nudity,
+    Violence,
+    Spam,
+    Harassment,
+    Misinformation,
+    Other(String),
+}
+
+impl PostReportReason {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PostReportReason::Other(reason) => {
+                !reason.trim().is_empty()
+                    && reason.chars().count() <= MAX_POST_REPORT_REASON_LENGTH_IN_CHARACTERS
+            }
+            _ => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PostReport {
+    pub reporter_principal_id: Principal,
+    pub reason: PostReportReason,
+    pub reported_at: SystemTime,
+}
+
+/// All reports received for a post, one per reporter.
+/// Reports up to `reports_dismissed` were already reviewed and do not count towards the threshold.
+#[derive(Serialize, Deserialize, Clone, CandidType, Debug, PartialEq, Eq, Default)]
+pub struct PostReports {
+    pub reports: Vec<PostReport>,
+    pub reports_dismissed: u64,
+}
+
+impl PostReports {
+    pub fn has_reported(&self, principal: &Principal) -> bool {
+        self.reports
+            .iter()
+            .any(|report| report.reporter_principal_id == *principal)
+    }
+
+    pub fn pending_report_count(&self) -> u64 {
+        (self.reports.len() as u64).saturating_sub(self.reports_dismissed)
+    }
+
+    pub fn pending_reports(&self) -> &[PostReport] {
+        &self.reports[self.reports_dismissed as usize..]
+    }
+}
+
+/// What the individual canister tells user_index about the reports of one of its posts
+#[derive(Serialize, Deserialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PostReportSummary {
+    pub post_creator_canister_id: Principal,
+    pub post_id: PostId,
+    pub pending_report_count: u64,
+    pub pending_reports: Vec<PostReport>,
+    pub banned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, CandidType, Debug, PartialEq, Eq)]
+pub enum PostReportReviewDecision {
+    /// Bans the post, or keeps it banned
+    Ban,
+    /// Clears the pending reports and lifts a ban caused by them
+    Dismiss,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_post_reports() {
+        let reporter = Principal::self_authenticating((0u64).to_ne_bytes());
+        let mut post_reports = PostReports::default();
+        assert!(!post_reports.has_reported(&reporter));
+
+        post_reports.reports.push(PostReport {
+            reporter_principal_id: reporter,
+            reason: PostReportReason::Spam,
+            reported_at: SystemTime::now(),
+        });
+        assert!(post_reports.has_reported(&reporter));
+        assert_eq!(post_reports.pending_report_count(), 1);
+
+        post_reports.reports_dismissed = 1;
+        assert_eq!(post_reports.pending_report_count(), 0);
+        assert!(post_reports.pending_reports().is_empty());
+        // * a dismissed reporter still can not report again
+        assert!(post_reports.has_reported(&reporter));
+    }
+
+    #[test]
+    fn test_post_report_reason_is_valid() {
+        assert!(PostReportReason::Nudity.is_valid());
+        assert!(PostReportReason::Other("Stolen video".into()).is_valid());
+        assert!(!PostReportReason::Other(" ".into()).is_valid());
+        assert!(!PostReportReason::Other(
+            "a".repeat(MAX_POST_REPORT_REASON_LENGTH_IN_CHARACTERS + 1)
+        )
+        .is_valid());
+    }
+}
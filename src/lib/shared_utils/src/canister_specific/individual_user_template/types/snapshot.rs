@@ -0,0 +1,53 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SnapshotChunkInfo {
+    pub length: u64,
+    // * SHA-256 of the chunk bytes
+    pub checksum: Vec<u8>,
+}
+
+/// Describes a binary snapshot.
+/// Chunks are downloaded and uploaded by their index in `chunks`.
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SnapshotManifest {
+    pub schema_version: u32,
+    pub total_length: u64,
+    pub chunks: Vec<SnapshotChunkInfo>,
+}
+
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SnapshotSaveProgress {
+    pub chunks_saved: u64,
+    /// Set once every record is saved
+    pub manifest: Option<SnapshotManifest>,
+}
+
+impl SnapshotSaveProgress {
+    pub fn is_complete(&self) -> bool {
+        self.manifest.is_some()
+    }
+}
+
+#[derive(CandidType, Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SnapshotRestoreProgress {
+    pub chunks_restored: u64,
+    pub total_chunks: u64,
+}
+
+impl SnapshotRestoreProgress {
+    pub fn is_complete(&self) -> bool {
+        self.chunks_restored == self.total_chunks
+    }
+}
+
+#[derive(CandidType, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    NoSnapshot,
+    ChunkIndexOutOfRange,
+    ChecksumMismatch,
+    MissingChunks,
+    UnsupportedSchemaVersion,
+    MalformedChunk(String),
+}
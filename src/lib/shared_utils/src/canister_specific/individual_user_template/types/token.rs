@@ -1,12 +1,15 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::SystemTime};
 
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 use serde_json_any_key::*;
 
-use crate::common::types::utility_token::token_event::{
-    HotOrNotOutcomePayoutEvent, MintEvent, StakeEvent, TokenEvent,
-    HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE, HOT_OR_NOT_BET_WINNINGS_MULTIPLIER,
+use crate::common::types::{
+    app_primitive_type::PostId,
+    utility_token::token_event::{
+        HotOrNotBetWithdrawalEvent, HotOrNotOutcomePayoutEvent, MintEvent, PostTipEvent,
+        PredictionOutcomePayoutEvent, StakeEvent, TokenEvent,
+    },
 };
 
 #[derive(Default, Clone, Deserialize, CandidType, Debug, Serialize)]
@@ -48,23 +51,62 @@ impl TokenBalance {
                 StakeEvent::BetOnHotOrNotPost { .. } => {
                     // self.utility_token_balance -= bet_amount;
                 }
+                StakeEvent::BetOnPredictionPost { .. } => {
+                    // * balance is adjusted before the creator's canister is called
+                }
             },
-            TokenEvent::HotOrNotOutcomePayout { details, .. } => match details {
-                HotOrNotOutcomePayoutEvent::CommissionFromHotOrNotBet {
-                    room_pot_total_amount,
-                    ..
-                } => {
-                    self.utility_token_balance +=
-                        room_pot_total_amount * HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE / 100;
-                    self.lifetime_earnings +=
-                        room_pot_total_amount * HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE / 100;
+            TokenEvent::HotOrNotOutcomePayout {
+                amount, details, ..
+            } => match details {
+                // * commission percentage lives in the post's game config, so the
+                // * amount computed at tabulation time is the source of truth
+                HotOrNotOutcomePayoutEvent::CommissionFromHotOrNotBet { .. } => {
+                    self.utility_token_balance += amount;
+                    self.lifetime_earnings += amount;
                 }
                 HotOrNotOutcomePayoutEvent::WinningsEarnedFromBet {
-                    winnings_amount, ..
+                    winnings_amount,
+                    amount_bet,
+                    ..
                 } => {
                     self.utility_token_balance += winnings_amount;
-                    self.lifetime_earnings +=
-                        get_earnings_amount_from_winnings_amount(winnings_amount);
+                    self.lifetime_earnings += winnings_amount.saturating_sub(*amount_bet);
+                }
+            },
+            TokenEvent::PostTip {
+                amount, details, ..
+            } => match details {
+                PostTipEvent::TipSent { .. } => {
+                    // * balance is adjusted before the creator's canister is called
+                }
+                PostTipEvent::TipReceived { .. } => {
+                    self.utility_token_balance += amount;
+                    self.lifetime_earnings += amount;
+                }
+            },
+            TokenEvent::HotOrNotBetWithdrawal {
+                amount, details, ..
+            } => match details {
+                HotOrNotBetWithdrawalEvent::BetCancelled { .. } => {
+                    self.utility_token_balance += amount;
+                }
+                HotOrNotBetWithdrawalEvent::BetCashedOut { amount_bet, .. } => {
+                    self.utility_token_balance += amount;
+                    self.lifetime_earnings += amount.saturating_sub(*amount_bet);
+                }
+            },
+            TokenEvent::PredictionOutcomePayout {
+                amount, details, ..
+            } => match details {
+                PredictionOutcomePayoutEvent::CommissionFromPrediction { .. } => {
+                    self.utility_token_balance += amount;
+                    self.lifetime_earnings += amount;
+                }
+                PredictionOutcomePayoutEvent::WinningsEarnedFromPrediction {
+                    amount_bet, ..
+                } => {
+                    self.utility_token_balance += amount;
+                    self.lifetime_earnings += amount.saturating_sub(*amount_bet);
                 }
             },
         }
@@ -92,13 +134,67 @@ impl TokenBalance {
     pub fn adjust_balance_for_failed_bet_placement(&mut self, bet_amount: u64) {
         self.utility_token_balance += bet_amount;
     }
+
+    // same as bets, the amount leaves the balance before the recipient canister is called
+    pub fn adjust_balance_pre_transfer(&mut self, amount: u64) {
+        self.utility_token_balance -= amount;
+    }
+
+    pub fn adjust_balance_for_failed_transfer(&mut self, amount: u64) {
+        self.utility_token_balance += amount;
+    }
+
+    // the amount was already taken out by `adjust_balance_pre_transfer`,
+    // so it is put back before the transfer event debits it for good
+    pub fn record_completed_transfer(
+        &mut self,
+        amount: u64,
+        to_account: Principal,
+        timestamp: SystemTime,
+    ) {
+        self.utility_token_balance += amount;
+        self.handle_token_event(TokenEvent::Transfer {
+            amount,
+            to_account,
+            timestamp,
+        });
+    }
 }
 
-fn get_earnings_amount_from_winnings_amount(winnings_amount: &u64) -> u64 {
-    let comission_subtracted_bet_amount = winnings_amount / HOT_OR_NOT_BET_WINNINGS_MULTIPLIER;
-    let bet_amount = comission_subtracted_bet_amount * 100
-        / (100 - HOT_OR_NOT_BET_CREATOR_COMMISSION_PERCENTAGE);
-    winnings_amount - bet_amount
+/// Chosen by the sender's canister so that the recipient's canister
+/// can tell whether it already credited a transfer
+pub type TokenTransferIdempotencyKey = u64;
+
+/// Recipients keep the keys of this many of the latest transfers from each sender
+pub const RECEIVED_TOKEN_TRANSFER_KEYS_KEPT_PER_SENDER: usize = 100;
+
+/// Tokens that have left the sender's balance
+/// but that the recipient's canister has not confirmed yet
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PendingTokenTransfer {
+    pub to_user_principal_id: Principal,
+    pub recipient_canister_id: Principal,
+    pub amount: u64,
+    pub initiated_at: SystemTime,
+    /// Set once the call to the recipient's canister has failed without saying
+    /// whether the tokens were credited. Only these are reconciled.
+    #[serde(default)]
+    pub awaiting_reconciliation: bool,
+}
+
+/// A tip that has left the tipper's balance
+/// but that the post creator's canister has not confirmed yet.
+/// Shares its idempotency keys with [`PendingTokenTransfer`].
+#[derive(Deserialize, Serialize, Clone, CandidType, Debug, PartialEq, Eq)]
+pub struct PendingPostTip {
+    pub post_canister_id: Principal,
+    pub post_id: PostId,
+    pub amount: u64,
+    pub initiated_at: SystemTime,
+    /// Set once the call to the post creator's canister has failed without saying
+    /// whether the tip was credited. Only these are reconciled.
+    #[serde(default)]
+    pub awaiting_reconciliation: bool,
 }
 
 #[cfg(test)]
@@ -113,7 +209,9 @@ mod test {
             get_mock_user_bob_principal_id,
         };
 
-        use crate::canister_specific::individual_user_template::types::hot_or_not::BetDirection;
+        use crate::canister_specific::individual_user_template::types::hot_or_not::{
+            BetDirection, BetOutcomeForBetMaker,
+        };
 
         use super::*;
 
@@ -224,6 +322,186 @@ mod test {
             // this event is special and does not change the balance
             assert_eq!(token_balance.utility_token_balance, 1500);
         }
+
+        #[test]
+        fn test_handle_token_event_for_post_tips() {
+            let mut token_balance = TokenBalance {
+                utility_token_balance: 1000,
+                ..Default::default()
+            };
+
+            token_balance.adjust_balance_pre_transfer(100);
+            token_balance.handle_token_event(TokenEvent::PostTip {
+                amount: 100,
+                details: PostTipEvent::TipSent {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 0,
+                    to_account: get_mock_user_alice_principal_id(),
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 900);
+            assert_eq!(token_balance.lifetime_earnings, 0);
+
+            token_balance.handle_token_event(TokenEvent::PostTip {
+                amount: 50,
+                details: PostTipEvent::TipReceived {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 0,
+                    from_account: get_mock_user_bob_principal_id(),
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 950);
+            assert_eq!(token_balance.lifetime_earnings, 50);
+        }
+
+        #[test]
+        fn test_handle_token_event_for_bet_withdrawals() {
+            let mut token_balance = TokenBalance {
+                utility_token_balance: 900,
+                ..Default::default()
+            };
+
+            token_balance.handle_token_event(TokenEvent::HotOrNotBetWithdrawal {
+                amount: 100,
+                details: HotOrNotBetWithdrawalEvent::BetCancelled {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 0,
+                    slot_id: 1,
+                    room_id: 1,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 1000);
+            assert_eq!(token_balance.lifetime_earnings, 0);
+
+            token_balance.adjust_balance_pre_bet(100);
+            token_balance.handle_token_event(TokenEvent::HotOrNotBetWithdrawal {
+                amount: 120,
+                details: HotOrNotBetWithdrawalEvent::BetCashedOut {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 1,
+                    slot_id: 1,
+                    room_id: 1,
+                    amount_bet: 100,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 1020);
+            assert_eq!(token_balance.lifetime_earnings, 20);
+        }
+
+        #[test]
+        fn test_handle_token_event_for_hot_or_not_winnings() {
+            let mut token_balance = TokenBalance::default();
+
+            // * a parimutuel payout, not a multiple of the stake
+            token_balance.handle_token_event(TokenEvent::HotOrNotOutcomePayout {
+                amount: 145,
+                details: HotOrNotOutcomePayoutEvent::WinningsEarnedFromBet {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 0,
+                    slot_id: 1,
+                    room_id: 1,
+                    event_outcome: BetOutcomeForBetMaker::Won(145),
+                    winnings_amount: 145,
+                    amount_bet: 100,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 145);
+            assert_eq!(token_balance.lifetime_earnings, 45);
+
+            // * a draw pays back less than the stake
+            token_balance.handle_token_event(TokenEvent::HotOrNotOutcomePayout {
+                amount: 90,
+                details: HotOrNotOutcomePayoutEvent::WinningsEarnedFromBet {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 1,
+                    slot_id: 1,
+                    room_id: 1,
+                    event_outcome: BetOutcomeForBetMaker::Draw(90),
+                    winnings_amount: 90,
+                    amount_bet: 100,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 235);
+            assert_eq!(token_balance.lifetime_earnings, 45);
+        }
+
+        #[test]
+        fn test_handle_token_event_for_prediction_payouts() {
+            let mut token_balance = TokenBalance::default();
+
+            token_balance.handle_token_event(TokenEvent::PredictionOutcomePayout {
+                amount: 60,
+                details: PredictionOutcomePayoutEvent::CommissionFromPrediction {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 0,
+                    pot_total_amount: 600,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 60);
+            assert_eq!(token_balance.lifetime_earnings, 60);
+
+            token_balance.handle_token_event(TokenEvent::PredictionOutcomePayout {
+                amount: 235,
+                details: PredictionOutcomePayoutEvent::WinningsEarnedFromPrediction {
+                    post_canister_id: get_mock_user_alice_canister_id(),
+                    post_id: 1,
+                    event_outcome: BetOutcomeForBetMaker::Won(235),
+                    amount_bet: 100,
+                    winnings_amount: 235,
+                },
+                timestamp: SystemTime::now(),
+            });
+
+            assert_eq!(token_balance.utility_token_balance, 295);
+            assert_eq!(token_balance.lifetime_earnings, 195);
+        }
+
+        #[test]
+        fn test_transfer_balance_adjustments() {
+            let mut token_balance = TokenBalance {
+                utility_token_balance: 1000,
+                ..Default::default()
+            };
+
+            token_balance.adjust_balance_pre_transfer(300);
+            assert_eq!(token_balance.utility_token_balance, 700);
+
+            token_balance.adjust_balance_for_failed_transfer(300);
+            assert_eq!(token_balance.utility_token_balance, 1000);
+            assert!(token_balance.utility_token_transaction_history.is_empty());
+
+            token_balance.adjust_balance_pre_transfer(300);
+            token_balance.record_completed_transfer(
+                300,
+                get_mock_user_bob_principal_id(),
+                SystemTime::now(),
+            );
+
+            assert_eq!(token_balance.utility_token_balance, 700);
+            assert_eq!(token_balance.utility_token_transaction_history.len(), 1);
+            assert!(matches!(
+                token_balance
+                    .utility_token_transaction_history
+                    .last_key_value()
+                    .unwrap()
+                    .1,
+                TokenEvent::Transfer { amount: 300, .. }
+            ));
+        }
     }
 
     mod test_get_earnings_amount_from_winnings_amount {
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use crate::canister_specific::user_index::types::UpgradeStatus;
 
 pub mod args;
+pub mod user_directory;
+pub mod user_migration;
 pub mod well_known_principal;
 
 #[derive(Default, Clone, CandidType, Serialize, Deserialize)]
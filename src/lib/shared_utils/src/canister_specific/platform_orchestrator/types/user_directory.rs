@@ -0,0 +1,32 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserDirectoryEntry {
+    pub user_principal_id: Principal,
+    pub user_canister_id: Principal,
+    pub subnet_orchestrator_canister_id: Principal,
+    pub unique_user_name: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum UserDirectoryLookup {
+    UserPrincipalId(Principal),
+    UniqueUserName(String),
+}
+
+/// Sent by a subnet orchestrator whenever one of its users changes.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum UserDirectoryUpdate {
+    UserSignedUp {
+        user_principal_id: Principal,
+        user_canister_id: Principal,
+    },
+    UniqueUserNameSet {
+        user_principal_id: Principal,
+        unique_user_name: String,
+    },
+    UserDeleted {
+        user_principal_id: Principal,
+    },
+}
@@ -0,0 +1,64 @@
+use std::time::SystemTime;
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::canister_specific::individual_user_template::types::snapshot::SnapshotManifest;
+
+/// Steps of a migration in the order they run.
+/// A migration that failed is resumed from the step it stopped at.
+/// Until the switch over, a migration can be rolled back instead, which runs the
+/// `RollBack*` steps in order.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserMigrationStep {
+    ReserveTargetCanister,
+    SnapshotSourceCanister,
+    SendSnapshotManifest,
+    CopySnapshotChunks {
+        next_chunk_index: u64,
+    },
+    RestoreTargetCanister,
+    /// Makes the target canister the user's canister and points the source canister at it
+    SwitchOverToTarget,
+    /// Releases the user's records on the source subnet, the source canister is kept as a pointer
+    RecycleSourceCanister,
+    Completed,
+    RollBackTargetCanister,
+    RollBackSourceCanister,
+    RolledBack,
+}
+
+impl UserMigrationStep {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            UserMigrationStep::Completed | UserMigrationStep::RolledBack
+        )
+    }
+
+    /// Once the target canister may have taken over, the migration can only go forward
+    pub fn can_be_rolled_back(&self) -> bool {
+        matches!(
+            self,
+            UserMigrationStep::ReserveTargetCanister
+                | UserMigrationStep::SnapshotSourceCanister
+                | UserMigrationStep::SendSnapshotManifest
+                | UserMigrationStep::CopySnapshotChunks { .. }
+                | UserMigrationStep::RestoreTargetCanister
+        )
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserMigration {
+    pub user_principal_id: Principal,
+    pub source_subnet_orchestrator_canister_id: Principal,
+    pub source_canister_id: Principal,
+    pub target_subnet_orchestrator_canister_id: Principal,
+    pub target_canister_id: Option<Principal>,
+    pub unique_user_name: Option<String>,
+    pub snapshot_manifest: Option<SnapshotManifest>,
+    pub step: UserMigrationStep,
+    pub started_at: SystemTime,
+    pub last_error: Option<String>,
+}
@@ -69,3 +69,94 @@ impl Default for BroadcastCallStatus {
         }
     }
 }
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum SignupChallenge {
+    /// Reject every signup over the quota.
+    Reject,
+    /// Only principals on the signup allow list may sign up over the quota.
+    AllowList,
+    /// Signups over the quota must present a nonce such that
+    /// `sha256(caller principal bytes || nonce as big endian)` starts with
+    /// `difficulty_bits` zero bits.
+    ProofOfWork { difficulty_bits: u8 },
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SignupRateLimitConfig {
+    pub window_duration_in_seconds: u64,
+    /// `None` leaves signups unlimited.
+    pub max_signups_per_window: Option<u64>,
+    /// A cooldown starts once the available canister pool shrinks to this size.
+    pub pool_drain_threshold: u64,
+    /// `0` disables the pool drain cooldown.
+    pub pool_drain_cooldown_in_seconds: u64,
+    /// Applied to signups that hit the quota or arrive during a cooldown.
+    pub challenge: SignupChallenge,
+}
+
+impl Default for SignupRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            window_duration_in_seconds: 60 * 60,
+            max_signups_per_window: None,
+            pool_drain_threshold: 0,
+            pool_drain_cooldown_in_seconds: 0,
+            challenge: SignupChallenge::Reject,
+        }
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SignupRejectionReason {
+    QuotaExceeded,
+    PoolDrainCooldown,
+    ChallengeFailed,
+}
+
+impl Display for SignupRejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignupRejectionReason::QuotaExceeded => write!(f, "Signup quota exceeded"),
+            SignupRejectionReason::PoolDrainCooldown => {
+                write!(
+                    f,
+                    "Signups are cooling down after the canister pool drained"
+                )
+            }
+            SignupRejectionReason::ChallengeFailed => write!(f, "Signup challenge failed"),
+        }
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct SignupRejectionStats {
+    pub quota_exceeded: u64,
+    pub pool_drain_cooldown: u64,
+    pub challenge_failed: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccountDeletionRequest {
+    pub canister_id: Principal,
+    pub requested_at: SystemTime,
+    /// The account is deleted on the first deletion run after this time.
+    pub scheduled_for: SystemTime,
+    pub in_progress: bool,
+    /// Set when the last deletion attempt failed. Failed deletions are retried.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UsernameChange {
+    pub username: String,
+    pub changed_at: SystemTime,
+}
+
+/// A username released by a rename. It keeps redirecting to its previous owner,
+/// and nobody else can claim it, until `reserved_until`.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReservedUsername {
+    pub user_principal_id: Principal,
+    pub reserved_until: SystemTime,
+}
@@ -3,8 +3,9 @@ use std::time::SystemTime;
 use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 
-use crate::canister_specific::individual_user_template::types::hot_or_not::{
-    BetDirection, BetOutcomeForBetMaker,
+use crate::canister_specific::individual_user_template::types::{
+    hot_or_not::{BetDirection, BetOutcomeForBetMaker},
+    prediction::PredictionOutcomeId,
 };
 
 #[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq, Serialize)]
@@ -35,6 +36,21 @@ pub enum TokenEvent {
         details: HotOrNotOutcomePayoutEvent,
         timestamp: SystemTime,
     },
+    PostTip {
+        amount: u64,
+        details: PostTipEvent,
+        timestamp: SystemTime,
+    },
+    HotOrNotBetWithdrawal {
+        amount: u64,
+        details: HotOrNotBetWithdrawalEvent,
+        timestamp: SystemTime,
+    },
+    PredictionOutcomePayout {
+        amount: u64,
+        details: PredictionOutcomePayoutEvent,
+        timestamp: SystemTime,
+    },
 }
 
 impl TokenEvent {
@@ -68,6 +84,12 @@ pub enum StakeEvent {
         bet_amount: u64,
         bet_direction: BetDirection,
     },
+    BetOnPredictionPost {
+        post_canister_id: Principal,
+        post_id: u64,
+        bet_amount: u64,
+        outcome_id: PredictionOutcomeId,
+    },
 }
 
 #[derive(Clone, CandidType, Deserialize, Serialize, Debug, PartialEq, Eq)]
@@ -86,6 +108,56 @@ pub enum HotOrNotOutcomePayoutEvent {
         room_id: u64,
         event_outcome: BetOutcomeForBetMaker,
         winnings_amount: u64,
+        /// 0 for events recorded before the stake was carried along
+        #[serde(default)]
+        amount_bet: u64,
+    },
+}
+
+#[derive(Clone, CandidType, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum PostTipEvent {
+    TipSent {
+        post_canister_id: Principal,
+        post_id: u64,
+        to_account: Principal,
+    },
+    TipReceived {
+        post_canister_id: Principal,
+        post_id: u64,
+        from_account: Principal,
+    },
+}
+
+#[derive(Clone, CandidType, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum HotOrNotBetWithdrawalEvent {
+    BetCancelled {
+        post_canister_id: Principal,
+        post_id: u64,
+        slot_id: u8,
+        room_id: u64,
+    },
+    BetCashedOut {
+        post_canister_id: Principal,
+        post_id: u64,
+        slot_id: u8,
+        room_id: u64,
+        amount_bet: u64,
+    },
+}
+
+#[derive(Clone, CandidType, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub enum PredictionOutcomePayoutEvent {
+    CommissionFromPrediction {
+        post_canister_id: Principal,
+        post_id: u64,
+        pot_total_amount: u64,
+    },
+    WinningsEarnedFromPrediction {
+        post_canister_id: Principal,
+        post_id: u64,
+        event_outcome: BetOutcomeForBetMaker,
+        amount_bet: u64,
+        winnings_amount: u64,
     },
 }
 
@@ -16,6 +16,14 @@ pub fn is_reclaim_canister_id() -> Result<(), String> {
 }
 
 
+pub fn is_caller_controller_or_reclaim_canister_id() -> Result<(), String> {
+    if is_controller(&caller()) {
+        return Ok(());
+    }
+
+    is_reclaim_canister_id()
+}
+
 pub fn is_caller_global_admin() -> Result<(), String> {
     if !caller().to_string().eq(GLOBAL_SUPER_ADMIN_USER_ID) {
         return Err("Unauthorize".into())
@@ -0,0 +1,98 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 24;
+
+/// Names that could be mistaken for the platform or its staff.
+pub const RESERVED_USERNAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "anonymous",
+    "help",
+    "hotornot",
+    "moderator",
+    "null",
+    "official",
+    "root",
+    "support",
+    "system",
+    "yral",
+];
+
+#[derive(CandidType, Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum UsernameValidationError {
+    TooShort,
+    TooLong,
+    /// Only lowercase ASCII letters, digits and underscores are allowed,
+    /// and the name has to start with a letter.
+    InvalidCharacters,
+    Reserved,
+}
+
+pub fn validate_username(username: &str) -> Result<(), UsernameValidationError> {
+    if username.len() < MIN_USERNAME_LENGTH {
+        return Err(UsernameValidationError::TooShort);
+    }
+
+    if username.len() > MAX_USERNAME_LENGTH {
+        return Err(UsernameValidationError::TooLong);
+    }
+
+    let starts_with_letter = username
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase());
+    let has_only_allowed_characters = username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !starts_with_letter || !has_only_allowed_characters {
+        return Err(UsernameValidationError::InvalidCharacters);
+    }
+
+    if RESERVED_USERNAMES.contains(&username) {
+        return Err(UsernameValidationError::Reserved);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_username() {
+        assert_eq!(validate_username("cool_alice_1234"), Ok(()));
+        assert_eq!(validate_username("bob"), Ok(()));
+
+        assert_eq!(
+            validate_username("al"),
+            Err(UsernameValidationError::TooShort)
+        );
+        assert_eq!(
+            validate_username(&"a".repeat(MAX_USERNAME_LENGTH + 1)),
+            Err(UsernameValidationError::TooLong)
+        );
+        assert_eq!(
+            validate_username("Alice"),
+            Err(UsernameValidationError::InvalidCharacters)
+        );
+        assert_eq!(
+            validate_username("1alice"),
+            Err(UsernameValidationError::InvalidCharacters)
+        );
+        assert_eq!(
+            validate_username("alice.bob"),
+            Err(UsernameValidationError::InvalidCharacters)
+        );
+        assert_eq!(
+            validate_username("ålice"),
+            Err(UsernameValidationError::InvalidCharacters)
+        );
+        assert_eq!(
+            validate_username("admin"),
+            Err(UsernameValidationError::Reserved)
+        );
+    }
+}
@@ -14,9 +14,12 @@ pub const USER_SNS_CANISTER_INITIAL_CYCLES: u128 = 500_000_000_000; //0.5T Cycle
 pub const PAGE_SIZE_RECHARGE_DIVIDER: u128 = 500; // 500 pages (recharge by page_size/page_size_recharge_divider * recharge_amout)
 
 pub const MAX_USERS_IN_FOLLOWER_FOLLOWING_LIST: u64 = 10000;
+pub const MAX_USERS_IN_BLOCK_OR_MUTE_LIST: u64 = 10000;
 pub const MAX_POSTS_IN_ONE_REQUEST: u64 = 100;
 pub const HOME_FEED_DIFFERENCE_TO_INITIATE_SYNCHRONISATION: u64 = 100;
 pub const HOT_OR_NOT_FEED_DIFFERENCE_TO_INITIATE_SYNCHRONISATION: u64 = 100;
+pub const DEFAULT_POST_REPORT_THRESHOLD: u64 = 5;
+pub const DEFAULT_PAYOUT_NOTIFICATION_RETRY_HORIZON_IN_SECONDS: u64 = 7 * 24 * 60 * 60;
 
 const BACKUP_INDIVIDUAL_USER_CANISTER_BATCH_SIZE: u64 = 7_000;
 const BACKUP_INDIVIDUAL_USER_CANISTER_THRESHOLD: u64 = 3_000;
@@ -1,5 +1,7 @@
 use candid::{CandidType, Deserialize};
 
+use crate::common::utils::username::UsernameValidationError;
+
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
 pub enum UpdateProfileSetUniqueUsernameError {
     NotAuthorized,
@@ -7,6 +9,8 @@ pub enum UpdateProfileSetUniqueUsernameError {
     SendingCanisterDoesNotMatchUserCanisterId,
     UserCanisterEntryDoesNotExist,
     UserIndexCrossCanisterCallFailed,
+    InvalidUsername(UsernameValidationError),
+    UsernameChangeOnCooldown,
 }
 
 #[derive(CandidType, Debug, Deserialize)]
@@ -1,8 +1,21 @@
 use candid::{CandidType, Deserialize};
 
+use crate::common::utils::username::UsernameValidationError;
+
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
 pub enum SetUniqueUsernameError {
     UsernameAlreadyTaken,
     SendingCanisterDoesNotMatchUserCanisterId,
     UserCanisterEntryDoesNotExist,
+    InvalidUsername(UsernameValidationError),
+    UsernameChangeOnCooldown,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum AccountDeletionError {
+    Unauthenticated,
+    UserCanisterEntryDoesNotExist,
+    DeletionAlreadyRequested,
+    NoDeletionRequested,
+    DeletionAlreadyInProgress,
 }